@@ -0,0 +1,355 @@
+//! [Bentley-Ottmann algorithm](https://en.wikipedia.org/wiki/Bentley%E2%80%93Ottmann_algorithm), report all intersections among many segments
+//!
+//! sweep line moves along x(ties broken by y), so vertical segments need no special case.
+//! endpoints touching other segments are reported as intersections too. collinear overlapping segments report
+//! the two endpoints of their shared part, zero-length segments are ignored.
+
+use crate::geom2d::*;
+use math::matrix::*;
+use math::precision::Real;
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound;
+use std::rc::Rc;
+
+const EPSILON: Real = 1e-9;
+
+#[derive(Clone, Debug)]
+pub struct SegIntersection {
+    pub pt: Vec2,
+    /// indices of all segments passing through `pt`, ascending
+    pub segs: Vec<usize>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct SweepPt(Vec2);
+
+impl PartialEq for SweepPt {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for SweepPt {}
+
+impl PartialOrd for SweepPt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SweepPt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .x()
+            .total_cmp(&other.0.x())
+            .then(self.0.y().total_cmp(&other.0.y()))
+    }
+}
+
+/// segment endpoints ordered along sweep direction
+#[derive(Clone, Copy)]
+struct SweepSeg {
+    left: Vec2,
+    right: Vec2,
+}
+
+impl SweepSeg {
+    fn is_vertical(&self) -> bool {
+        (self.right.x() - self.left.x()).abs() <= EPSILON
+    }
+
+    /// y on sweep line passing through event point `p`
+    fn y_at(&self, p: &Vec2) -> Real {
+        if self.is_vertical() {
+            p.y().clamp(self.left.y(), self.right.y())
+        } else {
+            let t = ((p.x() - self.left.x()) / (self.right.x() - self.left.x())).clamp(0.0, 1.0);
+            self.left.y() + (self.right.y() - self.left.y()) * t
+        }
+    }
+
+    fn slope(&self) -> Real {
+        if self.is_vertical() {
+            Real::INFINITY
+        } else {
+            (self.right.y() - self.left.y()) / (self.right.x() - self.left.x())
+        }
+    }
+
+    fn contain(&self, p: &Vec2) -> bool {
+        let d = self.right - self.left;
+        let len = d.length();
+        let dist = (*p - self.left).cross(&d).abs() / len;
+        let t = (*p - self.left).dot(&d) / len;
+        dist <= EPSILON && t >= -EPSILON && t <= len + EPSILON
+    }
+
+    /// proper crossing point, `None` for parallel(including collinear) segments
+    fn intersect(&self, other: &SweepSeg) -> Option<Vec2> {
+        let d1 = self.right - self.left;
+        let d2 = other.right - other.left;
+        let denom = d1.cross(&d2);
+        if denom.abs() <= EPSILON * d1.length() * d2.length() {
+            return None;
+        }
+
+        let diff = other.left - self.left;
+        let t = diff.cross(&d2) / denom;
+        let u = diff.cross(&d1) / denom;
+        let tolerance = EPSILON / d1.length().min(d2.length());
+        if t < -tolerance || t > 1.0 + tolerance || u < -tolerance || u > 1.0 + tolerance {
+            return None;
+        }
+        Some(self.left + d1 * t.clamp(0.0, 1.0))
+    }
+}
+
+/// state the status keys compare through, the order is evaluated on the sweep line at `pt`
+struct SweepLine {
+    segs: Vec<SweepSeg>,
+    pt: Cell<Vec2>,
+    /// order just after `pt` instead of just before, segments meeting at `pt` swap order between the two
+    after: Cell<bool>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum StatusEntry {
+    /// probe just below every segment through `pt`
+    Below,
+    Seg(usize),
+}
+
+/// status is ordered by y on the sweep line, then by slope for segments meeting there
+#[derive(Clone)]
+struct StatusKey {
+    entry: StatusEntry,
+    line: Rc<SweepLine>,
+}
+
+impl StatusKey {
+    fn seg(&self) -> usize {
+        match self.entry {
+            StatusEntry::Seg(s) => s,
+            StatusEntry::Below => unreachable!("probes are never stored"),
+        }
+    }
+}
+
+impl PartialEq for StatusKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for StatusKey {}
+
+impl PartialOrd for StatusKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for StatusKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let line = &self.line;
+        let p = line.pt.get();
+        let y = |e: StatusEntry| match e {
+            StatusEntry::Below => p.y(),
+            StatusEntry::Seg(s) => line.segs[s].y_at(&p),
+        };
+        let (ya, yb) = (y(self.entry), y(other.entry));
+        if (ya - yb).abs() > EPSILON {
+            return ya.total_cmp(&yb);
+        }
+
+        match (self.entry, other.entry) {
+            (StatusEntry::Below, StatusEntry::Below) => Ordering::Equal,
+            (StatusEntry::Below, _) => Ordering::Less,
+            (_, StatusEntry::Below) => Ordering::Greater,
+            (StatusEntry::Seg(a), StatusEntry::Seg(b)) => {
+                // steeper one is above after the meeting point and below before it
+                let by_slope = line.segs[a].slope().total_cmp(&line.segs[b].slope());
+                let by_slope = if line.after.get() { by_slope } else { by_slope.reverse() };
+                // collinear ones never swap
+                by_slope.then(a.cmp(&b))
+            }
+        }
+    }
+}
+
+struct Sweep {
+    line: Rc<SweepLine>,
+    // event point -> segments whose left endpoint is this point
+    events: BTreeMap<SweepPt, Vec<usize>>,
+    status: BTreeSet<StatusKey>,
+    result: Vec<SegIntersection>,
+}
+
+impl Sweep {
+    fn key(&self, entry: StatusEntry) -> StatusKey {
+        StatusKey {
+            entry,
+            line: self.line.clone(),
+        }
+    }
+
+    /// event within `EPSILON` of `p`, jumps between the few distinct x in range instead of scanning all their points
+    fn find_event(&self, p: &Vec2) -> Option<SweepPt> {
+        let window = |x: Real| SweepPt(Vec2::from_xy(x, p.y() - EPSILON));
+        let mut from = Bound::Included(window(p.x() - EPSILON));
+        loop {
+            let k = *self.events.range((from, Bound::Unbounded)).next()?.0;
+            if k.0.x() > p.x() + EPSILON {
+                return None;
+            }
+            if (k.0.y() - p.y()).abs() <= EPSILON {
+                return Some(k);
+            }
+            from = if k.0.y() < p.y() {
+                // always move past `k`, rounding may put it inside the window start
+                if window(k.0.x()) > k {
+                    Bound::Included(window(k.0.x()))
+                } else {
+                    Bound::Excluded(k)
+                }
+            } else {
+                let column_end = SweepPt(Vec2::from_xy(k.0.x(), Real::INFINITY));
+                let next = *self.events.range((Bound::Excluded(column_end), Bound::Unbounded)).next()?.0;
+                Bound::Included(window(next.0.x()))
+            };
+        }
+    }
+
+    /// merge points closer than `EPSILON` into one event
+    fn add_event(&mut self, p: Vec2, seg: Option<usize>) {
+        let existing = self.find_event(&p);
+        let list = self.events.entry(existing.unwrap_or(SweepPt(p))).or_default();
+        if let Some(s) = seg {
+            list.push(s);
+        }
+    }
+
+    fn check_pair(&mut self, a: usize, b: usize, p: &Vec2) {
+        if let Some(pt) = self.line.segs[a].intersect(&self.line.segs[b]) {
+            let after = pt.x() > p.x() + EPSILON
+                || ((pt.x() - p.x()).abs() <= EPSILON && pt.y() > p.y() + EPSILON);
+            if after {
+                self.add_event(pt, None);
+            }
+        }
+    }
+
+    fn handle_event(&mut self, p: Vec2, upper: Vec<usize>) {
+        self.line.pt.set(p);
+        self.line.after.set(false);
+        let segs = &self.line.segs;
+
+        // segments through `p` form a contiguous range starting just above the probe
+        let through: Vec<usize> = self
+            .status
+            .range(self.key(StatusEntry::Below)..)
+            .map(StatusKey::seg)
+            .take_while(|s| segs[*s].y_at(&p) <= p.y() + EPSILON)
+            .filter(|s| segs[*s].contain(&p))
+            .collect();
+
+        let mut involved: Vec<usize> = upper.iter().chain(through.iter()).copied().collect();
+        involved.sort();
+        involved.dedup();
+        if involved.len() > 1 {
+            self.result.push(SegIntersection {
+                pt: p,
+                segs: involved,
+            });
+        }
+
+        // remove segments ending at `p` or passing through it, then re-insert those continuing in order just after `p`
+        let ending = |s: &usize| (segs[*s].right - p).length() <= EPSILON;
+        let mut continuing: Vec<usize> = through
+            .iter()
+            .copied()
+            .filter(|s| !ending(s))
+            .chain(upper.iter().copied())
+            .collect();
+        continuing.sort_by(|a, b| segs[*a].slope().total_cmp(&segs[*b].slope()).then(a.cmp(b)));
+        continuing.dedup();
+
+        for s in &through {
+            let key = self.key(StatusEntry::Seg(*s));
+            if !self.status.remove(&key) {
+                // near degenerate input can leave the order inconsistent within `EPSILON`, fall back to a scan
+                self.status.retain(|k| k.entry != key.entry);
+            }
+        }
+        self.line.after.set(true);
+        for s in &continuing {
+            self.status.insert(self.key(StatusEntry::Seg(*s)));
+        }
+
+        let neighbors = match (continuing.first(), continuing.last()) {
+            (Some(first), Some(last)) => {
+                let (first, last) = (self.key(StatusEntry::Seg(*first)), self.key(StatusEntry::Seg(*last)));
+                let below = self.status.range(..&first).next_back().map(|k| (k.seg(), first.seg()));
+                let above = self
+                    .status
+                    .range((Bound::Excluded(&last), Bound::Unbounded))
+                    .next()
+                    .map(|k| (last.seg(), k.seg()));
+                [below, above]
+            }
+            _ => {
+                let probe = self.key(StatusEntry::Below);
+                let below = self.status.range(..&probe).next_back();
+                let above = self.status.range(&probe..).next();
+                [below.zip(above).map(|(a, b)| (a.seg(), b.seg())), None]
+            }
+        };
+        for (a, b) in neighbors.into_iter().flatten() {
+            self.check_pair(a, b, &p);
+        }
+    }
+}
+
+/// report every point where two or more segments meet, in sweep order
+pub fn segs_intersections(segs: &[Segment2D]) -> Vec<SegIntersection> {
+    let sweep_segs = segs
+        .iter()
+        .map(|seg| {
+            let start = seg.start;
+            let end = seg.start + seg.dir * seg.len;
+            let (left, right) = if SweepPt(start) <= SweepPt(end) {
+                (start, end)
+            } else {
+                (end, start)
+            };
+            SweepSeg { left, right }
+        })
+        .collect();
+    let mut sweep = Sweep {
+        line: Rc::new(SweepLine {
+            segs: sweep_segs,
+            pt: Cell::new(Vec2::zeros()),
+            after: Cell::new(false),
+        }),
+        events: BTreeMap::new(),
+        status: BTreeSet::new(),
+        result: Vec::new(),
+    };
+
+    for (i, seg) in segs.iter().enumerate() {
+        if seg.len <= EPSILON || !seg.len.is_finite() {
+            continue;
+        }
+        let SweepSeg { left, right } = sweep.line.segs[i];
+        sweep.add_event(left, Some(i));
+        sweep.add_event(right, None);
+    }
+
+    while let Some((p, upper)) = sweep.events.pop_first() {
+        sweep.handle_event(p.0, upper);
+    }
+
+    sweep.result
+}
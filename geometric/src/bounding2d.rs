@@ -0,0 +1,201 @@
+//! bounding volume construction for 2D point sets
+
+use crate::geom2d::*;
+use crate::utilitiy::shuffle;
+use math::matrix::*;
+use math::precision::Real;
+
+const CONTAIN_EPSILON: Real = 1e-9;
+const WELZL_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+fn is_circle_contain_pt_approx(c: &Circle, pt: &Vec2) -> bool {
+    (*pt - c.center).length() <= c.radius + CONTAIN_EPSILON * (1.0 + c.radius)
+}
+
+/// smallest circle whose diameter is `p1p2`
+pub fn circle_from_2pts(p1: &Vec2, p2: &Vec2) -> Circle {
+    let center = (*p1 + *p2) / 2.0;
+    Circle::new(center, (*p1 - center).length())
+}
+
+/// circumcircle of triangle `p1p2p3`, returns `None` if three points are collinear
+pub fn circle_from_3pts(p1: &Vec2, p2: &Vec2, p3: &Vec2) -> Option<Circle> {
+    let b = *p2 - *p1;
+    let c = *p3 - *p1;
+    let d = 2.0 * b.cross(&c);
+    if d.abs() <= Real::EPSILON {
+        return None;
+    }
+
+    let b_sqrd = b.length_sqrd();
+    let c_sqrd = c.length_sqrd();
+    let center = Vec2::from_xy(
+        c.y() * b_sqrd - b.y() * c_sqrd,
+        b.x() * c_sqrd - c.x() * b_sqrd,
+    ) / d;
+
+    Some(Circle::new(*p1 + center, center.length()))
+}
+
+fn circle_from_3pts_or_widest(p1: &Vec2, p2: &Vec2, p3: &Vec2) -> Circle {
+    match circle_from_3pts(p1, p2, p3) {
+        Some(c) => c,
+        None => {
+            // collinear, the widest pair spans all three points
+            let candidates = [
+                circle_from_2pts(p1, p2),
+                circle_from_2pts(p1, p3),
+                circle_from_2pts(p2, p3),
+            ];
+            candidates
+                .into_iter()
+                .fold(candidates[0], |a, b| if b.radius > a.radius { b } else { a })
+        }
+    }
+}
+
+/// [Welzl's algorithm](https://en.wikipedia.org/wiki/Smallest-circle_problem#Welzl's_algorithm)(iterative form)
+///
+/// points are shuffled with a fixed seed first, so it runs in expected linear time for any input order
+pub fn min_enclosing_circle(pts: &[Vec2]) -> Option<Circle> {
+    let mut pts = pts.to_vec();
+    shuffle(&mut pts, WELZL_SEED);
+    let first = pts.first()?;
+    let mut circle = Circle::new(*first, 0.0);
+
+    for i in 1..pts.len() {
+        if is_circle_contain_pt_approx(&circle, &pts[i]) {
+            continue;
+        }
+
+        circle = Circle::new(pts[i], 0.0);
+        for j in 0..i {
+            if is_circle_contain_pt_approx(&circle, &pts[j]) {
+                continue;
+            }
+
+            circle = circle_from_2pts(&pts[i], &pts[j]);
+            for k in 0..j {
+                if !is_circle_contain_pt_approx(&circle, &pts[k]) {
+                    circle = circle_from_3pts_or_widest(&pts[i], &pts[j], &pts[k]);
+                }
+            }
+        }
+    }
+
+    Some(circle)
+}
+
+/// tightest axis aligned rectangle of points
+pub fn aabb_from_pts(pts: &[Vec2]) -> Option<AABB> {
+    let first = pts.first()?;
+    let mut min = *first;
+    let mut max = *first;
+    for pt in pts {
+        min = Vec2::from_xy(min.x().min(pt.x()), min.y().min(pt.y()));
+        max = Vec2::from_xy(max.x().max(pt.x()), max.y().max(pt.y()));
+    }
+
+    Some(AABB::from_min_max(min, max))
+}
+
+/// [Andrew's monotone chain](https://en.wikibooks.org/wiki/Algorithm_Implementation/Geometry/Convex_hull/Monotone_chain), returns hull in counter-clockwise order without collinear points
+pub fn convex_hull(pts: &[Vec2]) -> Vec<Vec2> {
+    let mut sorted = pts.to_vec();
+    sorted.sort_by(|a, b| {
+        a.x()
+            .partial_cmp(&b.x())
+            .unwrap()
+            .then(a.y().partial_cmp(&b.y()).unwrap())
+    });
+    sorted.dedup();
+
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    let mut hull: Vec<Vec2> = Vec::with_capacity(sorted.len() * 2);
+    for pt in sorted.iter().chain(sorted.iter().rev().skip(1)) {
+        while hull.len() >= 2 {
+            let a = hull[hull.len() - 2];
+            let b = hull[hull.len() - 1];
+            if (b - a).cross(&(*pt - a)) <= 0.0 {
+                hull.pop();
+            } else {
+                break;
+            }
+        }
+        hull.push(*pt);
+    }
+    hull.pop();
+
+    hull
+}
+
+/// minimum area enclosing rectangle by [rotating calipers](https://en.wikipedia.org/wiki/Rotating_calipers)
+pub fn min_area_rect(pts: &[Vec2]) -> Option<OBB> {
+    let hull = convex_hull(pts);
+    match hull.len() {
+        0 => return None,
+        1 => return Some(OBB::new(hull[0], Vec2::zeros())),
+        2 => {
+            let dir = hull[1] - hull[0];
+            let mut obb = OBB::new(
+                (hull[0] + hull[1]) / 2.0,
+                Vec2::from_xy(dir.length() / 2.0, 0.0),
+            );
+            obb.set_rotation(dir.y().atan2(dir.x()));
+            return Some(obb);
+        }
+        _ => {}
+    }
+
+    let n = hull.len();
+    let edge_dir = |i: usize| (hull[(i + 1) % n] - hull[i]).normalize();
+
+    // caliper indices: farthest along edge, farthest from edge, nearest along edge
+    let mut right = 1;
+    let mut top = 1;
+    let mut left = 1;
+
+    let mut best: Option<(Real, Vec2, Vec2, Vec2)> = None;
+    for i in 0..n {
+        let dir = edge_dir(i);
+        let normal = Vec2::from_xy(-dir.y(), dir.x());
+        let origin = hull[i];
+        let along = |idx: usize| (hull[idx % n] - origin).dot(&dir);
+        let height = |idx: usize| (hull[idx % n] - origin).dot(&normal);
+
+        right = right.max(i + 1);
+        while along(right + 1) >= along(right) && right < i + n {
+            right += 1;
+        }
+        top = top.max(right);
+        while height(top + 1) >= height(top) && top < i + n {
+            top += 1;
+        }
+        left = left.max(top);
+        while along(left + 1) <= along(left) && left < i + n {
+            left += 1;
+        }
+
+        let min_along = along(left);
+        let max_along = along(right);
+        let max_height = height(top);
+        let area = (max_along - min_along) * max_height;
+
+        if best.is_none_or(|b| area < b.0) {
+            let center = origin
+                + dir * ((min_along + max_along) / 2.0)
+                + normal * (max_height / 2.0);
+            let half_size = Vec2::from_xy((max_along - min_along) / 2.0, max_height / 2.0);
+            best = Some((area, center, half_size, dir));
+        }
+    }
+
+    best.map(|(_, center, half_size, dir)| {
+        let mut obb = OBB::new(center, half_size);
+        obb.set_rotation(dir.y().atan2(dir.x()));
+        obb
+    })
+}
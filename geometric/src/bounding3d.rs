@@ -0,0 +1,260 @@
+//! bounding volume construction for 3D point sets(or mesh vertices)
+
+use crate::geom3d::*;
+use crate::utilitiy::shuffle;
+use math::matrix::*;
+use math::precision::Real;
+
+const CONTAIN_EPSILON: Real = 1e-9;
+const WELZL_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+fn is_sphere_contain_pt_approx(s: &Sphere, pt: &Vec3) -> bool {
+    (*pt - s.center).length() <= s.radius + CONTAIN_EPSILON * (1.0 + s.radius)
+}
+
+/// smallest sphere whose diameter is `p1p2`
+pub fn sphere_from_2pts(p1: &Vec3, p2: &Vec3) -> Sphere {
+    let center = (*p1 + *p2) * 0.5;
+    Sphere::new(center, (*p1 - center).length())
+}
+
+/// smallest sphere passing through three points(center lies on their plane), returns `None` if they are collinear
+pub fn sphere_from_3pts(p1: &Vec3, p2: &Vec3, p3: &Vec3) -> Option<Sphere> {
+    let a = *p2 - *p1;
+    let b = *p3 - *p1;
+    let n = a.cross(&b);
+    let n_sqrd = n.length_sqrd();
+    if n_sqrd <= Real::EPSILON {
+        return None;
+    }
+
+    let center = (b * a.length_sqrd() - a * b.length_sqrd()).cross(&n) / (2.0 * n_sqrd);
+    Some(Sphere::new(*p1 + center, center.length()))
+}
+
+/// circumsphere of tetrahedron, returns `None` if four points are coplanar
+pub fn sphere_from_4pts(p1: &Vec3, p2: &Vec3, p3: &Vec3, p4: &Vec3) -> Option<Sphere> {
+    let a = *p2 - *p1;
+    let b = *p3 - *p1;
+    let c = *p4 - *p1;
+    let det = 2.0 * a.dot(&b.cross(&c));
+    if det.abs() <= Real::EPSILON {
+        return None;
+    }
+
+    let center = (b.cross(&c) * a.length_sqrd()
+        + c.cross(&a) * b.length_sqrd()
+        + a.cross(&b) * c.length_sqrd())
+        / det;
+    Some(Sphere::new(*p1 + center, center.length()))
+}
+
+fn sphere_from_3pts_or_widest(p1: &Vec3, p2: &Vec3, p3: &Vec3) -> Sphere {
+    sphere_from_3pts(p1, p2, p3).unwrap_or_else(|| {
+        // collinear, the widest pair spans all three points
+        [
+            sphere_from_2pts(p1, p2),
+            sphere_from_2pts(p1, p3),
+            sphere_from_2pts(p2, p3),
+        ]
+        .into_iter()
+        .fold(Sphere::new(*p1, 0.0), |a, b| if b.radius > a.radius { b } else { a })
+    })
+}
+
+fn sphere_from_4pts_or_smallest(p1: &Vec3, p2: &Vec3, p3: &Vec3, p4: &Vec3) -> Sphere {
+    if let Some(s) = sphere_from_4pts(p1, p2, p3, p4) {
+        return s;
+    }
+
+    // coplanar, pick the smallest sphere over three of them containing the rest
+    let pts = [*p1, *p2, *p3, *p4];
+    let candidates = [
+        sphere_from_3pts_or_widest(p1, p2, p3),
+        sphere_from_3pts_or_widest(p1, p2, p4),
+        sphere_from_3pts_or_widest(p1, p3, p4),
+        sphere_from_3pts_or_widest(p2, p3, p4),
+    ];
+    candidates
+        .iter()
+        .filter(|s| pts.iter().all(|p| is_sphere_contain_pt_approx(s, p)))
+        .fold(None, |acc: Option<Sphere>, s| match acc {
+            Some(a) if a.radius <= s.radius => Some(a),
+            _ => Some(*s),
+        })
+        .unwrap_or_else(|| {
+            candidates
+                .into_iter()
+                .fold(candidates[0], |a, b| if b.radius > a.radius { b } else { a })
+        })
+}
+
+/// [Welzl's algorithm](https://en.wikipedia.org/wiki/Bounding_sphere#Welzl's_algorithm)(iterative form)
+///
+/// points are shuffled with a fixed seed first, so it runs in expected linear time for any input order
+pub fn min_enclosing_sphere(pts: &[Vec3]) -> Option<Sphere> {
+    let mut pts = pts.to_vec();
+    shuffle(&mut pts, WELZL_SEED);
+    let first = pts.first()?;
+    let mut sphere = Sphere::new(*first, 0.0);
+
+    for i in 1..pts.len() {
+        if is_sphere_contain_pt_approx(&sphere, &pts[i]) {
+            continue;
+        }
+
+        sphere = Sphere::new(pts[i], 0.0);
+        for j in 0..i {
+            if is_sphere_contain_pt_approx(&sphere, &pts[j]) {
+                continue;
+            }
+
+            sphere = sphere_from_2pts(&pts[i], &pts[j]);
+            for k in 0..j {
+                if is_sphere_contain_pt_approx(&sphere, &pts[k]) {
+                    continue;
+                }
+
+                sphere = sphere_from_3pts_or_widest(&pts[i], &pts[j], &pts[k]);
+                for l in 0..k {
+                    if !is_sphere_contain_pt_approx(&sphere, &pts[l]) {
+                        sphere = sphere_from_4pts_or_smallest(&pts[i], &pts[j], &pts[k], &pts[l]);
+                    }
+                }
+            }
+        }
+    }
+
+    Some(sphere)
+}
+
+/// [Ritter's bounding sphere](https://en.wikipedia.org/wiki/Bounding_sphere#Ritter's_bounding_sphere), fast but about 5%~20% larger than the minimum one
+pub fn ritter_sphere(pts: &[Vec3]) -> Option<Sphere> {
+    let first = pts.first()?;
+    let farthest_from = |p: &Vec3| {
+        *pts.iter()
+            .max_by(|a, b| {
+                (**a - *p)
+                    .length_sqrd()
+                    .partial_cmp(&(**b - *p).length_sqrd())
+                    .unwrap()
+            })
+            .unwrap()
+    };
+
+    let y = farthest_from(first);
+    let z = farthest_from(&y);
+    let mut sphere = sphere_from_2pts(&y, &z);
+
+    for pt in pts {
+        let dist = (*pt - sphere.center).length();
+        if dist > sphere.radius {
+            let radius = (sphere.radius + dist) / 2.0;
+            sphere.center = sphere.center + (*pt - sphere.center) * ((radius - sphere.radius) / dist);
+            sphere.radius = radius;
+        }
+    }
+
+    Some(sphere)
+}
+
+/// tightest axis aligned box of points
+pub fn cube_from_pts(pts: &[Vec3]) -> Option<Cube> {
+    let first = pts.first()?;
+    let mut min = *first;
+    let mut max = *first;
+    for pt in pts {
+        min = Vec3::from_xyz(min.x().min(pt.x()), min.y().min(pt.y()), min.z().min(pt.z()));
+        max = Vec3::from_xyz(max.x().max(pt.x()), max.y().max(pt.y()), max.z().max(pt.z()));
+    }
+
+    Some(Cube::from_min_max(min, max))
+}
+
+/// eigen decomposition of symmetric 3x3 matrix by [Jacobi method](https://en.wikipedia.org/wiki/Jacobi_eigenvalue_algorithm)
+///
+/// returns eigen values and eigen vectors(column `i` of the second matrix pairs with value `i`)
+fn symmetric_eigen(mut a: [[Real; 3]; 3]) -> ([Real; 3], [[Real; 3]; 3]) {
+    let mut v = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+    for _ in 0..50 {
+        let off = a[0][1] * a[0][1] + a[0][2] * a[0][2] + a[1][2] * a[1][2];
+        if off <= 1e-24 {
+            break;
+        }
+
+        for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+            if a[p][q].abs() <= 1e-30 {
+                continue;
+            }
+
+            let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+            let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+            let c = 1.0 / (t * t + 1.0).sqrt();
+            let s = t * c;
+
+            for row in a.iter_mut() {
+                let akp = row[p];
+                let akq = row[q];
+                row[p] = c * akp - s * akq;
+                row[q] = s * akp + c * akq;
+            }
+            let (row_p, row_q) = (a[p], a[q]);
+            a[p] = std::array::from_fn(|k| c * row_p[k] - s * row_q[k]);
+            a[q] = std::array::from_fn(|k| s * row_p[k] + c * row_q[k]);
+            for row in v.iter_mut() {
+                let vp = row[p];
+                let vq = row[q];
+                row[p] = c * vp - s * vq;
+                row[q] = s * vp + c * vq;
+            }
+        }
+    }
+
+    ([a[0][0], a[1][1], a[2][2]], v)
+}
+
+/// oriented box whose axes are principal components of points
+pub fn obb_from_pts_pca(pts: &[Vec3]) -> Option<OBB> {
+    if pts.is_empty() {
+        return None;
+    }
+
+    let n = pts.len() as Real;
+    let mean = pts.iter().fold(Vec3::zeros(), |acc, p| acc + *p) / n;
+
+    let mut cov = [[0.0; 3]; 3];
+    for pt in pts {
+        let d = *pt - mean;
+        for (i, row) in cov.iter_mut().enumerate() {
+            for (j, elem) in row.iter_mut().enumerate() {
+                *elem += d[i] * d[j] / n;
+            }
+        }
+    }
+
+    let (values, vectors) = symmetric_eigen(cov);
+    let mut order = [0, 1, 2];
+    order.sort_by(|a, b| values[*b].partial_cmp(&values[*a]).unwrap());
+
+    let column = |i: usize| Vec3::from_xyz(vectors[0][i], vectors[1][i], vectors[2][i]).normalize();
+    let x = column(order[0]);
+    let y = (column(order[1]) - x * x.dot(&column(order[1]))).normalize();
+    let z = x.cross(&y);
+    let axes = [x, y, z];
+
+    let mut min = Vec3::from_xyz(Real::MAX, Real::MAX, Real::MAX);
+    let mut max = -min;
+    for pt in pts {
+        let d = *pt - mean;
+        for i in 0..3 {
+            let proj = d.dot(&axes[i]);
+            min[i] = min[i].min(proj);
+            max[i] = max[i].max(proj);
+        }
+    }
+
+    let local_center = (min + max) * 0.5;
+    let center = mean + x * local_center.x() + y * local_center.y() + z * local_center.z();
+    Some(OBB::new(center, (max - min) * 0.5, axes))
+}
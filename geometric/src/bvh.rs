@@ -0,0 +1,854 @@
+//! [bounding volume hierarchy](https://en.wikipedia.org/wiki/Bounding_volume_hierarchy) over 2D/3D primitives
+//!
+//! tree is built top-down by binned SAH(surface area heuristic), and can also be modified incrementally(insert/remove).
+//! every leaf holds exactly one primitive, primitives are referred by the handle returned from `insert`(or their index when built by `build`)
+
+use crate::geom2d::{AABB, OBB};
+use crate::geom3d::{Cube, OBB as OBB3D};
+use crate::geom_common::*;
+use crate::{contain2d, intersect2d, intersect3d, nearest2d, nearest_common};
+use math::matrix::*;
+use math::precision::Real;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingBox<const DIM: usize> {
+    pub min: Vector<Real, DIM>,
+    pub max: Vector<Real, DIM>,
+}
+
+pub type BoundingBox2D = BoundingBox<2>;
+pub type BoundingBox3D = BoundingBox<3>;
+
+impl<const DIM: usize> BoundingBox<DIM> {
+    pub fn new(min: Vector<Real, DIM>, max: Vector<Real, DIM>) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_pt(pt: &Vector<Real, DIM>) -> Self {
+        Self { min: *pt, max: *pt }
+    }
+
+    pub fn from_pts(pts: &[Vector<Real, DIM>]) -> Option<Self> {
+        let first = pts.first()?;
+        Some(
+            pts.iter()
+                .fold(Self::from_pt(first), |b, pt| b.union(&Self::from_pt(pt))),
+        )
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut result = *self;
+        for i in 0..DIM {
+            result.min[i] = self.min[i].min(other.min[i]);
+            result.max[i] = self.max[i].max(other.max[i]);
+        }
+        result
+    }
+
+    pub fn expand(&self, margin: Real) -> Self {
+        let mut result = *self;
+        for i in 0..DIM {
+            result.min[i] -= margin;
+            result.max[i] += margin;
+        }
+        result
+    }
+
+    pub fn center(&self) -> Vector<Real, DIM> {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn size(&self) -> Vector<Real, DIM> {
+        self.max - self.min
+    }
+
+    /// perimeter in 2D and surface area in 3D, used as cost measure of SAH
+    pub fn surface_area(&self) -> Real {
+        let size = self.size();
+        if DIM == 2 {
+            2.0 * (size[0] + size[1])
+        } else {
+            let mut area = 0.0;
+            for i in 0..DIM {
+                for j in i + 1..DIM {
+                    area += size[i] * size[j];
+                }
+            }
+            2.0 * area
+        }
+    }
+
+    pub fn largest_axis(&self) -> usize {
+        let size = self.size();
+        (0..DIM).fold(0, |axis, i| if size[i] > size[axis] { i } else { axis })
+    }
+
+    pub fn is_intersect(&self, other: &Self) -> bool {
+        (0..DIM).all(|i| self.min[i] <= other.max[i] && other.min[i] <= self.max[i])
+    }
+
+    pub fn contain(&self, other: &Self) -> bool {
+        (0..DIM).all(|i| self.min[i] <= other.min[i] && other.max[i] <= self.max[i])
+    }
+
+    pub fn contain_pt(&self, pt: &Vector<Real, DIM>) -> bool {
+        (0..DIM).all(|i| self.min[i] <= pt[i] && pt[i] <= self.max[i])
+    }
+
+    pub fn nearest_pt(&self, pt: &Vector<Real, DIM>) -> Vector<Real, DIM> {
+        let mut result = *pt;
+        for i in 0..DIM {
+            result[i] = pt[i].clamp(self.min[i], self.max[i]);
+        }
+        result
+    }
+
+    pub fn pt_dist_sqrd(&self, pt: &Vector<Real, DIM>) -> Real {
+        (self.nearest_pt(pt) - *pt).length_sqrd()
+    }
+
+    pub fn is_circular_intersect(&self, c: &Circular<DIM>) -> bool {
+        self.pt_dist_sqrd(&c.center) <= c.radius * c.radius
+    }
+
+    /// slab method, returns the entering param(0 if ray starts inside)
+    pub fn ray_intersect_param(&self, ray: &Ray<DIM>, max_t: Real) -> Option<Real> {
+        let mut t_min: Real = 0.0;
+        let mut t_max = max_t;
+        for i in 0..DIM {
+            if ray.dir[i].abs() <= Real::EPSILON {
+                if ray.start[i] < self.min[i] || ray.start[i] > self.max[i] {
+                    return None;
+                }
+            } else {
+                let inv = 1.0 / ray.dir[i];
+                let t1 = (self.min[i] - ray.start[i]) * inv;
+                let t2 = (self.max[i] - ray.start[i]) * inv;
+                t_min = t_min.max(t1.min(t2));
+                t_max = t_max.min(t1.max(t2));
+                if t_min > t_max {
+                    return None;
+                }
+            }
+        }
+        Some(t_min)
+    }
+}
+
+impl From<AABB> for BoundingBox2D {
+    fn from(value: AABB) -> Self {
+        Self::new(value.min(), value.max())
+    }
+}
+
+impl From<BoundingBox2D> for AABB {
+    fn from(value: BoundingBox2D) -> Self {
+        AABB::from_min_max(value.min, value.max)
+    }
+}
+
+impl From<Cube> for BoundingBox3D {
+    fn from(value: Cube) -> Self {
+        Self::new(value.center - value.half_len, value.center + value.half_len)
+    }
+}
+
+impl From<BoundingBox3D> for Cube {
+    fn from(value: BoundingBox3D) -> Self {
+        Cube::from_min_max(value.min, value.max)
+    }
+}
+
+/// primitive which can be stored in `BVH`
+pub trait Bounded<const DIM: usize> {
+    fn bounding_box(&self) -> BoundingBox<DIM>;
+}
+
+/// primitive which can be hit by ray, returns the nearest non-negative ray param
+pub trait RayCastable<const DIM: usize> {
+    fn ray_cast_param(&self, ray: &Ray<DIM>) -> Option<Real>;
+}
+
+/// primitive which can find its nearest point to a given point
+pub trait NearestPt<const DIM: usize> {
+    fn nearest_pt(&self, pt: &Vector<Real, DIM>) -> Vector<Real, DIM>;
+}
+
+impl<const DIM: usize> Bounded<DIM> for BoundingBox<DIM> {
+    fn bounding_box(&self) -> BoundingBox<DIM> {
+        *self
+    }
+}
+
+impl<const DIM: usize> Bounded<DIM> for Vector<Real, DIM> {
+    fn bounding_box(&self) -> BoundingBox<DIM> {
+        BoundingBox::from_pt(self)
+    }
+}
+
+impl<const DIM: usize> Bounded<DIM> for Circular<DIM> {
+    fn bounding_box(&self) -> BoundingBox<DIM> {
+        BoundingBox::from_pt(&self.center).expand(self.radius)
+    }
+}
+
+impl<const DIM: usize> Bounded<DIM> for Segment<DIM> {
+    fn bounding_box(&self) -> BoundingBox<DIM> {
+        BoundingBox::from_pts(&[self.start, self.start + self.dir * self.len]).unwrap()
+    }
+}
+
+impl<const DIM: usize> Bounded<DIM> for Triangle<DIM> {
+    fn bounding_box(&self) -> BoundingBox<DIM> {
+        BoundingBox::from_pts(&self.pts).unwrap()
+    }
+}
+
+impl Bounded<2> for AABB {
+    fn bounding_box(&self) -> BoundingBox2D {
+        (*self).into()
+    }
+}
+
+impl Bounded<2> for OBB {
+    fn bounding_box(&self) -> BoundingBox2D {
+        let half = Vec2::from_xy(
+            (self.x_axis().x() * self.half_size.x()).abs()
+                + (self.y_axis().x() * self.half_size.y()).abs(),
+            (self.x_axis().y() * self.half_size.x()).abs()
+                + (self.y_axis().y() * self.half_size.y()).abs(),
+        );
+        BoundingBox::new(self.center - half, self.center + half)
+    }
+}
+
+impl Bounded<3> for Cube {
+    fn bounding_box(&self) -> BoundingBox3D {
+        (*self).into()
+    }
+}
+
+impl Bounded<3> for OBB3D {
+    fn bounding_box(&self) -> BoundingBox3D {
+        let mut half = Vec3::zeros();
+        for i in 0..3 {
+            for axis in 0..3 {
+                half[i] += (self.axes[axis][i] * self.half_len[axis]).abs();
+            }
+        }
+        BoundingBox::new(self.center - half, self.center + half)
+    }
+}
+
+impl<const DIM: usize> RayCastable<DIM> for BoundingBox<DIM> {
+    fn ray_cast_param(&self, ray: &Ray<DIM>) -> Option<Real> {
+        self.ray_intersect_param(ray, Real::MAX)
+    }
+}
+
+impl RayCastable<2> for AABB {
+    fn ray_cast_param(&self, ray: &Ray2D) -> Option<Real> {
+        BoundingBox2D::from(*self).ray_cast_param(ray)
+    }
+}
+
+impl RayCastable<3> for Cube {
+    fn ray_cast_param(&self, ray: &Ray3D) -> Option<Real> {
+        BoundingBox3D::from(*self).ray_cast_param(ray)
+    }
+}
+
+impl RayCastable<2> for Circle {
+    fn ray_cast_param(&self, ray: &Ray2D) -> Option<Real> {
+        if contain2d::is_circle_contain_pt(self, &ray.start) {
+            return Some(0.0);
+        }
+        intersect2d::ray_circle_intersect_param(ray, self).map(|(t, _)| t)
+    }
+}
+
+impl RayCastable<3> for Sphere {
+    fn ray_cast_param(&self, ray: &Ray3D) -> Option<Real> {
+        if crate::contain_common::is_circular_contain_pt(self, &ray.start) {
+            return Some(0.0);
+        }
+        intersect3d::ray_sphere_intersect_param(ray, self).map(|(t, _)| t)
+    }
+}
+
+impl RayCastable<2> for Segment2D {
+    fn ray_cast_param(&self, ray: &Ray2D) -> Option<Real> {
+        intersect2d::ray_seg_intersect(self, ray).map(|pt| (pt - ray.start).dot(&ray.dir))
+    }
+}
+
+impl RayCastable<2> for Triangle2D {
+    fn ray_cast_param(&self, ray: &Ray2D) -> Option<Real> {
+        if contain2d::is_triangle_contain_pt(&ray.start, self) {
+            return Some(0.0);
+        }
+
+        (0..3)
+            .filter_map(|i| Segment2D::new(self[i], self[(i + 1) % 3]).ray_cast_param(ray))
+            .min_by(|a, b| a.partial_cmp(b).unwrap())
+    }
+}
+
+impl RayCastable<3> for Triangle3D {
+    fn ray_cast_param(&self, ray: &Ray3D) -> Option<Real> {
+        intersect3d::ray_triangle_intersect_param(ray, self)
+    }
+}
+
+impl<const DIM: usize> NearestPt<DIM> for BoundingBox<DIM> {
+    fn nearest_pt(&self, pt: &Vector<Real, DIM>) -> Vector<Real, DIM> {
+        BoundingBox::nearest_pt(self, pt)
+    }
+}
+
+impl<const DIM: usize> NearestPt<DIM> for Vector<Real, DIM> {
+    fn nearest_pt(&self, _pt: &Vector<Real, DIM>) -> Vector<Real, DIM> {
+        *self
+    }
+}
+
+impl<const DIM: usize> NearestPt<DIM> for Circular<DIM> {
+    fn nearest_pt(&self, pt: &Vector<Real, DIM>) -> Vector<Real, DIM> {
+        let dir = *pt - self.center;
+        let len = dir.length();
+        if len <= self.radius {
+            *pt
+        } else {
+            self.center + dir * (self.radius / len)
+        }
+    }
+}
+
+impl<const DIM: usize> NearestPt<DIM> for Segment<DIM> {
+    fn nearest_pt(&self, pt: &Vector<Real, DIM>) -> Vector<Real, DIM> {
+        nearest_common::pt2segment(pt, self)
+    }
+}
+
+impl NearestPt<2> for AABB {
+    fn nearest_pt(&self, pt: &Vec2) -> Vec2 {
+        nearest2d::pt2rect(pt, self)
+    }
+}
+
+impl NearestPt<3> for Cube {
+    fn nearest_pt(&self, pt: &Vec3) -> Vec3 {
+        BoundingBox3D::from(*self).nearest_pt(pt)
+    }
+}
+
+impl NearestPt<2> for Triangle2D {
+    fn nearest_pt(&self, pt: &Vec2) -> Vec2 {
+        nearest2d::pt2triangle(pt, self)
+    }
+}
+
+impl NearestPt<3> for Triangle3D {
+    fn nearest_pt(&self, pt: &Vec3) -> Vec3 {
+        nearest_common::pt2triangle(pt, self)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum NodeKind {
+    Leaf(usize),
+    Internal(usize, usize),
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Node<const DIM: usize> {
+    bounds: BoundingBox<DIM>,
+    parent: Option<usize>,
+    kind: NodeKind,
+}
+
+struct Item<T> {
+    primitive: T,
+    leaf: usize,
+}
+
+pub struct BVH<T, const DIM: usize> {
+    nodes: Vec<Node<DIM>>,
+    free_nodes: Vec<usize>,
+    items: Vec<Option<Item<T>>>,
+    free_items: Vec<usize>,
+    root: Option<usize>,
+}
+
+pub type BVH2D<T> = BVH<T, 2>;
+pub type BVH3D<T> = BVH<T, 3>;
+
+const SAH_BIN_COUNT: usize = 12;
+
+impl<T: Bounded<DIM>, const DIM: usize> Default for BVH<T, DIM> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Bounded<DIM>, const DIM: usize> BVH<T, DIM> {
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            free_nodes: Vec::new(),
+            items: Vec::new(),
+            free_items: Vec::new(),
+            root: None,
+        }
+    }
+
+    /// build tree top-down by binned SAH, handle of each primitive is its index in `primitives`
+    pub fn build(primitives: Vec<T>) -> Self {
+        let mut bvh = Self::new();
+        let mut refs: Vec<(usize, BoundingBox<DIM>, Vector<Real, DIM>)> = primitives
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let b = p.bounding_box();
+                (i, b, b.center())
+            })
+            .collect();
+        bvh.items = primitives
+            .into_iter()
+            .map(|primitive| Some(Item { primitive, leaf: 0 }))
+            .collect();
+
+        if !refs.is_empty() {
+            bvh.root = Some(bvh.build_recursive(&mut refs, None));
+        }
+        bvh
+    }
+
+    fn build_recursive(
+        &mut self,
+        refs: &mut [(usize, BoundingBox<DIM>, Vector<Real, DIM>)],
+        parent: Option<usize>,
+    ) -> usize {
+        if refs.len() == 1 {
+            let (item, bounds, _) = refs[0];
+            let node = self.alloc_node(Node {
+                bounds,
+                parent,
+                kind: NodeKind::Leaf(item),
+            });
+            self.items[item].as_mut().unwrap().leaf = node;
+            return node;
+        }
+
+        let bounds = refs
+            .iter()
+            .skip(1)
+            .fold(refs[0].1, |b, r| b.union(&r.1));
+        let centroid_bounds = refs
+            .iter()
+            .skip(1)
+            .fold(BoundingBox::from_pt(&refs[0].2), |b, r| {
+                b.union(&BoundingBox::from_pt(&r.2))
+            });
+
+        let mid = Self::sah_split(refs, &centroid_bounds);
+
+        let node = self.alloc_node(Node {
+            bounds,
+            parent,
+            kind: NodeKind::Leaf(usize::MAX),
+        });
+        let (left_refs, right_refs) = refs.split_at_mut(mid);
+        let left = self.build_recursive(left_refs, Some(node));
+        let right = self.build_recursive(right_refs, Some(node));
+        self.nodes[node].kind = NodeKind::Internal(left, right);
+        node
+    }
+
+    /// partition `refs` in place and returns the split position
+    fn sah_split(
+        refs: &mut [(usize, BoundingBox<DIM>, Vector<Real, DIM>)],
+        centroid_bounds: &BoundingBox<DIM>,
+    ) -> usize {
+        let axis = centroid_bounds.largest_axis();
+        let lo = centroid_bounds.min[axis];
+        let extent = centroid_bounds.max[axis] - lo;
+
+        if extent <= Real::EPSILON {
+            return refs.len() / 2;
+        }
+
+        let bin_of = |c: &Vector<Real, DIM>| {
+            (((c[axis] - lo) / extent * SAH_BIN_COUNT as Real) as usize).min(SAH_BIN_COUNT - 1)
+        };
+
+        let mut bins: [(usize, Option<BoundingBox<DIM>>); SAH_BIN_COUNT] =
+            [(0, None); SAH_BIN_COUNT];
+        for r in refs.iter() {
+            let bin = &mut bins[bin_of(&r.2)];
+            bin.0 += 1;
+            bin.1 = Some(bin.1.map_or(r.1, |b| b.union(&r.1)));
+        }
+
+        let mut best_cost = Real::MAX;
+        let mut best_split = 1;
+        for split in 1..SAH_BIN_COUNT {
+            let side = |range: &[(usize, Option<BoundingBox<DIM>>)]| {
+                range.iter().fold((0, None), |(count, bounds), (c, b)| {
+                    let bounds = match (bounds, b) {
+                        (Some(a), Some(b)) => Some(b.union(&a)),
+                        (a, b) => a.or(*b),
+                    };
+                    (count + c, bounds)
+                })
+            };
+            let (left_count, left_bounds) = side(&bins[..split]);
+            let (right_count, right_bounds) = side(&bins[split..]);
+            if left_count == 0 || right_count == 0 {
+                continue;
+            }
+
+            let cost = left_count as Real * left_bounds.unwrap().surface_area()
+                + right_count as Real * right_bounds.unwrap().surface_area();
+            if cost < best_cost {
+                best_cost = cost;
+                best_split = split;
+            }
+        }
+
+        let mut mid = 0;
+        for i in 0..refs.len() {
+            if bin_of(&refs[i].2) < best_split {
+                refs.swap(i, mid);
+                mid += 1;
+            }
+        }
+
+        if mid == 0 || mid == refs.len() {
+            refs.len() / 2
+        } else {
+            mid
+        }
+    }
+
+    fn alloc_node(&mut self, node: Node<DIM>) -> usize {
+        if let Some(idx) = self.free_nodes.pop() {
+            self.nodes[idx] = node;
+            idx
+        } else {
+            self.nodes.push(node);
+            self.nodes.len() - 1
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len() - self.free_items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// bounds of the whole tree
+    pub fn bounds(&self) -> Option<BoundingBox<DIM>> {
+        self.root.map(|r| self.nodes[r].bounds)
+    }
+
+    pub fn get(&self, handle: usize) -> Option<&T> {
+        self.items
+            .get(handle)
+            .and_then(|i| i.as_ref())
+            .map(|i| &i.primitive)
+    }
+
+    /// primitive may be moved through the returned reference, call `refit` afterwards
+    pub fn get_mut(&mut self, handle: usize) -> Option<&mut T> {
+        self.items
+            .get_mut(handle)
+            .and_then(|i| i.as_mut())
+            .map(|i| &mut i.primitive)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item)| item.as_ref().map(|item| (i, &item.primitive)))
+    }
+
+    /// insert primitive by choosing the sibling which increases the least surface area
+    pub fn insert(&mut self, primitive: T) -> usize {
+        let bounds = primitive.bounding_box();
+        let handle = if let Some(h) = self.free_items.pop() {
+            self.items[h] = Some(Item { primitive, leaf: 0 });
+            h
+        } else {
+            self.items.push(Some(Item { primitive, leaf: 0 }));
+            self.items.len() - 1
+        };
+
+        let leaf = self.alloc_node(Node {
+            bounds,
+            parent: None,
+            kind: NodeKind::Leaf(handle),
+        });
+        self.items[handle].as_mut().unwrap().leaf = leaf;
+
+        let Some(root) = self.root else {
+            self.root = Some(leaf);
+            return handle;
+        };
+
+        let sibling = self.find_best_sibling(root, &bounds);
+        let old_parent = self.nodes[sibling].parent;
+        let new_parent = self.alloc_node(Node {
+            bounds: self.nodes[sibling].bounds.union(&bounds),
+            parent: old_parent,
+            kind: NodeKind::Internal(sibling, leaf),
+        });
+        self.nodes[sibling].parent = Some(new_parent);
+        self.nodes[leaf].parent = Some(new_parent);
+
+        match old_parent {
+            Some(p) => self.replace_child(p, sibling, new_parent),
+            None => self.root = Some(new_parent),
+        }
+        self.refit_ancestors(new_parent);
+
+        handle
+    }
+
+    fn find_best_sibling(&self, root: usize, bounds: &BoundingBox<DIM>) -> usize {
+        let mut idx = root;
+        while let NodeKind::Internal(left, right) = self.nodes[idx].kind {
+            let area = self.nodes[idx].bounds.surface_area();
+            let combined = self.nodes[idx].bounds.union(bounds).surface_area();
+
+            let cost_here = 2.0 * combined;
+            let inheritance = 2.0 * (combined - area);
+
+            let cost_of = |child: usize| {
+                let node = &self.nodes[child];
+                let union = node.bounds.union(bounds).surface_area();
+                match node.kind {
+                    NodeKind::Leaf(_) => union + inheritance,
+                    NodeKind::Internal(..) => union - node.bounds.surface_area() + inheritance,
+                }
+            };
+            let cost_left = cost_of(left);
+            let cost_right = cost_of(right);
+
+            if cost_here < cost_left && cost_here < cost_right {
+                break;
+            }
+            idx = if cost_left < cost_right { left } else { right };
+        }
+        idx
+    }
+
+    fn replace_child(&mut self, parent: usize, old: usize, new: usize) {
+        if let NodeKind::Internal(left, right) = self.nodes[parent].kind {
+            self.nodes[parent].kind = if left == old {
+                NodeKind::Internal(new, right)
+            } else {
+                NodeKind::Internal(left, new)
+            };
+        }
+    }
+
+    fn refit_ancestors(&mut self, mut idx: usize) {
+        loop {
+            if let NodeKind::Internal(left, right) = self.nodes[idx].kind {
+                self.nodes[idx].bounds = self.nodes[left].bounds.union(&self.nodes[right].bounds);
+            }
+            match self.nodes[idx].parent {
+                Some(p) => idx = p,
+                None => break,
+            }
+        }
+    }
+
+    pub fn remove(&mut self, handle: usize) -> Option<T> {
+        let item = self.items.get_mut(handle)?.take()?;
+        self.free_items.push(handle);
+
+        let leaf = item.leaf;
+        self.free_nodes.push(leaf);
+        match self.nodes[leaf].parent {
+            None => self.root = None,
+            Some(parent) => {
+                let sibling = match self.nodes[parent].kind {
+                    NodeKind::Internal(l, r) => {
+                        if l == leaf {
+                            r
+                        } else {
+                            l
+                        }
+                    }
+                    NodeKind::Leaf(_) => unreachable!(),
+                };
+                let grand_parent = self.nodes[parent].parent;
+                self.nodes[sibling].parent = grand_parent;
+                self.free_nodes.push(parent);
+                match grand_parent {
+                    Some(g) => {
+                        self.replace_child(g, parent, sibling);
+                        self.refit_ancestors(g);
+                    }
+                    None => self.root = Some(sibling),
+                }
+            }
+        }
+
+        Some(item.primitive)
+    }
+
+    /// recompute all bounds after primitives moved, tree topology is kept
+    pub fn refit(&mut self) {
+        if let Some(root) = self.root {
+            self.refit_recursive(root);
+        }
+    }
+
+    fn refit_recursive(&mut self, idx: usize) -> BoundingBox<DIM> {
+        let bounds = match self.nodes[idx].kind {
+            NodeKind::Leaf(item) => self.items[item].as_ref().unwrap().primitive.bounding_box(),
+            NodeKind::Internal(left, right) => {
+                let l = self.refit_recursive(left);
+                let r = self.refit_recursive(right);
+                l.union(&r)
+            }
+        };
+        self.nodes[idx].bounds = bounds;
+        bounds
+    }
+
+    /// visit primitives whose bounding box passes `node_test`, returns handles
+    fn query_by(&self, node_test: impl Fn(&BoundingBox<DIM>) -> bool) -> Vec<usize> {
+        let mut result = Vec::new();
+        let mut stack: Vec<usize> = self.root.into_iter().collect();
+        while let Some(idx) = stack.pop() {
+            let node = &self.nodes[idx];
+            if !node_test(&node.bounds) {
+                continue;
+            }
+            match node.kind {
+                NodeKind::Leaf(item) => result.push(item),
+                NodeKind::Internal(left, right) => stack.extend([left, right]),
+            }
+        }
+        result
+    }
+
+    /// handles of primitives whose bounding box overlaps `bounds`
+    pub fn query_box(&self, bounds: &BoundingBox<DIM>) -> Vec<usize> {
+        self.query_by(|b| b.is_intersect(bounds))
+    }
+
+    /// handles of primitives whose bounding box overlaps circle(2D) or sphere(3D)
+    pub fn query_circular(&self, c: &Circular<DIM>) -> Vec<usize> {
+        self.query_by(|b| b.is_circular_intersect(c))
+    }
+
+    /// handles of primitives whose bounding box contains `pt`
+    pub fn query_pt(&self, pt: &Vector<Real, DIM>) -> Vec<usize> {
+        self.query_by(|b| b.contain_pt(pt))
+    }
+}
+
+impl<T: Bounded<DIM> + RayCastable<DIM>, const DIM: usize> BVH<T, DIM> {
+    /// nearest hit within `[0, max_t]`, returns (handle, ray param)
+    pub fn ray_cast(&self, ray: &Ray<DIM>, max_t: Real) -> Option<(usize, Real)> {
+        self.ray_cast_impl(ray, max_t, false)
+    }
+
+    /// any hit within `[0, max_t]`, faster than `ray_cast` for occlusion tests
+    pub fn ray_cast_any(&self, ray: &Ray<DIM>, max_t: Real) -> Option<(usize, Real)> {
+        self.ray_cast_impl(ray, max_t, true)
+    }
+
+    fn ray_cast_impl(&self, ray: &Ray<DIM>, max_t: Real, any: bool) -> Option<(usize, Real)> {
+        let mut best: Option<(usize, Real)> = None;
+        let mut max_t = max_t;
+        let mut stack: Vec<usize> = self.root.into_iter().collect();
+
+        while let Some(idx) = stack.pop() {
+            let node = &self.nodes[idx];
+            if node.bounds.ray_intersect_param(ray, max_t).is_none() {
+                continue;
+            }
+
+            match node.kind {
+                NodeKind::Leaf(item) => {
+                    let primitive = &self.items[item].as_ref().unwrap().primitive;
+                    if let Some(t) = primitive.ray_cast_param(ray) {
+                        if t <= max_t {
+                            max_t = t;
+                            best = Some((item, t));
+                            if any {
+                                break;
+                            }
+                        }
+                    }
+                }
+                NodeKind::Internal(left, right) => {
+                    // push the farther child first so the nearer one is visited first
+                    let tl = self.nodes[left].bounds.ray_intersect_param(ray, max_t);
+                    let tr = self.nodes[right].bounds.ray_intersect_param(ray, max_t);
+                    match (tl, tr) {
+                        (Some(a), Some(b)) => {
+                            if a < b {
+                                stack.extend([right, left]);
+                            } else {
+                                stack.extend([left, right]);
+                            }
+                        }
+                        (Some(_), None) => stack.push(left),
+                        (None, Some(_)) => stack.push(right),
+                        (None, None) => {}
+                    }
+                }
+            }
+        }
+
+        best
+    }
+}
+
+impl<T: Bounded<DIM> + NearestPt<DIM>, const DIM: usize> BVH<T, DIM> {
+    /// nearest point among all primitives, returns (handle, nearest point)
+    pub fn nearest(&self, pt: &Vector<Real, DIM>) -> Option<(usize, Vector<Real, DIM>)> {
+        let mut best: Option<(usize, Vector<Real, DIM>, Real)> = None;
+        let mut stack: Vec<usize> = self.root.into_iter().collect();
+
+        while let Some(idx) = stack.pop() {
+            let node = &self.nodes[idx];
+            let node_dist = node.bounds.pt_dist_sqrd(pt);
+            if best.is_some_and(|b| node_dist > b.2) {
+                continue;
+            }
+
+            match node.kind {
+                NodeKind::Leaf(item) => {
+                    let primitive = &self.items[item].as_ref().unwrap().primitive;
+                    let nearest = primitive.nearest_pt(pt);
+                    let dist = (nearest - *pt).length_sqrd();
+                    if best.is_none_or(|b| dist < b.2) {
+                        best = Some((item, nearest, dist));
+                    }
+                }
+                NodeKind::Internal(left, right) => {
+                    let dl = self.nodes[left].bounds.pt_dist_sqrd(pt);
+                    let dr = self.nodes[right].bounds.pt_dist_sqrd(pt);
+                    if dl < dr {
+                        stack.extend([right, left]);
+                    } else {
+                        stack.extend([left, right]);
+                    }
+                }
+            }
+        }
+
+        best.map(|(item, nearest, _)| (item, nearest))
+    }
+}
@@ -0,0 +1,203 @@
+//! 2D conic sections as general second degree equations, shared by nearest, containment and intersection queries
+
+use crate::geom2d::*;
+use crate::utilitiy::polynomial_roots;
+use math::matrix::*;
+use math::precision::Real;
+
+/// general second degree curve `a x^2 + b xy + c y^2 + d x + e y + f = 0`
+#[derive(Clone, Copy, Debug)]
+pub struct ConicEquation {
+    pub a: Real,
+    pub b: Real,
+    pub c: Real,
+    pub d: Real,
+    pub e: Real,
+    pub f: Real,
+}
+
+impl ConicEquation {
+    /// symmetric matrix `m` where `[x y 1] m [x y 1]^T` is the equation
+    pub fn matrix(&self) -> [[Real; 3]; 3] {
+        [
+            [self.a, self.b / 2.0, self.d / 2.0],
+            [self.b / 2.0, self.c, self.e / 2.0],
+            [self.d / 2.0, self.e / 2.0, self.f],
+        ]
+    }
+
+    pub fn from_matrix(m: &[[Real; 3]; 3]) -> Self {
+        Self {
+            a: m[0][0],
+            b: m[0][1] + m[1][0],
+            c: m[1][1],
+            d: m[0][2] + m[2][0],
+            e: m[1][2] + m[2][1],
+            f: m[2][2],
+        }
+    }
+
+    pub fn eval(&self, pt: &Vec2) -> Real {
+        let (x, y) = (pt.x(), pt.y());
+        self.a * x * x + self.b * x * y + self.c * y * y + self.d * x + self.e * y + self.f
+    }
+
+    pub fn gradient(&self, pt: &Vec2) -> Vec2 {
+        let (x, y) = (pt.x(), pt.y());
+        Vec2::from_xy(
+            2.0 * self.a * x + self.b * y + self.d,
+            self.b * x + 2.0 * self.c * y + self.e,
+        )
+    }
+
+    /// first order approximation of distance from `pt` to the curve
+    pub fn approx_dist(&self, pt: &Vec2) -> Real {
+        self.eval(pt).abs() / self.gradient(pt).length()
+    }
+
+    /// params along `start + t * dir` where line meets the curve, ascending. a tangent line gives one param
+    pub fn line_param(&self, start: &Vec2, dir: &Vec2) -> Vec<Real> {
+        let (sx, sy, dx, dy) = (start.x(), start.y(), dir.x(), dir.y());
+        let a = self.a * dx * dx + self.b * dx * dy + self.c * dy * dy;
+        let b = 2.0 * self.a * sx * dx
+            + self.b * (sx * dy + sy * dx)
+            + 2.0 * self.c * sy * dy
+            + self.d * dx
+            + self.e * dy;
+        polynomial_roots(&[a, b, self.eval(start)])
+    }
+}
+
+/// `[s^2, s, 1]` coefficients of a quadratic polynomial
+pub(crate) type Quadratic = [Real; 3];
+
+impl Conic {
+    pub fn position(&self) -> Vec2 {
+        match self {
+            Conic::Ellipse(e) => e.position,
+            Conic::Hyperbola(h) => h.position,
+            Conic::Parabola(p) => p.position,
+        }
+    }
+
+    pub fn rotation(&self) -> Real {
+        match self {
+            Conic::Ellipse(e) => e.rotation,
+            Conic::Hyperbola(h) => h.rotation,
+            Conic::Parabola(p) => p.rotation,
+        }
+    }
+
+    pub fn to_local(&self, pt: &Vec2) -> Vec2 {
+        let (sin, cos) = self.rotation().sin_cos();
+        let d = *pt - self.position();
+        Vec2::from_xy(cos * d.x() + sin * d.y(), -sin * d.x() + cos * d.y())
+    }
+
+    pub fn to_world(&self, pt: &Vec2) -> Vec2 {
+        let (sin, cos) = self.rotation().sin_cos();
+        self.position() + Vec2::from_xy(cos * pt.x() - sin * pt.y(), sin * pt.x() + cos * pt.y())
+    }
+
+    /// canonical equation in local coordinate
+    pub fn local_equation(&self) -> ConicEquation {
+        let (a, c, d, f) = match self {
+            Conic::Ellipse(e) => (1.0 / (e.a * e.a), 1.0 / (e.b * e.b), 0.0, -1.0),
+            Conic::Hyperbola(h) => (1.0 / (h.a * h.a), -1.0 / (h.b * h.b), 0.0, -1.0),
+            Conic::Parabola(p) => (0.0, 1.0, -2.0 * p.p, 0.0),
+        };
+        ConicEquation { a, b: 0.0, c, d, e: 0.0, f }
+    }
+
+    /// equation in world coordinate
+    pub fn equation(&self) -> ConicEquation {
+        // homogeneous transform from world to local
+        let (sin, cos) = self.rotation().sin_cos();
+        let pos = self.position();
+        let t = [
+            [cos, sin, -(cos * pos.x() + sin * pos.y())],
+            [-sin, cos, sin * pos.x() - cos * pos.y()],
+            [0.0, 0.0, 1.0],
+        ];
+        let m = self.local_equation().matrix();
+        let mut world = [[0.0; 3]; 3];
+        for (i, row) in world.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                for k in 0..3 {
+                    for l in 0..3 {
+                        *value += t[k][i] * m[k][l] * t[l][j];
+                    }
+                }
+            }
+        }
+        ConicEquation::from_matrix(&world)
+    }
+
+    /// homogeneous rational parameterization `(x(s), y(s), w(s))` in world coordinate,
+    /// with the only point on conic not reached by finite `s`
+    pub(crate) fn rational_param(&self) -> ([Quadratic; 3], Option<Vec2>) {
+        let (x, y, w, missing) = match self {
+            Conic::Ellipse(e) => (
+                [-e.a, 0.0, e.a],
+                [0.0, 2.0 * e.b, 0.0],
+                [1.0, 0.0, 1.0],
+                Some(Vec2::from_xy(-e.a, 0.0)),
+            ),
+            Conic::Hyperbola(h) => (
+                [h.a, 0.0, h.a],
+                [0.0, 2.0 * h.b, 0.0],
+                [-1.0, 0.0, 1.0],
+                Some(Vec2::from_xy(-h.a, 0.0)),
+            ),
+            Conic::Parabola(p) => ([0.5 / p.p, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0], None),
+        };
+
+        let (sin, cos) = self.rotation().sin_cos();
+        let pos = self.position();
+        let world_x = [0, 1, 2].map(|i| cos * x[i] - sin * y[i] + pos.x() * w[i]);
+        let world_y = [0, 1, 2].map(|i| sin * x[i] + cos * y[i] + pos.y() * w[i]);
+        ([world_x, world_y, w], missing.map(|p| self.to_world(&p)))
+    }
+}
+
+impl From<Ellipse> for Conic {
+    fn from(value: Ellipse) -> Self {
+        Conic::Ellipse(value)
+    }
+}
+
+impl From<Hyperbola> for Conic {
+    fn from(value: Hyperbola) -> Self {
+        Conic::Hyperbola(value)
+    }
+}
+
+impl From<Parabola> for Conic {
+    fn from(value: Parabola) -> Self {
+        Conic::Parabola(value)
+    }
+}
+
+/// points where tangent lines through `pt` touch the conic, they lie on the polar line of `pt`.
+/// returns `pt` itself if it is on conic, nothing if it is inside
+pub fn tangent_pts(pt: &Vec2, conic: &Conic) -> Vec<Vec2> {
+    let eq = conic.equation();
+    if eq.approx_dist(pt) <= 1e-9 {
+        return vec![*pt];
+    }
+
+    // polar line `n.x * x + n.y * y + k = 0`
+    let m = eq.matrix();
+    let p = [pt.x(), pt.y(), 1.0];
+    let [nx, ny, k] = m.map(|row| row[0] * p[0] + row[1] * p[1] + row[2] * p[2]);
+    let normal = Vec2::from_xy(nx, ny);
+    if normal.length_sqrd() == 0.0 {
+        return vec![];
+    }
+    let start = -normal * (k / normal.length_sqrd());
+    let dir = Vec2::from_xy(-ny, nx);
+    eq.line_param(&start, &dir)
+        .into_iter()
+        .map(|t| start + dir * t)
+        .collect()
+}
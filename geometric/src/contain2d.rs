@@ -1,5 +1,5 @@
 use crate::{geom2d::*, contain_common::is_circular_contain_pt};
-use math::matrix::*;
+use math::{matrix::*, precision::Real};
 
 pub fn is_circle_contain_pt(c: &Circle, pt: &Vec2) -> bool {
     is_circular_contain_pt(c, pt)
@@ -12,8 +12,28 @@ pub fn is_rect_contain_pt(pt: &Vec2, rect: &AABB) -> bool {
         && pt.y() <= rect.min().y() + rect.size().y()
 }
 
-pub fn is_polygon_contain_pt(_pt: &Vec2, _polygon: &[Vec2]) -> bool {
-    todo!();
+pub fn is_triangle_contain_pt(pt: &Vec2, triangle: &Triangle2D) -> bool {
+    let d1 = (triangle[1] - triangle[0]).cross(&(*pt - triangle[0]));
+    let d2 = (triangle[2] - triangle[1]).cross(&(*pt - triangle[1]));
+    let d3 = (triangle[0] - triangle[2]).cross(&(*pt - triangle[2]));
+
+    (d1 >= 0.0 && d2 >= 0.0 && d3 >= 0.0) || (d1 <= 0.0 && d2 <= 0.0 && d3 <= 0.0)
+}
+
+/// [crossing number](https://en.wikipedia.org/wiki/Point_in_polygon#Ray_casting_algorithm) test, any orientation.
+/// points exactly on an edge may go either way
+pub fn is_polygon_contain_pt(pt: &Vec2, polygon: &[Vec2]) -> bool {
+    let n = polygon.len();
+    let mut inside = false;
+    for i in 0..n {
+        let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+        if (a.y() > pt.y()) != (b.y() > pt.y())
+            && pt.x() < a.x() + (pt.y() - a.y()) / (b.y() - a.y()) * (b.x() - a.x())
+        {
+            inside = !inside;
+        }
+    }
+    inside
 }
 
 pub fn is_obb_contain_pt(pt: &Vec2, obb: &OBB) -> bool {
@@ -23,3 +43,51 @@ pub fn is_obb_contain_pt(pt: &Vec2, obb: &OBB) -> bool {
 
     x_magnitude < obb.half_size.x() && y_magnitude < obb.half_size.y()
 }
+
+pub fn is_ellipse_contain_pt(pt: &Vec2, ellipse: &Ellipse) -> bool {
+    Conic::Ellipse(*ellipse).equation().eval(pt) <= 0.0
+}
+
+/// region on the focus side of parabola
+pub fn is_parabola_contain_pt(pt: &Vec2, parabola: &Parabola) -> bool {
+    Conic::Parabola(*parabola).equation().eval(pt) <= 0.0
+}
+
+/// regions enclosed by either branch of hyperbola(the sides containing foci)
+pub fn is_hyperbola_contain_pt(pt: &Vec2, hyperbola: &Hyperbola) -> bool {
+    Conic::Hyperbola(*hyperbola).equation().eval(pt) >= 0.0
+}
+
+pub fn is_conic_contain_pt(pt: &Vec2, conic: &Conic) -> bool {
+    match conic {
+        Conic::Ellipse(e) => is_ellipse_contain_pt(pt, e),
+        Conic::Hyperbola(h) => is_hyperbola_contain_pt(pt, h),
+        Conic::Parabola(p) => is_parabola_contain_pt(pt, p),
+    }
+}
+
+/// whether `pt` is on arc, distance to the circle is compared with a tolerance relative to radius
+pub fn is_arc_contain_pt(pt: &Vec2, arc: &CircleArc) -> bool {
+    const EPSILON: Real = 1e-9;
+    let dir = *pt - arc.center;
+    (dir.length() - arc.radius).abs() <= EPSILON * arc.radius.max(1.0)
+        && arc.contain_angle(dir.y().atan2(dir.x()))
+}
+
+pub fn is_annulus_contain_pt(pt: &Vec2, annulus: &Annulus) -> bool {
+    let len_sqrd = (*pt - annulus.center).length_sqrd();
+    len_sqrd >= annulus.inner_radius * annulus.inner_radius
+        && len_sqrd <= annulus.outer_radius * annulus.outer_radius
+}
+
+pub fn is_annular_sector_contain_pt(pt: &Vec2, sector: &AnnularSector) -> bool {
+    let dir = *pt - sector.center;
+    let len_sqrd = dir.length_sqrd();
+    if len_sqrd < sector.inner_radius * sector.inner_radius
+        || len_sqrd > sector.outer_radius * sector.outer_radius
+    {
+        return false;
+    }
+    // center has no angle, it's only contained by sector without hole
+    len_sqrd == 0.0 || sector.outer_arc().contain_angle(dir.y().atan2(dir.x()))
+}
@@ -0,0 +1,49 @@
+use crate::{contain_common::is_circular_contain_pt, geom3d::*, nearest_common::pt2segment};
+use math::{matrix::*, precision::Real};
+
+pub fn is_sphere_contain_pt(s: &Sphere, pt: &Vec3) -> bool {
+    is_circular_contain_pt(s, pt)
+}
+
+pub fn is_cube_contain_pt(pt: &Vec3, cube: &Cube) -> bool {
+    (0..3).all(|i| (pt[i] - cube.center[i]).abs() <= cube.half_len[i])
+}
+
+pub fn is_capsule_contain_pt(pt: &Vec3, capsule: &Capsule) -> bool {
+    (*pt - pt2segment(pt, &capsule.segment())).length_sqrd() <= capsule.radius * capsule.radius
+}
+
+pub fn is_ellipsoid_contain_pt(pt: &Vec3, ellipsoid: &Ellipsoid) -> bool {
+    let dir = *pt - ellipsoid.center;
+    let sum: Real = (0..3)
+        .map(|i| {
+            let x = dir.dot(&ellipsoid.axes[i]) / ellipsoid.radii[i];
+            x * x
+        })
+        .sum();
+    sum <= 1.0
+}
+
+pub fn is_torus_contain_pt(pt: &Vec3, torus: &Torus) -> bool {
+    let dir = *pt - torus.center;
+    let h = dir.dot(&torus.normal);
+    let radial = (dir - torus.normal * h).length() - torus.major_radius;
+    radial * radial + h * h <= torus.minor_radius * torus.minor_radius
+}
+
+pub fn is_rounded_box_contain_pt(pt: &Vec3, rounded: &RoundedBox) -> bool {
+    let mut dist_sqrd = 0.0;
+    for i in 0..3 {
+        let d = ((pt[i] - rounded.center[i]).abs() - rounded.half_len[i]).max(0.0);
+        dist_sqrd += d * d;
+    }
+    dist_sqrd <= rounded.radius * rounded.radius
+}
+
+pub fn is_half_space_contain_pt(pt: &Vec3, half_space: &HalfSpace) -> bool {
+    half_space.signed_dist(pt) <= 0.0
+}
+
+pub fn is_slab_contain_pt(pt: &Vec3, slab: &Slab) -> bool {
+    slab.signed_dist(pt).abs() <= slab.half_thickness
+}
@@ -0,0 +1,104 @@
+//! classify shapes against a `ViewFrustum` for visibility culling
+//!
+//! half space tests decide most cases cheaply, shapes straddling a plane are confirmed by GJK,
+//! so a shape near frustum corner but outside it is not reported as intersecting.
+
+use crate::geom3d::{Cube, ViewFrustum, OBB};
+use crate::geom_common::Sphere;
+use crate::gjk::{self, Support};
+use math::matrix::*;
+use math::precision::Real;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CullResult {
+    Inside,
+    Outside,
+    Intersecting,
+}
+
+impl Support<3> for ViewFrustum {
+    fn support(&self, dir: &Vec3) -> Vec3 {
+        self.corners
+            .iter()
+            .copied()
+            .max_by(|a, b| a.dot(dir).total_cmp(&b.dot(dir)))
+            .unwrap()
+    }
+}
+
+/// classify a convex shape whose extent along plane normal `n` is `radius(n)`, measured from `center`
+fn classify_by_planes<S: Support<3>>(
+    frustum: &ViewFrustum,
+    shape: &S,
+    center: &Vec3,
+    radius: impl Fn(&Vec3) -> Real,
+) -> CullResult {
+    let mut inside = true;
+    for plane in &frustum.planes {
+        let dist = plane.signed_dist(center);
+        let r = radius(&plane.normal);
+        if dist > r {
+            return CullResult::Outside;
+        }
+        if dist > -r {
+            inside = false;
+        }
+    }
+
+    if inside {
+        CullResult::Inside
+    } else if gjk::closest_pts(frustum, shape).is_some() {
+        CullResult::Outside
+    } else {
+        CullResult::Intersecting
+    }
+}
+
+/// point on boundary is inside
+pub fn frustum_pt_classify(frustum: &ViewFrustum, pt: &Vec3) -> CullResult {
+    if frustum.planes.iter().all(|p| p.signed_dist(pt) <= 0.0) {
+        CullResult::Inside
+    } else {
+        CullResult::Outside
+    }
+}
+
+pub fn frustum_sphere_classify(frustum: &ViewFrustum, sphere: &Sphere) -> CullResult {
+    classify_by_planes(frustum, sphere, &sphere.center, |_| sphere.radius)
+}
+
+pub fn frustum_aabb_classify(frustum: &ViewFrustum, aabb: &Cube) -> CullResult {
+    classify_by_planes(frustum, aabb, &aabb.center, |n| {
+        (0..3).map(|i| aabb.half_len[i] * n[i].abs()).sum()
+    })
+}
+
+pub fn frustum_obb_classify(frustum: &ViewFrustum, obb: &OBB) -> CullResult {
+    classify_by_planes(frustum, obb, &obb.center, |n| {
+        (0..3).map(|i| obb.half_len[i] * n.dot(&obb.axes[i]).abs()).sum()
+    })
+}
+
+/// classify frustum `other` against `frustum`
+pub fn frustums_classify(frustum: &ViewFrustum, other: &ViewFrustum) -> CullResult {
+    let all_outside = |a: &ViewFrustum, b: &ViewFrustum| {
+        a.planes
+            .iter()
+            .any(|p| b.corners.iter().all(|c| p.signed_dist(c) > 0.0))
+    };
+    if all_outside(frustum, other) || all_outside(other, frustum) {
+        return CullResult::Outside;
+    }
+
+    if other
+        .corners
+        .iter()
+        .all(|c| frustum_pt_classify(frustum, c) == CullResult::Inside)
+    {
+        CullResult::Inside
+    } else if gjk::closest_pts(frustum, other).is_some() {
+        CullResult::Outside
+    } else {
+        CullResult::Intersecting
+    }
+}
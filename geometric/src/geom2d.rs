@@ -1,6 +1,7 @@
 use math::cg::EularRotationXY;
 use math::precision::Real;
 use math::{cg::Transformation2D, matrix::*};
+use std::f64::consts::PI;
 pub use crate::geom_common::{Linear2D, Line2D, Ray2D, Segment2D, Triangle2D, Circle};
 
 #[derive(Clone, Copy, Debug)]
@@ -41,6 +42,7 @@ impl AABB {
     }
 }
 
+#[derive(Clone, Copy, Debug)]
 pub struct OBB {
     pub center: Vec2,
     pub half_size: Vec2,
@@ -85,28 +87,198 @@ pub struct Ellipse {
     pub a: Real,
     pub b: Real,
     pub position: Vec2,
+    pub rotation: Real, // rotation of `a` axis from x axis, in radians
+}
+
+impl Ellipse {
+    pub fn new(position: Vec2, a: Real, b: Real) -> Self {
+        Self {
+            a,
+            b,
+            position,
+            rotation: 0.0,
+        }
+    }
+}
+
+/// simple polygon, points can be either clockwise or counter-clockwise
+#[derive(Clone, Debug)]
+pub struct Polygon {
+    pub points: Vec<Vec2>,
+}
+
+impl Polygon {
+    pub fn new(points: Vec<Vec2>) -> Self {
+        Self { points }
+    }
+
+    /// [shoelace formula](https://en.wikipedia.org/wiki/Shoelace_formula), positive if counter-clockwise
+    pub fn signed_area(&self) -> Real {
+        let n = self.points.len();
+        (0..n)
+            .map(|i| self.points[i].cross(&self.points[(i + 1) % n]))
+            .sum::<Real>()
+            * 0.5
+    }
 }
 
+/// `y^2 = 2px` in local coordinate, `position` is the vertex
 #[derive(Clone, Copy, Debug)]
 pub struct Parabola {
     pub p: Real,
     pub position: Vec2,
+    pub rotation: Real, // rotation of opening direction from x axis, in radians
+}
+
+impl Parabola {
+    pub fn new(position: Vec2, p: Real) -> Self {
+        Self {
+            p,
+            position,
+            rotation: 0.0,
+        }
+    }
 }
 
+/// `x^2/a^2 - y^2/b^2 = 1` in local coordinate, `position` is the center
 #[derive(Clone, Copy, Debug)]
 pub struct Hyperbola {
     pub a: Real,
     pub b: Real,
     pub position: Vec2,
+    pub rotation: Real, // rotation of `a` axis from x axis, in radians
+}
+
+impl Hyperbola {
+    pub fn new(position: Vec2, a: Real, b: Real) -> Self {
+        Self {
+            a,
+            b,
+            position,
+            rotation: 0.0,
+        }
+    }
 }
 
+#[derive(Clone, Copy, Debug)]
+pub enum Conic {
+    Ellipse(Ellipse),
+    Hyperbola(Hyperbola),
+    Parabola(Parabola),
+}
+
+/// arc going counter-clockwise from angle `range.0` to `range.1`(radians, from x axis), `range.1 - range.0` must be in `[0, 2PI]`
 #[derive(Clone, Copy, Debug)]
 pub struct CircleArc {
+    pub center: Vec2,
     pub radius: Real,
-    pub center: Vec3,
-    pub norm: Vec3,
-    pub x_axis: Vec3,
     pub range: (Real, Real),
 }
 
+impl CircleArc {
+    /// tolerance of `contain_angle` in radians
+    pub const ANGLE_EPSILON: Real = 1e-9;
+
+    pub fn new(center: Vec2, radius: Real, range: (Real, Real)) -> Self {
+        Self {
+            center,
+            radius,
+            range,
+        }
+    }
+
+    pub fn circle(&self) -> Circle {
+        Circle::new(self.center, self.radius)
+    }
+
+    pub fn sweep(&self) -> Real {
+        self.range.1 - self.range.0
+    }
+
+    pub fn length(&self) -> Real {
+        self.sweep() * self.radius
+    }
+
+    pub fn pt_at(&self, angle: Real) -> Vec2 {
+        self.center + Vec2::from_xy(angle.cos(), angle.sin()) * self.radius
+    }
+
+    pub fn start_pt(&self) -> Vec2 {
+        self.pt_at(self.range.0)
+    }
+
+    pub fn end_pt(&self) -> Vec2 {
+        self.pt_at(self.range.1)
+    }
+
+    /// whether `angle` is in range, angles differ by 2PI are the same
+    pub fn contain_angle(&self, angle: Real) -> bool {
+        let offset = (angle - self.range.0).rem_euclid(2.0 * PI);
+        offset <= self.sweep() + Self::ANGLE_EPSILON || offset >= 2.0 * PI - Self::ANGLE_EPSILON
+    }
+
+    pub fn aabb(&self) -> AABB {
+        let mut min = self.start_pt();
+        let mut max = min;
+        let end = self.end_pt();
+        let extremes = (0..4)
+            .map(|i| i as Real * PI / 2.0)
+            .filter(|a| self.contain_angle(*a))
+            .map(|a| self.pt_at(a));
+        for p in extremes.chain([end]) {
+            for i in 0..2 {
+                min[i] = min[i].min(p[i]);
+                max[i] = max[i].max(p[i]);
+            }
+        }
+        AABB::from_min_max(min, max)
+    }
+}
+
+/// ring between two concentric circles
+#[derive(Clone, Copy, Debug)]
+pub struct Annulus {
+    pub center: Vec2,
+    pub inner_radius: Real,
+    pub outer_radius: Real,
+}
+
+impl Annulus {
+    pub fn new(center: Vec2, inner_radius: Real, outer_radius: Real) -> Self {
+        Self {
+            center,
+            inner_radius,
+            outer_radius,
+        }
+    }
+}
+
+/// part of annulus going counter-clockwise from angle `range.0` to `range.1`, same convention as `CircleArc`
+#[derive(Clone, Copy, Debug)]
+pub struct AnnularSector {
+    pub center: Vec2,
+    pub inner_radius: Real,
+    pub outer_radius: Real,
+    pub range: (Real, Real),
+}
+
+impl AnnularSector {
+    pub fn new(center: Vec2, inner_radius: Real, outer_radius: Real, range: (Real, Real)) -> Self {
+        Self {
+            center,
+            inner_radius,
+            outer_radius,
+            range,
+        }
+    }
+
+    pub fn inner_arc(&self) -> CircleArc {
+        CircleArc::new(self.center, self.inner_radius, self.range)
+    }
+
+    pub fn outer_arc(&self) -> CircleArc {
+        CircleArc::new(self.center, self.outer_radius, self.range)
+    }
+}
+
 // TODO: implement B-Splin, Bezier Curve
@@ -3,6 +3,7 @@ use math::{coord::Cartesian3D, matrix::*, precision::Real};
 use std::ops::{Index, IndexMut};
 pub use crate::geom_common::{Linear3D, Line3D, Segment3D, Ray3D, Triangle3D, Sphere};
 
+#[derive(Clone, Copy, Debug)]
 pub struct Plane {
     pub normal: Vec3,
     pub pt: Vec3,
@@ -18,6 +19,7 @@ impl Plane {
     }
 }
 
+#[derive(Clone, Copy, Debug)]
 pub struct Line {
     pub start: Vec3,
     pub dir: Vec3, // normalized
@@ -76,6 +78,95 @@ impl Frustum {
             aspect,
         }
     }
+
+    /// place frustum at camera `frame`, camera looks at `-z` with `y` up. `half_fovy` is the vertical half angle and `aspect` is width / height
+    pub fn placed(&self, frame: &Cartesian3D) -> ViewFrustum {
+        let corners_at = |dist: Real| {
+            let half_h = dist * self.half_fovy.tan();
+            let half_w = half_h * self.aspect;
+            let center = frame.position() - frame.z_axis() * dist;
+            let (x, y) = (frame.x_axis() * half_w, frame.y_axis() * half_h);
+            [center - x - y, center + x - y, center + x + y, center - x + y]
+        };
+        let (near, far) = (corners_at(self.near), corners_at(self.far));
+        ViewFrustum::from_corners([near[0], near[1], near[2], near[3], far[0], far[1], far[2], far[3]])
+    }
+}
+
+/// frustum placed in world, bounded by 6 half spaces
+#[derive(Clone, Copy, Debug)]
+pub struct ViewFrustum {
+    /// left, right, bottom, top, near, far
+    pub planes: [HalfSpace; 6],
+    /// near left-bottom, right-bottom, right-top, left-top, then far ones in the same order
+    pub corners: [Vec3; 8],
+}
+
+impl ViewFrustum {
+    const LEFT: usize = 0;
+    const RIGHT: usize = 1;
+    const BOTTOM: usize = 2;
+    const TOP: usize = 3;
+    const NEAR: usize = 4;
+    const FAR: usize = 5;
+
+    /// corners in the same order as `corners` field
+    pub fn from_corners(corners: [Vec3; 8]) -> Self {
+        let center = corners.iter().fold(Vec3::zeros(), |acc, p| acc + *p) * 0.125;
+        // a face is given by 3 of its corners, normal is flipped to point away from center
+        let plane = |a: usize, b: usize, c: usize| {
+            let normal = (corners[b] - corners[a]).cross(&(corners[c] - corners[a])).normalize();
+            let normal = if normal.dot(&(center - corners[a])) > 0.0 { -normal } else { normal };
+            HalfSpace::new(normal, corners[a])
+        };
+
+        let mut planes = [HalfSpace::new(Vec3::zeros(), Vec3::zeros()); 6];
+        planes[Self::LEFT] = plane(0, 3, 4);
+        planes[Self::RIGHT] = plane(1, 2, 5);
+        planes[Self::BOTTOM] = plane(0, 1, 4);
+        planes[Self::TOP] = plane(3, 2, 7);
+        planes[Self::NEAR] = plane(0, 1, 2);
+        planes[Self::FAR] = plane(4, 5, 6);
+        Self { planes, corners }
+    }
+
+    /// extract planes from view-projection matrix by [Gribb-Hartmann method](https://www.gamedevs.org/uploads/fast-extraction-viewing-frustum-planes-from-world-view-projection-matrix.pdf).
+    /// matrix maps world point(column vector) to clip space, depth in `[-w, w]` if `zero_to_one_depth` is false, otherwise `[0, w]`
+    pub fn from_view_proj(m: &Mat44, zero_to_one_depth: bool) -> Self {
+        let row = |i: usize| Vec4::from_xyzw(m.get(0, i), m.get(1, i), m.get(2, i), m.get(3, i));
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+        // inside if `dot(coeff, [pt, 1]) >= 0`
+        let near = if zero_to_one_depth { z } else { w + z };
+        let coeffs = [w + x, w - x, w + y, w - y, near, w - z];
+
+        // `n . pt = d` for each plane, normal is outward
+        let eqs = coeffs.map(|c| {
+            let n = Vec3::from_xyz(c.x(), c.y(), c.z());
+            let len = n.length();
+            (-n / len, c.w() / len)
+        });
+        let planes = eqs.map(|(n, d)| HalfSpace::new(n, n * d));
+
+        let corner = |a: usize, b: usize, c: usize| {
+            let ((n1, d1), (n2, d2), (n3, d3)) = (eqs[a], eqs[b], eqs[c]);
+            (n2.cross(&n3) * d1 + n3.cross(&n1) * d2 + n1.cross(&n2) * d3) / n1.dot(&n2.cross(&n3))
+        };
+        let corners = [Self::NEAR, Self::FAR].map(|depth| {
+            [
+                corner(Self::LEFT, Self::BOTTOM, depth),
+                corner(Self::RIGHT, Self::BOTTOM, depth),
+                corner(Self::RIGHT, Self::TOP, depth),
+                corner(Self::LEFT, Self::TOP, depth),
+            ]
+        });
+        Self {
+            planes,
+            corners: [
+                corners[0][0], corners[0][1], corners[0][2], corners[0][3],
+                corners[1][0], corners[1][1], corners[1][2], corners[1][3],
+            ],
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -96,6 +187,40 @@ impl Cube {
     }
 }
 
+/// oriented bounding box, `axes` must be normalized and orthogonal to each other
+#[derive(Clone, Copy, Debug)]
+pub struct OBB {
+    pub center: Vec3,
+    pub half_len: Vec3,
+    pub axes: [Vec3; 3],
+}
+
+impl OBB {
+    pub fn new(center: Vec3, half_len: Vec3, axes: [Vec3; 3]) -> Self {
+        Self {
+            center,
+            half_len,
+            axes,
+        }
+    }
+
+    pub fn x_axis(&self) -> Vec3 {
+        self.axes[0]
+    }
+
+    pub fn y_axis(&self) -> Vec3 {
+        self.axes[1]
+    }
+
+    pub fn z_axis(&self) -> Vec3 {
+        self.axes[2]
+    }
+
+    pub fn get_coord(&self) -> Cartesian3D {
+        Cartesian3D::new(self.axes[0], self.axes[1], self.axes[2], self.center)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Polygon {
     pub points: Vec<Vec3>,
@@ -246,24 +371,163 @@ impl ConicArcInPolar {
     pub fn get_minor_axis_len(&self) -> Real {
         let a = self.get_major_axis_len();
         let c = self.get_focal_len();
-        (a * a - c * c).sqrt()
+        // `c > a` for hyperbola
+        (a * a - c * c).abs().sqrt()
     }
 
+    /// semi-latus rectum, `r = l / (1 - e * cos(theta))`
+    pub fn get_semi_latus_rectum(&self) -> Real {
+        self.e * self.p
+    }
+
+    /// whether `pt` is in the region enclosed by the whole curve(the side containing the focus at `origin`).
+    /// `pt` is projected onto the conic plane
     pub fn contain(&self, pt: &Vec3) -> bool {
+        // local coordinate with focus as origin, `axis` as x axis and `normal` as y axis
+        let dir = *pt - self.origin;
+        let (x, y) = (dir.dot(&self.axis), dir.dot(&self.normal));
+
+        if self.is_parabola() {
+            // directrix is `x = -p`
+            return y * y <= 2.0 * self.p * (x + self.p / 2.0);
+        }
+
+        // center is at `(c, 0)`, at right side of focus for ellipse and left side for hyperbola
         let a = self.get_major_axis_len();
         let b = self.get_minor_axis_len();
-        let c = (a * a - b * b).sqrt();
-
+        let c = self.get_focal_len();
+        let u = (x - c) * (x - c) / (a * a);
+        let v = y * y / (b * b);
         if self.is_ellipse() {
-            (pt.x() - c) * (pt.x() - c) / a * a + pt.y() * pt.y() / b * b <= 1.0
-        } else if self.is_hyperbola() {
-            (pt.x() - c) * (pt.x() - c) / a * a + pt.y() * pt.y() / b * b >= 1.0
+            u + v <= 1.0
         } else {
-            pt.y() * pt.y() - 2.0 * self.p * (pt.x() - self.p / 2.0) <= 0.0
+            u - v >= 1.0 && x > c
+        }
+    }
+
+    fn pt_at(&self, theta: Real) -> (Real, Real) {
+        let r = self.get_semi_latus_rectum() / (1.0 - self.e * theta.cos());
+        (r * theta.cos(), r * theta.sin())
+    }
+
+    /// same curve in cartesian form. `range` goes counter-clockwise, so end of range may be larger than 2PI after conversion
+    pub fn to_conic_arc(&self) -> ConicArc {
+        let l = self.get_semi_latus_rectum();
+        let (x_axis, normal) = (self.axis, self.normal);
+        let to_t = |theta: Real| {
+            let (x, y) = self.pt_at(theta);
+            if self.is_parabola() {
+                y / (2.0 * self.p)
+            } else if self.is_ellipse() {
+                let c = self.get_focal_len();
+                let a = self.get_major_axis_len();
+                (y / self.get_minor_axis_len()).atan2((x - c) / a)
+            } else {
+                (y / self.get_minor_axis_len()).atan()
+            }
+        };
+
+        let conic = if self.is_parabola() {
+            Conic::Parabola(Parabola {
+                x_axis,
+                normal,
+                p: self.p,
+                position: self.origin - self.axis * (self.p / 2.0),
+            })
+        } else if self.is_ellipse() {
+            let e2 = 1.0 - self.e * self.e;
+            Conic::Ellipse(Ellipse {
+                x_axis,
+                normal,
+                a: l / e2,
+                b: l / e2.sqrt(),
+                position: self.origin + self.axis * self.get_focal_len(),
+            })
+        } else {
+            let e2 = self.e * self.e - 1.0;
+            Conic::Hyperbola(Hyperbola {
+                x_axis,
+                normal,
+                a: l / e2,
+                b: l / e2.sqrt(),
+                position: self.origin + self.axis * self.get_focal_len(),
+            })
+        };
+
+        let start = to_t(self.range.0);
+        let mut end = to_t(self.range.1);
+        if self.is_ellipse() && end <= start {
+            end += 2.0 * std::f64::consts::PI;
+        }
+        ConicArc {
+            conic,
+            range: (start, end),
         }
     }
 }
 
+impl ConicArc {
+    /// polar angle around the focus used by `to_polar` of point at param `t`
+    fn polar_angle(&self, t: Real) -> Real {
+        match self.conic {
+            Conic::Ellipse(e) => {
+                let (a, b, t) = if e.a >= e.b {
+                    (e.a, e.b, t)
+                } else {
+                    (e.b, e.a, t - std::f64::consts::FRAC_PI_2)
+                };
+                let c = (a * a - b * b).sqrt();
+                (b * t.sin()).atan2(a * t.cos() + c)
+            }
+            Conic::Hyperbola(h) => {
+                let c = (h.a * h.a + h.b * h.b).sqrt();
+                (h.b * t.tan()).atan2(h.a / t.cos() - c)
+            }
+            Conic::Parabola(p) => (2.0 * p.p * t).atan2(2.0 * p.p * t * t - p.p / 2.0),
+        }
+    }
+
+    /// same curve in polar form with focus as origin.
+    /// `None` for circle, and for hyperbola arc not on the branch at positive x axis(polar form only covers one branch).
+    /// ellipse with `b > a` uses `normal` as major axis
+    pub fn to_polar(&self) -> Option<ConicArcInPolar> {
+        let (p, e, origin, axis, normal) = match self.conic {
+            Conic::Ellipse(e) => {
+                // rotate local coordinate so that major axis is x axis
+                let (a, b, axis, normal) = if e.a >= e.b {
+                    (e.a, e.b, e.x_axis, e.normal)
+                } else {
+                    (e.b, e.a, e.normal, -e.x_axis)
+                };
+                let c = (a * a - b * b).sqrt();
+                if c == 0.0 {
+                    return None;
+                }
+                (b * b / c, c / a, e.position - axis * c, axis, normal)
+            }
+            Conic::Hyperbola(h) => {
+                let half_pi = std::f64::consts::FRAC_PI_2;
+                if self.range.0.abs() >= half_pi || self.range.1.abs() >= half_pi {
+                    return None;
+                }
+                let c = (h.a * h.a + h.b * h.b).sqrt();
+                (h.b * h.b / c, c / h.a, h.position + h.x_axis * c, h.x_axis, h.normal)
+            }
+            Conic::Parabola(p) => (p.p, 1.0, p.position + p.x_axis * (p.p / 2.0), p.x_axis, p.normal),
+        };
+
+        Some(ConicArcInPolar::new(
+            p,
+            e,
+            origin,
+            axis,
+            normal,
+            (self.polar_angle(self.range.0), self.polar_angle(self.range.1)),
+        ))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
 pub struct Cylinder {
     pub bottom: Vec3,
     pub dir: Vec3, // normalized
@@ -271,6 +535,7 @@ pub struct Cylinder {
     pub radius: Real,
 }
 
+#[derive(Clone, Copy, Debug)]
 pub struct Cone {
     pub bottom: Vec3,
     pub bottom_radius: Real,
@@ -278,6 +543,7 @@ pub struct Cone {
     pub height: Real,
 }
 
+#[derive(Clone, Copy, Debug)]
 pub struct TruncatedCone {
     pub bottom: Vec3,
     pub bottom_radius: Real,
@@ -292,6 +558,7 @@ pub enum CylinderLike {
     TruncatedCone(TruncatedCone),
 }
 
+#[derive(Clone, Copy, Debug)]
 pub struct CircleArc {
     pub radius: Real,
     pub center: Vec3,
@@ -299,3 +566,126 @@ pub struct CircleArc {
     pub x_axis: Vec3,
     pub range: (Real, Real),
 }
+
+/// segment swept by a sphere
+#[derive(Clone, Copy, Debug)]
+pub struct Capsule {
+    pub start: Vec3,
+    pub end: Vec3,
+    pub radius: Real,
+}
+
+impl Capsule {
+    pub fn new(start: Vec3, end: Vec3, radius: Real) -> Self {
+        Self { start, end, radius }
+    }
+
+    pub fn segment(&self) -> Segment3D {
+        Segment3D::new(self.start, self.end)
+    }
+}
+
+/// `radii` are semi-axis lengths along `axes`, `axes` must be normalized, orthogonal and right-handed
+#[derive(Clone, Copy, Debug)]
+pub struct Ellipsoid {
+    pub center: Vec3,
+    pub radii: Vec3,
+    pub axes: [Vec3; 3],
+}
+
+impl Ellipsoid {
+    pub fn new(center: Vec3, radii: Vec3, axes: [Vec3; 3]) -> Self {
+        Self {
+            center,
+            radii,
+            axes,
+        }
+    }
+
+    pub fn get_coord(&self) -> Cartesian3D {
+        Cartesian3D::new(self.axes[0], self.axes[1], self.axes[2], self.center)
+    }
+}
+
+/// tube of `minor_radius` around a circle of `major_radius` lying on plane with `normal`
+#[derive(Clone, Copy, Debug)]
+pub struct Torus {
+    pub center: Vec3,
+    pub normal: Vec3, // normalized
+    pub major_radius: Real,
+    pub minor_radius: Real,
+}
+
+impl Torus {
+    pub fn new(center: Vec3, normal: Vec3, major_radius: Real, minor_radius: Real) -> Self {
+        Self {
+            center,
+            normal,
+            major_radius,
+            minor_radius,
+        }
+    }
+}
+
+/// axis-aligned box with rounded edges, it is the inner box(`half_len`) expanded by `radius`
+#[derive(Clone, Copy, Debug)]
+pub struct RoundedBox {
+    pub center: Vec3,
+    pub half_len: Vec3,
+    pub radius: Real,
+}
+
+impl RoundedBox {
+    pub fn new(center: Vec3, half_len: Vec3, radius: Real) -> Self {
+        Self {
+            center,
+            half_len,
+            radius,
+        }
+    }
+
+    pub fn inner(&self) -> Cube {
+        Cube::from_center(self.center, self.half_len)
+    }
+}
+
+/// points behind the plane, `normal` points outside
+#[derive(Clone, Copy, Debug)]
+pub struct HalfSpace {
+    pub normal: Vec3, // normalized
+    pub pt: Vec3,
+}
+
+impl HalfSpace {
+    pub fn new(normal: Vec3, pt: Vec3) -> Self {
+        Self { normal, pt }
+    }
+
+    /// signed distance to boundary plane, negative inside
+    pub fn signed_dist(&self, pt: &Vec3) -> Real {
+        self.normal.dot(&(*pt - self.pt))
+    }
+}
+
+/// infinite region between two parallel planes, `pt` is on the middle plane
+#[derive(Clone, Copy, Debug)]
+pub struct Slab {
+    pub normal: Vec3, // normalized
+    pub pt: Vec3,
+    pub half_thickness: Real,
+}
+
+impl Slab {
+    pub fn new(normal: Vec3, pt: Vec3, half_thickness: Real) -> Self {
+        Self {
+            normal,
+            pt,
+            half_thickness,
+        }
+    }
+
+    /// signed distance to the middle plane
+    pub fn signed_dist(&self, pt: &Vec3) -> Real {
+        self.normal.dot(&(*pt - self.pt))
+    }
+}
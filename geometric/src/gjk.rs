@@ -0,0 +1,194 @@
+//! [GJK](https://en.wikipedia.org/wiki/Gilbert%E2%80%93Johnson%E2%80%93Keerthi_distance_algorithm) distance between convex shapes
+//!
+//! closest point on simplex is found by checking every sub-simplex(at most 15 for a tetrahedron),
+//! which is simple and works in any dimension.
+
+use crate::shape::{Shape2D, Shape3D};
+use math::matrix::*;
+use math::precision::Real;
+
+const MAX_ITERATION: usize = 64;
+const EPSILON: Real = 1e-10;
+
+/// convex shape described by its support mapping
+pub trait Support<const DIM: usize> {
+    /// farthest point of shape along `dir`
+    fn support(&self, dir: &Vector<Real, DIM>) -> Vector<Real, DIM>;
+}
+
+impl<T: Shape2D> Support<2> for T {
+    fn support(&self, dir: &Vec2) -> Vec2 {
+        Shape2D::support(self, dir)
+    }
+}
+
+impl<T: Shape3D> Support<3> for T {
+    fn support(&self, dir: &Vec3) -> Vec3 {
+        Shape3D::support(self, dir)
+    }
+}
+
+/// shape moved by `offset` without copying it
+pub struct Translated<'a, S, const DIM: usize> {
+    pub shape: &'a S,
+    pub offset: Vector<Real, DIM>,
+}
+
+impl<S: Support<DIM>, const DIM: usize> Support<DIM> for Translated<'_, S, DIM> {
+    fn support(&self, dir: &Vector<Real, DIM>) -> Vector<Real, DIM> {
+        self.shape.support(dir) + self.offset
+    }
+}
+
+/// vertex of Minkowski difference `a - b`, remembers the support points it came from
+#[derive(Clone, Copy)]
+struct SimplexVertex<const DIM: usize> {
+    w: Vector<Real, DIM>,
+    a: Vector<Real, DIM>,
+    b: Vector<Real, DIM>,
+}
+
+/// solve `mat * x = rhs` by gaussian elimination with partial pivoting, `None` if singular
+fn solve(mut mat: Vec<Vec<Real>>, mut rhs: Vec<Real>) -> Option<Vec<Real>> {
+    let n = rhs.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|i, j| mat[*i][col].abs().total_cmp(&mat[*j][col].abs()))?;
+        if mat[pivot][col].abs() <= EPSILON {
+            return None;
+        }
+        mat.swap(col, pivot);
+        rhs.swap(col, pivot);
+        let pivot_row = mat[col].clone();
+        for row in col + 1..n {
+            let factor = mat[row][col] / pivot_row[col];
+            for (value, pivot) in mat[row].iter_mut().zip(&pivot_row).skip(col) {
+                *value -= factor * pivot;
+            }
+            rhs[row] -= factor * rhs[col];
+        }
+    }
+
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum: Real = (row + 1..n).map(|k| mat[row][k] * x[k]).sum();
+        x[row] = (rhs[row] - sum) / mat[row][row];
+    }
+    Some(x)
+}
+
+/// barycentric weights of the point in affine hull of `pts` closest to origin
+fn affine_closest<const DIM: usize>(pts: &[Vector<Real, DIM>]) -> Option<Vec<Real>> {
+    let edges: Vec<Vector<Real, DIM>> = pts[1..].iter().map(|p| *p - pts[0]).collect();
+    let mat = edges
+        .iter()
+        .map(|e1| edges.iter().map(|e2| e1.dot(e2)).collect())
+        .collect();
+    let rhs = edges.iter().map(|e| -e.dot(&pts[0])).collect();
+    let mu = solve(mat, rhs)?;
+    let mut lambda = vec![1.0 - mu.iter().sum::<Real>()];
+    lambda.extend(mu);
+    Some(lambda)
+}
+
+/// reduce simplex to the smallest face containing the point closest to origin, return weights of the face
+fn reduce_simplex<const DIM: usize>(
+    simplex: &[SimplexVertex<DIM>],
+) -> (Vec<SimplexVertex<DIM>>, Vec<Real>) {
+    let mut best: Option<(Real, Vec<SimplexVertex<DIM>>, Vec<Real>)> = None;
+    for mask in 1..(1u32 << simplex.len()) {
+        let face: Vec<SimplexVertex<DIM>> = (0..simplex.len())
+            .filter(|i| mask & (1 << i) != 0)
+            .map(|i| simplex[i])
+            .collect();
+        let pts: Vec<Vector<Real, DIM>> = face.iter().map(|v| v.w).collect();
+        let Some(lambda) = affine_closest(&pts) else {
+            continue;
+        };
+        if lambda.iter().any(|l| *l <= 0.0) {
+            continue;
+        }
+
+        let v = pts
+            .iter()
+            .zip(&lambda)
+            .fold(Vector::zeros(), |acc, (p, l)| acc + *p * *l);
+        let dist = v.length_sqrd();
+        if best.as_ref().is_none_or(|(d, _, _)| dist < *d) {
+            best = Some((dist, face, lambda));
+        }
+    }
+
+    // a vertex always has a valid weight, so `best` exists
+    let (_, face, lambda) = best.unwrap();
+    (face, lambda)
+}
+
+/// closest points between two convex shapes, `None` if they overlap(or touch within precision)
+pub fn closest_pts<A: Support<DIM>, B: Support<DIM>, const DIM: usize>(
+    a: &A,
+    b: &B,
+) -> Option<(Vector<Real, DIM>, Vector<Real, DIM>)> {
+    let vertex = |dir: &Vector<Real, DIM>| {
+        let pa = a.support(dir);
+        let pb = b.support(&-*dir);
+        SimplexVertex {
+            w: pa - pb,
+            a: pa,
+            b: pb,
+        }
+    };
+
+    let mut init_dir = Vector::zeros();
+    init_dir[0] = 1.0;
+    let mut simplex = vec![vertex(&init_dir)];
+    let mut lambda = vec![1.0];
+    let mut v = simplex[0].w;
+
+    for _ in 0..MAX_ITERATION {
+        let dist_sqrd = v.length_sqrd();
+        if dist_sqrd <= EPSILON * EPSILON {
+            return None;
+        }
+
+        let w = vertex(&-v);
+        // no support point is closer to origin than `v` by more than tolerance
+        if dist_sqrd - v.dot(&w.w) <= EPSILON * dist_sqrd
+            || simplex.iter().any(|s| (s.w - w.w).length_sqrd() <= EPSILON * EPSILON)
+        {
+            break;
+        }
+
+        simplex.push(w);
+        let (face, weights) = reduce_simplex(&simplex);
+        let new_v = face
+            .iter()
+            .zip(&weights)
+            .fold(Vector::zeros(), |acc, (s, l)| acc + s.w * *l);
+        if face.len() == DIM + 1 {
+            return None;
+        }
+        if new_v.length_sqrd() >= dist_sqrd {
+            // numerical stall, keep previous simplex
+            simplex.pop();
+            break;
+        }
+        simplex = face;
+        lambda = weights;
+        v = new_v;
+    }
+
+    let pa = simplex
+        .iter()
+        .zip(&lambda)
+        .fold(Vector::zeros(), |acc, (s, l)| acc + s.a * *l);
+    let pb = simplex
+        .iter()
+        .zip(&lambda)
+        .fold(Vector::zeros(), |acc, (s, l)| acc + s.b * *l);
+    Some((pa, pb))
+}
+
+/// distance between two convex shapes, 0 if they overlap
+pub fn distance<A: Support<DIM>, B: Support<DIM>, const DIM: usize>(a: &A, b: &B) -> Real {
+    closest_pts(a, b).map_or(0.0, |(pa, pb)| (pa - pb).length())
+}
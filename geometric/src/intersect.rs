@@ -0,0 +1,386 @@
+//! uniform intersection queries over pairs of shapes
+//!
+//! every pair is implemented in both argument orders, `intersects(a, b)` agrees with
+//! `intersection(a, b).is_some()` except concentric circles/spheres(which have no finite intersection).
+//! `AnyShape` dispatches the same queries at runtime.
+
+use crate::geom2d::{Circle, Line2D, Ray2D, Segment2D, AABB};
+use crate::geom3d::{CircleArc, Line, Line3D, Plane, Ray3D, Sphere, Triangle3D};
+use crate::{intersect2d, intersect3d, intersect_common};
+use math::matrix::*;
+use math::precision::Real;
+
+pub trait Intersects<B> {
+    fn intersects(&self, other: &B) -> bool;
+}
+
+pub trait Intersection<B> {
+    type Output;
+
+    /// `None` if shapes don't intersect
+    fn intersection(&self, other: &B) -> Option<Self::Output>;
+}
+
+pub fn intersects<A: Intersects<B>, B>(a: &A, b: &B) -> bool {
+    a.intersects(b)
+}
+
+pub fn intersection<A: Intersection<B>, B>(a: &A, b: &B) -> Option<A::Output> {
+    a.intersection(b)
+}
+
+fn pts<T>(pair: (T, Option<T>)) -> Vec<T> {
+    let (a, b) = pair;
+    std::iter::once(a).chain(b).collect()
+}
+
+fn param_pts<const DIM: usize>(
+    start: &Vector<Real, DIM>,
+    dir: &Vector<Real, DIM>,
+    pair: (Real, Option<Real>),
+) -> Vec<Vector<Real, DIM>> {
+    pts(pair).into_iter().map(|t| *start + *dir * t).collect()
+}
+
+/// implement `Intersects`/`Intersection` of (B, A) by swapping arguments of (A, B)
+macro_rules! symmetric {
+    ($a:ty, $b:ty) => {
+        impl Intersects<$a> for $b {
+            fn intersects(&self, other: &$a) -> bool {
+                other.intersects(self)
+            }
+        }
+
+        impl Intersection<$a> for $b {
+            type Output = <$a as Intersection<$b>>::Output;
+
+            fn intersection(&self, other: &$a) -> Option<Self::Output> {
+                other.intersection(self)
+            }
+        }
+    };
+}
+
+/// implement `Intersects` by `Intersection`
+macro_rules! intersects_by_intersection {
+    ($a:ty, $b:ty) => {
+        impl Intersects<$b> for $a {
+            fn intersects(&self, other: &$b) -> bool {
+                self.intersection(other).is_some()
+            }
+        }
+    };
+}
+
+impl Intersects<Circle> for Circle {
+    fn intersects(&self, other: &Circle) -> bool {
+        intersect2d::is_circles_intersect(self, other)
+    }
+}
+
+impl Intersection<Circle> for Circle {
+    type Output = Vec<Vec2>;
+
+    fn intersection(&self, other: &Circle) -> Option<Vec<Vec2>> {
+        let concentric = (self.center - other.center).length_sqrd() == 0.0;
+        if concentric || !self.intersects(other) {
+            return None;
+        }
+        Some(pts(intersect2d::circles_intersect(self, other)))
+    }
+}
+
+impl Intersects<AABB> for AABB {
+    fn intersects(&self, other: &AABB) -> bool {
+        intersect2d::is_rect_intersect(self, other)
+    }
+}
+
+impl Intersection<AABB> for AABB {
+    type Output = AABB;
+
+    fn intersection(&self, other: &AABB) -> Option<AABB> {
+        intersect2d::rect_intersect(self, other)
+    }
+}
+
+impl Intersection<Line2D> for Line2D {
+    type Output = Vec2;
+
+    fn intersection(&self, other: &Line2D) -> Option<Vec2> {
+        intersect2d::line_intersect(self, other)
+    }
+}
+intersects_by_intersection!(Line2D, Line2D);
+
+impl Intersection<Segment2D> for Line2D {
+    type Output = Vec2;
+
+    fn intersection(&self, other: &Segment2D) -> Option<Vec2> {
+        intersect2d::line_seg_intersect(other, self)
+    }
+}
+intersects_by_intersection!(Line2D, Segment2D);
+symmetric!(Line2D, Segment2D);
+
+impl Intersection<Ray2D> for Line2D {
+    type Output = Vec2;
+
+    fn intersection(&self, other: &Ray2D) -> Option<Vec2> {
+        intersect2d::line_ray_intersect(other, self)
+    }
+}
+intersects_by_intersection!(Line2D, Ray2D);
+symmetric!(Line2D, Ray2D);
+
+impl Intersection<Circle> for Line2D {
+    type Output = Vec<Vec2>;
+
+    fn intersection(&self, other: &Circle) -> Option<Vec<Vec2>> {
+        intersect2d::line_circle_intersect(self, other).map(pts)
+    }
+}
+intersects_by_intersection!(Line2D, Circle);
+symmetric!(Line2D, Circle);
+
+impl Intersection<Ray2D> for Ray2D {
+    type Output = Vec2;
+
+    fn intersection(&self, other: &Ray2D) -> Option<Vec2> {
+        intersect2d::rays_intersect(self, other)
+    }
+}
+intersects_by_intersection!(Ray2D, Ray2D);
+
+impl Intersects<Segment2D> for Ray2D {
+    fn intersects(&self, other: &Segment2D) -> bool {
+        intersect2d::is_ray_seg_intersect(self, other)
+    }
+}
+
+impl Intersection<Segment2D> for Ray2D {
+    type Output = Vec2;
+
+    fn intersection(&self, other: &Segment2D) -> Option<Vec2> {
+        intersect2d::ray_seg_intersect(other, self)
+    }
+}
+symmetric!(Ray2D, Segment2D);
+
+impl Intersects<Circle> for Ray2D {
+    fn intersects(&self, other: &Circle) -> bool {
+        intersect2d::is_ray_circle_intersect(self, other)
+    }
+}
+
+impl Intersection<Circle> for Ray2D {
+    type Output = Vec<Vec2>;
+
+    fn intersection(&self, other: &Circle) -> Option<Vec<Vec2>> {
+        intersect2d::ray_circle_intersect(self, other).map(pts)
+    }
+}
+symmetric!(Ray2D, Circle);
+
+impl Intersects<AABB> for Ray2D {
+    fn intersects(&self, other: &AABB) -> bool {
+        intersect2d::is_ray_aabb_intersect(self, other)
+    }
+}
+
+impl Intersection<AABB> for Ray2D {
+    type Output = Vec<Vec2>;
+
+    fn intersection(&self, other: &AABB) -> Option<Vec<Vec2>> {
+        intersect2d::ray_aabb_intersect(self, other).map(pts)
+    }
+}
+symmetric!(Ray2D, AABB);
+
+impl Intersection<Segment2D> for Segment2D {
+    type Output = Vec2;
+
+    fn intersection(&self, other: &Segment2D) -> Option<Vec2> {
+        intersect2d::seg_intersect(self, other)
+    }
+}
+intersects_by_intersection!(Segment2D, Segment2D);
+
+impl Intersection<Circle> for Segment2D {
+    type Output = Vec<Vec2>;
+
+    fn intersection(&self, other: &Circle) -> Option<Vec<Vec2>> {
+        intersect2d::segs_circle_intersect(self, other).map(pts)
+    }
+}
+intersects_by_intersection!(Segment2D, Circle);
+symmetric!(Segment2D, Circle);
+
+impl Intersects<Plane> for Plane {
+    fn intersects(&self, other: &Plane) -> bool {
+        !self.is_parallel(other)
+    }
+}
+
+impl Intersection<Plane> for Plane {
+    type Output = Line;
+
+    fn intersection(&self, other: &Plane) -> Option<Line> {
+        if self.is_parallel(other) {
+            None
+        } else {
+            Some(intersect3d::planes_intersect(self, other))
+        }
+    }
+}
+
+impl Intersects<Sphere> for Sphere {
+    fn intersects(&self, other: &Sphere) -> bool {
+        intersect_common::is_circular_intersect(self, other)
+    }
+}
+
+impl Intersection<Sphere> for Sphere {
+    type Output = CircleArc;
+
+    fn intersection(&self, other: &Sphere) -> Option<CircleArc> {
+        intersect3d::spheres_intersect(self, other)
+    }
+}
+
+impl Intersection<Sphere> for Line3D {
+    type Output = Vec<Vec3>;
+
+    fn intersection(&self, other: &Sphere) -> Option<Vec<Vec3>> {
+        intersect3d::line_sphere_intersect_param(self, other)
+            .map(|pair| param_pts(&self.start, &self.dir, pair))
+    }
+}
+intersects_by_intersection!(Line3D, Sphere);
+symmetric!(Line3D, Sphere);
+
+impl Intersection<Sphere> for Ray3D {
+    type Output = Vec<Vec3>;
+
+    fn intersection(&self, other: &Sphere) -> Option<Vec<Vec3>> {
+        intersect3d::ray_sphere_intersect_param(self, other)
+            .map(|pair| param_pts(&self.start, &self.dir, pair))
+    }
+}
+intersects_by_intersection!(Ray3D, Sphere);
+symmetric!(Ray3D, Sphere);
+
+impl Intersection<Triangle3D> for Ray3D {
+    type Output = Vec3;
+
+    fn intersection(&self, other: &Triangle3D) -> Option<Vec3> {
+        intersect3d::ray_triangle_intersect_param(self, other).map(|t| self.start + self.dir * t)
+    }
+}
+intersects_by_intersection!(Ray3D, Triangle3D);
+symmetric!(Ray3D, Triangle3D);
+
+/// shape chosen at runtime
+#[derive(Clone, Copy, Debug)]
+pub enum AnyShape {
+    Circle(Circle),
+    AABB(AABB),
+    Line2D(Line2D),
+    Ray2D(Ray2D),
+    Segment2D(Segment2D),
+    Sphere(Sphere),
+    Line3D(Line3D),
+    Ray3D(Ray3D),
+    Triangle3D(Triangle3D),
+    Plane(Plane),
+}
+
+#[derive(Clone, Debug)]
+pub enum ShapeIntersection {
+    Points2D(Vec<Vec2>),
+    Points3D(Vec<Vec3>),
+    Rect(AABB),
+    Line(Line),
+    Circle(CircleArc),
+}
+
+impl From<Vec2> for ShapeIntersection {
+    fn from(pt: Vec2) -> Self {
+        Self::Points2D(vec![pt])
+    }
+}
+
+impl From<Vec<Vec2>> for ShapeIntersection {
+    fn from(pts: Vec<Vec2>) -> Self {
+        Self::Points2D(pts)
+    }
+}
+
+impl From<Vec3> for ShapeIntersection {
+    fn from(pt: Vec3) -> Self {
+        Self::Points3D(vec![pt])
+    }
+}
+
+impl From<Vec<Vec3>> for ShapeIntersection {
+    fn from(pts: Vec<Vec3>) -> Self {
+        Self::Points3D(pts)
+    }
+}
+
+impl From<AABB> for ShapeIntersection {
+    fn from(rect: AABB) -> Self {
+        Self::Rect(rect)
+    }
+}
+
+impl From<CircleArc> for ShapeIntersection {
+    fn from(circle: CircleArc) -> Self {
+        Self::Circle(circle)
+    }
+}
+
+impl From<Line> for ShapeIntersection {
+    fn from(line: Line) -> Self {
+        Self::Line(line)
+    }
+}
+
+/// evaluate `$body` with `$a`, `$b` bound to the concrete shapes of a supported pair, `$default` otherwise
+macro_rules! dispatch {
+    ($lhs:expr, $rhs:expr, |$a:ident, $b:ident| $body:expr, $default:expr) => {
+        dispatch!(@pairs $lhs, $rhs, |$a, $b| $body, $default,
+            same: [Circle, AABB, Line2D, Ray2D, Segment2D, Sphere, Plane],
+            mixed: [
+                (Line2D, Segment2D), (Line2D, Ray2D), (Line2D, Circle),
+                (Ray2D, Segment2D), (Ray2D, Circle), (Ray2D, AABB), (Segment2D, Circle),
+                (Line3D, Sphere), (Ray3D, Sphere), (Ray3D, Triangle3D)
+            ])
+    };
+    (@pairs $lhs:expr, $rhs:expr, |$a:ident, $b:ident| $body:expr, $default:expr,
+        same: [$($s:ident),*], mixed: [$(($l:ident, $r:ident)),*]) => {
+        match ($lhs, $rhs) {
+            $((AnyShape::$s($a), AnyShape::$s($b)) => $body,)*
+            $((AnyShape::$l($a), AnyShape::$r($b)) => $body,)*
+            $((AnyShape::$r($a), AnyShape::$l($b)) => $body,)*
+            _ => $default,
+        }
+    };
+}
+
+impl AnyShape {
+    /// whether intersection between these two kinds of shapes is supported
+    pub fn supports(&self, other: &AnyShape) -> bool {
+        dispatch!(self, other, |_a, _b| true, false)
+    }
+
+    /// `false` for unsupported pairs
+    pub fn intersects(&self, other: &AnyShape) -> bool {
+        dispatch!(self, other, |a, b| a.intersects(b), false)
+    }
+
+    /// `None` for unsupported pairs
+    pub fn intersection(&self, other: &AnyShape) -> Option<ShapeIntersection> {
+        dispatch!(self, other, |a, b| a.intersection(b).map(Into::into), None)
+    }
+}
@@ -1,5 +1,6 @@
 use crate::intersect_common::*;
-use crate::utilitiy::approx_equal;
+use crate::conic::Quadratic;
+use crate::utilitiy::{approx_equal, polynomial_roots};
 use crate::{contain2d, distance2d, geom2d::*};
 use math::matrix::*;
 use math::precision::Real;
@@ -207,9 +208,10 @@ pub fn is_ray_seg_intersect(r: &Ray2D, seg: &Segment2D) -> bool {
 }
 
 pub fn seg_intersect(s1: &Segment2D, s2: &Segment2D) -> Option<Vec2> {
-    if let Some(p) = line_intersect(&Line2D::new(s1.start, s1.dir), &Line2D::new(s2.start, s2.dir)) {
+    if let Some(t) = line_intersect_param(&Line2D::new(s1.start, s1.dir), &Line2D::new(s2.start, s2.dir)) {
+        let p = s1.start + s1.dir * t;
         let proj = (p - s2.start).dot(&s2.dir);
-        if proj >= 0.0 && proj <= s2.len {
+        if t >= 0.0 && t <= s1.len && proj >= 0.0 && proj <= s2.len {
             Some(p)
         } else {
             None
@@ -312,3 +314,200 @@ pub fn rays_intersect(r1: &Ray2D, r2: &Ray2D) -> Option<Vec2> {
         None
     }
 }
+
+/// overlapped part of two rects, touching rects don't intersect(same as `is_rect_intersect`)
+pub fn rect_intersect(r1: &AABB, r2: &AABB) -> Option<AABB> {
+    if !is_rect_intersect(r1, r2) {
+        return None;
+    }
+
+    let (min1, max1) = (r1.min(), r1.max());
+    let (min2, max2) = (r2.min(), r2.max());
+    Some(AABB::from_min_max(
+        Vec2::from_xy(min1.x().max(min2.x()), min1.y().max(min2.y())),
+        Vec2::from_xy(max1.x().min(max2.x()), max1.y().min(max2.y())),
+    ))
+}
+
+/// [slab method](https://en.wikipedia.org/wiki/Slab_method), only returns exit param if ray starts inside aabb
+pub fn ray_aabb_intersect_param(r: &Ray2D, aabb: &AABB) -> Option<(Real, Option<Real>)> {
+    let (min, max) = (aabb.min(), aabb.max());
+    let mut t_min = Real::NEG_INFINITY;
+    let mut t_max = Real::INFINITY;
+    for i in 0..2 {
+        if r.dir[i].abs() <= Real::EPSILON {
+            if r.start[i] < min[i] || r.start[i] > max[i] {
+                return None;
+            }
+            continue;
+        }
+
+        let t1 = (min[i] - r.start[i]) / r.dir[i];
+        let t2 = (max[i] - r.start[i]) / r.dir[i];
+        t_min = t_min.max(t1.min(t2));
+        t_max = t_max.min(t1.max(t2));
+    }
+
+    if t_max < t_min || t_max < 0.0 {
+        None
+    } else if t_min < 0.0 || approx_equal(t_min, t_max, 6) {
+        Some((t_max, None))
+    } else {
+        Some((t_min, Some(t_max)))
+    }
+}
+
+pub fn ray_aabb_intersect(r: &Ray2D, aabb: &AABB) -> Option<(Vec2, Option<Vec2>)> {
+    let (a, b) = ray_aabb_intersect_param(r, aabb)?;
+    Some((r.start + r.dir * a, b.map(|b| r.start + r.dir * b)))
+}
+
+/// params along line where it meets conic, ascending. a tangent line gives one param
+pub fn line_conic_intersect_param(l: &Line2D, conic: &Conic) -> Vec<Real> {
+    conic.equation().line_param(&l.start, &l.dir)
+}
+
+pub fn line_conic_intersect(l: &Line2D, conic: &Conic) -> Vec<Vec2> {
+    line_conic_intersect_param(l, conic)
+        .into_iter()
+        .map(|t| l.start + l.dir * t)
+        .collect()
+}
+
+pub fn ray_conic_intersect_param(r: &Ray2D, conic: &Conic) -> Vec<Real> {
+    conic
+        .equation()
+        .line_param(&r.start, &r.dir)
+        .into_iter()
+        .filter(|t| *t >= 0.0)
+        .collect()
+}
+
+pub fn ray_conic_intersect(r: &Ray2D, conic: &Conic) -> Vec<Vec2> {
+    ray_conic_intersect_param(r, conic)
+        .into_iter()
+        .map(|t| r.start + r.dir * t)
+        .collect()
+}
+
+fn mul_quadratic(p: &Quadratic, q: &Quadratic) -> [Real; 5] {
+    let mut result = [0.0; 5];
+    for (i, a) in p.iter().enumerate() {
+        for (j, b) in q.iter().enumerate() {
+            result[i + j] += a * b;
+        }
+    }
+    result
+}
+
+/// intersect points of two conics, found by substituting rational parameterization of `c1` into equation of `c2`.
+/// identical conics give no point
+pub fn conics_intersect(c1: &Conic, c2: &Conic) -> Vec<Vec2> {
+    const EPSILON: Real = 1e-9;
+
+    let eq = c2.equation();
+    let ([x, y, w], missing) = c1.rational_param();
+    let terms = [
+        (eq.a, mul_quadratic(&x, &x)),
+        (eq.b, mul_quadratic(&x, &y)),
+        (eq.c, mul_quadratic(&y, &y)),
+        (eq.d, mul_quadratic(&x, &w)),
+        (eq.e, mul_quadratic(&y, &w)),
+        (eq.f, mul_quadratic(&w, &w)),
+    ];
+    let mut quartic = [0.0; 5];
+    // magnitude of the summed terms, to tell cancellation from a small coefficient
+    let mut scale: Real = 0.0;
+    for (factor, poly) in terms {
+        for (q, p) in quartic.iter_mut().zip(poly) {
+            *q += factor * p;
+            scale = scale.max((factor * p).abs());
+        }
+    }
+    // `c1` lies on `c2` entirely
+    if quartic.iter().all(|q| q.abs() <= EPSILON * scale) {
+        return vec![];
+    }
+
+    let eval = |p: &Quadratic, s: Real| (p[0] * s + p[1]) * s + p[2];
+    let mut pts: Vec<Vec2> = polynomial_roots(&quartic)
+        .into_iter()
+        .filter(|s| eval(&w, *s).abs() > EPSILON)
+        .map(|s| Vec2::from_xy(eval(&x, s), eval(&y, s)) / eval(&w, s))
+        .collect();
+    if let Some(p) = missing {
+        if eq.approx_dist(&p) <= EPSILON {
+            pts.push(p);
+        }
+    }
+    pts
+}
+
+fn filter_on_arc(pts: impl IntoIterator<Item = Vec2>, arc: &CircleArc) -> Vec<Vec2> {
+    pts.into_iter()
+        .filter(|p| {
+            let dir = *p - arc.center;
+            arc.contain_angle(dir.y().atan2(dir.x()))
+        })
+        .collect()
+}
+
+fn line_arc_intersect_in(start: &Vec2, dir: &Vec2, arc: &CircleArc, min: Real, max: Real) -> Vec<Vec2> {
+    let params = match line_circle_intersect_param(&Line2D::new(*start, *dir), &arc.circle()) {
+        Some((a, Some(b))) => vec![a, b],
+        Some((a, None)) => vec![a],
+        None => vec![],
+    };
+    filter_on_arc(
+        params
+            .into_iter()
+            .filter(|t| *t >= min && *t <= max)
+            .map(|t| *start + *dir * t),
+        arc,
+    )
+}
+
+pub fn line_arc_intersect(l: &Line2D, arc: &CircleArc) -> Vec<Vec2> {
+    line_arc_intersect_in(&l.start, &l.dir, arc, Real::NEG_INFINITY, Real::INFINITY)
+}
+
+pub fn ray_arc_intersect(r: &Ray2D, arc: &CircleArc) -> Vec<Vec2> {
+    line_arc_intersect_in(&r.start, &r.dir, arc, 0.0, Real::INFINITY)
+}
+
+pub fn seg_arc_intersect(s: &Segment2D, arc: &CircleArc) -> Vec<Vec2> {
+    line_arc_intersect_in(&s.start, &s.dir, arc, 0.0, s.len)
+}
+
+/// intersect points of two circle curves, concentric circles give no point
+fn circle_curves_intersect(c1: &Circle, c2: &Circle) -> Vec<Vec2> {
+    if c1.center == c2.center || !is_circles_intersect(c1, c2) {
+        return vec![];
+    }
+    match circles_intersect(c1, c2) {
+        (a, Some(b)) => vec![a, b],
+        (a, None) => vec![a],
+    }
+}
+
+pub fn circle_arc_intersect(c: &Circle, arc: &CircleArc) -> Vec<Vec2> {
+    filter_on_arc(circle_curves_intersect(c, &arc.circle()), arc)
+}
+
+/// intersect points of two arcs. arcs on the same circle overlap along a curve, ends of the overlapping parts are returned then
+pub fn arcs_intersect(a1: &CircleArc, a2: &CircleArc) -> Vec<Vec2> {
+    const EPSILON: Real = 1e-9;
+    let same_circle = (a1.center - a2.center).length() <= EPSILON * a1.radius.max(1.0)
+        && (a1.radius - a2.radius).abs() <= EPSILON * a1.radius.max(1.0);
+    if !same_circle {
+        return filter_on_arc(filter_on_arc(circle_curves_intersect(&a1.circle(), &a2.circle()), a1), a2);
+    }
+
+    let mut pts = filter_on_arc([a1.start_pt(), a1.end_pt()], a2);
+    for p in filter_on_arc([a2.start_pt(), a2.end_pt()], a1) {
+        if pts.iter().all(|q| (*q - p).length() > EPSILON * a1.radius.max(1.0)) {
+            pts.push(p);
+        }
+    }
+    pts
+}
@@ -1,19 +1,21 @@
+use math::matrix::*;
 use math::precision::Real;
 
 use crate::geom3d::*;
-use crate::geom_common::Sphere;
-use crate::utilitiy::approx_equal;
+use crate::geom_common::{Sphere, Triangle3D};
+use crate::intersect_common::is_circular_intersect;
+use crate::utilitiy::{approx_equal, polynomial_roots_in};
 
 pub fn planes_intersect(p1: &Plane, p2: &Plane) -> Line {
     let dir = p1.normal.cross(&p2.normal);
     let s1 = p1.normal.dot(&p1.pt);
-    let s2 = p1.normal.dot(&p1.pt);
+    let s2 = p2.normal.dot(&p2.pt);
     let dot = p1.normal.dot(&p2.normal);
     let base = dot * dot - 1.0;
     let a = (s2 * dot - s1) / base;
     let b = (s1 * dot - s2) / base;
 
-    Line::new(p1.normal * a + p2.normal * b, dir)
+    Line::new(p1.normal * a + p2.normal * b, dir.normalize())
 }
 
 pub fn line_sphere_intersect_param(r: &Line3D, c: &Sphere) -> Option<(Real, Option<Real>)> {
@@ -53,3 +55,273 @@ pub fn ray_sphere_intersect_param(r: &Ray3D, c: &Sphere) -> Option<(Real, Option
         None => None,
     }
 }
+
+/// [Möller–Trumbore algorithm](https://en.wikipedia.org/wiki/M%C3%B6ller%E2%80%93Trumbore_intersection_algorithm), hits both sides of triangle
+pub fn ray_triangle_intersect_param(r: &Ray3D, triangle: &Triangle3D) -> Option<Real> {
+    let e1 = triangle[1] - triangle[0];
+    let e2 = triangle[2] - triangle[0];
+    let p = r.dir.cross(&e2);
+    let det = e1.dot(&p);
+    if det.abs() <= Real::EPSILON {
+        return None;
+    }
+
+    let inv_det = 1.0 / det;
+    let s = r.start - triangle[0];
+    let u = s.dot(&p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = s.cross(&e1);
+    let v = r.dir.dot(&q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = e2.dot(&q) * inv_det;
+    if t >= 0.0 {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+/// intersection circle of two sphere surfaces, `None` if they don't intersect or are concentric
+pub fn spheres_intersect(s1: &Sphere, s2: &Sphere) -> Option<CircleArc> {
+    let dir = s2.center - s1.center;
+    let d = dir.length();
+    if d == 0.0 || !is_circular_intersect(s1, s2) {
+        return None;
+    }
+
+    let norm = dir / d;
+    let a = (d * d + s1.radius * s1.radius - s2.radius * s2.radius) / (2.0 * d);
+    // pick the axis least parallel to `norm` to build a perpendicular `x_axis`
+    let helper = if norm.x().abs() < 0.9 {
+        Vec3::x_axis()
+    } else {
+        Vec3::y_axis()
+    };
+    Some(CircleArc {
+        radius: (s1.radius * s1.radius - a * a).max(0.0).sqrt(),
+        center: s1.center + norm * a,
+        norm,
+        x_axis: norm.cross(&helper).normalize(),
+        range: (0.0, 2.0 * std::f64::consts::PI),
+    })
+}
+
+// solids below report the first part of ray inside them as `(enter, exit)` params,
+// `enter` is 0 if ray starts inside, `exit` may be infinity for unbounded solids
+
+/// clip interval of the whole line to the ray
+fn clip_to_ray(interval: Option<(Real, Real)>) -> Option<(Real, Real)> {
+    let (enter, exit) = interval?;
+    if exit < 0.0 {
+        None
+    } else {
+        Some((enter.max(0.0), exit))
+    }
+}
+
+fn merge_interval(a: Option<(Real, Real)>, b: Option<(Real, Real)>) -> Option<(Real, Real)> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some((a.0.min(b.0), a.1.max(b.1))),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+/// params where `a*t^2 + b*t + c <= 0`, `a` must be positive
+fn quadratic_interval(a: Real, b: Real, c: Real) -> Option<(Real, Real)> {
+    let delta = b * b - 4.0 * a * c;
+    if delta < 0.0 {
+        return None;
+    }
+    let delta = delta.sqrt();
+    Some(((-b - delta) / (2.0 * a), (-b + delta) / (2.0 * a)))
+}
+
+/// params where `lo <= start + t * dir <= hi`
+fn slab_interval(start: Real, dir: Real, lo: Real, hi: Real) -> Option<(Real, Real)> {
+    if dir == 0.0 {
+        return if (lo..=hi).contains(&start) {
+            Some((Real::NEG_INFINITY, Real::INFINITY))
+        } else {
+            None
+        };
+    }
+    let t1 = (lo - start) / dir;
+    let t2 = (hi - start) / dir;
+    Some((t1.min(t2), t1.max(t2)))
+}
+
+fn intersect_interval(a: (Real, Real), b: (Real, Real)) -> Option<(Real, Real)> {
+    let enter = a.0.max(b.0);
+    let exit = a.1.min(b.1);
+    if enter <= exit {
+        Some((enter, exit))
+    } else {
+        None
+    }
+}
+
+fn line_sphere_interval(r: &Ray3D, center: &Vec3, radius: Real) -> Option<(Real, Real)> {
+    let d = r.start - *center;
+    quadratic_interval(1.0, 2.0 * r.dir.dot(&d), d.length_sqrd() - radius * radius)
+}
+
+fn line_capsule_interval(r: &Ray3D, start: &Vec3, end: &Vec3, radius: Real) -> Option<(Real, Real)> {
+    let axis = *end - *start;
+    let len = axis.length();
+    let spheres = merge_interval(
+        line_sphere_interval(r, start, radius),
+        line_sphere_interval(r, end, radius),
+    );
+    if len == 0.0 {
+        return spheres;
+    }
+
+    // finite cylinder part: infinite cylinder clipped by planes of the two end caps
+    let axis = axis / len;
+    let o = r.start - *start;
+    let (ow, dw) = (o.dot(&axis), r.dir.dot(&axis));
+    let o_perp = o - axis * ow;
+    let d_perp = r.dir - axis * dw;
+    let a = d_perp.length_sqrd();
+    let c = o_perp.length_sqrd() - radius * radius;
+    let tube = if a <= Real::EPSILON {
+        if c <= 0.0 {
+            Some((Real::NEG_INFINITY, Real::INFINITY))
+        } else {
+            None
+        }
+    } else {
+        quadratic_interval(a, 2.0 * o_perp.dot(&d_perp), c)
+    };
+    let cylinder = tube
+        .zip(slab_interval(ow, dw, 0.0, len))
+        .and_then(|(tube, slab)| intersect_interval(tube, slab));
+
+    // capsule is convex, so union of its parts' intervals is a single interval
+    merge_interval(cylinder, spheres)
+}
+
+fn line_box_interval(r: &Ray3D, center: &Vec3, half_len: &Vec3) -> Option<(Real, Real)> {
+    let mut interval = (Real::NEG_INFINITY, Real::INFINITY);
+    for i in 0..3 {
+        let slab = slab_interval(
+            r.start[i],
+            r.dir[i],
+            center[i] - half_len[i],
+            center[i] + half_len[i],
+        )?;
+        interval = intersect_interval(interval, slab)?;
+    }
+    Some(interval)
+}
+
+pub fn ray_capsule_intersect_param(r: &Ray3D, capsule: &Capsule) -> Option<(Real, Real)> {
+    clip_to_ray(line_capsule_interval(
+        r,
+        &capsule.start,
+        &capsule.end,
+        capsule.radius,
+    ))
+}
+
+pub fn ray_ellipsoid_intersect_param(r: &Ray3D, ellipsoid: &Ellipsoid) -> Option<(Real, Real)> {
+    // scale ellipsoid to unit sphere, params along ray are unchanged
+    let o = r.start - ellipsoid.center;
+    let mut local_o = Vec3::zeros();
+    let mut local_d = Vec3::zeros();
+    for i in 0..3 {
+        local_o[i] = o.dot(&ellipsoid.axes[i]) / ellipsoid.radii[i];
+        local_d[i] = r.dir.dot(&ellipsoid.axes[i]) / ellipsoid.radii[i];
+    }
+    clip_to_ray(quadratic_interval(
+        local_d.length_sqrd(),
+        2.0 * local_o.dot(&local_d),
+        local_o.length_sqrd() - 1.0,
+    ))
+}
+
+/// rounded box is the union of the inner box expanded along each axis and capsules around its 12 edges
+pub fn ray_rounded_box_intersect_param(r: &Ray3D, rounded: &RoundedBox) -> Option<(Real, Real)> {
+    let mut interval = None;
+    for i in 0..3 {
+        let mut half_len = rounded.half_len;
+        half_len[i] += rounded.radius;
+        interval = merge_interval(interval, line_box_interval(r, &rounded.center, &half_len));
+
+        let (j, k) = ((i + 1) % 3, (i + 2) % 3);
+        for (sj, sk) in [(1.0, 1.0), (1.0, -1.0), (-1.0, 1.0), (-1.0, -1.0)] {
+            let mut offset = Vec3::zeros();
+            offset[j] = rounded.half_len[j] * sj;
+            offset[k] = rounded.half_len[k] * sk;
+            let mut along = Vec3::zeros();
+            along[i] = rounded.half_len[i];
+            let start = rounded.center + offset - along;
+            let end = rounded.center + offset + along;
+            interval = merge_interval(
+                interval,
+                line_capsule_interval(r, &start, &end, rounded.radius),
+            );
+        }
+    }
+    clip_to_ray(interval)
+}
+
+/// torus surface is a quartic in ray param, torus isn't convex so only the first part inside is reported
+pub fn ray_torus_intersect_param(r: &Ray3D, torus: &Torus) -> Option<(Real, Real)> {
+    let o = r.start - torus.center;
+    let d = r.dir;
+    let (big, small) = (torus.major_radius, torus.minor_radius);
+
+    // (|p|^2 + R^2 - r^2)^2 = 4R^2 * |p projected on torus plane|^2, p = o + t * d
+    let (on, dn) = (o.dot(&torus.normal), d.dot(&torus.normal));
+    let (o_perp, d_perp) = (o - torus.normal * on, d - torus.normal * dn);
+    let k = 2.0 * o.dot(&d);
+    let l = o.length_sqrd() + big * big - small * small;
+    let g = 4.0 * big * big * d_perp.length_sqrd();
+    let h = 8.0 * big * big * o_perp.dot(&d_perp);
+    let i = 4.0 * big * big * o_perp.length_sqrd();
+    let coeffs = [1.0, 2.0 * k, 2.0 * l + k * k - g, 2.0 * k * l - h, l * l - i];
+    let inside = |t: Real| {
+        let p = o + d * t;
+        let v = p.length_sqrd() + big * big - small * small;
+        let q = p - torus.normal * p.dot(&torus.normal);
+        v * v - 4.0 * big * big * q.length_sqrd() <= 0.0
+    };
+
+    // whole torus lies in its bounding sphere
+    let center_t = -o.dot(&d);
+    let bound = big + small + 1.0;
+    let mut ts = vec![center_t - bound];
+    ts.extend(polynomial_roots_in(&coeffs, center_t - bound, center_t + bound));
+    ts.push(center_t + bound);
+    ts.windows(2)
+        .map(|pair| (pair[0], pair[1]))
+        .filter(|(a, b)| *b >= 0.0 && inside((a + b) * 0.5))
+        .map(|(a, b)| (a.max(0.0), b))
+        .next()
+}
+
+pub fn ray_half_space_intersect_param(r: &Ray3D, half_space: &HalfSpace) -> Option<(Real, Real)> {
+    clip_to_ray(slab_interval(
+        half_space.signed_dist(&r.start),
+        half_space.normal.dot(&r.dir),
+        Real::NEG_INFINITY,
+        0.0,
+    ))
+}
+
+pub fn ray_slab_intersect_param(r: &Ray3D, slab: &Slab) -> Option<(Real, Real)> {
+    clip_to_ray(slab_interval(
+        slab.signed_dist(&r.start),
+        slab.normal.dot(&r.dir),
+        -slab.half_thickness,
+        slab.half_thickness,
+    ))
+}
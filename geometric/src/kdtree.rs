@@ -0,0 +1,234 @@
+//! [k-d tree](https://en.wikipedia.org/wiki/K-d_tree) over points
+//!
+//! `build` creates a balanced tree by median split, `insert` appends leaves without rebalancing
+//! and `remove` leaves a tombstone, tree is rebuilt when tombstones outnumber live points.
+
+use crate::bvh::BoundingBox;
+use crate::spatial_index::{KNearest, Slots, SpatialIndex};
+use math::matrix::*;
+use math::precision::Real;
+
+struct Node<const DIM: usize> {
+    pos: Vector<Real, DIM>,
+    // `None` if the point was removed
+    handle: Option<usize>,
+    axis: usize,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+pub struct KdTree<T, const DIM: usize> {
+    nodes: Vec<Node<DIM>>,
+    root: Option<usize>,
+    items: Slots<(Vector<Real, DIM>, usize, T)>,
+    tombstones: usize,
+}
+
+pub type KdTree2D<T> = KdTree<T, 2>;
+pub type KdTree3D<T> = KdTree<T, 3>;
+
+impl<T, const DIM: usize> Default for KdTree<T, DIM> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const DIM: usize> KdTree<T, DIM> {
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            root: None,
+            items: Slots::new(),
+            tombstones: 0,
+        }
+    }
+
+    /// build a balanced tree, handle of each value is its index in `points`
+    pub fn build(points: Vec<(Vector<Real, DIM>, T)>) -> Self {
+        let mut tree = Self::new();
+        for (pos, value) in points {
+            tree.items.insert((pos, 0, value));
+        }
+        tree.rebuild();
+        tree
+    }
+
+    /// rebalance tree and drop tombstones
+    pub fn rebuild(&mut self) {
+        self.nodes.clear();
+        self.tombstones = 0;
+        let mut refs: Vec<(usize, Vector<Real, DIM>)> =
+            self.items.iter().map(|(h, item)| (h, item.0)).collect();
+        self.root = self.build_recursive(&mut refs, 0);
+    }
+
+    fn build_recursive(&mut self, refs: &mut [(usize, Vector<Real, DIM>)], depth: usize) -> Option<usize> {
+        if refs.is_empty() {
+            return None;
+        }
+
+        let axis = depth % DIM;
+        let mid = refs.len() / 2;
+        refs.select_nth_unstable_by(mid, |a, b| a.1[axis].partial_cmp(&b.1[axis]).unwrap());
+        let (handle, pos) = refs[mid];
+
+        let node = self.nodes.len();
+        self.nodes.push(Node {
+            pos,
+            handle: Some(handle),
+            axis,
+            left: None,
+            right: None,
+        });
+        self.items.get_mut(handle).unwrap().1 = node;
+
+        let (left, right) = refs.split_at_mut(mid);
+        self.nodes[node].left = self.build_recursive(left, depth + 1);
+        self.nodes[node].right = self.build_recursive(&mut right[1..], depth + 1);
+        Some(node)
+    }
+
+    fn attach(&mut self, handle: usize, pos: Vector<Real, DIM>) {
+        let mut parent = None;
+        let mut next = self.root;
+        let mut depth = 0;
+        while let Some(idx) = next {
+            let node = &self.nodes[idx];
+            let go_left = pos[node.axis] < node.pos[node.axis];
+            parent = Some((idx, go_left));
+            next = if go_left { node.left } else { node.right };
+            depth += 1;
+        }
+
+        let node = self.nodes.len();
+        self.nodes.push(Node {
+            pos,
+            handle: Some(handle),
+            axis: depth % DIM,
+            left: None,
+            right: None,
+        });
+        self.items.get_mut(handle).unwrap().1 = node;
+
+        match parent {
+            Some((p, true)) => self.nodes[p].left = Some(node),
+            Some((p, false)) => self.nodes[p].right = Some(node),
+            None => self.root = Some(node),
+        }
+    }
+
+    fn detach(&mut self, node: usize) {
+        self.nodes[node].handle = None;
+        self.tombstones += 1;
+    }
+
+    fn rebuild_if_sparse(&mut self) {
+        if self.tombstones > self.items.len() {
+            self.rebuild();
+        }
+    }
+
+    fn visit_box(&self, bounds: &BoundingBox<DIM>, mut f: impl FnMut(usize, &Vector<Real, DIM>)) {
+        let mut stack: Vec<usize> = self.root.into_iter().collect();
+        while let Some(idx) = stack.pop() {
+            let node = &self.nodes[idx];
+            if let Some(h) = node.handle {
+                if bounds.contain_pt(&node.pos) {
+                    f(h, &node.pos);
+                }
+            }
+            let split = node.pos[node.axis];
+            if bounds.min[node.axis] <= split {
+                stack.extend(node.left);
+            }
+            if bounds.max[node.axis] >= split {
+                stack.extend(node.right);
+            }
+        }
+    }
+
+    fn nearest_recursive(&self, idx: usize, pt: &Vector<Real, DIM>, nearest: &mut KNearest) {
+        let node = &self.nodes[idx];
+        if let Some(h) = node.handle {
+            nearest.push((node.pos - *pt).length_sqrd(), h);
+        }
+
+        let diff = pt[node.axis] - node.pos[node.axis];
+        let (near, far) = if diff < 0.0 {
+            (node.left, node.right)
+        } else {
+            (node.right, node.left)
+        };
+        if let Some(near) = near {
+            self.nearest_recursive(near, pt, nearest);
+        }
+        if let Some(far) = far {
+            if diff * diff <= nearest.worst() {
+                self.nearest_recursive(far, pt, nearest);
+            }
+        }
+    }
+}
+
+impl<T, const DIM: usize> SpatialIndex<T, DIM> for KdTree<T, DIM> {
+    fn insert(&mut self, pos: Vector<Real, DIM>, value: T) -> usize {
+        let handle = self.items.insert((pos, 0, value));
+        self.attach(handle, pos);
+        handle
+    }
+
+    fn remove(&mut self, handle: usize) -> Option<T> {
+        let (_, node, value) = self.items.remove(handle)?;
+        self.detach(node);
+        self.rebuild_if_sparse();
+        Some(value)
+    }
+
+    fn update(&mut self, handle: usize, pos: Vector<Real, DIM>) -> bool {
+        let Some(node) = self.items.get(handle).map(|i| i.1) else {
+            return false;
+        };
+        self.items.get_mut(handle).unwrap().0 = pos;
+        self.detach(node);
+        self.attach(handle, pos);
+        self.rebuild_if_sparse();
+        true
+    }
+
+    fn get(&self, handle: usize) -> Option<&T> {
+        self.items.get(handle).map(|i| &i.2)
+    }
+
+    fn position(&self, handle: usize) -> Option<Vector<Real, DIM>> {
+        self.items.get(handle).map(|i| i.0)
+    }
+
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn query_box(&self, bounds: &BoundingBox<DIM>) -> Vec<usize> {
+        let mut result = Vec::new();
+        self.visit_box(bounds, |h, _| result.push(h));
+        result
+    }
+
+    fn query_radius(&self, center: &Vector<Real, DIM>, radius: Real) -> Vec<usize> {
+        let mut result = Vec::new();
+        let bounds = BoundingBox::from_pt(center).expand(radius);
+        self.visit_box(&bounds, |h, pos| {
+            if (*pos - *center).length_sqrd() <= radius * radius {
+                result.push(h);
+            }
+        });
+        result
+    }
+
+    fn nearest_k(&self, pt: &Vector<Real, DIM>, k: usize) -> Vec<usize> {
+        let mut nearest = KNearest::new(k);
+        if let Some(root) = self.root {
+            self.nearest_recursive(root, pt, &mut nearest);
+        }
+        nearest.into_handles()
+    }
+}
@@ -1,13 +1,30 @@
 //! this crate contains geometrics defination(2D and 3D), nearest point algorithm, distance algorithm and intersect algorithm between geometrics.
 
+pub mod bentley_ottmann;
+pub mod bounding2d;
+pub mod bounding3d;
+pub mod bvh;
+pub mod conic;
 pub mod contain2d;
+pub mod contain3d;
+pub mod culling;
 pub mod distance2d;
 pub mod geom2d;
 pub mod geom3d;
 mod geom_common;
+pub mod gjk;
+pub mod intersect;
 pub mod intersect2d;
 pub mod intersect3d;
+pub mod kdtree;
+pub mod loose_tree;
 pub mod nearest2d;
+pub mod nearest3d;
+pub mod polyline;
+pub mod shape;
+pub mod spatial_index;
+pub mod toi;
+pub mod triangulation;
 pub mod intersect_common;
 pub mod contain_common;
 pub mod distance_common;
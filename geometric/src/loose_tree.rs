@@ -0,0 +1,287 @@
+//! [loose quadtree/octree](https://anteru.net/blog/2008/loose-octrees/) for dynamic objects
+//!
+//! each node's loose bounds are twice its cell size, so an object is stored in the deepest node whose cell holds its center
+//! and whose half size is not smaller than the object's, it never needs to straddle nodes.
+
+use crate::bvh::BoundingBox;
+use crate::spatial_index::{KNearest, Slots, SpatialIndex};
+use math::matrix::*;
+use math::precision::Real;
+
+struct Node<const DIM: usize> {
+    center: Vector<Real, DIM>,
+    half_size: Vector<Real, DIM>,
+    parent: Option<usize>,
+    children: Vec<Option<usize>>,
+    items: Vec<usize>,
+}
+
+impl<const DIM: usize> Node<DIM> {
+    fn new(center: Vector<Real, DIM>, half_size: Vector<Real, DIM>, parent: Option<usize>) -> Self {
+        Self {
+            center,
+            half_size,
+            parent,
+            children: vec![None; 1 << DIM],
+            items: Vec::new(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.items.is_empty() && self.children.iter().all(|c| c.is_none())
+    }
+
+    fn loose_bounds(&self) -> BoundingBox<DIM> {
+        BoundingBox::new(
+            self.center - self.half_size * 2.0,
+            self.center + self.half_size * 2.0,
+        )
+    }
+
+    fn child_index(&self, pt: &Vector<Real, DIM>) -> usize {
+        (0..DIM).fold(0, |idx, i| {
+            if pt[i] >= self.center[i] {
+                idx | (1 << i)
+            } else {
+                idx
+            }
+        })
+    }
+
+    fn child_center(&self, idx: usize) -> Vector<Real, DIM> {
+        let mut center = self.center;
+        for i in 0..DIM {
+            let offset = self.half_size[i] * 0.5;
+            center[i] += if idx & (1 << i) != 0 { offset } else { -offset };
+        }
+        center
+    }
+}
+
+struct Item<T, const DIM: usize> {
+    bounds: BoundingBox<DIM>,
+    node: usize,
+    value: T,
+}
+
+pub struct LooseTree<T, const DIM: usize> {
+    nodes: Vec<Node<DIM>>,
+    /// slots of pruned nodes, reused by `locate`
+    free_nodes: Vec<usize>,
+    items: Slots<Item<T, DIM>>,
+    max_depth: u32,
+}
+
+pub type Quadtree<T> = LooseTree<T, 2>;
+pub type Octree<T> = LooseTree<T, 3>;
+
+impl<T, const DIM: usize> LooseTree<T, DIM> {
+    /// `world` is the region split by tree, objects outside it are kept in root
+    pub fn new(world: BoundingBox<DIM>, max_depth: u32) -> Self {
+        Self {
+            nodes: vec![Node::new(world.center(), world.size() * 0.5, None)],
+            free_nodes: Vec::new(),
+            items: Slots::new(),
+            max_depth,
+        }
+    }
+
+    /// insert an object with extent, point values inserted by `SpatialIndex::insert` have zero extent
+    pub fn insert_bounded(&mut self, bounds: BoundingBox<DIM>, value: T) -> usize {
+        let node = self.locate(&bounds);
+        let handle = self.items.insert(Item {
+            bounds,
+            node,
+            value,
+        });
+        self.nodes[node].items.push(handle);
+        handle
+    }
+
+    /// move an object with extent, returns `false` if handle is invalid
+    pub fn update_bounded(&mut self, handle: usize, bounds: BoundingBox<DIM>) -> bool {
+        let Some(old_node) = self.items.get(handle).map(|i| i.node) else {
+            return false;
+        };
+        let node = self.locate(&bounds);
+        if node != old_node {
+            // push first so pruning the old branch can't free the new node
+            self.nodes[node].items.push(handle);
+            self.detach(old_node, handle);
+        }
+        let item = self.items.get_mut(handle).unwrap();
+        item.bounds = bounds;
+        item.node = node;
+        true
+    }
+
+    /// nodes in use, empty nodes are freed when their last object leaves
+    pub fn node_count(&self) -> usize {
+        self.nodes.len() - self.free_nodes.len()
+    }
+
+    pub fn bounds_of(&self, handle: usize) -> Option<BoundingBox<DIM>> {
+        self.items.get(handle).map(|i| i.bounds)
+    }
+
+    /// handles of objects whose bounds overlap `bounds`
+    pub fn query_overlap(&self, bounds: &BoundingBox<DIM>) -> Vec<usize> {
+        let mut result = Vec::new();
+        self.visit(
+            |node_bounds| node_bounds.is_intersect(bounds),
+            |h, b| {
+                if b.is_intersect(bounds) {
+                    result.push(h);
+                }
+            },
+        );
+        result
+    }
+
+    fn locate(&mut self, bounds: &BoundingBox<DIM>) -> usize {
+        let center = bounds.center();
+        let half = bounds.size() * 0.5;
+
+        let mut idx = 0;
+        for _ in 0..self.max_depth {
+            let node = &self.nodes[idx];
+            let inside_cell = (0..DIM).all(|i| (center[i] - node.center[i]).abs() <= node.half_size[i]);
+            let fits_child = (0..DIM).all(|i| half[i] <= node.half_size[i] * 0.5);
+            if !inside_cell || !fits_child {
+                break;
+            }
+
+            let child = node.child_index(&center);
+            idx = match node.children[child] {
+                Some(c) => c,
+                None => {
+                    let new_node = Node::new(node.child_center(child), node.half_size * 0.5, Some(idx));
+                    let c = match self.free_nodes.pop() {
+                        Some(c) => {
+                            self.nodes[c] = new_node;
+                            c
+                        }
+                        None => {
+                            self.nodes.push(new_node);
+                            self.nodes.len() - 1
+                        }
+                    };
+                    self.nodes[idx].children[child] = Some(c);
+                    c
+                }
+            };
+        }
+        idx
+    }
+
+    /// remove `handle` from `node`, then free the emptied nodes up to the first one still in use
+    fn detach(&mut self, node: usize, handle: usize) {
+        let items = &mut self.nodes[node].items;
+        if let Some(i) = items.iter().position(|h| *h == handle) {
+            items.swap_remove(i);
+        }
+
+        let mut idx = node;
+        while let Some(parent) = self.nodes[idx].parent {
+            if !self.nodes[idx].is_empty() {
+                break;
+            }
+            let slot = self.nodes[parent].children.iter().position(|c| *c == Some(idx)).unwrap();
+            self.nodes[parent].children[slot] = None;
+            self.free_nodes.push(idx);
+            idx = parent;
+        }
+    }
+
+    fn visit(
+        &self,
+        node_test: impl Fn(&BoundingBox<DIM>) -> bool,
+        mut f: impl FnMut(usize, &BoundingBox<DIM>),
+    ) {
+        let mut stack = vec![0];
+        while let Some(idx) = stack.pop() {
+            let node = &self.nodes[idx];
+            // root also keeps objects outside the world region
+            if idx != 0 && !node_test(&node.loose_bounds()) {
+                continue;
+            }
+            for h in &node.items {
+                f(*h, &self.items.get(*h).unwrap().bounds);
+            }
+            stack.extend(node.children.iter().flatten());
+        }
+    }
+}
+
+impl<T, const DIM: usize> SpatialIndex<T, DIM> for LooseTree<T, DIM> {
+    fn insert(&mut self, pos: Vector<Real, DIM>, value: T) -> usize {
+        self.insert_bounded(BoundingBox::from_pt(&pos), value)
+    }
+
+    fn remove(&mut self, handle: usize) -> Option<T> {
+        let item = self.items.remove(handle)?;
+        self.detach(item.node, handle);
+        Some(item.value)
+    }
+
+    fn update(&mut self, handle: usize, pos: Vector<Real, DIM>) -> bool {
+        self.update_bounded(handle, BoundingBox::from_pt(&pos))
+    }
+
+    fn get(&self, handle: usize) -> Option<&T> {
+        self.items.get(handle).map(|i| &i.value)
+    }
+
+    /// center of the object's bounds
+    fn position(&self, handle: usize) -> Option<Vector<Real, DIM>> {
+        self.items.get(handle).map(|i| i.bounds.center())
+    }
+
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn query_box(&self, bounds: &BoundingBox<DIM>) -> Vec<usize> {
+        self.query_overlap(bounds)
+    }
+
+    fn query_radius(&self, center: &Vector<Real, DIM>, radius: Real) -> Vec<usize> {
+        let radius_sqrd = radius * radius;
+        let mut result = Vec::new();
+        self.visit(
+            |b| b.pt_dist_sqrd(center) <= radius_sqrd,
+            |h, b| {
+                if b.pt_dist_sqrd(center) <= radius_sqrd {
+                    result.push(h);
+                }
+            },
+        );
+        result
+    }
+
+    /// distance of an object is measured to its bounds
+    fn nearest_k(&self, pt: &Vector<Real, DIM>, k: usize) -> Vec<usize> {
+        let mut nearest = KNearest::new(k);
+        let mut stack = vec![0];
+        while let Some(idx) = stack.pop() {
+            let node = &self.nodes[idx];
+            if idx != 0 && node.loose_bounds().pt_dist_sqrd(pt) > nearest.worst() {
+                continue;
+            }
+            for h in &node.items {
+                nearest.push(self.items.get(*h).unwrap().bounds.pt_dist_sqrd(pt), *h);
+            }
+
+            let mut children: Vec<(Real, usize)> = node
+                .children
+                .iter()
+                .flatten()
+                .map(|c| (self.nodes[*c].loose_bounds().pt_dist_sqrd(pt), *c))
+                .collect();
+            // farthest first, so the nearest child is popped next
+            children.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+            stack.extend(children.into_iter().map(|c| c.1));
+        }
+        nearest.into_handles()
+    }
+}
@@ -1,5 +1,7 @@
 use crate::{geom_common::*, geom2d::*};
+use crate::utilitiy::polynomial_roots;
 use math::matrix::*;
+use math::precision::Real;
 
 
 pub fn pt2rect(pt: &Vec2, rect: &AABB) -> Vec2 {
@@ -15,8 +17,143 @@ pub fn pt2rect(pt: &Vec2, rect: &AABB) -> Vec2 {
     )
 }
 
-pub fn pt2triangle(_pt: &Vec2, _triangle: &Triangle2D) -> Vec2 {
-    todo!();
+pub fn pt2triangle(pt: &Vec2, triangle: &Triangle2D) -> Vec2 {
+    if crate::contain2d::is_triangle_contain_pt(pt, triangle) {
+        return *pt;
+    }
+
+    crate::nearest_common::pt2triangle(pt, triangle)
+}
+
+/// nearest point on the curve in local coordinate of ellipse with semi axes `a >= b`, `pt` is in first quadrant.
+/// [Eberly's robust point-ellipse distance](https://www.geometrictools.com/Documentation/DistancePointEllipseEllipsoid.pdf)
+fn pt2canonical_ellipse(x: Real, y: Real, a: Real, b: Real) -> (Real, Real) {
+    if y > 0.0 {
+        if x > 0.0 {
+            // root of g is where pt - nearest is along the normal. g is decreasing in `w = t + b^2 > 0`,
+            // bisect on `w` directly so tiny `w` near the major axis keeps its precision
+            let g = |w: Real| {
+                let u = a * x / (w + a * a - b * b);
+                let v = b * y / w;
+                u * u + v * v - 1.0
+            };
+            let (mut lo, mut hi) = (0.0, a * (x * x + y * y).sqrt() + b * b);
+            for _ in 0..256 {
+                let mid = (lo + hi) * 0.5;
+                if g(mid) > 0.0 {
+                    lo = mid;
+                } else {
+                    hi = mid;
+                }
+            }
+            let w = (lo + hi) * 0.5;
+            (a * a * x / (w + a * a - b * b), b * b * y / w)
+        } else {
+            (0.0, b)
+        }
+    } else if x < (a * a - b * b) / a {
+        let nx = a * a * x / (a * a - b * b);
+        (nx, b * (1.0 - (nx / a) * (nx / a)).max(0.0).sqrt())
+    } else {
+        (a, 0.0)
+    }
+}
+
+/// nearest point on the curve
+pub fn pt2ellipse(pt: &Vec2, ellipse: &Ellipse) -> Vec2 {
+    let conic = Conic::Ellipse(*ellipse);
+    let local = conic.to_local(pt);
+    let (x, y) = (local.x().abs(), local.y().abs());
+    let (nx, ny) = if ellipse.a >= ellipse.b {
+        pt2canonical_ellipse(x, y, ellipse.a, ellipse.b)
+    } else {
+        let (ny, nx) = pt2canonical_ellipse(y, x, ellipse.b, ellipse.a);
+        (nx, ny)
+    };
+    conic.to_world(&Vec2::from_xy(nx.copysign(local.x()), ny.copysign(local.y())))
+}
+
+/// nearest point on the curve, candidates solve `y^3 + 2p(p - x)y - 2p^2 y0 = 0` in local coordinate
+pub fn pt2parabola(pt: &Vec2, parabola: &Parabola) -> Vec2 {
+    let conic = Conic::Parabola(*parabola);
+    let local = conic.to_local(pt);
+    let p = parabola.p;
+    let to_curve = |y: Real| Vec2::from_xy(y * y / (2.0 * p), y);
+    let candidates = polynomial_roots(&[1.0, 0.0, 2.0 * p * (p - local.x()), -2.0 * p * p * local.y()]);
+    let nearest = candidates
+        .into_iter()
+        .map(to_curve)
+        .min_by(|a, b| (*a - local).length_sqrd().total_cmp(&(*b - local).length_sqrd()))
+        .unwrap_or_else(|| to_curve(0.0));
+    conic.to_world(&nearest)
 }
 
-// TODO: implement nearest pt to conic curve
+/// nearest point on the curve, it's always on the branch at the same side as `pt`
+pub fn pt2hyperbola(pt: &Vec2, hyperbola: &Hyperbola) -> Vec2 {
+    let conic = Conic::Hyperbola(*hyperbola);
+    let local = conic.to_local(pt);
+    let (x, y) = (local.x().abs(), local.y().abs());
+    let (a, b) = (hyperbola.a, hyperbola.b);
+
+    let (nx, ny) = if x == 0.0 {
+        let ny = y * b * b / (a * a + b * b);
+        (a * (1.0 + ny * ny / (b * b)).sqrt(), ny)
+    } else if y == 0.0 {
+        if x < (a * a + b * b) / a {
+            (a, 0.0)
+        } else {
+            let nx = x * a * a / (a * a + b * b);
+            (nx, b * ((nx / a) * (nx / a) - 1.0).max(0.0).sqrt())
+        }
+    } else {
+        // g is decreasing on (-a^2, b^2) from +inf to -inf. bisect on distance to the nearer end of the interval,
+        // so either `t + a^2` or `b^2 - t` keeps its precision when it is tiny
+        let g = |u: Real, v: Real| {
+            let gu = a * x / u;
+            let gv = b * y / v;
+            gu * gu - gv * gv - 1.0
+        };
+        let sum = a * a + b * b;
+        let half = sum * 0.5;
+        let near_left = g(half, sum - half) <= 0.0;
+        let (mut lo, mut hi) = (0.0, half);
+        for _ in 0..256 {
+            let mid = (lo + hi) * 0.5;
+            let value = if near_left { g(mid, sum - mid) } else { g(sum - mid, mid) };
+            if (value > 0.0) == near_left {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        let d = (lo + hi) * 0.5;
+        let (u, v) = if near_left { (d, sum - d) } else { (sum - d, d) };
+        (a * a * x / u, b * b * y / v)
+    };
+    conic.to_world(&Vec2::from_xy(nx.copysign(local.x()), ny.copysign(local.y())))
+}
+
+/// nearest point on the curve
+pub fn pt2conic(pt: &Vec2, conic: &Conic) -> Vec2 {
+    match conic {
+        Conic::Ellipse(e) => pt2ellipse(pt, e),
+        Conic::Hyperbola(h) => pt2hyperbola(pt, h),
+        Conic::Parabola(p) => pt2parabola(pt, p),
+    }
+}
+
+/// nearest point on arc, it's either the projection onto circle or one of the arc ends.
+/// every point is nearest if `pt` is the center, returns the start point then
+pub fn pt2arc(pt: &Vec2, arc: &CircleArc) -> Vec2 {
+    let dir = *pt - arc.center;
+    if dir.length_sqrd() > 0.0 && arc.contain_angle(dir.y().atan2(dir.x())) {
+        return arc.center + dir.normalize() * arc.radius;
+    }
+
+    let (start, end) = (arc.start_pt(), arc.end_pt());
+    if (start - *pt).length_sqrd() <= (end - *pt).length_sqrd() {
+        start
+    } else {
+        end
+    }
+}
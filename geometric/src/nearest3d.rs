@@ -0,0 +1,107 @@
+use crate::{contain3d::*, geom3d::*};
+use math::matrix::*;
+use math::precision::Real;
+
+/// pull `pt` back to distance `radius` from `core`, where `core` is the nearest point on the shape's skeleton
+fn inflate(pt: &Vec3, core: Vec3, radius: Real) -> Vec3 {
+    let dir = *pt - core;
+    let len = dir.length();
+    if len <= radius {
+        *pt
+    } else {
+        core + dir * (radius / len)
+    }
+}
+
+pub fn pt2cube(pt: &Vec3, cube: &Cube) -> Vec3 {
+    let mut result = *pt;
+    for i in 0..3 {
+        result[i] = pt[i].clamp(
+            cube.center[i] - cube.half_len[i],
+            cube.center[i] + cube.half_len[i],
+        );
+    }
+    result
+}
+
+pub fn pt2capsule(pt: &Vec3, capsule: &Capsule) -> Vec3 {
+    let core = crate::nearest_common::pt2segment(pt, &capsule.segment());
+    inflate(pt, core, capsule.radius)
+}
+
+pub fn pt2rounded_box(pt: &Vec3, rounded: &RoundedBox) -> Vec3 {
+    inflate(pt, pt2cube(pt, &rounded.inner()), rounded.radius)
+}
+
+pub fn pt2torus(pt: &Vec3, torus: &Torus) -> Vec3 {
+    let dir = *pt - torus.center;
+    let radial = dir - torus.normal * dir.dot(&torus.normal);
+    // every point of the tube circle is nearest for points on the axis, pick any
+    let radial = if radial.length_sqrd() > 0.0 {
+        radial.normalize()
+    } else {
+        (crate::misc::get_arbitrary_from_plane(&torus.normal, &torus.center) - torus.center)
+            .normalize()
+    };
+    inflate(
+        pt,
+        torus.center + radial * torus.major_radius,
+        torus.minor_radius,
+    )
+}
+
+/// [Eberly's robust point-ellipsoid distance](https://www.geometrictools.com/Documentation/DistancePointEllipseEllipsoid.pdf), solved by bisection
+pub fn pt2ellipsoid(pt: &Vec3, ellipsoid: &Ellipsoid) -> Vec3 {
+    if is_ellipsoid_contain_pt(pt, ellipsoid) {
+        return *pt;
+    }
+
+    let dir = *pt - ellipsoid.center;
+    let y = Vec3::from_xyz(
+        dir.dot(&ellipsoid.axes[0]),
+        dir.dot(&ellipsoid.axes[1]),
+        dir.dot(&ellipsoid.axes[2]),
+    );
+    let r = ellipsoid.radii;
+
+    // f is decreasing on t >= 0, f(0) > 0 outside and f(max(r) * |y|) <= 0
+    let f = |t: Real| -> Real {
+        (0..3)
+            .map(|i| {
+                let v = r[i] * y[i] / (t + r[i] * r[i]);
+                v * v
+            })
+            .sum::<Real>()
+            - 1.0
+    };
+    let mut lo = 0.0;
+    let mut hi = r.x().max(r.y()).max(r.z()) * y.length();
+    for _ in 0..128 {
+        let mid = (lo + hi) * 0.5;
+        if f(mid) > 0.0 {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    let t = (lo + hi) * 0.5;
+    (0..3).fold(ellipsoid.center, |acc, i| {
+        acc + ellipsoid.axes[i] * (r[i] * r[i] * y[i] / (t + r[i] * r[i]))
+    })
+}
+
+pub fn pt2half_space(pt: &Vec3, half_space: &HalfSpace) -> Vec3 {
+    let d = half_space.signed_dist(pt);
+    if d <= 0.0 {
+        *pt
+    } else {
+        *pt - half_space.normal * d
+    }
+}
+
+pub fn pt2slab(pt: &Vec3, slab: &Slab) -> Vec3 {
+    let d = slab.signed_dist(pt);
+    let clamped = d.clamp(-slab.half_thickness, slab.half_thickness);
+    *pt - slab.normal * (d - clamped)
+}
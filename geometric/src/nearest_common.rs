@@ -29,3 +29,49 @@ pub fn pt2segment<const DIM: usize>(pt: &Vector<Real, DIM>, seg: &Segment<DIM>)
     let t = pt2segment_param(pt, seg);
     seg.start + seg.dir * t
 }
+
+/// [Ericson's closest point on triangle](https://www.r-5.org/files/books/computers/algo-list/realtime-3d/Christer_Ericson-Real-Time_Collision_Detection-EN.pdf)(Real-Time Collision Detection 5.1.5)
+pub fn pt2triangle<const DIM: usize>(pt: &Vector<Real, DIM>, triangle: &Triangle<DIM>) -> Vector<Real, DIM> {
+    let (a, b, c) = (triangle[0], triangle[1], triangle[2]);
+    let ab = b - a;
+    let ac = c - a;
+    let ap = *pt - a;
+
+    let d1 = ab.dot(&ap);
+    let d2 = ac.dot(&ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+
+    let bp = *pt - b;
+    let d3 = ab.dot(&bp);
+    let d4 = ac.dot(&bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+
+    let cp = *pt - c;
+    let d5 = ab.dot(&cp);
+    let d6 = ac.dot(&cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    let denom = 1.0 / (va + vb + vc);
+    a + ab * (vb * denom) + ac * (vc * denom)
+}
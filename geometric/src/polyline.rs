@@ -0,0 +1,246 @@
+//! simplification, resampling and smoothing of 2D/3D polylines
+
+use math::matrix::*;
+use math::precision::Real;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// squared distance from `pt` to segment `ab`, works for degenerated segment
+fn pt2seg_sqrd<const DIM: usize>(
+    pt: &Vector<Real, DIM>,
+    a: &Vector<Real, DIM>,
+    b: &Vector<Real, DIM>,
+) -> Real {
+    let ab = *b - *a;
+    let len_sqrd = ab.length_sqrd();
+    let t = if len_sqrd <= Real::EPSILON {
+        0.0
+    } else {
+        ((*pt - *a).dot(&ab) / len_sqrd).clamp(0.0, 1.0)
+    };
+    (*a + ab * t - *pt).length_sqrd()
+}
+
+fn triangle_area<const DIM: usize>(
+    a: &Vector<Real, DIM>,
+    b: &Vector<Real, DIM>,
+    c: &Vector<Real, DIM>,
+) -> Real {
+    let u = *b - *a;
+    let v = *c - *a;
+    let dot = u.dot(&v);
+    0.5 * (u.length_sqrd() * v.length_sqrd() - dot * dot).max(0.0).sqrt()
+}
+
+/// [Douglas-Peucker algorithm](https://en.wikipedia.org/wiki/Ramer%E2%80%93Douglas%E2%80%93Peucker_algorithm)
+///
+/// keeps endpoints, every removed point lies within `tolerance` of the result
+pub fn douglas_peucker<const DIM: usize>(
+    pts: &[Vector<Real, DIM>],
+    tolerance: Real,
+) -> Vec<Vector<Real, DIM>> {
+    if pts.len() <= 2 {
+        return pts.to_vec();
+    }
+
+    let mut keep = vec![false; pts.len()];
+    keep[0] = true;
+    keep[pts.len() - 1] = true;
+
+    let tolerance_sqrd = tolerance * tolerance;
+    let mut stack = vec![(0, pts.len() - 1)];
+    while let Some((first, last)) = stack.pop() {
+        let mut max_dist = 0.0;
+        let mut max_idx = first;
+        for i in first + 1..last {
+            let dist = pt2seg_sqrd(&pts[i], &pts[first], &pts[last]);
+            if dist > max_dist {
+                max_dist = dist;
+                max_idx = i;
+            }
+        }
+
+        if max_dist > tolerance_sqrd {
+            keep[max_idx] = true;
+            stack.push((first, max_idx));
+            stack.push((max_idx, last));
+        }
+    }
+
+    pts.iter()
+        .zip(keep)
+        .filter_map(|(p, k)| if k { Some(*p) } else { None })
+        .collect()
+}
+
+#[derive(PartialEq)]
+struct AreaEntry {
+    area: Real,
+    idx: usize,
+}
+
+impl Eq for AreaEntry {}
+
+impl PartialOrd for AreaEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for AreaEntry {
+    // reversed, so `BinaryHeap` pops the smallest area first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .area
+            .total_cmp(&self.area)
+            .then(other.idx.cmp(&self.idx))
+    }
+}
+
+/// [Visvalingam-Whyatt algorithm](https://en.wikipedia.org/wiki/Visvalingam%E2%80%93Whyatt_algorithm)
+///
+/// repeatedly removes the point forming the smallest triangle with its neighbors until every triangle's area reaches `area_tolerance`, keeps endpoints
+pub fn visvalingam_whyatt<const DIM: usize>(
+    pts: &[Vector<Real, DIM>],
+    area_tolerance: Real,
+) -> Vec<Vector<Real, DIM>> {
+    let n = pts.len();
+    if n <= 2 {
+        return pts.to_vec();
+    }
+
+    let mut prev: Vec<usize> = (0..n).map(|i| i.wrapping_sub(1)).collect();
+    let mut next: Vec<usize> = (1..=n).collect();
+    let mut area: Vec<Real> = vec![Real::INFINITY; n];
+    let mut removed = vec![false; n];
+
+    let mut heap = BinaryHeap::new();
+    for i in 1..n - 1 {
+        area[i] = triangle_area(&pts[i - 1], &pts[i], &pts[i + 1]);
+        heap.push(AreaEntry { area: area[i], idx: i });
+    }
+
+    // effective area never decreases, so a removed point can't be smaller than the last removed one
+    let mut last_area: Real = 0.0;
+    while let Some(AreaEntry { area: a, idx }) = heap.pop() {
+        if removed[idx] || a != area[idx] {
+            continue;
+        }
+        if a.max(last_area) >= area_tolerance {
+            break;
+        }
+        last_area = last_area.max(a);
+
+        removed[idx] = true;
+        let (p, q) = (prev[idx], next[idx]);
+        next[p] = q;
+        prev[q] = p;
+
+        for i in [p, q] {
+            if i == 0 || i == n - 1 {
+                continue;
+            }
+            area[i] = triangle_area(&pts[prev[i]], &pts[i], &pts[next[i]]).max(last_area);
+            heap.push(AreaEntry { area: area[i], idx: i });
+        }
+    }
+
+    pts.iter()
+        .zip(removed)
+        .filter_map(|(p, r)| if r { None } else { Some(*p) })
+        .collect()
+}
+
+pub fn polyline_length<const DIM: usize>(pts: &[Vector<Real, DIM>]) -> Real {
+    pts.windows(2).map(|w| (w[1] - w[0]).length()).sum()
+}
+
+/// resample by arc length so adjacent points are `spacing` apart along the polyline(the last interval may be shorter), keeps endpoints
+pub fn resample_uniform<const DIM: usize>(
+    pts: &[Vector<Real, DIM>],
+    spacing: Real,
+) -> Vec<Vector<Real, DIM>> {
+    if pts.len() <= 1 || spacing <= 0.0 {
+        return pts.to_vec();
+    }
+
+    let mut result = vec![pts[0]];
+    let mut next_at = spacing;
+    let mut walked = 0.0;
+    for w in pts.windows(2) {
+        let dir = w[1] - w[0];
+        let len = dir.length();
+        while next_at <= walked + len {
+            result.push(w[0] + dir * ((next_at - walked) / len));
+            next_at += spacing;
+        }
+        walked += len;
+    }
+
+    let last = *pts.last().unwrap();
+    if (*result.last().unwrap() - last).length() > spacing * 1e-6 {
+        result.push(last);
+    } else {
+        *result.last_mut().unwrap() = last;
+    }
+    result
+}
+
+/// resample into `count` points evenly spaced by arc length
+pub fn resample_count<const DIM: usize>(
+    pts: &[Vector<Real, DIM>],
+    count: usize,
+) -> Vec<Vector<Real, DIM>> {
+    if pts.len() <= 1 || count < 2 {
+        return pts.to_vec();
+    }
+
+    let total = polyline_length(pts);
+    let mut result = resample_uniform(pts, total / (count - 1) as Real);
+    result.truncate(count - 1);
+    result.push(*pts.last().unwrap());
+    result
+}
+
+fn chaikin_step<const DIM: usize>(pts: &[Vector<Real, DIM>]) -> Vec<Vector<Real, DIM>> {
+    let mut result = Vec::with_capacity(pts.len() * 2);
+    result.push(pts[0]);
+    for w in pts.windows(2) {
+        result.push(w[0] * 0.75 + w[1] * 0.25);
+        result.push(w[0] * 0.25 + w[1] * 0.75);
+    }
+    result.push(*pts.last().unwrap());
+    result
+}
+
+/// [Chaikin's corner cutting](https://www.cs.unc.edu/~dm/UNC/COMP258/LECTURES/Chaikins-Algorithm.pdf), keeps endpoints
+///
+/// cuts corners until one more iteration moves the curve less than `tolerance`(at most 16 iterations)
+pub fn chaikin_smooth<const DIM: usize>(
+    pts: &[Vector<Real, DIM>],
+    tolerance: Real,
+) -> Vec<Vector<Real, DIM>> {
+    const MAX_ITERATION: usize = 16;
+    if pts.len() <= 2 {
+        return pts.to_vec();
+    }
+
+    let mut result = pts.to_vec();
+    for _ in 0..MAX_ITERATION {
+        let refined = chaikin_step(&result);
+        // each old corner is replaced by the midpoint of its two cut points
+        let moved = result[1..result.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let mid = (refined[2 * i + 2] + refined[2 * i + 3]) * 0.5;
+                (mid - *p).length()
+            })
+            .fold(0.0, Real::max);
+        result = refined;
+        if moved < tolerance {
+            break;
+        }
+    }
+    result
+}
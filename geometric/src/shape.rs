@@ -0,0 +1,738 @@
+//! common queries shared by all 2D/3D shapes
+//!
+//! `transformed` only accepts similarity transforms(uniform scale, rotation, translation),
+//! so circles stay circles and boxes stay boxes.
+
+use crate::geom2d::{self, AnnularSector, Annulus, Circle, Ellipse, Polygon, Triangle2D, AABB};
+use crate::geom3d::{self, Cone, Cube, Cylinder, Sphere, TruncatedCone};
+use math::cg::{EularRotationXY, EularRotationXYZ, Transformation2D, Transformation3D};
+use math::matrix::*;
+use math::precision::Real;
+use std::f64::consts::PI;
+
+/// scale first, then rotate, then translate
+#[derive(Clone, Copy, Debug)]
+pub struct Transform2D {
+    pub translation: Vec2,
+    pub rotation: Real, // radians
+    pub scale: Real,
+}
+
+impl Default for Transform2D {
+    fn default() -> Self {
+        Self::new(Vec2::zeros(), 0.0, 1.0)
+    }
+}
+
+impl Transform2D {
+    pub fn new(translation: Vec2, rotation: Real, scale: Real) -> Self {
+        Self {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    pub fn rotate_vec(&self, v: &Vec2) -> Vec2 {
+        EularRotationXY::new(self.rotation).get_mat() * *v
+    }
+
+    pub fn apply_vec(&self, v: &Vec2) -> Vec2 {
+        self.rotate_vec(v) * self.scale
+    }
+
+    pub fn apply_pt(&self, p: &Vec2) -> Vec2 {
+        self.apply_vec(p) + self.translation
+    }
+}
+
+/// scale first, then rotate, then translate. `rotation` must be orthonormal
+#[derive(Clone, Copy, Debug)]
+pub struct Transform3D {
+    pub translation: Vec3,
+    pub rotation: Mat33,
+    pub scale: Real,
+}
+
+impl Default for Transform3D {
+    fn default() -> Self {
+        Self::new(Vec3::zeros(), Mat33::identity(), 1.0)
+    }
+}
+
+impl Transform3D {
+    pub fn new(translation: Vec3, rotation: Mat33, scale: Real) -> Self {
+        Self {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    pub fn from_eular(translation: Vec3, rotation: &EularRotationXYZ, scale: Real) -> Self {
+        let mat = rotation.get_mat();
+        let mut rotation = Mat33::zeros();
+        for x in 0..3 {
+            for y in 0..3 {
+                rotation.set(x, y, mat.get(x, y));
+            }
+        }
+        Self::new(translation, rotation, scale)
+    }
+
+    pub fn rotate_vec(&self, v: &Vec3) -> Vec3 {
+        self.rotation * *v
+    }
+
+    pub fn apply_vec(&self, v: &Vec3) -> Vec3 {
+        self.rotate_vec(v) * self.scale
+    }
+
+    pub fn apply_pt(&self, p: &Vec3) -> Vec3 {
+        self.apply_vec(p) + self.translation
+    }
+}
+
+pub trait Shape2D {
+    type Transformed: Shape2D;
+
+    fn aabb(&self) -> AABB;
+    fn area(&self) -> Real;
+    fn perimeter(&self) -> Real;
+    fn centroid(&self) -> Vec2;
+    /// farthest point of shape along `dir`, `dir` needn't be normalized
+    fn support(&self, dir: &Vec2) -> Vec2;
+    fn transformed(&self, t: &Transform2D) -> Self::Transformed;
+}
+
+pub trait Shape3D {
+    type Transformed: Shape3D;
+
+    fn aabb(&self) -> Cube;
+    fn volume(&self) -> Real;
+    fn surface_area(&self) -> Real;
+    fn centroid(&self) -> Vec3;
+    /// farthest point of shape along `dir`, `dir` needn't be normalized
+    fn support(&self, dir: &Vec3) -> Vec3;
+    fn transformed(&self, t: &Transform3D) -> Self::Transformed;
+}
+
+fn sign(x: Real) -> Real {
+    if x >= 0.0 {
+        1.0
+    } else {
+        -1.0
+    }
+}
+
+fn normalize_or_zero<const DIM: usize>(v: &Vector<Real, DIM>) -> Vector<Real, DIM> {
+    let len = v.length();
+    if len <= Real::EPSILON {
+        Vector::zeros()
+    } else {
+        *v / len
+    }
+}
+
+fn abs<const DIM: usize>(v: &Vector<Real, DIM>) -> Vector<Real, DIM> {
+    let mut result = *v;
+    for i in 0..DIM {
+        result[i] = v[i].abs();
+    }
+    result
+}
+
+fn farthest_pt<const DIM: usize>(
+    pts: &[Vector<Real, DIM>],
+    dir: &Vector<Real, DIM>,
+) -> Vector<Real, DIM> {
+    *pts
+        .iter()
+        .max_by(|a, b| a.dot(dir).total_cmp(&b.dot(dir)))
+        .unwrap()
+}
+
+fn min_max_of<const DIM: usize>(
+    pts: impl Iterator<Item = Vector<Real, DIM>>,
+) -> (Vector<Real, DIM>, Vector<Real, DIM>) {
+    let mut min = Vector::from_col(&[Real::INFINITY; DIM]);
+    let mut max = Vector::from_col(&[Real::NEG_INFINITY; DIM]);
+    for p in pts {
+        for i in 0..DIM {
+            min[i] = min[i].min(p[i]);
+            max[i] = max[i].max(p[i]);
+        }
+    }
+    (min, max)
+}
+
+impl Shape2D for Circle {
+    type Transformed = Circle;
+
+    fn aabb(&self) -> AABB {
+        AABB::from_center(self.center, Vec2::from_xy(self.radius, self.radius))
+    }
+
+    fn area(&self) -> Real {
+        PI * self.radius * self.radius
+    }
+
+    fn perimeter(&self) -> Real {
+        2.0 * PI * self.radius
+    }
+
+    fn centroid(&self) -> Vec2 {
+        self.center
+    }
+
+    fn support(&self, dir: &Vec2) -> Vec2 {
+        self.center + normalize_or_zero(dir) * self.radius
+    }
+
+    fn transformed(&self, t: &Transform2D) -> Circle {
+        Circle::new(t.apply_pt(&self.center), self.radius * t.scale)
+    }
+}
+
+impl Shape2D for AABB {
+    type Transformed = geom2d::OBB;
+
+    fn aabb(&self) -> AABB {
+        *self
+    }
+
+    fn area(&self) -> Real {
+        4.0 * self.half_size.x() * self.half_size.y()
+    }
+
+    fn perimeter(&self) -> Real {
+        4.0 * (self.half_size.x() + self.half_size.y())
+    }
+
+    fn centroid(&self) -> Vec2 {
+        self.center
+    }
+
+    fn support(&self, dir: &Vec2) -> Vec2 {
+        self.center
+            + Vec2::from_xy(
+                sign(dir.x()) * self.half_size.x(),
+                sign(dir.y()) * self.half_size.y(),
+            )
+    }
+
+    fn transformed(&self, t: &Transform2D) -> geom2d::OBB {
+        let mut obb = geom2d::OBB::new(t.apply_pt(&self.center), self.half_size * t.scale);
+        obb.set_rotation(t.rotation);
+        obb
+    }
+}
+
+impl Shape2D for geom2d::OBB {
+    type Transformed = geom2d::OBB;
+
+    fn aabb(&self) -> AABB {
+        let half_size =
+            abs(&self.x_axis()) * self.half_size.x() + abs(&self.y_axis()) * self.half_size.y();
+        AABB::from_center(self.center, half_size)
+    }
+
+    fn area(&self) -> Real {
+        4.0 * self.half_size.x() * self.half_size.y()
+    }
+
+    fn perimeter(&self) -> Real {
+        4.0 * (self.half_size.x() + self.half_size.y())
+    }
+
+    fn centroid(&self) -> Vec2 {
+        self.center
+    }
+
+    fn support(&self, dir: &Vec2) -> Vec2 {
+        self.center
+            + self.x_axis() * (sign(dir.dot(&self.x_axis())) * self.half_size.x())
+            + self.y_axis() * (sign(dir.dot(&self.y_axis())) * self.half_size.y())
+    }
+
+    fn transformed(&self, t: &Transform2D) -> geom2d::OBB {
+        let mut obb = geom2d::OBB::new(t.apply_pt(&self.center), self.half_size * t.scale);
+        obb.set_rotation(self.rotation() + t.rotation);
+        obb
+    }
+}
+
+impl Shape2D for Triangle2D {
+    type Transformed = Triangle2D;
+
+    fn aabb(&self) -> AABB {
+        let (min, max) = min_max_of(self.pts.iter().copied());
+        AABB::from_min_max(min, max)
+    }
+
+    fn area(&self) -> Real {
+        (self.pts[1] - self.pts[0]).cross(&(self.pts[2] - self.pts[0])).abs() * 0.5
+    }
+
+    fn perimeter(&self) -> Real {
+        (0..3)
+            .map(|i| (self.pts[(i + 1) % 3] - self.pts[i]).length())
+            .sum()
+    }
+
+    fn centroid(&self) -> Vec2 {
+        (self.pts[0] + self.pts[1] + self.pts[2]) / 3.0
+    }
+
+    fn support(&self, dir: &Vec2) -> Vec2 {
+        farthest_pt(&self.pts, dir)
+    }
+
+    fn transformed(&self, t: &Transform2D) -> Triangle2D {
+        Triangle2D::new(self.pts.map(|p| t.apply_pt(&p)))
+    }
+}
+
+impl Shape2D for Ellipse {
+    type Transformed = Ellipse;
+
+    fn aabb(&self) -> AABB {
+        let (s, c) = self.rotation.sin_cos();
+        let (a2, b2) = (self.a * self.a, self.b * self.b);
+        let half_size = Vec2::from_xy((a2 * c * c + b2 * s * s).sqrt(), (a2 * s * s + b2 * c * c).sqrt());
+        AABB::from_center(self.position, half_size)
+    }
+
+    fn area(&self) -> Real {
+        PI * self.a * self.b
+    }
+
+    /// [Ramanujan's second approximation](https://en.wikipedia.org/wiki/Ellipse#Circumference)
+    fn perimeter(&self) -> Real {
+        let h = ((self.a - self.b) / (self.a + self.b)).powi(2);
+        PI * (self.a + self.b) * (1.0 + 3.0 * h / (10.0 + (4.0 - 3.0 * h).sqrt()))
+    }
+
+    fn centroid(&self) -> Vec2 {
+        self.position
+    }
+
+    fn support(&self, dir: &Vec2) -> Vec2 {
+        // in local frame, support of x^2/a^2 + y^2/b^2 = 1 along d is (a^2 dx, b^2 dy) / |(a dx, b dy)|
+        let rotation = EularRotationXY::new(self.rotation).get_mat();
+        let local = rotation.transpose() * *dir;
+        let len = Vec2::from_xy(self.a * local.x(), self.b * local.y()).length();
+        if len <= Real::EPSILON {
+            return self.position;
+        }
+        let pt = Vec2::from_xy(self.a * self.a * local.x(), self.b * self.b * local.y()) / len;
+        self.position + rotation * pt
+    }
+
+    fn transformed(&self, t: &Transform2D) -> Ellipse {
+        Ellipse {
+            a: self.a * t.scale,
+            b: self.b * t.scale,
+            position: t.apply_pt(&self.position),
+            rotation: self.rotation + t.rotation,
+        }
+    }
+}
+
+impl Shape2D for Polygon {
+    type Transformed = Polygon;
+
+    fn aabb(&self) -> AABB {
+        let (min, max) = min_max_of(self.points.iter().copied());
+        AABB::from_min_max(min, max)
+    }
+
+    fn area(&self) -> Real {
+        self.signed_area().abs()
+    }
+
+    fn perimeter(&self) -> Real {
+        let n = self.points.len();
+        (0..n)
+            .map(|i| (self.points[(i + 1) % n] - self.points[i]).length())
+            .sum()
+    }
+
+    fn centroid(&self) -> Vec2 {
+        let n = self.points.len();
+        let signed_area = self.signed_area();
+        if signed_area.abs() <= Real::EPSILON {
+            // degenerated polygon, fallback to average of points
+            return self.points.iter().fold(Vec2::zeros(), |acc, p| acc + *p) / n as Real;
+        }
+
+        let mut sum = Vec2::zeros();
+        for i in 0..n {
+            let (p, q) = (self.points[i], self.points[(i + 1) % n]);
+            sum += (p + q) * p.cross(&q);
+        }
+        sum / (6.0 * signed_area)
+    }
+
+    fn support(&self, dir: &Vec2) -> Vec2 {
+        farthest_pt(&self.points, dir)
+    }
+
+    fn transformed(&self, t: &Transform2D) -> Polygon {
+        Polygon::new(self.points.iter().map(|p| t.apply_pt(p)).collect())
+    }
+}
+
+impl Shape2D for Annulus {
+    type Transformed = Annulus;
+
+    fn aabb(&self) -> AABB {
+        AABB::from_center(self.center, Vec2::from_xy(self.outer_radius, self.outer_radius))
+    }
+
+    fn area(&self) -> Real {
+        PI * (self.outer_radius * self.outer_radius - self.inner_radius * self.inner_radius)
+    }
+
+    fn perimeter(&self) -> Real {
+        2.0 * PI * (self.outer_radius + self.inner_radius)
+    }
+
+    fn centroid(&self) -> Vec2 {
+        self.center
+    }
+
+    fn support(&self, dir: &Vec2) -> Vec2 {
+        self.center + normalize_or_zero(dir) * self.outer_radius
+    }
+
+    fn transformed(&self, t: &Transform2D) -> Annulus {
+        Annulus::new(
+            t.apply_pt(&self.center),
+            self.inner_radius * t.scale,
+            self.outer_radius * t.scale,
+        )
+    }
+}
+
+impl Shape2D for AnnularSector {
+    type Transformed = AnnularSector;
+
+    fn aabb(&self) -> AABB {
+        // radial edges connect arc ends, so arcs bound the whole sector
+        let (outer, inner) = (self.outer_arc().aabb(), self.inner_arc().aabb());
+        let (min, max) = min_max_of([outer.min(), outer.max(), inner.min(), inner.max()].into_iter());
+        AABB::from_min_max(min, max)
+    }
+
+    fn area(&self) -> Real {
+        let (r, big) = (self.inner_radius, self.outer_radius);
+        (self.range.1 - self.range.0) * (big * big - r * r) * 0.5
+    }
+
+    fn perimeter(&self) -> Real {
+        let sweep = self.range.1 - self.range.0;
+        let arcs = sweep * (self.outer_radius + self.inner_radius);
+        if sweep < 2.0 * PI {
+            arcs + 2.0 * (self.outer_radius - self.inner_radius)
+        } else {
+            arcs
+        }
+    }
+
+    fn centroid(&self) -> Vec2 {
+        let half = (self.range.1 - self.range.0) * 0.5;
+        let (r, big) = (self.inner_radius, self.outer_radius);
+        let mid = self.range.0 + half;
+        // centroid of sector with radius R is `2R sin(half) / (3 half)` away from center, combine two sectors
+        let ratio = if half <= Real::EPSILON { 1.0 } else { half.sin() / half };
+        let dist = 2.0 / 3.0 * (big.powi(3) - r.powi(3)) / (big * big - r * r) * ratio;
+        self.center + Vec2::from_xy(mid.cos(), mid.sin()) * dist
+    }
+
+    fn support(&self, dir: &Vec2) -> Vec2 {
+        let outer = self.outer_arc();
+        let inner = self.inner_arc();
+        let mut candidates = vec![outer.start_pt(), outer.end_pt(), inner.start_pt(), inner.end_pt()];
+        let angle = dir.y().atan2(dir.x());
+        if outer.contain_angle(angle) {
+            candidates.push(outer.pt_at(angle));
+        }
+        farthest_pt(&candidates, dir)
+    }
+
+    fn transformed(&self, t: &Transform2D) -> AnnularSector {
+        AnnularSector::new(
+            t.apply_pt(&self.center),
+            self.inner_radius * t.scale,
+            self.outer_radius * t.scale,
+            (self.range.0 + t.rotation, self.range.1 + t.rotation),
+        )
+    }
+}
+
+impl Shape3D for Sphere {
+    type Transformed = Sphere;
+
+    fn aabb(&self) -> Cube {
+        Cube::from_center(self.center, Vec3::from_xyz(self.radius, self.radius, self.radius))
+    }
+
+    fn volume(&self) -> Real {
+        4.0 / 3.0 * PI * self.radius.powi(3)
+    }
+
+    fn surface_area(&self) -> Real {
+        4.0 * PI * self.radius * self.radius
+    }
+
+    fn centroid(&self) -> Vec3 {
+        self.center
+    }
+
+    fn support(&self, dir: &Vec3) -> Vec3 {
+        self.center + normalize_or_zero(dir) * self.radius
+    }
+
+    fn transformed(&self, t: &Transform3D) -> Sphere {
+        Sphere::new(t.apply_pt(&self.center), self.radius * t.scale)
+    }
+}
+
+fn box_volume(half_len: &Vec3) -> Real {
+    8.0 * half_len.x() * half_len.y() * half_len.z()
+}
+
+fn box_surface_area(half_len: &Vec3) -> Real {
+    let (x, y, z) = (half_len.x(), half_len.y(), half_len.z());
+    8.0 * (x * y + y * z + z * x)
+}
+
+impl Shape3D for Cube {
+    type Transformed = geom3d::OBB;
+
+    fn aabb(&self) -> Cube {
+        *self
+    }
+
+    fn volume(&self) -> Real {
+        box_volume(&self.half_len)
+    }
+
+    fn surface_area(&self) -> Real {
+        box_surface_area(&self.half_len)
+    }
+
+    fn centroid(&self) -> Vec3 {
+        self.center
+    }
+
+    fn support(&self, dir: &Vec3) -> Vec3 {
+        self.center
+            + Vec3::from_xyz(
+                sign(dir.x()) * self.half_len.x(),
+                sign(dir.y()) * self.half_len.y(),
+                sign(dir.z()) * self.half_len.z(),
+            )
+    }
+
+    fn transformed(&self, t: &Transform3D) -> geom3d::OBB {
+        geom3d::OBB::new(
+            t.apply_pt(&self.center),
+            self.half_len * t.scale,
+            [Vec3::x_axis(), Vec3::y_axis(), Vec3::z_axis()].map(|axis| t.rotate_vec(&axis)),
+        )
+    }
+}
+
+impl Shape3D for geom3d::OBB {
+    type Transformed = geom3d::OBB;
+
+    fn aabb(&self) -> Cube {
+        let half_len = (0..3).fold(Vec3::zeros(), |acc, i| {
+            acc + abs(&self.axes[i]) * self.half_len[i]
+        });
+        Cube::from_center(self.center, half_len)
+    }
+
+    fn volume(&self) -> Real {
+        box_volume(&self.half_len)
+    }
+
+    fn surface_area(&self) -> Real {
+        box_surface_area(&self.half_len)
+    }
+
+    fn centroid(&self) -> Vec3 {
+        self.center
+    }
+
+    fn support(&self, dir: &Vec3) -> Vec3 {
+        (0..3).fold(self.center, |acc, i| {
+            acc + self.axes[i] * (sign(dir.dot(&self.axes[i])) * self.half_len[i])
+        })
+    }
+
+    fn transformed(&self, t: &Transform3D) -> geom3d::OBB {
+        geom3d::OBB::new(
+            t.apply_pt(&self.center),
+            self.half_len * t.scale,
+            self.axes.map(|axis| t.rotate_vec(&axis)),
+        )
+    }
+}
+
+/// bounding box of a disc, extent along axis i is `radius * sqrt(1 - normal_i^2)`
+fn disc_min_max(center: &Vec3, normal: &Vec3, radius: Real) -> (Vec3, Vec3) {
+    let mut half = Vec3::zeros();
+    for i in 0..3 {
+        half[i] = radius * (1.0 - normal[i] * normal[i]).max(0.0).sqrt();
+    }
+    (*center - half, *center + half)
+}
+
+fn disc_support(center: &Vec3, normal: &Vec3, radius: Real, dir: &Vec3) -> Vec3 {
+    let on_plane = *dir - *normal * normal.dot(dir);
+    *center + normalize_or_zero(&on_plane) * radius
+}
+
+/// cylinder, cone and truncated cone are all the convex hull of two coaxial discs
+fn discs_aabb(bottom: &Vec3, dir: &Vec3, height: Real, bottom_radius: Real, top_radius: Real) -> Cube {
+    let top = *bottom + *dir * height;
+    let (min1, max1) = disc_min_max(bottom, dir, bottom_radius);
+    let (min2, max2) = disc_min_max(&top, dir, top_radius);
+    let (min, max) = min_max_of([min1, max1, min2, max2].into_iter());
+    Cube::from_min_max(min, max)
+}
+
+fn discs_support(
+    bottom: &Vec3,
+    axis: &Vec3,
+    height: Real,
+    bottom_radius: Real,
+    top_radius: Real,
+    dir: &Vec3,
+) -> Vec3 {
+    let top = *bottom + *axis * height;
+    farthest_pt(
+        &[
+            disc_support(bottom, axis, bottom_radius, dir),
+            disc_support(&top, axis, top_radius, dir),
+        ],
+        dir,
+    )
+}
+
+impl Shape3D for Cylinder {
+    type Transformed = Cylinder;
+
+    fn aabb(&self) -> Cube {
+        discs_aabb(&self.bottom, &self.dir, self.height, self.radius, self.radius)
+    }
+
+    fn volume(&self) -> Real {
+        PI * self.radius * self.radius * self.height
+    }
+
+    fn surface_area(&self) -> Real {
+        2.0 * PI * self.radius * (self.radius + self.height)
+    }
+
+    fn centroid(&self) -> Vec3 {
+        self.bottom + self.dir * (self.height * 0.5)
+    }
+
+    fn support(&self, dir: &Vec3) -> Vec3 {
+        discs_support(&self.bottom, &self.dir, self.height, self.radius, self.radius, dir)
+    }
+
+    fn transformed(&self, t: &Transform3D) -> Cylinder {
+        Cylinder {
+            bottom: t.apply_pt(&self.bottom),
+            dir: t.rotate_vec(&self.dir),
+            height: self.height * t.scale,
+            radius: self.radius * t.scale,
+        }
+    }
+}
+
+impl Shape3D for Cone {
+    type Transformed = Cone;
+
+    fn aabb(&self) -> Cube {
+        discs_aabb(&self.bottom, &self.dir, self.height, self.bottom_radius, 0.0)
+    }
+
+    fn volume(&self) -> Real {
+        PI * self.bottom_radius * self.bottom_radius * self.height / 3.0
+    }
+
+    fn surface_area(&self) -> Real {
+        let slant = self.bottom_radius.hypot(self.height);
+        PI * self.bottom_radius * (self.bottom_radius + slant)
+    }
+
+    fn centroid(&self) -> Vec3 {
+        self.bottom + self.dir * (self.height * 0.25)
+    }
+
+    fn support(&self, dir: &Vec3) -> Vec3 {
+        discs_support(&self.bottom, &self.dir, self.height, self.bottom_radius, 0.0, dir)
+    }
+
+    fn transformed(&self, t: &Transform3D) -> Cone {
+        Cone {
+            bottom: t.apply_pt(&self.bottom),
+            bottom_radius: self.bottom_radius * t.scale,
+            dir: t.rotate_vec(&self.dir),
+            height: self.height * t.scale,
+        }
+    }
+}
+
+impl Shape3D for TruncatedCone {
+    type Transformed = TruncatedCone;
+
+    fn aabb(&self) -> Cube {
+        discs_aabb(&self.bottom, &self.dir, self.height, self.bottom_radius, self.top_radius)
+    }
+
+    fn volume(&self) -> Real {
+        let (r1, r2) = (self.bottom_radius, self.top_radius);
+        PI * self.height * (r1 * r1 + r1 * r2 + r2 * r2) / 3.0
+    }
+
+    fn surface_area(&self) -> Real {
+        let (r1, r2) = (self.bottom_radius, self.top_radius);
+        let slant = (r1 - r2).hypot(self.height);
+        PI * (r1 * r1 + r2 * r2 + (r1 + r2) * slant)
+    }
+
+    fn centroid(&self) -> Vec3 {
+        let (r1, r2) = (self.bottom_radius, self.top_radius);
+        let h = self.height * (r1 * r1 + 2.0 * r1 * r2 + 3.0 * r2 * r2)
+            / (4.0 * (r1 * r1 + r1 * r2 + r2 * r2));
+        self.bottom + self.dir * h
+    }
+
+    fn support(&self, dir: &Vec3) -> Vec3 {
+        discs_support(
+            &self.bottom,
+            &self.dir,
+            self.height,
+            self.bottom_radius,
+            self.top_radius,
+            dir,
+        )
+    }
+
+    fn transformed(&self, t: &Transform3D) -> TruncatedCone {
+        TruncatedCone {
+            bottom: t.apply_pt(&self.bottom),
+            bottom_radius: self.bottom_radius * t.scale,
+            top_radius: self.top_radius * t.scale,
+            dir: t.rotate_vec(&self.dir),
+            height: self.height * t.scale,
+        }
+    }
+}
@@ -0,0 +1,338 @@
+//! point oriented spatial indices sharing the `SpatialIndex` query trait
+//!
+//! implementations: `loose_tree::{Quadtree, Octree}`, `kdtree::KdTree` and `SpatialHashGrid` in this module
+
+use crate::bvh::BoundingBox;
+use math::matrix::*;
+use math::precision::Real;
+use std::collections::HashMap;
+
+/// common interface of spatial indices, every stored value is referred by the handle returned from `insert`
+pub trait SpatialIndex<T, const DIM: usize> {
+    fn insert(&mut self, pos: Vector<Real, DIM>, value: T) -> usize;
+
+    fn remove(&mut self, handle: usize) -> Option<T>;
+
+    /// move a stored value, returns `false` if handle is invalid
+    fn update(&mut self, handle: usize, pos: Vector<Real, DIM>) -> bool;
+
+    fn get(&self, handle: usize) -> Option<&T>;
+
+    fn position(&self, handle: usize) -> Option<Vector<Real, DIM>>;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// handles of values inside `bounds`
+    fn query_box(&self, bounds: &BoundingBox<DIM>) -> Vec<usize>;
+
+    /// handles of values whose distance to `center` is not greater than `radius`
+    fn query_radius(&self, center: &Vector<Real, DIM>, radius: Real) -> Vec<usize>;
+
+    /// handles of `k` nearest values, sorted from near to far
+    fn nearest_k(&self, pt: &Vector<Real, DIM>, k: usize) -> Vec<usize>;
+
+    fn nearest(&self, pt: &Vector<Real, DIM>) -> Option<usize> {
+        self.nearest_k(pt, 1).first().copied()
+    }
+}
+
+/// slot storage with handle reuse, shared by spatial indices
+pub(crate) struct Slots<T> {
+    slots: Vec<Option<T>>,
+    free: Vec<usize>,
+}
+
+impl<T> Slots<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    pub(crate) fn insert(&mut self, value: T) -> usize {
+        if let Some(h) = self.free.pop() {
+            self.slots[h] = Some(value);
+            h
+        } else {
+            self.slots.push(Some(value));
+            self.slots.len() - 1
+        }
+    }
+
+    pub(crate) fn remove(&mut self, handle: usize) -> Option<T> {
+        let value = self.slots.get_mut(handle)?.take()?;
+        self.free.push(handle);
+        Some(value)
+    }
+
+    pub(crate) fn get(&self, handle: usize) -> Option<&T> {
+        self.slots.get(handle).and_then(|s| s.as_ref())
+    }
+
+    pub(crate) fn get_mut(&mut self, handle: usize) -> Option<&mut T> {
+        self.slots.get_mut(handle).and_then(|s| s.as_mut())
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| s.as_ref().map(|s| (i, s)))
+    }
+}
+
+/// keeps the `k` smallest (distance, handle) pairs
+pub(crate) struct KNearest {
+    k: usize,
+    found: Vec<(Real, usize)>,
+}
+
+impl KNearest {
+    pub(crate) fn new(k: usize) -> Self {
+        Self {
+            k,
+            found: Vec::with_capacity(k + 1),
+        }
+    }
+
+    pub(crate) fn push(&mut self, dist_sqrd: Real, handle: usize) {
+        if self.k == 0 || (self.is_full() && dist_sqrd >= self.worst()) {
+            return;
+        }
+        let pos = self.found.partition_point(|f| f.0 <= dist_sqrd);
+        self.found.insert(pos, (dist_sqrd, handle));
+        self.found.truncate(self.k);
+    }
+
+    pub(crate) fn is_full(&self) -> bool {
+        self.found.len() >= self.k
+    }
+
+    /// squared distance a candidate must beat, infinite until `k` values are found
+    pub(crate) fn worst(&self) -> Real {
+        if self.is_full() {
+            self.found.last().map_or(Real::MAX, |f| f.0)
+        } else {
+            Real::MAX
+        }
+    }
+
+    pub(crate) fn into_handles(self) -> Vec<usize> {
+        self.found.into_iter().map(|f| f.1).collect()
+    }
+}
+
+/// call `f` on every integer cell in `[min, max]`
+fn for_each_cell<const DIM: usize>(min: [i64; DIM], max: [i64; DIM], mut f: impl FnMut([i64; DIM])) {
+    if (0..DIM).any(|i| min[i] > max[i]) {
+        return;
+    }
+
+    let mut cell = min;
+    loop {
+        f(cell);
+
+        let mut axis = 0;
+        loop {
+            if axis == DIM {
+                return;
+            }
+            if cell[axis] < max[axis] {
+                cell[axis] += 1;
+                break;
+            }
+            cell[axis] = min[axis];
+            axis += 1;
+        }
+    }
+}
+
+/// uniform grid hashing points into cells of `cell_size`
+pub struct SpatialHashGrid<T, const DIM: usize> {
+    cell_size: Real,
+    cells: HashMap<[i64; DIM], Vec<usize>>,
+    items: Slots<(Vector<Real, DIM>, T)>,
+}
+
+pub type SpatialHashGrid2D<T> = SpatialHashGrid<T, 2>;
+pub type SpatialHashGrid3D<T> = SpatialHashGrid<T, 3>;
+
+impl<T, const DIM: usize> SpatialHashGrid<T, DIM> {
+    pub fn new(cell_size: Real) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            items: Slots::new(),
+        }
+    }
+
+    pub fn cell_size(&self) -> Real {
+        self.cell_size
+    }
+
+    fn cell_of(&self, pos: &Vector<Real, DIM>) -> [i64; DIM] {
+        std::array::from_fn(|i| (pos[i] / self.cell_size).floor() as i64)
+    }
+
+    fn remove_from_cell(&mut self, cell: [i64; DIM], handle: usize) {
+        if let Some(list) = self.cells.get_mut(&cell) {
+            if let Some(i) = list.iter().position(|h| *h == handle) {
+                list.swap_remove(i);
+            }
+            if list.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+    }
+
+    fn visit_box(&self, bounds: &BoundingBox<DIM>, mut f: impl FnMut(usize, &Vector<Real, DIM>)) {
+        let min = self.cell_of(&bounds.min);
+        let max = self.cell_of(&bounds.max);
+        let range_count = (0..DIM).fold(1.0, |c, i| c * (max[i] - min[i] + 1) as Real);
+
+        let mut visit_cell = |list: &Vec<usize>| {
+            for h in list {
+                let pos = &self.items.get(*h).unwrap().0;
+                if bounds.contain_pt(pos) {
+                    f(*h, pos);
+                }
+            }
+        };
+
+        if range_count > self.cells.len() as Real {
+            for (cell, list) in &self.cells {
+                if (0..DIM).all(|i| min[i] <= cell[i] && cell[i] <= max[i]) {
+                    visit_cell(list);
+                }
+            }
+        } else {
+            for_each_cell(min, max, |cell| {
+                if let Some(list) = self.cells.get(&cell) {
+                    visit_cell(list);
+                }
+            });
+        }
+    }
+}
+
+impl<T, const DIM: usize> SpatialIndex<T, DIM> for SpatialHashGrid<T, DIM> {
+    fn insert(&mut self, pos: Vector<Real, DIM>, value: T) -> usize {
+        let handle = self.items.insert((pos, value));
+        self.cells.entry(self.cell_of(&pos)).or_default().push(handle);
+        handle
+    }
+
+    fn remove(&mut self, handle: usize) -> Option<T> {
+        let (pos, value) = self.items.remove(handle)?;
+        self.remove_from_cell(self.cell_of(&pos), handle);
+        Some(value)
+    }
+
+    fn update(&mut self, handle: usize, pos: Vector<Real, DIM>) -> bool {
+        let Some(old) = self.items.get(handle).map(|i| i.0) else {
+            return false;
+        };
+        let (old_cell, new_cell) = (self.cell_of(&old), self.cell_of(&pos));
+        if old_cell != new_cell {
+            self.remove_from_cell(old_cell, handle);
+            self.cells.entry(new_cell).or_default().push(handle);
+        }
+        self.items.get_mut(handle).unwrap().0 = pos;
+        true
+    }
+
+    fn get(&self, handle: usize) -> Option<&T> {
+        self.items.get(handle).map(|i| &i.1)
+    }
+
+    fn position(&self, handle: usize) -> Option<Vector<Real, DIM>> {
+        self.items.get(handle).map(|i| i.0)
+    }
+
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn query_box(&self, bounds: &BoundingBox<DIM>) -> Vec<usize> {
+        let mut result = Vec::new();
+        self.visit_box(bounds, |h, _| result.push(h));
+        result
+    }
+
+    fn query_radius(&self, center: &Vector<Real, DIM>, radius: Real) -> Vec<usize> {
+        let mut result = Vec::new();
+        let bounds = BoundingBox::from_pt(center).expand(radius);
+        self.visit_box(&bounds, |h, pos| {
+            if (*pos - *center).length_sqrd() <= radius * radius {
+                result.push(h);
+            }
+        });
+        result
+    }
+
+    /// search cells ring by ring around `pt` until no closer value can exist
+    fn nearest_k(&self, pt: &Vector<Real, DIM>, k: usize) -> Vec<usize> {
+        let mut nearest = KNearest::new(k);
+        if k == 0 || self.cells.is_empty() {
+            return nearest.into_handles();
+        }
+
+        let origin = self.cell_of(pt);
+        let ring_of = |cell: &[i64; DIM]| (0..DIM).map(|i| (cell[i] - origin[i]).abs()).max().unwrap_or(0);
+
+        let mut visited = 0;
+        let mut ring: i64 = 0;
+        loop {
+            let mut visit_cell = |list: &Vec<usize>| {
+                for h in list {
+                    let pos = self.items.get(*h).unwrap().0;
+                    nearest.push((pos - *pt).length_sqrd(), *h);
+                    visited += 1;
+                }
+            };
+
+            let shell_count = (2.0 * ring as Real + 1.0).powi(DIM as i32);
+            if shell_count > self.cells.len() as Real {
+                // sparse grid, cheaper to scan occupied cells
+                for (cell, list) in &self.cells {
+                    if ring_of(cell) == ring {
+                        visit_cell(list);
+                    }
+                }
+            } else {
+                let min = std::array::from_fn(|i| origin[i] - ring);
+                let max = std::array::from_fn(|i| origin[i] + ring);
+                for_each_cell(min, max, |cell| {
+                    if ring_of(&cell) == ring {
+                        if let Some(list) = self.cells.get(&cell) {
+                            visit_cell(list);
+                        }
+                    }
+                });
+            }
+
+            // unvisited values are at least `ring` cells away
+            let reach = ring as Real * self.cell_size;
+            if visited >= self.items.len() || (nearest.is_full() && nearest.worst() <= reach * reach) {
+                break;
+            }
+            ring = if visited == 0 {
+                // jump straight to the nearest occupied ring
+                self.cells.keys().map(ring_of).min().unwrap()
+            } else {
+                ring + 1
+            };
+        }
+
+        nearest.into_handles()
+    }
+}
@@ -0,0 +1,368 @@
+//! continuous collision detection, shapes translate linearly by a displacement during t in [0, 1]
+//!
+//! all queries return the first contact, shapes already touching at t = 0 report `toi` 0.
+
+use crate::bvh::BoundingBox;
+use crate::geom2d::{Circle, Line2D};
+use crate::geom3d::{Plane, Sphere, Triangle3D};
+use crate::geom_common::{Circular, Ray};
+use crate::gjk::{closest_pts, Support, Translated};
+use crate::nearest_common;
+use math::matrix::*;
+use math::precision::Real;
+
+const MAX_ITERATION: usize = 64;
+const TOLERANCE: Real = 1e-6;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Contact<const DIM: usize> {
+    /// time of impact in [0, 1]
+    pub toi: Real,
+    pub pt: Vector<Real, DIM>,
+    /// unit normal pointing from the second shape to the first one
+    pub normal: Vector<Real, DIM>,
+}
+
+pub type Contact2D = Contact<2>;
+pub type Contact3D = Contact<3>;
+
+/// unit vector of `v`, or `fallback` if `v` is too short
+fn normalize_or<const DIM: usize>(
+    v: &Vector<Real, DIM>,
+    fallback: Vector<Real, DIM>,
+) -> Vector<Real, DIM> {
+    let len = v.length();
+    if len <= Real::EPSILON {
+        fallback
+    } else {
+        *v / len
+    }
+}
+
+fn first_axis<const DIM: usize>() -> Vector<Real, DIM> {
+    let mut axis = Vector::zeros();
+    axis[0] = 1.0;
+    axis
+}
+
+/// smallest root in [0, 1] of `a t^2 + 2 b t + c = 0`, only when the quadratic goes from positive to negative
+fn first_root(a: Real, b: Real, c: Real) -> Option<Real> {
+    if a <= Real::EPSILON || b >= 0.0 {
+        return None;
+    }
+    let delta = b * b - a * c;
+    if delta < 0.0 {
+        return None;
+    }
+    let t = (-b - delta.sqrt()) / a;
+    if (0.0..=1.0).contains(&t) {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+/// moving circle/sphere `a` against moving circle/sphere `b`
+pub fn swept_circular<const DIM: usize>(
+    a: &Circular<DIM>,
+    vel_a: &Vector<Real, DIM>,
+    b: &Circular<DIM>,
+    vel_b: &Vector<Real, DIM>,
+) -> Option<Contact<DIM>> {
+    let d = a.center - b.center;
+    let vel = *vel_a - *vel_b;
+    let radius = a.radius + b.radius;
+
+    let toi = if d.length_sqrd() <= radius * radius {
+        0.0
+    } else {
+        first_root(vel.length_sqrd(), d.dot(&vel), d.length_sqrd() - radius * radius)?
+    };
+
+    let center_a = a.center + *vel_a * toi;
+    let center_b = b.center + *vel_b * toi;
+    let normal = normalize_or(&(center_a - center_b), normalize_or(&-vel, first_axis()));
+    Some(Contact {
+        toi,
+        pt: center_b + normal * b.radius,
+        normal,
+    })
+}
+
+/// moving circle/sphere against the hyperplane through `pt` with unit `normal`, from either side
+fn swept_circular_hyperplane<const DIM: usize>(
+    c: &Circular<DIM>,
+    vel: &Vector<Real, DIM>,
+    pt: &Vector<Real, DIM>,
+    normal: &Vector<Real, DIM>,
+) -> Option<Contact<DIM>> {
+    let dist = normal.dot(&(c.center - *pt));
+    let side = if dist >= 0.0 { 1.0 } else { -1.0 };
+    let normal = *normal * side;
+
+    let toi = if dist.abs() <= c.radius {
+        0.0
+    } else {
+        let approach = -normal.dot(vel);
+        if approach <= Real::EPSILON {
+            return None;
+        }
+        let t = (dist.abs() - c.radius) / approach;
+        if t > 1.0 {
+            return None;
+        }
+        t
+    };
+
+    let center = c.center + *vel * toi;
+    let dist = normal.dot(&(center - *pt));
+    Some(Contact {
+        toi,
+        pt: center - normal * dist,
+        normal,
+    })
+}
+
+pub fn swept_circle_line(c: &Circle, vel: &Vec2, line: &Line2D) -> Option<Contact2D> {
+    swept_circular_hyperplane(c, vel, &line.start, &line.normal())
+}
+
+pub fn swept_sphere_plane(s: &Sphere, vel: &Vec3, plane: &Plane) -> Option<Contact3D> {
+    swept_circular_hyperplane(s, vel, &plane.pt, &plane.normal.normalize())
+}
+
+/// moving sphere against a static triangle(double sided)
+///
+/// first contact is either with the face at the moment sphere touches triangle's plane,
+/// or with one of the edges/vertices
+pub fn swept_sphere_triangle(s: &Sphere, vel: &Vec3, triangle: &Triangle3D) -> Option<Contact3D> {
+    let contact_at = |toi: Real, pt: Vec3| {
+        let center = s.center + *vel * toi;
+        Contact {
+            toi,
+            pt,
+            normal: normalize_or(&(center - pt), normalize_or(&-*vel, first_axis())),
+        }
+    };
+
+    let nearest = nearest_common::pt2triangle(&s.center, triangle);
+    if (nearest - s.center).length_sqrd() <= s.radius * s.radius {
+        return Some(contact_at(0.0, nearest));
+    }
+
+    let normal = (triangle[1] - triangle[0]).cross(&(triangle[2] - triangle[0]));
+    if normal.length_sqrd() > Real::EPSILON {
+        let normal = normal.normalize();
+        if normal.dot(&(s.center - triangle[0])).abs() > s.radius {
+            if let Some(contact) = swept_circular_hyperplane(s, vel, &triangle[0], &normal) {
+                let on_face = nearest_common::pt2triangle(&contact.pt, triangle);
+                if (on_face - contact.pt).length_sqrd() <= TOLERANCE * TOLERANCE {
+                    return Some(contact);
+                }
+            }
+        }
+    }
+
+    let mut first: Option<(Real, Vec3)> = None;
+    let mut update = |t: Real, pt: Vec3| {
+        if first.is_none_or(|(toi, _)| t < toi) {
+            first = Some((t, pt));
+        }
+    };
+
+    let r_sqrd = s.radius * s.radius;
+    for i in 0..3 {
+        // sphere center against sphere around vertex
+        let m = s.center - triangle[i];
+        if let Some(t) = first_root(vel.length_sqrd(), m.dot(vel), m.length_sqrd() - r_sqrd) {
+            update(t, triangle[i]);
+        }
+
+        // sphere center against cylinder around edge
+        let e = triangle[(i + 1) % 3] - triangle[i];
+        let ee = e.length_sqrd();
+        let (me, ve) = (m.dot(&e), vel.dot(&e));
+        let a = ee * vel.length_sqrd() - ve * ve;
+        let b = ee * m.dot(vel) - me * ve;
+        let c = ee * (m.length_sqrd() - r_sqrd) - me * me;
+        if let Some(t) = first_root(a, b, c) {
+            let s = (me + ve * t) / ee;
+            if (0.0..=1.0).contains(&s) {
+                update(t, triangle[i] + e * s);
+            }
+        }
+    }
+
+    first.map(|(toi, pt)| contact_at(toi, pt))
+}
+
+/// moving circle/sphere against a static box
+pub fn swept_circular_aabb<const DIM: usize>(
+    c: &Circular<DIM>,
+    vel: &Vector<Real, DIM>,
+    bbox: &BoundingBox<DIM>,
+) -> Option<Contact<DIM>> {
+    let contact_at = |toi: Real| {
+        let center = c.center + *vel * toi;
+        let pt = bbox.nearest_pt(&center);
+        Contact {
+            toi,
+            pt,
+            normal: normalize_or(&(center - pt), normalize_or(&-*vel, first_axis())),
+        }
+    };
+
+    if bbox.is_circular_intersect(c) {
+        return Some(contact_at(0.0));
+    }
+    let speed = vel.length();
+    if speed <= Real::EPSILON {
+        return None;
+    }
+
+    // box expanded by radius contains the rounded box, so hitting it gives a lower bound of toi
+    let ray = Ray::new(c.center, *vel);
+    let mut toi = bbox.expand(c.radius).ray_intersect_param(&ray, speed)? / speed;
+
+    // exact for faces, edges and corners need conservative advancement from the lower bound
+    for _ in 0..MAX_ITERATION {
+        let center = c.center + *vel * toi;
+        let diff = center - bbox.nearest_pt(&center);
+        let gap = diff.length() - c.radius;
+        if gap <= TOLERANCE {
+            return Some(contact_at(toi));
+        }
+        let approach = -vel.dot(&(diff / diff.length()));
+        if approach <= Real::EPSILON {
+            return None;
+        }
+        toi += gap / approach;
+        if toi > 1.0 {
+            return None;
+        }
+    }
+    None
+}
+
+/// moving box `a` against moving box `b`
+pub fn swept_aabb<const DIM: usize>(
+    a: &BoundingBox<DIM>,
+    vel_a: &Vector<Real, DIM>,
+    b: &BoundingBox<DIM>,
+    vel_b: &Vector<Real, DIM>,
+) -> Option<Contact<DIM>> {
+    let vel = *vel_a - *vel_b;
+    let mut t_enter = Real::NEG_INFINITY;
+    let mut t_exit = Real::INFINITY;
+    let mut enter_axis = 0;
+    for i in 0..DIM {
+        if vel[i].abs() <= Real::EPSILON {
+            if a.max[i] < b.min[i] || a.min[i] > b.max[i] {
+                return None;
+            }
+            continue;
+        }
+
+        let (t1, t2) = if vel[i] > 0.0 {
+            ((b.min[i] - a.max[i]) / vel[i], (b.max[i] - a.min[i]) / vel[i])
+        } else {
+            ((b.max[i] - a.min[i]) / vel[i], (b.min[i] - a.max[i]) / vel[i])
+        };
+        if t1 > t_enter {
+            t_enter = t1;
+            enter_axis = i;
+        }
+        t_exit = t_exit.min(t2);
+    }
+
+    let mut normal = Vector::zeros();
+    let toi = if a.is_intersect(b) {
+        // least penetration axis
+        let (axis, sign) = (0..DIM)
+            .flat_map(|i| [(i, a.max[i] - b.min[i], -1.0), (i, b.max[i] - a.min[i], 1.0)])
+            .min_by(|x, y| x.1.total_cmp(&y.1))
+            .map(|(i, _, sign)| (i, sign))
+            .unwrap();
+        normal[axis] = sign;
+        0.0
+    } else {
+        if t_enter > t_exit || t_enter > 1.0 || t_exit < 0.0 {
+            return None;
+        }
+        normal[enter_axis] = if vel[enter_axis] > 0.0 { -1.0 } else { 1.0 };
+        t_enter
+    };
+
+    // center of touching region
+    let moved_a = BoundingBox::new(a.min + *vel_a * toi, a.max + *vel_a * toi);
+    let moved_b = BoundingBox::new(b.min + *vel_b * toi, b.max + *vel_b * toi);
+    let mut pt = Vector::zeros();
+    for i in 0..DIM {
+        pt[i] = (moved_a.min[i].max(moved_b.min[i]) + moved_a.max[i].min(moved_b.max[i])) * 0.5;
+    }
+    Some(Contact { toi, pt, normal })
+}
+
+/// [conservative advancement](https://graphics.stanford.edu/courses/cs468-03-fall/Papers/completeCollision.pdf) for any pair of convex shapes
+///
+/// shapes initially overlapping report `toi` 0 with a normal opposing relative motion
+pub fn time_of_impact<A: Support<DIM>, B: Support<DIM>, const DIM: usize>(
+    a: &A,
+    vel_a: &Vector<Real, DIM>,
+    b: &B,
+    vel_b: &Vector<Real, DIM>,
+) -> Option<Contact<DIM>> {
+    let vel = *vel_a - *vel_b;
+    let mut toi: Real = 0.0;
+    let mut last: Option<Contact<DIM>> = None;
+    for _ in 0..MAX_ITERATION {
+        let moved_a = Translated {
+            shape: a,
+            offset: *vel_a * toi,
+        };
+        let moved_b = Translated {
+            shape: b,
+            offset: *vel_b * toi,
+        };
+        let Some((pa, pb)) = closest_pts(&moved_a, &moved_b) else {
+            // overlapping, either from the beginning or advanced within precision
+            return last.or_else(|| {
+                let normal = normalize_or(&-vel, first_axis());
+                Some(Contact {
+                    toi,
+                    pt: (moved_a.support(&-normal) + moved_b.support(&normal)) * 0.5,
+                    normal,
+                })
+            });
+        };
+
+        let diff = pa - pb;
+        let dist = diff.length();
+        let normal = diff / dist;
+        let contact = Contact {
+            toi,
+            pt: (pa + pb) * 0.5,
+            normal,
+        };
+        if dist <= TOLERANCE {
+            return Some(contact);
+        }
+
+        // shapes are separated by a plane with `normal`, gap along it closes at `approach` speed
+        let approach = -vel.dot(&normal);
+        if approach <= Real::EPSILON {
+            return None;
+        }
+        let step = dist / approach;
+        toi += step;
+        if toi > 1.0 {
+            return None;
+        }
+        last = Some(Contact {
+            toi,
+            pt: contact.pt + (*vel_a + *vel_b) * (0.5 * step),
+            normal,
+        });
+    }
+    None
+}
@@ -0,0 +1,155 @@
+//! [ear clipping](https://www.geometrictools.com/Documentation/TriangulationByEarClipping.pdf) triangulation of simple polygons with holes
+//!
+//! holes are joined to the outer ring by bridges(a pair of coincident edges), then the single ring is clipped.
+//! every ear test scans the whole ring, so it is meant for outlines of up to a few thousand points.
+
+use crate::contain2d::is_polygon_contain_pt;
+use crate::geom2d::Polygon;
+use math::matrix::*;
+use math::precision::Real;
+
+/// whether segments `a1-a2` and `b1-b2` cross, touching at an end doesn't count
+fn is_segments_cross(a1: &Vec2, a2: &Vec2, b1: &Vec2, b2: &Vec2) -> bool {
+    let d1 = (*a2 - *a1).cross(&(*b1 - *a1));
+    let d2 = (*a2 - *a1).cross(&(*b2 - *a1));
+    let d3 = (*b2 - *b1).cross(&(*a1 - *b1));
+    let d4 = (*b2 - *b1).cross(&(*a2 - *b1));
+    d1 * d2 < 0.0 && d3 * d4 < 0.0
+}
+
+fn is_on_segment(p: &Vec2, a: &Vec2, b: &Vec2) -> bool {
+    (*b - *a).cross(&(*p - *a)) == 0.0 && (*p - *a).dot(&(*p - *b)) <= 0.0
+}
+
+fn is_in_triangle(p: &Vec2, a: &Vec2, b: &Vec2, c: &Vec2) -> bool {
+    (*b - *a).cross(&(*p - *a)) >= 0.0
+        && (*c - *b).cross(&(*p - *b)) >= 0.0
+        && (*a - *c).cross(&(*p - *c)) >= 0.0
+}
+
+/// ring of point indices with the given orientation
+fn oriented_ring(pts: &[Vec2], offset: usize, ccw: bool) -> Vec<usize> {
+    let mut ring: Vec<usize> = (offset..offset + pts.len()).collect();
+    if (Polygon::new(pts.to_vec()).signed_area() > 0.0) != ccw {
+        ring.reverse();
+    }
+    ring
+}
+
+/// join `hole` into `ring` through the nearest ring vertex which can see the hole's rightmost vertex
+fn bridge_hole(ring: &mut Vec<usize>, hole: &[usize], others: &[Vec<usize>], pts: &[Vec2]) {
+    let start = (0..hole.len())
+        .max_by(|a, b| pts[hole[*a]].x().total_cmp(&pts[hole[*b]].x()))
+        .unwrap();
+    let m = pts[hole[start]];
+
+    let edges = |r: &[usize]| -> Vec<(Vec2, Vec2)> {
+        (0..r.len())
+            .map(|i| (pts[r[i]], pts[r[(i + 1) % r.len()]]))
+            .collect()
+    };
+    let mut blockers = edges(ring);
+    blockers.extend(edges(hole));
+    for other in others {
+        blockers.extend(edges(other));
+    }
+
+    // a vertex in the middle of the bridge blocks it too, or the bridge may run along an edge
+    let visible = |v: &Vec2| {
+        !blockers.iter().any(|(a, b)| {
+            is_segments_cross(&m, v, a, b) || (*a != m && *a != *v && is_on_segment(a, &m, v))
+        })
+    };
+    let target = (0..ring.len())
+        .filter(|i| visible(&pts[ring[*i]]))
+        .min_by(|a, b| {
+            let da = (pts[ring[*a]] - m).length_sqrd();
+            let db = (pts[ring[*b]] - m).length_sqrd();
+            da.total_cmp(&db)
+        })
+        // a valid polygon always has a visible vertex, fall back to the nearest one for broken input
+        .unwrap_or(0);
+
+    let mut bridged: Vec<usize> = ring[..=target].to_vec();
+    bridged.extend((0..=hole.len()).map(|i| hole[(start + i) % hole.len()]));
+    bridged.extend(ring[target..].iter());
+    *ring = bridged;
+}
+
+/// triangles of simple polygon `outer` with `holes` inside it, rings can have any orientation.
+/// indices refer to points of `outer` followed by points of each hole in order, triangles are counter-clockwise.
+/// holes of less than 3 points are ignored, an outer ring of less than 3 points gives no triangle
+pub fn ear_clip(outer: &[Vec2], holes: &[Vec<Vec2>]) -> Vec<[usize; 3]> {
+    if outer.len() < 3 {
+        return Vec::new();
+    }
+    let mut pts: Vec<Vec2> = outer.to_vec();
+    let mut hole_rings = Vec::new();
+    for hole in holes {
+        if hole.len() >= 3 {
+            hole_rings.push(oriented_ring(hole, pts.len(), false));
+        }
+        pts.extend(hole.iter());
+    }
+
+    // bridge from right to left so bridges never cross holes not yet joined
+    hole_rings.sort_by(|a, b| {
+        let max_x = |r: &Vec<usize>| {
+            r.iter()
+                .map(|i| pts[*i].x())
+                .fold(Real::NEG_INFINITY, Real::max)
+        };
+        max_x(b).total_cmp(&max_x(a))
+    });
+    let mut ring = oriented_ring(outer, 0, true);
+    for i in 0..hole_rings.len() {
+        bridge_hole(&mut ring, &hole_rings[i], &hole_rings[i + 1..], &pts);
+    }
+
+    let mut triangles = Vec::new();
+    while ring.len() > 3 {
+        let n = ring.len();
+        let ring_pts: Vec<Vec2> = ring.iter().map(|i| pts[*i]).collect();
+        let is_ear = |i: usize| {
+            let (a, b, c) = (ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]);
+            let (pa, pb, pc) = (pts[a], pts[b], pts[c]);
+            if (pb - pa).cross(&(pc - pb)) <= 0.0 {
+                return false;
+            }
+            // bridge duplicates points, those at triangle corners don't block it
+            if ring.iter().any(|j| {
+                let p = pts[*j];
+                p != pa && p != pb && p != pc && is_in_triangle(&p, &pa, &pb, &pc)
+            }) {
+                return false;
+            }
+            // at a duplicated corner the triangle may still be outside, the new edge must run inside the ring
+            let mid = (pa + pc) * 0.5;
+            (0..n).all(|j| !is_segments_cross(&pa, &pc, &pts[ring[j]], &pts[ring[(j + 1) % n]]))
+                && is_polygon_contain_pt(&mid, &ring_pts)
+        };
+
+        // degenerated input may have no ear, clip the most convex vertex to make progress
+        let ear = (0..n).find(|i| is_ear(*i)).unwrap_or_else(|| {
+            (0..n)
+                .max_by(|x, y| {
+                    let turn = |i: usize| {
+                        let (pa, pb, pc) = (
+                            pts[ring[(i + n - 1) % n]],
+                            pts[ring[i]],
+                            pts[ring[(i + 1) % n]],
+                        );
+                        (pb - pa).cross(&(pc - pb))
+                    };
+                    turn(*x).total_cmp(&turn(*y))
+                })
+                .unwrap()
+        });
+        triangles.push([ring[(ear + n - 1) % n], ring[ear], ring[(ear + 1) % n]]);
+        ring.remove(ear);
+    }
+    if ring.len() == 3 {
+        triangles.push([ring[0], ring[1], ring[2]]);
+    }
+    triangles
+}
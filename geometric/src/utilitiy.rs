@@ -89,3 +89,90 @@ pub fn approx_equal(a: Real, b: Real, decimal_places: u8) -> bool {
     let b = (b * factor).trunc();
     a == b
 }
+
+fn eval_polynomial(coeffs: &[Real], t: Real) -> Real {
+    coeffs.iter().fold(0.0, |acc, c| acc * t + c)
+}
+
+/// real roots of polynomial in `[lo, hi]` in ascending order, `coeffs` starts from the highest degree.
+/// roots are isolated between roots of the derivative then found by bisection,
+/// a derivative root where polynomial is nearly zero is reported as a double root
+pub fn polynomial_roots_in(coeffs: &[Real], lo: Real, hi: Real) -> Vec<Real> {
+    let degree = coeffs.len().saturating_sub(1);
+    if degree == 0 {
+        return vec![];
+    }
+    if degree == 1 {
+        let t = -coeffs[1] / coeffs[0];
+        return if (lo..=hi).contains(&t) { vec![t] } else { vec![] };
+    }
+
+    let derivative: Vec<Real> = coeffs[..degree]
+        .iter()
+        .enumerate()
+        .map(|(i, c)| c * (degree - i) as Real)
+        .collect();
+    let critical = polynomial_roots_in(&derivative, lo, hi);
+    let tolerance = |t: Real| {
+        1e-12 * coeffs.iter().map(|c| c.abs()).sum::<Real>() * t.abs().max(1.0).powi(degree as i32)
+    };
+    let mut bounds = vec![lo];
+    bounds.extend(critical.iter());
+    bounds.push(hi);
+
+    fn push(roots: &mut Vec<Real>, t: Real) {
+        if roots.last() != Some(&t) {
+            roots.push(t);
+        }
+    }
+
+    let mut roots = Vec::new();
+    for (i, pair) in bounds.windows(2).enumerate() {
+        let (mut a, mut b) = (pair[0], pair[1]);
+        let (fa, fb) = (eval_polynomial(coeffs, a), eval_polynomial(coeffs, b));
+        // `bounds[i]` is a critical point unless it is `lo`
+        if fa == 0.0 || (i > 0 && fa.abs() <= tolerance(a)) {
+            push(&mut roots, a);
+        } else if fb != 0.0 && fa * fb < 0.0 && !(i + 2 < bounds.len() && fb.abs() <= tolerance(b)) {
+            for _ in 0..128 {
+                let mid = (a + b) * 0.5;
+                if eval_polynomial(coeffs, mid) * fa > 0.0 {
+                    a = mid;
+                } else {
+                    b = mid;
+                }
+            }
+            push(&mut roots, (a + b) * 0.5);
+        }
+    }
+    if eval_polynomial(coeffs, hi) == 0.0 {
+        push(&mut roots, hi);
+    }
+    roots
+}
+
+/// all real roots of polynomial in ascending order, nearly vanishing leading coefficients are dropped
+pub fn polynomial_roots(coeffs: &[Real]) -> Vec<Real> {
+    let max = coeffs.iter().fold(0.0, |acc: Real, c| acc.max(c.abs()));
+    let Some(first) = coeffs.iter().position(|c| c.abs() > 1e-12 * max) else {
+        return vec![];
+    };
+    let coeffs = &coeffs[first..];
+    // [Cauchy's bound](https://en.wikipedia.org/wiki/Geometrical_properties_of_polynomial_roots#Lagrange's_and_Cauchy's_bounds)
+    let bound = 1.0 + coeffs[1..].iter().fold(0.0, |acc: Real, c| acc.max((c / coeffs[0]).abs()));
+    polynomial_roots_in(coeffs, -bound, bound)
+}
+
+/// [Fisher-Yates shuffle](https://en.wikipedia.org/wiki/Fisher%E2%80%93Yates_shuffle) driven by xorshift64*,
+/// same `seed` gives same order
+pub(crate) fn shuffle<T>(items: &mut [T], seed: u64) {
+    // xorshift gets stuck at zero
+    let mut state = seed | 1;
+    for i in (1..items.len()).rev() {
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        let r = state.wrapping_mul(0x2545_F491_4F6C_DD1D);
+        items.swap(i, (r % (i as u64 + 1)) as usize);
+    }
+}
@@ -0,0 +1,198 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use geometric::contain2d::*;
+    use geometric::geom2d::*;
+    use geometric::intersect2d::*;
+    use geometric::nearest2d::pt2arc;
+    use math::matrix::*;
+    use math::precision::Real;
+    use std::f64::consts::PI;
+    use crate::common::{rand, rand_vec2};
+
+    fn arcs() -> Vec<CircleArc> {
+        vec![
+            CircleArc::new(Vec2::from_xy(1.0, -0.5), 2.0, (0.3, 2.0)),
+            // crosses the angle 2PI
+            CircleArc::new(Vec2::from_xy(-0.5, 0.5), 1.5, (5.0, 7.5)),
+            CircleArc::new(Vec2::zeros(), 1.0, (-PI, PI)),
+            CircleArc::new(Vec2::from_xy(0.5, 1.0), 3.0, (-2.5, -0.5)),
+        ]
+    }
+
+    fn samples(arc: &CircleArc) -> Vec<Vec2> {
+        let n = 20000;
+        (0..=n)
+            .map(|i| arc.pt_at(arc.range.0 + arc.sweep() * i as Real / n as Real))
+            .collect()
+    }
+
+    fn assert_on_arc(pt: &Vec2, arc: &CircleArc) {
+        assert!(((*pt - arc.center).length() - arc.radius).abs() < 1e-9);
+        let dir = *pt - arc.center;
+        assert!(arc.contain_angle(dir.y().atan2(dir.x())));
+    }
+
+    #[test]
+    fn arc_measure() {
+        for arc in arcs() {
+            let pts = samples(&arc);
+            let length: Real = pts.windows(2).map(|w| (w[1] - w[0]).length()).sum();
+            assert!((arc.length() - length).abs() < 1e-6);
+
+            let aabb = arc.aabb();
+            let (min, max) = (aabb.min(), aabb.max());
+            for i in 0..2 {
+                let lo = pts.iter().map(|p| p[i]).fold(Real::INFINITY, Real::min);
+                let hi = pts.iter().map(|p| p[i]).fold(Real::NEG_INFINITY, Real::max);
+                assert!((min[i] - lo).abs() < 1e-6 && (max[i] - hi).abs() < 1e-6);
+            }
+
+            for p in pts.iter().step_by(97) {
+                assert!(is_arc_contain_pt(p, &arc));
+            }
+            let off_circle = arc.center + (arc.start_pt() - arc.center) * 1.01;
+            assert!(!is_arc_contain_pt(&off_circle, &arc));
+        }
+
+        let arc = CircleArc::new(Vec2::zeros(), 1.0, (0.0, PI / 2.0));
+        assert!(is_arc_contain_pt(&Vec2::from_xy(0.0, 1.0), &arc));
+        assert!(!is_arc_contain_pt(&Vec2::from_xy(0.0, -1.0), &arc));
+        assert!(!is_arc_contain_pt(&Vec2::from_xy(0.5, 0.5), &arc));
+    }
+
+    #[test]
+    fn nearest_on_arc() {
+        let mut seed = 7;
+        for arc in arcs() {
+            let pts = samples(&arc);
+            for _ in 0..50 {
+                let pt = rand_vec2(&mut seed, 10.0);
+                let nearest = pt2arc(&pt, &arc);
+                assert_on_arc(&nearest, &arc);
+                let brute = pts
+                    .iter()
+                    .map(|p| (*p - pt).length())
+                    .fold(Real::INFINITY, Real::min);
+                assert!((nearest - pt).length() <= brute + 1e-9);
+                assert!((nearest - pt).length() >= brute - 1e-3);
+            }
+            assert_on_arc(&pt2arc(&arc.center, &arc), &arc);
+        }
+    }
+
+    /// every brute force crossing of a polyline approximating arc is near some intersect point, and vice versa
+    fn check_crossings(found: &[Vec2], expect: &[Vec2], tolerance: Real) {
+        assert_eq!(found.len(), expect.len(), "{:?} {:?}", found, expect);
+        for p in expect {
+            assert!(found.iter().any(|q| (*q - *p).length() < tolerance));
+        }
+    }
+
+    /// points where the sampled arc crosses the zero set of `f`
+    fn sign_changes(arc: &CircleArc, f: impl Fn(&Vec2) -> Real) -> Vec<Vec2> {
+        samples(arc)
+            .windows(2)
+            .filter(|w| f(&w[0]) * f(&w[1]) < 0.0)
+            .map(|w| w[0])
+            .collect()
+    }
+
+    #[test]
+    fn linear_arc_intersect() {
+        let mut seed = 11;
+        for arc in arcs() {
+            for _ in 0..40 {
+                let start = rand_vec2(&mut seed, 8.0);
+                let dir = rand_vec2(&mut seed, 2.0).normalize();
+                let side = |p: &Vec2| (*p - start).cross(&dir);
+                let along = |p: &Vec2| (*p - start).dot(&dir);
+
+                let line = Line2D::new(start, dir);
+                let found = line_arc_intersect(&line, &arc);
+                for p in &found {
+                    assert_on_arc(p, &arc);
+                    assert!(side(p).abs() < 1e-9);
+                }
+                check_crossings(&found, &sign_changes(&arc, side), 1e-3);
+
+                let ray = Ray2D::new(start, dir);
+                let expect: Vec<Vec2> =
+                    sign_changes(&arc, side).into_iter().filter(|p| along(p) >= 0.0).collect();
+                check_crossings(&ray_arc_intersect(&ray, &arc), &expect, 1e-3);
+
+                let end = start + dir * 3.0;
+                let seg = Segment2D::new(start, end);
+                let expect: Vec<Vec2> = sign_changes(&arc, side)
+                    .into_iter()
+                    .filter(|p| along(p) >= 0.0 && along(p) <= 3.0)
+                    .collect();
+                check_crossings(&seg_arc_intersect(&seg, &arc), &expect, 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn circular_arc_intersect() {
+        let mut seed = 5;
+        let all = arcs();
+        for arc in &all {
+            for _ in 0..40 {
+                let circle = Circle::new(rand_vec2(&mut seed, 6.0), 0.5 + rand(&mut seed) * 3.0);
+                let f = |p: &Vec2| (*p - circle.center).length() - circle.radius;
+                let found = circle_arc_intersect(&circle, arc);
+                for p in &found {
+                    assert_on_arc(p, arc);
+                    assert!(f(p).abs() < 1e-9);
+                }
+                check_crossings(&found, &sign_changes(arc, f), 1e-3);
+
+                let start = rand(&mut seed) * 2.0 * PI;
+                let end = start + 0.5 + rand(&mut seed) * 4.0;
+                let other = CircleArc::new(circle.center, circle.radius, (start, end));
+                let found = arcs_intersect(arc, &other);
+                for p in &found {
+                    assert_on_arc(p, arc);
+                    assert_on_arc(p, &other);
+                }
+                let expect: Vec<Vec2> = sign_changes(arc, f)
+                    .into_iter()
+                    .filter(|p| (pt2arc(p, &other) - *p).length() < 1e-3)
+                    .collect();
+                check_crossings(&found, &expect, 1e-2);
+            }
+        }
+
+        // overlapping arcs on the same circle meet at ends of the overlap
+        let a = CircleArc::new(Vec2::zeros(), 1.0, (0.0, 2.0));
+        let b = CircleArc::new(Vec2::zeros(), 1.0, (1.0, 3.0));
+        let found = arcs_intersect(&a, &b);
+        check_crossings(&found, &[a.end_pt(), b.start_pt()], 1e-9);
+        let c = CircleArc::new(Vec2::zeros(), 1.0, (3.0, 4.0));
+        assert!(arcs_intersect(&a, &c).is_empty());
+        assert!(circle_arc_intersect(&a.circle(), &a).is_empty());
+    }
+
+    #[test]
+    fn annulus_contain() {
+        let mut seed = 3;
+        let annulus = Annulus::new(Vec2::from_xy(1.0, 1.0), 1.0, 2.0);
+        let sector = AnnularSector::new(Vec2::from_xy(1.0, 1.0), 1.0, 2.0, (2.5, 5.0));
+        let full = AnnularSector::new(Vec2::zeros(), 0.0, 1.0, (0.0, 0.5));
+        assert!(is_annular_sector_contain_pt(&Vec2::zeros(), &full));
+        for _ in 0..1000 {
+            let pt = rand_vec2(&mut seed, 6.0) + annulus.center;
+            let dir = pt - annulus.center;
+            let len = dir.length();
+            let in_ring = (1.0..=2.0).contains(&len);
+            assert_eq!(is_annulus_contain_pt(&pt, &annulus), in_ring);
+
+            let angle = dir.y().atan2(dir.x()).rem_euclid(2.0 * PI);
+            assert_eq!(
+                is_annular_sector_contain_pt(&pt, &sector),
+                in_ring && (2.5..=5.0).contains(&angle)
+            );
+        }
+    }
+}
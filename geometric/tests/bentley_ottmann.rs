@@ -0,0 +1,128 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use geometric::bentley_ottmann::*;
+    use geometric::geom2d::*;
+    use geometric::intersect2d;
+    use math::matrix::*;
+    use math::precision::Real;
+    use std::collections::BTreeSet;
+    use crate::common::rand;
+
+    fn pairs_of(result: &[SegIntersection]) -> BTreeSet<(usize, usize)> {
+        let mut pairs = BTreeSet::new();
+        for r in result {
+            for i in 0..r.segs.len() {
+                for j in i + 1..r.segs.len() {
+                    pairs.insert((r.segs[i], r.segs[j]));
+                }
+            }
+        }
+        pairs
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut seed = 7;
+        for round in 0..20 {
+            let segs: Vec<Segment2D> = (0..40 + round * 5)
+                .map(|_| {
+                    let start = Vec2::from_xy(rand(&mut seed) * 100.0, rand(&mut seed) * 100.0);
+                    let end = start
+                        + Vec2::from_xy(rand(&mut seed) * 60.0 - 30.0, rand(&mut seed) * 60.0 - 30.0);
+                    Segment2D::new(start, end)
+                })
+                .collect();
+
+            let result = segs_intersections(&segs);
+
+            let mut expect = BTreeSet::new();
+            for i in 0..segs.len() {
+                for j in i + 1..segs.len() {
+                    if let Some(pt) = intersect2d::seg_intersect(&segs[i], &segs[j]) {
+                        expect.insert((i, j));
+                        let reported = result.iter().find(|r| r.segs.contains(&i) && r.segs.contains(&j));
+                        assert!((reported.unwrap().pt - pt).length() < 1e-6);
+                    }
+                }
+            }
+            assert_eq!(pairs_of(&result), expect);
+        }
+    }
+
+    #[test]
+    fn shared_points_and_vertical() {
+        // three segments through (5, 5), one of them vertical
+        let segs = [
+            Segment2D::new(Vec2::from_xy(0.0, 0.0), Vec2::from_xy(10.0, 10.0)),
+            Segment2D::new(Vec2::from_xy(0.0, 10.0), Vec2::from_xy(10.0, 0.0)),
+            Segment2D::new(Vec2::from_xy(5.0, -1.0), Vec2::from_xy(5.0, 12.0)),
+            Segment2D::new(Vec2::from_xy(20.0, 0.0), Vec2::from_xy(30.0, 0.0)),
+        ];
+        let result = segs_intersections(&segs);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].segs, vec![0, 1, 2]);
+        assert!((result[0].pt - Vec2::from_xy(5.0, 5.0)).length() < 1e-9);
+
+        // polyline corners touch at endpoints
+        let segs = [
+            Segment2D::new(Vec2::from_xy(0.0, 0.0), Vec2::from_xy(4.0, 0.0)),
+            Segment2D::new(Vec2::from_xy(4.0, 0.0), Vec2::from_xy(4.0, 4.0)),
+            Segment2D::new(Vec2::from_xy(4.0, 4.0), Vec2::from_xy(2.0, -2.0)),
+        ];
+        let result = segs_intersections(&segs);
+        let pairs = pairs_of(&result);
+        assert!(pairs.contains(&(0, 1)));
+        assert!(pairs.contains(&(1, 2)));
+        assert!(pairs.contains(&(0, 2)));
+    }
+
+    #[test]
+    fn collinear_overlap() {
+        let segs = [
+            Segment2D::new(Vec2::from_xy(0.0, 0.0), Vec2::from_xy(4.0, 0.0)),
+            Segment2D::new(Vec2::from_xy(6.0, 0.0), Vec2::from_xy(2.0, 0.0)),
+            Segment2D::new(Vec2::from_xy(8.0, 0.0), Vec2::from_xy(9.0, 0.0)),
+        ];
+        let result = segs_intersections(&segs);
+        let pts: Vec<Vec2> = result.iter().map(|r| r.pt).collect();
+        assert_eq!(result.len(), 2);
+        assert!((pts[0] - Vec2::from_xy(2.0, 0.0)).length() < 1e-9);
+        assert!((pts[1] - Vec2::from_xy(4.0, 0.0)).length() < 1e-9);
+        assert!(result.iter().all(|r| r.segs == vec![0, 1]));
+    }
+
+    #[test]
+    fn grid() {
+        // every horizontal crosses every vertical, endpoints stick out so no point is shared by three
+        let n = 30;
+        let mut segs = Vec::new();
+        for i in 0..n {
+            let c = i as Real;
+            segs.push(Segment2D::new(Vec2::from_xy(-1.0, c), Vec2::from_xy(n as Real, c)));
+            segs.push(Segment2D::new(Vec2::from_xy(c + 0.5, -1.0), Vec2::from_xy(c + 0.5, n as Real)));
+        }
+        let result = segs_intersections(&segs);
+        assert_eq!(result.len(), n * n);
+        for r in &result {
+            assert_eq!(r.segs.len(), 2);
+            let (h, v) = if r.segs[0] % 2 == 0 { (r.segs[0], r.segs[1]) } else { (r.segs[1], r.segs[0]) };
+            assert!(h % 2 == 0 && v % 2 == 1);
+            assert!((r.pt - Vec2::from_xy((v / 2) as Real + 0.5, (h / 2) as Real)).length() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn many_disjoint() {
+        // stacked short segments keep a large status, a linear status makes this quadratic
+        let n = 20000;
+        let segs: Vec<Segment2D> = (0..n)
+            .map(|i| {
+                let y = i as Real;
+                Segment2D::new(Vec2::from_xy(0.0, y), Vec2::from_xy(1000.0, y + 0.5))
+            })
+            .collect();
+        assert!(segs_intersections(&segs).is_empty());
+    }
+}
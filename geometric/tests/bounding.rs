@@ -0,0 +1,168 @@
+#[cfg(test)]
+mod test {
+    use geometric::{bounding2d, bounding3d};
+    use math::matrix::*;
+    use math::precision::Real;
+
+    const EPS: Real = 1e-6;
+
+    fn grid_2d() -> Vec<Vec2> {
+        let mut pts = Vec::new();
+        for i in 0..7 {
+            for j in 0..5 {
+                let x = i as Real * 0.37 - 1.0;
+                let y = j as Real * 0.53 + (i * j % 3) as Real * 0.1;
+                pts.push(Vec2::from_xy(x, y));
+            }
+        }
+        pts
+    }
+
+    fn cloud_3d() -> Vec<Vec3> {
+        (0..200)
+            .map(|i| {
+                let t = i as Real;
+                Vec3::from_xyz((t * 1.3).sin() * 2.0, (t * 0.7).cos(), (t * 2.9).sin() * 0.5)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn min_enclosing_circle() {
+        assert!(bounding2d::min_enclosing_circle(&[]).is_none());
+
+        let square = [
+            Vec2::from_xy(0.0, 0.0),
+            Vec2::from_xy(2.0, 0.0),
+            Vec2::from_xy(2.0, 2.0),
+            Vec2::from_xy(0.0, 2.0),
+            Vec2::from_xy(1.0, 1.0),
+        ];
+        let c = bounding2d::min_enclosing_circle(&square).unwrap();
+        assert!((c.center - Vec2::from_xy(1.0, 1.0)).length() < EPS);
+        assert!((c.radius - Real::sqrt(2.0)).abs() < EPS);
+
+        let pts = grid_2d();
+        let c = bounding2d::min_enclosing_circle(&pts).unwrap();
+        let max_dist = pts
+            .iter()
+            .map(|p| (*p - c.center).length())
+            .fold(0.0, Real::max);
+        assert!(max_dist <= c.radius + EPS);
+        // at least two points touch the boundary
+        let touching = pts
+            .iter()
+            .filter(|p| ((**p - c.center).length() - c.radius).abs() < EPS)
+            .count();
+        assert!(touching >= 2);
+
+        // every point of an outward spiral is outside the previous circle, input order doesn't matter
+        let mut spiral: Vec<Vec2> = (0..2000)
+            .map(|i| {
+                let t = i as Real * 0.1;
+                Vec2::from_xy(t.cos(), t.sin()) * (1.0 + t)
+            })
+            .collect();
+        let c = bounding2d::min_enclosing_circle(&spiral).unwrap();
+        spiral.reverse();
+        let reversed = bounding2d::min_enclosing_circle(&spiral).unwrap();
+        assert!((c.center - reversed.center).length() < EPS);
+        assert!((c.radius - reversed.radius).abs() < EPS);
+        assert!(spiral.iter().all(|p| (*p - c.center).length() <= c.radius + EPS));
+    }
+
+    #[test]
+    fn min_area_rect() {
+        let hull = bounding2d::convex_hull(&grid_2d());
+        for i in 0..hull.len() {
+            let a = hull[i];
+            let b = hull[(i + 1) % hull.len()];
+            let c = hull[(i + 2) % hull.len()];
+            assert!((b - a).cross(&(c - b)) > 0.0);
+        }
+
+        // rotated rectangle must be recovered exactly
+        let angle: Real = 0.4;
+        let (x, y) = (
+            Vec2::from_xy(angle.cos(), angle.sin()),
+            Vec2::from_xy(-angle.sin(), angle.cos()),
+        );
+        let center = Vec2::from_xy(3.0, -1.0);
+        let pts: Vec<Vec2> = [(-2.0, -1.0), (2.0, -1.0), (2.0, 1.0), (-2.0, 1.0), (0.5, 0.2)]
+            .iter()
+            .map(|(a, b)| center + x * *a + y * *b)
+            .collect();
+        let obb = bounding2d::min_area_rect(&pts).unwrap();
+        assert!((obb.center - center).length() < EPS);
+        assert!((area_of(&obb) - 8.0).abs() < EPS);
+
+        let pts = grid_2d();
+        let obb = bounding2d::min_area_rect(&pts).unwrap();
+        for pt in &pts {
+            let d = *pt - obb.center;
+            assert!(d.dot(&obb.x_axis()).abs() <= obb.half_size.x() + EPS);
+            assert!(d.dot(&obb.y_axis()).abs() <= obb.half_size.y() + EPS);
+        }
+        let aabb = bounding2d::aabb_from_pts(&pts).unwrap();
+        assert!(area_of(&obb) <= aabb.size().x() * aabb.size().y() + EPS);
+    }
+
+    fn area_of(obb: &geometric::geom2d::OBB) -> Real {
+        obb.half_size.x() * obb.half_size.y() * 4.0
+    }
+
+    #[test]
+    fn min_enclosing_sphere() {
+        let tetra = [
+            Vec3::from_xyz(1.0, 1.0, 1.0),
+            Vec3::from_xyz(1.0, -1.0, -1.0),
+            Vec3::from_xyz(-1.0, 1.0, -1.0),
+            Vec3::from_xyz(-1.0, -1.0, 1.0),
+            Vec3::from_xyz(0.1, 0.2, 0.3),
+        ];
+        let s = bounding3d::min_enclosing_sphere(&tetra).unwrap();
+        assert!(s.center.length() < EPS);
+        assert!((s.radius - Real::sqrt(3.0)).abs() < EPS);
+
+        let pts = cloud_3d();
+        let welzl = bounding3d::min_enclosing_sphere(&pts).unwrap();
+        let ritter = bounding3d::ritter_sphere(&pts).unwrap();
+        for pt in &pts {
+            assert!((*pt - welzl.center).length() <= welzl.radius + EPS);
+            assert!((*pt - ritter.center).length() <= ritter.radius + EPS);
+        }
+        assert!(welzl.radius <= ritter.radius + EPS);
+
+        let mut reversed = pts.clone();
+        reversed.reverse();
+        let s = bounding3d::min_enclosing_sphere(&reversed).unwrap();
+        assert!((s.center - welzl.center).length() < EPS);
+        assert!((s.radius - welzl.radius).abs() < EPS);
+    }
+
+    #[test]
+    fn pca_obb() {
+        let x = Vec3::from_xyz(1.0, 1.0, 0.0).normalize();
+        let y = Vec3::from_xyz(-1.0, 1.0, 0.0).normalize();
+        let z = Vec3::z_axis();
+        let mut pts = Vec::new();
+        for i in -5..=5 {
+            for j in -2..=2 {
+                for k in -1..=1 {
+                    pts.push(x * (i as Real) + y * (j as Real * 0.5) + z * (k as Real * 0.1));
+                }
+            }
+        }
+
+        let obb = bounding3d::obb_from_pts_pca(&pts).unwrap();
+        assert!(obb.x_axis().dot(&x).abs() > 1.0 - EPS);
+        assert!((obb.half_len.x() - 5.0).abs() < EPS);
+        assert!((obb.half_len.y() - 1.0).abs() < EPS);
+        assert!((obb.half_len.z() - 0.1).abs() < EPS);
+        assert!(obb.center.length() < EPS);
+
+        let cube = bounding3d::cube_from_pts(&pts).unwrap();
+        let volume = obb.half_len.x() * obb.half_len.y() * obb.half_len.z();
+        assert!(volume <= cube.half_len.x() * cube.half_len.y() * cube.half_len.z());
+    }
+}
@@ -0,0 +1,139 @@
+#[cfg(test)]
+mod test {
+    use geometric::bvh::*;
+    use geometric::geom2d::{Circle, Ray2D, AABB};
+    use geometric::geom3d::{Ray3D, Sphere, Triangle3D};
+    use math::matrix::*;
+    use math::precision::Real;
+
+    fn triangles() -> Vec<Triangle3D> {
+        let mut result = Vec::new();
+        for i in 0..20 {
+            for j in 0..20 {
+                let (x, y) = (i as Real, j as Real);
+                let z = (x * 0.3).sin() + (y * 0.2).cos();
+                let z2 = ((x + 1.0) * 0.3).sin() + (y * 0.2).cos();
+                let z3 = (x * 0.3).sin() + ((y + 1.0) * 0.2).cos();
+                result.push(Triangle3D::new([
+                    Vec3::from_xyz(x, y, z),
+                    Vec3::from_xyz(x + 1.0, y, z2),
+                    Vec3::from_xyz(x, y + 1.0, z3),
+                ]));
+            }
+        }
+        result
+    }
+
+    fn brute_ray_cast<T: RayCastable<3>>(prims: &[T], ray: &Ray3D) -> Option<(usize, Real)> {
+        prims
+            .iter()
+            .enumerate()
+            .filter_map(|(i, p)| p.ray_cast_param(ray).map(|t| (i, t)))
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+    }
+
+    #[test]
+    fn ray_cast_matches_brute_force() {
+        let tris = triangles();
+        let bvh = BVH3D::build(tris.clone());
+        assert_eq!(bvh.len(), tris.len());
+
+        for i in 0..50 {
+            let t = i as Real;
+            let ray = Ray3D::new(
+                Vec3::from_xyz(10.0 + (t * 0.7).sin() * 8.0, 10.0 + (t * 1.1).cos() * 8.0, 10.0),
+                Vec3::from_xyz((t * 0.3).sin() * 0.2, (t * 0.5).cos() * 0.2, -1.0),
+            );
+            let expect = brute_ray_cast(&tris, &ray);
+            let hit = bvh.ray_cast(&ray, Real::MAX);
+            assert_eq!(hit.is_some(), expect.is_some());
+            if let (Some(hit), Some(expect)) = (hit, expect) {
+                assert!((hit.1 - expect.1).abs() < 1e-9);
+            }
+            assert_eq!(bvh.ray_cast_any(&ray, Real::MAX).is_some(), expect.is_some());
+        }
+
+        let miss = Ray3D::new(Vec3::from_xyz(-5.0, -5.0, 10.0), Vec3::from_xyz(0.0, 0.0, 1.0));
+        assert!(bvh.ray_cast(&miss, Real::MAX).is_none());
+    }
+
+    #[test]
+    fn nearest_and_overlap() {
+        let tris = triangles();
+        let bvh = BVH3D::build(tris.clone());
+
+        let pt = Vec3::from_xyz(7.3, 4.1, 5.0);
+        let (_, nearest) = bvh.nearest(&pt).unwrap();
+        let brute = tris
+            .iter()
+            .map(|t| (t.nearest_pt(&pt) - pt).length_sqrd())
+            .fold(Real::MAX, Real::min);
+        assert!(((nearest - pt).length_sqrd() - brute).abs() < 1e-9);
+
+        let query = BoundingBox3D::new(Vec3::from_xyz(2.0, 2.0, -5.0), Vec3::from_xyz(4.5, 3.5, 5.0));
+        let mut found = bvh.query_box(&query);
+        found.sort();
+        let expect: Vec<usize> = (0..tris.len())
+            .filter(|i| tris[*i].bounding_box().is_intersect(&query))
+            .collect();
+        assert_eq!(found, expect);
+
+        let sphere = Sphere::new(Vec3::from_xyz(10.0, 10.0, 0.0), 2.0);
+        let mut found = bvh.query_circular(&sphere);
+        found.sort();
+        let expect: Vec<usize> = (0..tris.len())
+            .filter(|i| tris[*i].bounding_box().is_circular_intersect(&sphere))
+            .collect();
+        assert_eq!(found, expect);
+    }
+
+    #[test]
+    fn incremental_insert_remove_refit() {
+        let mut bvh = BVH2D::new();
+        let mut handles = Vec::new();
+        for i in 0..100 {
+            let t = i as Real;
+            handles.push(bvh.insert(Circle::new(
+                Vec2::from_xy((t * 1.7).sin() * 50.0, (t * 0.9).cos() * 50.0),
+                1.0 + (i % 3) as Real,
+            )));
+        }
+        assert_eq!(bvh.len(), 100);
+
+        for h in handles.iter().step_by(2) {
+            assert!(bvh.remove(*h).is_some());
+        }
+        assert!(bvh.remove(handles[0]).is_none());
+        assert_eq!(bvh.len(), 50);
+
+        for h in handles.iter().skip(1).step_by(2) {
+            bvh.get_mut(*h).unwrap().center += Vec2::from_xy(100.0, 0.0);
+        }
+        bvh.refit();
+
+        let bounds = bvh.bounds().unwrap();
+        for (_, c) in bvh.iter() {
+            assert!(bounds.contain(&c.bounding_box()));
+        }
+
+        let ray = Ray2D::new(Vec2::from_xy(0.0, 0.0), Vec2::from_xy(1.0, 0.0));
+        let expect = bvh
+            .iter()
+            .filter_map(|(i, c)| c.ray_cast_param(&ray).map(|t| (i, t)))
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        assert_eq!(bvh.ray_cast(&ray, Real::MAX).map(|h| h.0), expect.map(|h| h.0));
+
+        // removed slot is reused
+        let h = bvh.insert(Circle::new(Vec2::zeros(), 1.0));
+        assert!(handles.iter().step_by(2).any(|old| *old == h));
+
+        let rects = BVH2D::build(vec![
+            AABB::from_min_max(Vec2::from_xy(0.0, 0.0), Vec2::from_xy(1.0, 1.0)),
+            AABB::from_min_max(Vec2::from_xy(3.0, 0.0), Vec2::from_xy(4.0, 1.0)),
+        ]);
+        assert_eq!(rects.query_pt(&Vec2::from_xy(3.5, 0.5)), vec![1]);
+        let (idx, nearest) = rects.nearest(&Vec2::from_xy(1.8, 2.0)).unwrap();
+        assert_eq!(idx, 0);
+        assert!((nearest - Vec2::from_xy(1.0, 1.0)).length() < 1e-9);
+    }
+}
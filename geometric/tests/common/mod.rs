@@ -0,0 +1,25 @@
+//! helpers shared by integration tests, each test binary uses a different subset
+#![allow(dead_code)]
+
+use math::matrix::*;
+use math::precision::Real;
+
+/// deterministic pseudo random number in [0, 1)
+pub fn rand(seed: &mut u64) -> Real {
+    *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    (*seed >> 11) as Real / (1u64 << 53) as Real
+}
+
+/// components in `[-scale / 2, scale / 2)`
+pub fn rand_vec2(seed: &mut u64, scale: Real) -> Vec2 {
+    Vec2::from_xy((rand(seed) - 0.5) * scale, (rand(seed) - 0.5) * scale)
+}
+
+/// components in `[-scale / 2, scale / 2)`
+pub fn rand_vec3(seed: &mut u64, scale: Real) -> Vec3 {
+    Vec3::from_xyz(
+        (rand(seed) - 0.5) * scale,
+        (rand(seed) - 0.5) * scale,
+        (rand(seed) - 0.5) * scale,
+    )
+}
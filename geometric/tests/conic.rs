@@ -0,0 +1,289 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use geometric::conic::*;
+    use geometric::contain2d::is_conic_contain_pt;
+    use geometric::geom2d::{self, Conic, Ellipse, Hyperbola, Line2D, Parabola};
+    use geometric::geom3d::{self, ConicArc, ConicArcInPolar};
+    use geometric::intersect2d::*;
+    use geometric::nearest2d::pt2conic;
+    use math::matrix::*;
+    use math::precision::Real;
+    use std::f64::consts::PI;
+    use crate::common::rand_vec2;
+
+    fn conics() -> Vec<Conic> {
+        let mut ellipse = Ellipse::new(Vec2::from_xy(1.0, -0.5), 3.0, 1.5);
+        ellipse.rotation = 0.4;
+        let mut tall = Ellipse::new(Vec2::from_xy(-1.0, 0.5), 1.0, 2.5);
+        tall.rotation = -1.1;
+        let mut hyperbola = Hyperbola::new(Vec2::from_xy(0.5, 0.5), 1.5, 1.0);
+        hyperbola.rotation = 2.0;
+        let mut parabola = Parabola::new(Vec2::from_xy(-1.0, -1.0), 0.8);
+        parabola.rotation = 0.7;
+        vec![ellipse.into(), tall.into(), hyperbola.into(), parabola.into()]
+    }
+
+    /// dense points on conic(both branches of hyperbola), in world coordinate
+    fn samples(conic: &Conic) -> Vec<Vec2> {
+        let n = 40000;
+        (0..=n)
+            .flat_map(|i| {
+                let k = i as Real / n as Real;
+                let local = match conic {
+                    Conic::Ellipse(e) => {
+                        let t = k * 2.0 * PI;
+                        vec![Vec2::from_xy(e.a * t.cos(), e.b * t.sin())]
+                    }
+                    Conic::Hyperbola(h) => {
+                        let s = (k - 0.5) * 8.0;
+                        vec![
+                            Vec2::from_xy(h.a * s.cosh(), h.b * s.sinh()),
+                            Vec2::from_xy(-h.a * s.cosh(), h.b * s.sinh()),
+                        ]
+                    }
+                    Conic::Parabola(p) => {
+                        let y = (k - 0.5) * 40.0;
+                        vec![Vec2::from_xy(y * y / (2.0 * p.p), y)]
+                    }
+                };
+                local.into_iter().map(|p| conic.to_world(&p)).collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn nearest() {
+        let mut seed = 3;
+        for conic in conics() {
+            let eq = conic.equation();
+            let samples = samples(&conic);
+            let mut pts: Vec<Vec2> = (0..100).map(|_| rand_vec2(&mut seed, 10.0)).collect();
+            // points on local axes are degenerate cases
+            for v in [0.0, 0.3, 2.0, 6.0] {
+                pts.push(conic.to_world(&Vec2::from_xy(v, 0.0)));
+                pts.push(conic.to_world(&Vec2::from_xy(-v, 0.0)));
+                pts.push(conic.to_world(&Vec2::from_xy(0.0, v)));
+            }
+
+            for p in pts {
+                let q = pt2conic(&p, &conic);
+                assert!(eq.approx_dist(&q) < 1e-8, "{:?} {:?}", conic, p);
+                let dist = (q - p).length();
+                let brute = samples
+                    .iter()
+                    .map(|s| (*s - p).length())
+                    .fold(Real::INFINITY, Real::min);
+                assert!(dist <= brute + 1e-9 && brute - dist < 1e-3, "{:?} {:?} {} {}", conic, p, dist, brute);
+            }
+        }
+    }
+
+    #[test]
+    fn containment() {
+        for conic in conics() {
+            let eq = conic.equation();
+            for q in samples(&conic).iter().step_by(997) {
+                let n = eq.gradient(q).normalize() * 1e-6;
+                assert_ne!(is_conic_contain_pt(&(*q + n), &conic), is_conic_contain_pt(&(*q - n), &conic));
+            }
+        }
+
+        let e = Conic::from(Ellipse::new(Vec2::from_xy(1.0, 1.0), 2.0, 1.0));
+        assert!(is_conic_contain_pt(&Vec2::from_xy(1.0, 1.0), &e));
+        let h = Conic::from(Hyperbola::new(Vec2::zeros(), 1.0, 1.0));
+        assert!(!is_conic_contain_pt(&Vec2::zeros(), &h));
+        assert!(is_conic_contain_pt(&Vec2::from_xy(2.0_f64.sqrt(), 0.0), &h));
+        assert!(is_conic_contain_pt(&Vec2::from_xy(-2.0_f64.sqrt(), 0.0), &h));
+        let p = Conic::from(Parabola::new(Vec2::zeros(), 1.0));
+        assert!(is_conic_contain_pt(&Vec2::from_xy(0.5, 0.0), &p));
+        assert!(!is_conic_contain_pt(&Vec2::from_xy(-0.5, 0.0), &p));
+    }
+
+    #[test]
+    fn line_intersect() {
+        let mut seed = 11;
+        for conic in conics() {
+            let eq = conic.equation();
+            for _ in 0..50 {
+                let line = Line2D::new(rand_vec2(&mut seed, 6.0), rand_vec2(&mut seed, 2.0));
+                let params = line_conic_intersect_param(&line, &conic);
+                for t in &params {
+                    assert!(eq.approx_dist(&(line.start + line.dir * *t)) < 1e-8);
+                }
+
+                // every sign change along the line is reported
+                let f = |t: Real| eq.eval(&(line.start + line.dir * t));
+                let step = 1e-3;
+                for i in -20000..20000 {
+                    let (t0, t1) = (i as Real * step, (i + 1) as Real * step);
+                    if f(t0) * f(t1) < 0.0 {
+                        assert!(params.iter().any(|t| *t >= t0 - 1e-9 && *t <= t1 + 1e-9));
+                    }
+                }
+
+                let ray = geom2d::Ray2D::new(line.start, line.dir);
+                let ray_params = ray_conic_intersect_param(&ray, &conic);
+                assert_eq!(ray_params.len(), params.iter().filter(|t| **t >= 0.0).count());
+            }
+        }
+    }
+
+    /// number of sign changes of `other` along closed ellipse
+    fn crossings(ellipse: &Conic, other: &Conic) -> usize {
+        let eq = other.equation();
+        let s = samples(ellipse);
+        s.windows(2)
+            .filter(|w| eq.eval(&w[0]) * eq.eval(&w[1]) < 0.0)
+            .count()
+    }
+
+    #[test]
+    fn conic_intersect() {
+        let all = conics();
+        for c1 in &all[..2] {
+            for c2 in &all {
+                if std::ptr::eq(c1, c2) {
+                    continue;
+                }
+                let expect = crossings(c1, c2);
+                for (a, b) in [(c1, c2), (c2, c1)] {
+                    let pts = conics_intersect(a, b);
+                    assert_eq!(pts.len(), expect, "{:?} {:?}", a, b);
+                    for p in &pts {
+                        assert!(a.equation().approx_dist(p) < 1e-7);
+                        assert!(b.equation().approx_dist(p) < 1e-7);
+                    }
+                }
+            }
+        }
+
+        // touching ellipses, tangent point is a double root
+        let e1 = Conic::from(Ellipse::new(Vec2::zeros(), 2.0, 1.0));
+        let e2 = Conic::from(Ellipse::new(Vec2::from_xy(3.0, 0.0), 1.0, 1.0));
+        for (a, b) in [(&e1, &e2), (&e2, &e1)] {
+            let pts = conics_intersect(a, b);
+            assert_eq!(pts.len(), 1);
+            assert!((pts[0] - Vec2::from_xy(2.0, 0.0)).length() < 1e-6);
+        }
+
+        for c in &all {
+            assert!(conics_intersect(c, c).is_empty(), "{:?}", c);
+        }
+    }
+
+    #[test]
+    fn tangents() {
+        let mut seed = 5;
+        for conic in conics() {
+            let eq = conic.equation();
+            for _ in 0..100 {
+                let p = rand_vec2(&mut seed, 12.0);
+                let pts = tangent_pts(&p, &conic);
+                if is_conic_contain_pt(&p, &conic) {
+                    assert!(pts.is_empty());
+                    continue;
+                }
+                for q in &pts {
+                    assert!(eq.approx_dist(q) < 1e-8);
+                    assert!((*q - p).normalize().dot(&eq.gradient(q).normalize()).abs() < 1e-6);
+                }
+            }
+        }
+
+        let e = Conic::from(Ellipse::new(Vec2::zeros(), 1.0, 1.0));
+        let pts = tangent_pts(&Vec2::from_xy(2.0, 0.0), &e);
+        assert_eq!(pts.len(), 2);
+        for q in pts {
+            assert!((q.x() - 0.5).abs() < 1e-9 && (q.y().abs() - 0.75_f64.sqrt()).abs() < 1e-9);
+        }
+        assert!(tangent_pts(&Vec2::zeros(), &e).is_empty());
+    }
+
+    fn frame() -> (Vec3, Vec3, Vec3) {
+        let axis = Vec3::from_xyz(1.0, 2.0, 2.0) / 3.0;
+        let normal = Vec3::from_xyz(2.0, 1.0, -2.0) / 3.0;
+        (axis, normal, Vec3::from_xyz(1.0, -2.0, 0.5))
+    }
+
+    fn polar_pt(arc: &ConicArcInPolar, theta: Real) -> Vec3 {
+        let r = arc.e * arc.p / (1.0 - arc.e * theta.cos());
+        arc.origin + (arc.axis * theta.cos() + arc.normal * theta.sin()) * r
+    }
+
+    fn cartesian_pt(arc: &ConicArc, t: Real) -> Vec3 {
+        match arc.conic {
+            geom3d::Conic::Ellipse(e) => e.position + e.x_axis * (e.a * t.cos()) + e.normal * (e.b * t.sin()),
+            geom3d::Conic::Hyperbola(h) => h.position + h.x_axis * (h.a / t.cos()) + h.normal * (h.b * t.tan()),
+            geom3d::Conic::Parabola(p) => p.position + p.x_axis * (2.0 * p.p * t * t) + p.normal * (2.0 * p.p * t),
+        }
+    }
+
+    #[test]
+    fn polar_contain() {
+        let (axis, normal, origin) = frame();
+        for e in [0.5, 1.0, 1.5] {
+            let arc = ConicArcInPolar::new(2.0, e, origin, axis, normal, (0.0, 2.0 * PI));
+            for i in 0..100 {
+                let theta = (i as Real + 0.5) / 100.0 * 2.0 * PI;
+                if 1.0 - e * theta.cos() <= 1e-3 {
+                    continue;
+                }
+                let q = polar_pt(&arc, theta);
+                assert!(arc.contain(&(origin + (q - origin) * 0.98)), "{} {}", e, theta);
+                assert!(!arc.contain(&(origin + (q - origin) * 1.02)), "{} {}", e, theta);
+            }
+            assert!(arc.contain(&origin));
+        }
+    }
+
+    #[test]
+    fn polar_conversion() {
+        let (axis, normal, origin) = frame();
+        for (e, range) in [(0.5, (2.5, -2.0)), (1.0, (1.0, -1.0)), (1.5, (1.0, -1.0)), (0.8, (-0.5, 3.0))] {
+            let polar = ConicArcInPolar::new(2.0, e, origin, axis, normal, range);
+            let arc = polar.to_conic_arc();
+            for (theta, t) in [(range.0, arc.range.0), (range.1, arc.range.1)] {
+                assert!((polar_pt(&polar, theta) - cartesian_pt(&arc, t)).length() < 1e-9);
+            }
+            // polar angle goes counter-clockwise, so does ellipse param. param of the other conics goes the other way
+            assert_eq!(arc.range.1 > arc.range.0, e < 1.0);
+
+            let back = arc.to_polar().unwrap();
+            assert!((back.p - polar.p).abs() < 1e-9 && (back.e - polar.e).abs() < 1e-12);
+            assert!((back.origin - origin).length() < 1e-9);
+            assert!((back.axis - axis).length() < 1e-12 && (back.normal - normal).length() < 1e-12);
+            assert!((back.range.0 - range.0).abs() < 1e-9 && (back.range.1 - range.1).abs() < 1e-9);
+        }
+
+        // ellipse with minor axis along `x_axis` maps to the same curve
+        let arc = ConicArc {
+            conic: geom3d::Conic::Ellipse(geom3d::Ellipse {
+                x_axis: axis,
+                normal,
+                a: 1.0,
+                b: 2.0,
+                position: origin,
+            }),
+            range: (0.3, 2.0),
+        };
+        let polar = arc.to_polar().unwrap();
+        assert!((polar.axis - normal).length() < 1e-12);
+        for (t, theta) in [(arc.range.0, polar.range.0), (arc.range.1, polar.range.1)] {
+            assert!((polar_pt(&polar, theta) - cartesian_pt(&arc, t)).length() < 1e-9);
+        }
+
+        let circle = ConicArc {
+            conic: geom3d::Conic::Ellipse(geom3d::Ellipse {
+                x_axis: axis,
+                normal,
+                a: 1.0,
+                b: 1.0,
+                position: origin,
+            }),
+            range: (0.0, 1.0),
+        };
+        assert!(circle.to_polar().is_none());
+    }
+}
@@ -0,0 +1,171 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use geometric::culling::*;
+    use geometric::geom3d::*;
+    use math::coord::Cartesian3D;
+    use math::matrix::*;
+    use math::precision::Real;
+    use crate::common::{rand, rand_vec3};
+
+    fn camera_frame() -> Cartesian3D {
+        let z = Vec3::from_xyz(1.0, 2.0, 3.0).normalize();
+        let x = Vec3::y_axis().cross(&z).normalize();
+        let y = z.cross(&x);
+        Cartesian3D::new(x, y, z, Vec3::from_xyz(1.0, -1.0, 2.0))
+    }
+
+    fn frustum() -> Frustum {
+        Frustum::new(0.5, 10.0, 0.4, 1.5)
+    }
+
+    #[rustfmt::skip]
+    fn view_proj(frame: &Cartesian3D, f: &Frustum, zero_to_one_depth: bool) -> Mat44 {
+        let (x, y, z, pos) = (frame.x_axis(), frame.y_axis(), frame.z_axis(), frame.position());
+        let view = Mat44::from_row(&[
+            x.x(), x.y(), x.z(), -x.dot(&pos),
+            y.x(), y.y(), y.z(), -y.dot(&pos),
+            z.x(), z.y(), z.z(), -z.dot(&pos),
+              0.0,   0.0,   0.0,          1.0,
+        ]);
+        let inv_tan = 1.0 / f.half_fovy.tan();
+        let (a, b) = if zero_to_one_depth {
+            (f.far / (f.near - f.far), f.near * f.far / (f.near - f.far))
+        } else {
+            ((f.far + f.near) / (f.near - f.far), 2.0 * f.far * f.near / (f.near - f.far))
+        };
+        let proj = Mat44::from_row(&[
+            inv_tan / f.aspect,     0.0,  0.0, 0.0,
+                           0.0, inv_tan,  0.0, 0.0,
+                           0.0,     0.0,    a,   b,
+                           0.0,     0.0, -1.0, 0.0,
+        ]);
+        proj * view
+    }
+
+    #[test]
+    fn plane_extraction() {
+        let frame = camera_frame();
+        let placed = frustum().placed(&frame);
+        for zero_to_one_depth in [false, true] {
+            let m = view_proj(&frame, &frustum(), zero_to_one_depth);
+            let extracted = ViewFrustum::from_view_proj(&m, zero_to_one_depth);
+            for (a, b) in placed.corners.iter().zip(&extracted.corners) {
+                assert!((*a - *b).length() < 1e-9, "{:?} {:?}", a, b);
+            }
+            for (a, b) in placed.planes.iter().zip(&extracted.planes) {
+                assert!((a.normal - b.normal).length() < 1e-9);
+                assert!(b.signed_dist(&a.pt).abs() < 1e-9);
+            }
+        }
+
+        // every corner is on three planes and inside the others
+        for (i, corner) in placed.corners.iter().enumerate() {
+            let on_planes = placed
+                .planes
+                .iter()
+                .filter(|p| p.signed_dist(corner).abs() < 1e-9)
+                .count();
+            assert_eq!(on_planes, 3, "corner {}", i);
+            assert!(placed.planes.iter().all(|p| p.signed_dist(corner) < 1e-9));
+        }
+        let center = frame.position() - frame.z_axis() * 5.0;
+        assert_eq!(frustum_pt_classify(&placed, &center), CullResult::Inside);
+        assert_eq!(frustum_pt_classify(&placed, &frame.position()), CullResult::Outside);
+    }
+
+    /// classification agrees with points sampled in shape
+    fn check_samples(frustum: &ViewFrustum, result: CullResult, samples: &[Vec3]) {
+        let inside = samples
+            .iter()
+            .filter(|p| frustum_pt_classify(frustum, p) == CullResult::Inside)
+            .count();
+        match result {
+            CullResult::Inside => assert_eq!(inside, samples.len()),
+            CullResult::Outside => assert_eq!(inside, 0),
+            CullResult::Intersecting => {}
+        }
+    }
+
+    fn box_samples(center: &Vec3, half_len: &Vec3, axes: &[Vec3; 3]) -> Vec<Vec3> {
+        let n = 6;
+        let mut samples = Vec::new();
+        for i in 0..=n {
+            for j in 0..=n {
+                for k in 0..=n {
+                    let t = [i, j, k].map(|v| v as Real / n as Real * 2.0 - 1.0);
+                    samples.push((0..3).fold(*center, |acc, a| acc + axes[a] * (t[a] * half_len[a])));
+                }
+            }
+        }
+        samples
+    }
+
+    #[test]
+    fn classify_shapes() {
+        let frame = camera_frame();
+        let f = frustum().placed(&frame);
+        let mut seed = 17;
+        let mut counts = [0; 3];
+        for _ in 0..300 {
+            let center = frame.position() - frame.z_axis() * 5.0 + rand_vec3(&mut seed, 10.0);
+            let radius = 0.2 + rand(&mut seed) * 1.5;
+
+            let sphere = Sphere::new(center, radius);
+            let result = frustum_sphere_classify(&f, &sphere);
+            let axes = [Vec3::x_axis(), Vec3::y_axis(), Vec3::z_axis()];
+            let samples: Vec<Vec3> = box_samples(&center, &Vec3::ones(), &axes)
+                .into_iter()
+                .map(|p| center + (p - center) * radius)
+                .filter(|p| (*p - center).length() <= radius)
+                .collect();
+            check_samples(&f, result, &samples);
+            counts[result as usize] += 1;
+
+            let half_len = Vec3::from_xyz(rand(&mut seed), rand(&mut seed), rand(&mut seed)) * radius;
+            let aabb = Cube::from_center(center, half_len);
+            check_samples(&f, frustum_aabb_classify(&f, &aabb), &box_samples(&center, &half_len, &axes));
+
+            let z = rand_vec3(&mut seed, 2.0).normalize();
+            let x = z.cross(&rand_vec3(&mut seed, 2.0)).normalize();
+            let axes = [x, z.cross(&x), z];
+            let obb = OBB::new(center, half_len, axes);
+            check_samples(&f, frustum_obb_classify(&f, &obb), &box_samples(&center, &half_len, &axes));
+        }
+        assert!(counts.iter().all(|c| *c > 10), "{:?}", counts);
+
+        // outside of every plane but near a corner, plane tests alone can't reject it
+        let corner = f.corners[6];
+        let out = (corner - f.corners[0]).normalize();
+        let sphere = Sphere::new(corner + out * 0.6, 0.5);
+        assert!(f.planes.iter().all(|p| p.signed_dist(&sphere.center) < sphere.radius));
+        assert_eq!(frustum_sphere_classify(&f, &sphere), CullResult::Outside);
+    }
+
+    #[test]
+    fn classify_frustums() {
+        let frame = camera_frame();
+        let f = frustum().placed(&frame);
+
+        let inner = Frustum::new(1.0, 5.0, 0.2, 1.0).placed(&frame);
+        assert_eq!(frustums_classify(&f, &inner), CullResult::Inside);
+        assert_eq!(frustums_classify(&inner, &f), CullResult::Intersecting);
+
+        let behind = Cartesian3D::new(
+            -frame.x_axis(),
+            frame.y_axis(),
+            -frame.z_axis(),
+            frame.position() + frame.z_axis() * 0.1,
+        );
+        assert_eq!(frustums_classify(&f, &frustum().placed(&behind)), CullResult::Outside);
+
+        let side = Cartesian3D::new(
+            frame.x_axis(),
+            frame.y_axis(),
+            frame.z_axis(),
+            frame.position() + frame.x_axis() * 3.0,
+        );
+        assert_eq!(frustums_classify(&f, &frustum().placed(&side)), CullResult::Intersecting);
+    }
+}
@@ -0,0 +1,134 @@
+#[cfg(test)]
+mod test {
+    use geometric::geom2d::*;
+    use geometric::geom3d::{Line3D, Plane, Ray3D, Sphere, Triangle3D};
+    use geometric::intersect::*;
+    use math::matrix::*;
+    use math::precision::Real;
+
+    fn shapes_2d() -> Vec<AnyShape> {
+        vec![
+            AnyShape::Circle(Circle::new(Vec2::from_xy(0.0, 0.0), 2.0)),
+            AnyShape::Circle(Circle::new(Vec2::from_xy(3.0, 0.0), 2.0)),
+            AnyShape::AABB(AABB::from_min_max(Vec2::from_xy(1.0, -1.0), Vec2::from_xy(4.0, 1.0))),
+            AnyShape::AABB(AABB::from_min_max(Vec2::from_xy(-3.0, 3.0), Vec2::from_xy(-2.0, 4.0))),
+            AnyShape::Line2D(Line2D::new(Vec2::from_xy(0.0, 0.5), Vec2::from_xy(1.0, 0.2))),
+            AnyShape::Ray2D(Ray2D::new(Vec2::from_xy(-5.0, 0.3), Vec2::from_xy(1.0, 0.0))),
+            AnyShape::Ray2D(Ray2D::new(Vec2::from_xy(-5.0, 5.0), Vec2::from_xy(-1.0, 1.0))),
+            AnyShape::Segment2D(Segment2D::new(Vec2::from_xy(-1.0, -3.0), Vec2::from_xy(1.5, 3.0))),
+        ]
+    }
+
+    fn shapes_3d() -> Vec<AnyShape> {
+        vec![
+            AnyShape::Sphere(Sphere::new(Vec3::from_xyz(0.0, 0.0, 0.0), 2.0)),
+            AnyShape::Sphere(Sphere::new(Vec3::from_xyz(1.0, 2.0, 0.0), 1.5)),
+            AnyShape::Line3D(Line3D::new(Vec3::zeros(), Vec3::from_xyz(1.0, 1.0, 0.0))),
+            AnyShape::Ray3D(Ray3D::new(Vec3::from_xyz(0.2, 0.2, -5.0), Vec3::z_axis())),
+            AnyShape::Triangle3D(Triangle3D::new([
+                Vec3::from_xyz(0.0, 0.0, 0.0),
+                Vec3::from_xyz(1.0, 0.0, 0.0),
+                Vec3::from_xyz(0.0, 1.0, 0.0),
+            ])),
+            AnyShape::Plane(Plane::new(Vec3::z_axis(), Vec3::from_xyz(0.0, 0.0, 1.0))),
+            AnyShape::Plane(Plane::new(Vec3::from_xyz(1.0, 1.0, 0.0).normalize(), Vec3::x_axis())),
+        ]
+    }
+
+    #[test]
+    fn typed_pairs() {
+        let c1 = Circle::new(Vec2::from_xy(0.0, 0.0), 2.0);
+        let c2 = Circle::new(Vec2::from_xy(2.0, 0.0), 2.0);
+        let pts = intersection(&c1, &c2).unwrap();
+        assert_eq!(pts.len(), 2);
+        for p in pts {
+            assert!(((p - c1.center).length() - 2.0).abs() < 1e-9);
+            assert!(((p - c2.center).length() - 2.0).abs() < 1e-9);
+        }
+        assert!(!intersects(&c1, &Circle::new(Vec2::zeros(), 1.0)));
+        assert!(intersection(&c1, &c1).is_none());
+
+        let seg = Segment2D::new(Vec2::from_xy(-3.0, 0.0), Vec2::from_xy(3.0, 0.0));
+        let ray = Ray2D::new(Vec2::from_xy(0.0, -1.0), Vec2::from_xy(0.0, 1.0));
+        assert!(intersects(&seg, &ray) && intersects(&ray, &seg));
+        assert!((intersection(&seg, &ray).unwrap() - Vec2::zeros()).length() < 1e-9);
+        assert_eq!(intersection(&seg, &c1).unwrap().len(), 2);
+        assert_eq!(intersection(&c1, &seg).unwrap().len(), 2);
+
+        let r1 = AABB::from_min_max(Vec2::from_xy(0.0, 0.0), Vec2::from_xy(2.0, 2.0));
+        let r2 = AABB::from_min_max(Vec2::from_xy(1.0, -1.0), Vec2::from_xy(3.0, 1.0));
+        let overlap = intersection(&r1, &r2).unwrap();
+        assert!((overlap.min() - Vec2::from_xy(1.0, 0.0)).length() < 1e-9);
+        assert!((overlap.max() - Vec2::from_xy(2.0, 1.0)).length() < 1e-9);
+
+        let ray = Ray2D::new(Vec2::from_xy(-1.0, 0.5), Vec2::from_xy(1.0, 0.0));
+        let pts = intersection(&ray, &r1).unwrap();
+        assert!((pts[0] - Vec2::from_xy(0.0, 0.5)).length() < 1e-9);
+        assert!((pts[1] - Vec2::from_xy(2.0, 0.5)).length() < 1e-9);
+        let inside = Ray2D::new(Vec2::from_xy(1.0, 1.0), Vec2::from_xy(0.0, -1.0));
+        let pts = intersection(&r1, &inside).unwrap();
+        assert_eq!(pts.len(), 1);
+        assert!((pts[0] - Vec2::from_xy(1.0, 0.0)).length() < 1e-9);
+
+        let p1 = Plane::new(Vec3::z_axis(), Vec3::from_xyz(0.0, 0.0, 1.0));
+        let p2 = Plane::new(Vec3::x_axis(), Vec3::from_xyz(2.0, 5.0, 0.0));
+        let line = intersection(&p1, &p2).unwrap();
+        for t in [-1.0, 0.0, 3.0] {
+            let p = line.start + line.dir * t;
+            assert!((p.z() - 1.0).abs() < 1e-9);
+            assert!((p.x() - 2.0).abs() < 1e-9);
+        }
+        assert!(!intersects(&p1, &p1));
+
+        let s1 = Sphere::new(Vec3::zeros(), 2.0);
+        let s2 = Sphere::new(Vec3::from_xyz(1.0, 2.0, 2.0), 2.0);
+        let circle = intersection(&s1, &s2).unwrap();
+        let y_axis = circle.norm.cross(&circle.x_axis);
+        for i in 0..8 {
+            let t = i as Real;
+            let p = circle.center + (circle.x_axis * t.cos() + y_axis * t.sin()) * circle.radius;
+            assert!(((p - s1.center).length() - 2.0).abs() < 1e-9);
+            assert!(((p - s2.center).length() - 2.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn any_shape_dispatch() {
+        for shapes in [shapes_2d(), shapes_3d()] {
+            for a in &shapes {
+                for b in &shapes {
+                    assert_eq!(a.supports(b), b.supports(a));
+                    assert_eq!(a.intersects(b), b.intersects(a));
+                    let (ab, ba) = (a.intersection(b), b.intersection(a));
+                    assert_eq!(ab.is_some(), ba.is_some());
+                    // identical circles/spheres intersect everywhere, documented exception
+                    let concentric = std::ptr::eq(a, b)
+                        && matches!(a, AnyShape::Circle(_) | AnyShape::Sphere(_));
+                    if a.supports(b) && !concentric {
+                        assert_eq!(ab.is_some(), a.intersects(b), "{:?} {:?}", a, b);
+                    } else if !a.supports(b) {
+                        assert!(!a.intersects(b));
+                    }
+                }
+            }
+        }
+
+        let all: Vec<AnyShape> = shapes_2d().into_iter().chain(shapes_3d()).collect();
+        let supported = all
+            .iter()
+            .flat_map(|a| all.iter().map(move |b| (a, b)))
+            .filter(|(a, b)| a.supports(b))
+            .count();
+        assert!(supported > 0);
+        assert!(!shapes_2d()[0].supports(&shapes_3d()[0]));
+
+        let ray = AnyShape::Ray3D(Ray3D::new(Vec3::from_xyz(0.2, 0.2, -5.0), Vec3::z_axis()));
+        let triangle = &shapes_3d()[4];
+        match triangle.intersection(&ray) {
+            Some(ShapeIntersection::Points3D(pts)) => {
+                assert!((pts[0] - Vec3::from_xyz(0.2, 0.2, 0.0)).length() < 1e-9)
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,161 @@
+#[cfg(test)]
+mod test {
+    use geometric::polyline::*;
+    use math::matrix::*;
+    use math::precision::Real;
+
+    fn pt2seg<const DIM: usize>(
+        pt: &Vector<Real, DIM>,
+        a: &Vector<Real, DIM>,
+        b: &Vector<Real, DIM>,
+    ) -> Real {
+        let ab = *b - *a;
+        let t = if ab.length_sqrd() == 0.0 {
+            0.0
+        } else {
+            ((*pt - *a).dot(&ab) / ab.length_sqrd()).clamp(0.0, 1.0)
+        };
+        (*a + ab * t - *pt).length()
+    }
+
+    fn directed<const DIM: usize>(from: &[Vector<Real, DIM>], to: &[Vector<Real, DIM>]) -> Real {
+        let mut max: Real = 0.0;
+        for w in from.windows(2) {
+            // sample segments densely, distance to polyline is not convex along a segment
+            for s in 0..=16 {
+                let p = w[0] + (w[1] - w[0]) * (s as Real / 16.0);
+                let d = to
+                    .windows(2)
+                    .map(|v| pt2seg(&p, &v[0], &v[1]))
+                    .fold(Real::MAX, Real::min);
+                max = max.max(d);
+            }
+        }
+        max
+    }
+
+    fn hausdorff<const DIM: usize>(a: &[Vector<Real, DIM>], b: &[Vector<Real, DIM>]) -> Real {
+        directed(a, b).max(directed(b, a))
+    }
+
+    /// smallest area of the triangles formed by consecutive points
+    fn min_triangle_area<const DIM: usize>(pts: &[Vector<Real, DIM>]) -> Real {
+        pts.windows(3)
+            .map(|w| {
+                let (u, v) = (w[1] - w[0], w[2] - w[0]);
+                let dot = u.dot(&v);
+                0.5 * (u.length_sqrd() * v.length_sqrd() - dot * dot).max(0.0).sqrt()
+            })
+            .fold(Real::INFINITY, Real::min)
+    }
+
+    fn ellipse_arc() -> Vec<Vec2> {
+        // same density as the conic tessellators in `mesh_generate`
+        let mut pts = Vec::new();
+        let mut deg: Real = 0.0;
+        while deg < 3.0 {
+            pts.push(Vec2::from_xy(4.0 * deg.cos(), 2.0 * deg.sin()));
+            deg += 0.01;
+        }
+        pts
+    }
+
+    fn helix() -> Vec<Vec3> {
+        (0..500)
+            .map(|i| {
+                let t = i as Real * 0.02;
+                Vec3::from_xyz(t.cos(), t.sin(), t * 0.1)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn douglas_peucker_bounds_error() {
+        let pts = ellipse_arc();
+        for tolerance in [0.001, 0.01, 0.1] {
+            let simplified = douglas_peucker(&pts, tolerance);
+            assert!(simplified.len() < pts.len());
+            assert_eq!(simplified.first(), pts.first());
+            assert_eq!(simplified.last(), pts.last());
+            assert!(hausdorff(&pts, &simplified) <= tolerance + 1e-9);
+        }
+
+        let pts = helix();
+        let simplified = douglas_peucker(&pts, 0.01);
+        assert!(simplified.len() * 4 < pts.len());
+        assert!(hausdorff(&pts, &simplified) <= 0.01 + 1e-9);
+
+        let straight: Vec<Vec2> = (0..10).map(|i| Vec2::from_xy(i as Real, 0.0)).collect();
+        assert_eq!(douglas_peucker(&straight, 1e-6).len(), 2);
+    }
+
+    #[test]
+    fn visvalingam_whyatt_keeps_large_triangles() {
+        let pts = ellipse_arc();
+        assert!(min_triangle_area(&pts) < 1e-4);
+        let simplified = visvalingam_whyatt(&pts, 1e-4);
+        assert!(simplified.len() * 2 < pts.len());
+        assert_eq!(simplified.first(), pts.first());
+        assert_eq!(simplified.last(), pts.last());
+        assert!(min_triangle_area(&simplified) >= 1e-4);
+
+        let pts = helix();
+        let simplified = visvalingam_whyatt(&pts, 1e-4);
+        assert!(simplified.len() < pts.len());
+        assert!(min_triangle_area(&simplified) >= 1e-4);
+
+        let spike = [
+            Vec2::from_xy(0.0, 0.0),
+            Vec2::from_xy(1.0, 0.01),
+            Vec2::from_xy(2.0, 0.0),
+            Vec2::from_xy(3.0, 5.0),
+            Vec2::from_xy(4.0, 0.0),
+        ];
+        let simplified = visvalingam_whyatt(&spike, 0.1);
+        assert_eq!(simplified.len(), 4);
+        assert!(simplified.contains(&Vec2::from_xy(3.0, 5.0)));
+    }
+
+    #[test]
+    fn resample() {
+        let pts = ellipse_arc();
+        let spacing = 0.25;
+        let resampled = resample_uniform(&pts, spacing);
+        assert_eq!(resampled.first(), pts.first());
+        assert_eq!(resampled.last(), pts.last());
+        for w in resampled.windows(2).take(resampled.len() - 2) {
+            // chord never exceeds arc length
+            assert!((w[1] - w[0]).length() <= spacing + 1e-9);
+            assert!((w[1] - w[0]).length() > spacing * 0.95);
+        }
+        // points lie on the input, chords deviate at most the sagitta of a spacing-long arc
+        assert!(directed(&resampled[..], &pts[..]) < 0.01);
+
+        let resampled = resample_count(&helix(), 50);
+        assert_eq!(resampled.len(), 50);
+        let total = polyline_length(&helix());
+        assert!((polyline_length(&resampled) - total).abs() < total * 0.01);
+    }
+
+    #[test]
+    fn chaikin() {
+        let square = [
+            Vec2::from_xy(0.0, 0.0),
+            Vec2::from_xy(1.0, 0.0),
+            Vec2::from_xy(1.0, 1.0),
+            Vec2::from_xy(0.0, 1.0),
+        ];
+        let smooth = chaikin_smooth(&square, 0.001);
+        assert_eq!(smooth.first(), square.first());
+        assert_eq!(smooth.last(), square.last());
+        assert!(smooth.len() > 16);
+        // corner cutting stays inside the control polygon's hull and never moves more than a quarter of each edge
+        assert!(hausdorff(&smooth[..], &square[..]) <= 0.25 + 1e-9);
+        assert!(smooth
+            .iter()
+            .all(|p| p.x() >= 0.0 && p.x() <= 1.0 && p.y() >= 0.0 && p.y() <= 1.0));
+
+        let coarse = chaikin_smooth(&square, 10.0);
+        assert_eq!(coarse.len(), 8);
+    }
+}
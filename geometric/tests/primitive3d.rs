@@ -0,0 +1,141 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use geometric::contain3d::*;
+    use geometric::geom3d::*;
+    use geometric::intersect3d::*;
+    use geometric::nearest3d::*;
+    use math::matrix::*;
+    use math::precision::Real;
+    use crate::common::rand_vec3;
+
+    fn axes() -> [Vec3; 3] {
+        [
+            Vec3::from_xyz(1.0, 2.0, 2.0) / 3.0,
+            Vec3::from_xyz(2.0, 1.0, -2.0) / 3.0,
+            Vec3::from_xyz(-2.0, 2.0, -1.0) / 3.0,
+        ]
+    }
+
+    const SCALE: Real = 8.0;
+    const STEP: Real = 1e-3;
+    const RAY_LEN: Real = 16.0;
+
+    /// nearest point agrees with containment and sampled points, ray params agree with marching along ray
+    fn check(
+        contain: impl Fn(&Vec3) -> bool,
+        nearest: impl Fn(&Vec3) -> Vec3,
+        ray_param: impl Fn(&Ray3D) -> Option<(Real, Real)>,
+    ) {
+        let mut seed = 7;
+        let samples: Vec<Vec3> = (0..10000)
+            .map(|_| rand_vec3(&mut seed, SCALE))
+            .filter(|p| contain(p))
+            .collect();
+        assert!(!samples.is_empty());
+
+        for _ in 0..200 {
+            let p = rand_vec3(&mut seed, SCALE);
+            let n = nearest(&p);
+            if contain(&p) {
+                assert!((n - p).length() < 1e-12);
+                continue;
+            }
+            let dist = (n - p).length();
+            assert!(dist > 0.0);
+            let inward = (n - p) / dist;
+            assert!(contain(&(n + inward * 1e-6)));
+            assert!(!contain(&(n - inward * 1e-6)));
+            for s in &samples {
+                assert!((*s - p).length() >= dist - 1e-9);
+            }
+        }
+
+        for _ in 0..200 {
+            let ray = Ray3D::new(rand_vec3(&mut seed, SCALE), rand_vec3(&mut seed, 2.0));
+            let steps = (RAY_LEN / STEP) as usize;
+            let at = |i: usize| contain(&(ray.start + ray.dir * (i as Real * STEP)));
+            let enter = (0..=steps).find(|i| at(*i));
+            let exit = enter.and_then(|e| (e..=steps).find(|i| !at(*i)));
+
+            match (ray_param(&ray), enter) {
+                (Some((t0, t1)), Some(e)) => {
+                    assert!(t0 <= t1);
+                    assert!((t0 - e as Real * STEP).abs() <= STEP, "{} {}", t0, e);
+                    match exit {
+                        Some(x) => assert!((t1 - x as Real * STEP).abs() <= STEP, "{} {}", t1, x),
+                        None => assert!(t1 >= RAY_LEN - STEP),
+                    }
+                }
+                // grazing hits may fall between samples
+                (Some((t0, t1)), None) => assert!(t1 - t0 < 2.0 * STEP || t0 > RAY_LEN),
+                (None, Some(e)) => panic!("missed hit at {}", e as Real * STEP),
+                (None, None) => {}
+            }
+        }
+    }
+
+    #[test]
+    fn capsule() {
+        let capsule = Capsule::new(Vec3::from_xyz(-1.0, 0.5, 0.0), Vec3::from_xyz(1.5, 1.0, -1.0), 1.2);
+        check(
+            |p| is_capsule_contain_pt(p, &capsule),
+            |p| pt2capsule(p, &capsule),
+            |r| ray_capsule_intersect_param(r, &capsule),
+        );
+    }
+
+    #[test]
+    fn ellipsoid() {
+        let ellipsoid = Ellipsoid::new(Vec3::from_xyz(0.5, 0.0, -0.5), Vec3::from_xyz(3.0, 1.0, 2.0), axes());
+        check(
+            |p| is_ellipsoid_contain_pt(p, &ellipsoid),
+            |p| pt2ellipsoid(p, &ellipsoid),
+            |r| ray_ellipsoid_intersect_param(r, &ellipsoid),
+        );
+    }
+
+    #[test]
+    fn torus() {
+        let torus = Torus::new(Vec3::from_xyz(0.0, 0.5, 0.0), axes()[0], 2.0, 0.7);
+        check(
+            |p| is_torus_contain_pt(p, &torus),
+            |p| pt2torus(p, &torus),
+            |r| ray_torus_intersect_param(r, &torus),
+        );
+        // ray through the hole misses, ray along the plane hits the near side of the tube only
+        let hole = Ray3D::new(torus.center - torus.normal * 5.0, torus.normal);
+        assert!(ray_torus_intersect_param(&hole, &torus).is_none());
+        let side = axes()[1];
+        let (t0, t1) = ray_torus_intersect_param(&Ray3D::new(torus.center - side * 5.0, side), &torus).unwrap();
+        assert!((t0 - 2.3).abs() < 1e-9 && (t1 - 3.7).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rounded_box() {
+        let rounded = RoundedBox::new(Vec3::from_xyz(0.5, -0.5, 0.0), Vec3::from_xyz(1.5, 1.0, 0.5), 0.6);
+        check(
+            |p| is_rounded_box_contain_pt(p, &rounded),
+            |p| pt2rounded_box(p, &rounded),
+            |r| ray_rounded_box_intersect_param(r, &rounded),
+        );
+    }
+
+    #[test]
+    fn half_space_and_slab() {
+        let half_space = HalfSpace::new(axes()[1], Vec3::from_xyz(0.5, 0.5, 0.0));
+        check(
+            |p| is_half_space_contain_pt(p, &half_space),
+            |p| pt2half_space(p, &half_space),
+            |r| ray_half_space_intersect_param(r, &half_space),
+        );
+
+        let slab = Slab::new(axes()[2], Vec3::from_xyz(0.0, 0.5, 0.0), 1.0);
+        check(
+            |p| is_slab_contain_pt(p, &slab),
+            |p| pt2slab(p, &slab),
+            |r| ray_slab_intersect_param(r, &slab),
+        );
+    }
+}
@@ -0,0 +1,243 @@
+#[cfg(test)]
+mod test {
+    use geometric::geom2d::{self, *};
+    use geometric::geom3d::*;
+    use geometric::shape::*;
+    use math::cg::EularRotationXYZ;
+    use math::matrix::*;
+    use math::precision::Real;
+    use std::f64::consts::PI;
+
+    fn dirs2d() -> Vec<Vec2> {
+        (0..32)
+            .map(|i| {
+                let t = i as Real / 32.0 * 2.0 * PI + 0.1;
+                Vec2::from_xy(t.cos(), t.sin())
+            })
+            .collect()
+    }
+
+    fn dirs3d() -> Vec<Vec3> {
+        let mut dirs = Vec::new();
+        for i in 0..12 {
+            for j in 0..6 {
+                let theta = i as Real / 12.0 * 2.0 * PI + 0.1;
+                let phi = (j as Real + 0.5) / 6.0 * PI;
+                dirs.push(Vec3::from_xyz(
+                    phi.sin() * theta.cos(),
+                    phi.sin() * theta.sin(),
+                    phi.cos(),
+                ));
+            }
+        }
+        dirs
+    }
+
+    fn in_aabb(aabb: &AABB, p: &Vec2) -> bool {
+        let (min, max) = (aabb.min(), aabb.max());
+        (0..2).all(|i| p[i] >= min[i] - 1e-9 && p[i] <= max[i] + 1e-9)
+    }
+
+    fn in_cube(cube: &Cube, p: &Vec3) -> bool {
+        (0..3).all(|i| (p[i] - cube.center[i]).abs() <= cube.half_len[i] + 1e-9)
+    }
+
+    /// aabb is tight around support points, transforming scales measures and commutes with support
+    fn check_2d<S: Shape2D>(shape: &S) {
+        let aabb = shape.aabb();
+        for axis in [Vec2::x_axis(), Vec2::y_axis()] {
+            for d in [axis, -axis] {
+                let p = shape.support(&d);
+                assert!((p.dot(&d) - aabb.support(&d).dot(&d)).abs() < 1e-9);
+            }
+        }
+        for d in dirs2d() {
+            assert!(in_aabb(&aabb, &shape.support(&d)));
+        }
+
+        let t = Transform2D::new(Vec2::from_xy(3.0, -2.0), 0.7, 1.5);
+        let inv_rotation = Transform2D::new(Vec2::zeros(), -0.7, 1.0);
+        let moved = shape.transformed(&t);
+        assert!((moved.area() - shape.area() * 2.25).abs() < 1e-6);
+        assert!((moved.perimeter() - shape.perimeter() * 1.5).abs() < 1e-6);
+        assert!((moved.centroid() - t.apply_pt(&shape.centroid())).length() < 1e-9);
+        for d in dirs2d() {
+            let expect = t.apply_pt(&shape.support(&inv_rotation.rotate_vec(&d)));
+            assert!((moved.support(&d).dot(&d) - expect.dot(&d)).abs() < 1e-9);
+        }
+    }
+
+    fn check_3d<S: Shape3D>(shape: &S) {
+        let aabb = shape.aabb();
+        for axis in [Vec3::x_axis(), Vec3::y_axis(), Vec3::z_axis()] {
+            for d in [axis, -axis] {
+                let p = shape.support(&d);
+                assert!((p.dot(&d) - aabb.support(&d).dot(&d)).abs() < 1e-9);
+            }
+        }
+        for d in dirs3d() {
+            assert!(in_cube(&aabb, &shape.support(&d)));
+        }
+
+        let t = Transform3D::from_eular(
+            Vec3::from_xyz(1.0, 2.0, -3.0),
+            &EularRotationXYZ::new(0.3, -0.5, 1.1),
+            2.0,
+        );
+        let inv_rotation = Transform3D::new(Vec3::zeros(), t.rotation.transpose(), 1.0);
+        let moved = shape.transformed(&t);
+        assert!((moved.volume() - shape.volume() * 8.0).abs() < 1e-6);
+        assert!((moved.surface_area() - shape.surface_area() * 4.0).abs() < 1e-6);
+        assert!((moved.centroid() - t.apply_pt(&shape.centroid())).length() < 1e-9);
+        for d in dirs3d() {
+            let expect = t.apply_pt(&shape.support(&inv_rotation.rotate_vec(&d)));
+            assert!((moved.support(&d).dot(&d) - expect.dot(&d)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn shapes_2d() {
+        let circle = Circle::new(Vec2::from_xy(1.0, 2.0), 3.0);
+        check_2d(&circle);
+        assert!((circle.area() - 9.0 * PI).abs() < 1e-9);
+
+        let aabb = AABB::from_min_max(Vec2::from_xy(-1.0, 0.0), Vec2::from_xy(3.0, 1.0));
+        check_2d(&aabb);
+        assert_eq!(aabb.area(), 4.0);
+        assert_eq!(aabb.perimeter(), 10.0);
+        let obb = aabb.transformed(&Transform2D::new(Vec2::zeros(), PI / 2.0, 1.0));
+        assert!((obb.aabb().half_size - Vec2::from_xy(0.5, 2.0)).length() < 1e-9);
+
+        let mut obb = geom2d::OBB::new(Vec2::from_xy(0.0, 1.0), Vec2::from_xy(2.0, 0.5));
+        obb.set_rotation(0.4);
+        check_2d(&obb);
+
+        let triangle = Triangle2D::new([
+            Vec2::from_xy(0.0, 0.0),
+            Vec2::from_xy(4.0, 0.0),
+            Vec2::from_xy(0.0, 3.0),
+        ]);
+        check_2d(&triangle);
+        assert_eq!(triangle.area(), 6.0);
+        assert_eq!(triangle.perimeter(), 12.0);
+
+        let mut ellipse = geom2d::Ellipse::new(Vec2::from_xy(1.0, -1.0), 3.0, 1.0);
+        ellipse.rotation = 0.5;
+        check_2d(&ellipse);
+        // compare perimeter with numeric integration
+        let n = 100000;
+        let numeric: Real = (0..n)
+            .map(|i| {
+                let t = (i as Real + 0.5) / n as Real * 2.0 * PI;
+                (9.0 * t.sin() * t.sin() + t.cos() * t.cos()).sqrt() * 2.0 * PI / n as Real
+            })
+            .sum();
+        assert!((ellipse.perimeter() - numeric).abs() < 1e-4);
+
+        // L-shape, clockwise
+        let polygon = geom2d::Polygon::new(vec![
+            Vec2::from_xy(0.0, 0.0),
+            Vec2::from_xy(0.0, 2.0),
+            Vec2::from_xy(1.0, 2.0),
+            Vec2::from_xy(1.0, 1.0),
+            Vec2::from_xy(2.0, 1.0),
+            Vec2::from_xy(2.0, 0.0),
+        ]);
+        check_2d(&polygon);
+        assert_eq!(polygon.signed_area(), -3.0);
+        assert_eq!(polygon.area(), 3.0);
+        assert_eq!(polygon.perimeter(), 8.0);
+        let expect = Vec2::from_xy(5.0 / 6.0, 5.0 / 6.0);
+        assert!((polygon.centroid() - expect).length() < 1e-9);
+
+        let annulus = Annulus::new(Vec2::from_xy(1.0, 1.0), 1.0, 2.0);
+        check_2d(&annulus);
+        assert!((annulus.area() - 3.0 * PI).abs() < 1e-9);
+
+        let sector = AnnularSector::new(Vec2::from_xy(-1.0, 0.5), 1.0, 2.0, (2.5, 5.0));
+        check_2d(&sector);
+        assert!((sector.perimeter() - (2.5 * 3.0 + 2.0)).abs() < 1e-9);
+        // centroid agrees with integration over polar grid
+        let n = 400;
+        let (mut area, mut moment) = (0.0, Vec2::zeros());
+        for i in 0..n {
+            for j in 0..n {
+                let r = 1.0 + (i as Real + 0.5) / n as Real;
+                let t = 2.5 + 2.5 * (j as Real + 0.5) / n as Real;
+                let da = r * (2.5 / n as Real) * (1.0 / n as Real);
+                area += da;
+                moment += Vec2::from_xy(t.cos(), t.sin()) * (r * da);
+            }
+        }
+        assert!((sector.area() - area).abs() < 1e-6);
+        assert!((sector.centroid() - sector.center - moment * (1.0 / area)).length() < 1e-4);
+    }
+
+    #[test]
+    fn shapes_3d() {
+        let sphere = Sphere::new(Vec3::from_xyz(1.0, 0.0, -1.0), 2.0);
+        check_3d(&sphere);
+
+        let cube = Cube::from_min_max(Vec3::zeros(), Vec3::from_xyz(1.0, 2.0, 3.0));
+        check_3d(&cube);
+        assert_eq!(cube.volume(), 6.0);
+        assert_eq!(cube.surface_area(), 22.0);
+
+        let obb = cube.transformed(&Transform3D::from_eular(
+            Vec3::zeros(),
+            &EularRotationXYZ::new(0.2, 0.4, 0.6),
+            1.0,
+        ));
+        check_3d(&obb);
+
+        let dir = Vec3::from_xyz(1.0, 2.0, 2.0).normalize();
+        let cylinder = Cylinder {
+            bottom: Vec3::from_xyz(0.0, 1.0, 0.0),
+            dir,
+            height: 3.0,
+            radius: 1.0,
+        };
+        check_3d(&cylinder);
+        assert!((cylinder.volume() - 3.0 * PI).abs() < 1e-9);
+
+        let cone = Cone {
+            bottom: Vec3::zeros(),
+            bottom_radius: 3.0,
+            dir,
+            height: 4.0,
+        };
+        check_3d(&cone);
+        assert!((cone.surface_area() - 24.0 * PI).abs() < 1e-9);
+
+        let truncated = TruncatedCone {
+            bottom: Vec3::zeros(),
+            bottom_radius: 2.0,
+            top_radius: 1.0,
+            dir,
+            height: 3.0,
+        };
+        check_3d(&truncated);
+        // volume and centroid agree with slicing along axis
+        let n = 10000;
+        let (mut volume, mut moment) = (0.0, 0.0);
+        for i in 0..n {
+            let h = (i as Real + 0.5) / n as Real * 3.0;
+            let r = 2.0 - h / 3.0;
+            let slice = PI * r * r * 3.0 / n as Real;
+            volume += slice;
+            moment += slice * h;
+        }
+        assert!((truncated.volume() - volume).abs() < 1e-6);
+        assert!((truncated.centroid() - dir * (moment / volume)).length() < 1e-6);
+
+        let degenerated = TruncatedCone {
+            bottom: Vec3::zeros(),
+            bottom_radius: 3.0,
+            top_radius: 0.0,
+            dir,
+            height: 4.0,
+        };
+        assert!((degenerated.volume() - cone.volume()).abs() < 1e-9);
+        assert!((degenerated.centroid() - cone.centroid()).length() < 1e-9);
+    }
+}
@@ -0,0 +1,152 @@
+#[cfg(test)]
+mod test {
+    use geometric::bvh::BoundingBox;
+    use geometric::kdtree::*;
+    use geometric::loose_tree::*;
+    use geometric::spatial_index::*;
+    use math::matrix::*;
+    use math::precision::Real;
+
+    fn point(i: usize) -> Vec3 {
+        let t = i as Real;
+        Vec3::from_xyz((t * 1.37).sin() * 40.0, (t * 0.71).cos() * 25.0, (t * 2.3).sin() * 10.0)
+    }
+
+    fn sorted(mut v: Vec<usize>) -> Vec<usize> {
+        v.sort();
+        v
+    }
+
+    /// exercises an index through the shared trait and compares with brute force
+    fn check_index<I: SpatialIndex<usize, 3>>(mut index: I) {
+        let mut handles = Vec::new();
+        for i in 0..300 {
+            handles.push(index.insert(point(i), i));
+        }
+        for h in handles.iter().step_by(3) {
+            assert!(index.remove(*h).is_some());
+        }
+        for h in handles.iter().skip(1).step_by(3) {
+            let pos = index.position(*h).unwrap();
+            assert!(index.update(*h, pos + Vec3::from_xyz(5.0, -3.0, 1.0)));
+        }
+        assert!(index.remove(handles[0]).is_none());
+        assert_eq!(index.len(), 200);
+
+        let live: Vec<(usize, Vec3)> = handles
+            .iter()
+            .filter_map(|h| index.position(*h).map(|p| (*h, p)))
+            .collect();
+        assert_eq!(live.len(), 200);
+        for (h, _) in &live {
+            assert!(index.get(*h).is_some());
+        }
+
+        let bounds = BoundingBox::new(Vec3::from_xyz(-10.0, -20.0, -5.0), Vec3::from_xyz(25.0, 5.0, 8.0));
+        let expect: Vec<usize> = live
+            .iter()
+            .filter(|(_, p)| bounds.contain_pt(p))
+            .map(|(h, _)| *h)
+            .collect();
+        assert_eq!(sorted(index.query_box(&bounds)), sorted(expect));
+
+        let center = Vec3::from_xyz(3.0, 2.0, 1.0);
+        let radius = 15.0;
+        let expect: Vec<usize> = live
+            .iter()
+            .filter(|(_, p)| (*p - center).length() <= radius)
+            .map(|(h, _)| *h)
+            .collect();
+        assert_eq!(sorted(index.query_radius(&center, radius)), sorted(expect));
+
+        for query in [center, Vec3::from_xyz(100.0, 100.0, 100.0), Vec3::from_xyz(-30.0, 0.0, 4.0)] {
+            let mut by_dist = live.clone();
+            by_dist.sort_by(|a, b| {
+                (a.1 - query)
+                    .length_sqrd()
+                    .partial_cmp(&(b.1 - query).length_sqrd())
+                    .unwrap()
+            });
+            let found = index.nearest_k(&query, 7);
+            assert_eq!(found.len(), 7);
+            for (f, e) in found.iter().zip(by_dist.iter()) {
+                let d1 = (index.position(*f).unwrap() - query).length_sqrd();
+                let d2 = (e.1 - query).length_sqrd();
+                assert!((d1 - d2).abs() < 1e-9);
+            }
+            assert_eq!(index.nearest(&query), found.first().copied());
+        }
+        assert_eq!(index.nearest_k(&center, 1000).len(), 200);
+    }
+
+    #[test]
+    fn octree() {
+        let world = BoundingBox::new(Vec3::from_xyz(-50.0, -50.0, -50.0), Vec3::from_xyz(50.0, 50.0, 50.0));
+        check_index(Octree::new(world, 6));
+    }
+
+    #[test]
+    fn kdtree() {
+        check_index(KdTree3D::new());
+
+        let tree = KdTree3D::build((0..100).map(|i| (point(i), i)).collect());
+        let nearest = tree.nearest(&point(42)).unwrap();
+        assert_eq!(*tree.get(nearest).unwrap(), 42);
+    }
+
+    #[test]
+    fn hash_grid() {
+        check_index(SpatialHashGrid3D::new(4.0));
+    }
+
+    #[test]
+    fn quadtree_objects() {
+        let world = BoundingBox::new(Vec2::from_xy(0.0, 0.0), Vec2::from_xy(64.0, 64.0));
+        let mut tree = Quadtree::new(world, 5);
+        let small = tree.insert_bounded(
+            BoundingBox::new(Vec2::from_xy(1.0, 1.0), Vec2::from_xy(2.0, 2.0)),
+            "small",
+        );
+        let large = tree.insert_bounded(
+            BoundingBox::new(Vec2::from_xy(10.0, 10.0), Vec2::from_xy(50.0, 30.0)),
+            "large",
+        );
+        let outside = tree.insert(Vec2::from_xy(-10.0, 80.0), "outside");
+
+        let query = BoundingBox::new(Vec2::from_xy(40.0, 25.0), Vec2::from_xy(45.0, 28.0));
+        assert_eq!(tree.query_overlap(&query), vec![large]);
+        assert_eq!(tree.nearest(&Vec2::from_xy(0.0, 0.0)), Some(small));
+        assert_eq!(tree.nearest(&Vec2::from_xy(-20.0, 90.0)), Some(outside));
+
+        assert!(tree.update_bounded(
+            small,
+            BoundingBox::new(Vec2::from_xy(60.0, 60.0), Vec2::from_xy(61.0, 61.0)),
+        ));
+        assert_eq!(tree.nearest(&Vec2::from_xy(63.0, 63.0)), Some(small));
+        assert_eq!(tree.remove(large), Some("large"));
+        assert!(tree.query_overlap(&query).is_empty());
+
+        // nodes emptied by moves and removals are freed and reused
+        assert_eq!(tree.remove(small), Some("small"));
+        assert_eq!(tree.remove(outside), Some("outside"));
+        assert_eq!(tree.node_count(), 1);
+        let mut handles = Vec::new();
+        for i in 0..64 {
+            let pt = Vec2::from_xy(i as Real + 0.5, (i * 7 % 64) as Real + 0.5);
+            handles.push(tree.insert(pt, "point"));
+        }
+        let full = tree.node_count();
+        for round in 1..4 {
+            for (i, h) in handles.iter().enumerate() {
+                let pt = Vec2::from_xy(((i + round * 13) % 64) as Real + 0.5, ((i * 7 + round) % 64) as Real + 0.5);
+                assert!(tree.update(*h, pt));
+            }
+            assert!(tree.node_count() <= 2 * full);
+        }
+        for h in handles {
+            assert_eq!(tree.remove(h), Some("point"));
+        }
+        assert_eq!(tree.node_count(), 1);
+        assert!(tree.query_overlap(&world).is_empty());
+    }
+}
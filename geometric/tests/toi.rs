@@ -0,0 +1,190 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use geometric::bvh::BoundingBox;
+    use geometric::geom2d::{self, Circle, Line2D, AABB};
+    use geometric::geom3d::{Cube, Plane, Sphere, Triangle3D};
+    use geometric::gjk;
+    use geometric::nearest_common;
+    use geometric::toi::*;
+    use math::matrix::*;
+    use math::precision::Real;
+    use crate::common::{rand, rand_vec3};
+
+    const STEPS: usize = 4000;
+
+    /// first sampled time where `gap(t) <= 0`
+    fn brute_toi(gap: impl Fn(Real) -> Real) -> Option<Real> {
+        (0..=STEPS)
+            .map(|i| i as Real / STEPS as Real)
+            .find(|t| gap(*t) <= 0.0)
+    }
+
+    fn check_toi(contact: Option<Real>, brute: Option<Real>) {
+        match (contact, brute) {
+            (Some(t), Some(b)) => assert!(t <= b + 1e-6 && t >= b - 1.5 / STEPS as Real, "{} {}", t, b),
+            // grazing contacts may fall between samples
+            (Some(t), None) => assert!(t <= 1.0),
+            (None, Some(b)) => panic!("missed contact at {}", b),
+            (None, None) => {}
+        }
+    }
+
+    #[test]
+    fn circular() {
+        let a = Circle::new(Vec2::from_xy(0.0, 0.0), 1.0);
+        let b = Circle::new(Vec2::from_xy(10.0, 0.0), 1.0);
+        let contact = swept_circular(&a, &Vec2::from_xy(8.0, 0.0), &b, &Vec2::from_xy(-8.0, 0.0)).unwrap();
+        assert!((contact.toi - 0.5).abs() < 1e-9);
+        assert!((contact.pt - Vec2::from_xy(5.0, 0.0)).length() < 1e-9);
+        assert!((contact.normal - Vec2::from_xy(-1.0, 0.0)).length() < 1e-9);
+        assert!(swept_circular(&a, &Vec2::from_xy(3.0, 0.0), &b, &Vec2::zeros()).is_none());
+        assert!(swept_circular(&a, &Vec2::from_xy(0.0, 30.0), &b, &Vec2::zeros()).is_none());
+
+        // tunneling: thin line, fast circle
+        let line = Line2D::new(Vec2::from_xy(5.0, 0.0), Vec2::from_xy(0.0, 1.0));
+        let contact = swept_circle_line(&a, &Vec2::from_xy(100.0, 0.0), &line).unwrap();
+        assert!((contact.toi - 0.04).abs() < 1e-9);
+        assert!((contact.normal - Vec2::from_xy(-1.0, 0.0)).length() < 1e-9);
+        assert!((contact.pt - Vec2::from_xy(5.0, 0.0)).length() < 1e-9);
+
+        let plane = Plane::new(Vec3::z_axis(), Vec3::zeros());
+        let sphere = Sphere::new(Vec3::from_xyz(1.0, 2.0, -3.0), 1.0);
+        let contact = swept_sphere_plane(&sphere, &Vec3::from_xyz(0.0, 0.0, 8.0), &plane).unwrap();
+        assert!((contact.toi - 0.25).abs() < 1e-9);
+        assert!((contact.normal - Vec3::from_xyz(0.0, 0.0, -1.0)).length() < 1e-9);
+        assert!(swept_sphere_plane(&sphere, &Vec3::from_xyz(0.0, 0.0, -8.0), &plane).is_none());
+    }
+
+    #[test]
+    fn sphere_triangle() {
+        let triangle = Triangle3D::new([
+            Vec3::from_xyz(0.0, 0.0, 0.0),
+            Vec3::from_xyz(4.0, 0.0, 0.0),
+            Vec3::from_xyz(0.0, 4.0, 0.0),
+        ]);
+
+        // face
+        let sphere = Sphere::new(Vec3::from_xyz(1.0, 1.0, 5.0), 1.0);
+        let contact = swept_sphere_triangle(&sphere, &Vec3::from_xyz(0.0, 0.0, -8.0), &triangle).unwrap();
+        assert!((contact.toi - 0.5).abs() < 1e-9);
+        assert!((contact.pt - Vec3::from_xyz(1.0, 1.0, 0.0)).length() < 1e-9);
+
+        let mut seed = 3;
+        for _ in 0..200 {
+            let sphere = Sphere::new(rand_vec3(&mut seed, 12.0), 0.3 + rand(&mut seed));
+            let target = rand_vec3(&mut seed, 4.0) + Vec3::from_xyz(1.0, 1.0, 0.0);
+            let vel = (target - sphere.center) * (1.0 + rand(&mut seed));
+
+            let contact = swept_sphere_triangle(&sphere, &vel, &triangle);
+            let brute = brute_toi(|t| {
+                let center = sphere.center + vel * t;
+                (nearest_common::pt2triangle(&center, &triangle) - center).length() - sphere.radius
+            });
+            check_toi(contact.map(|c| c.toi), brute);
+            if let Some(c) = contact {
+                let center = sphere.center + vel * c.toi;
+                assert!(((center - c.pt).length() - sphere.radius).abs() < 1e-6 || c.toi == 0.0);
+                assert!((nearest_common::pt2triangle(&c.pt, &triangle) - c.pt).length() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn circular_aabb() {
+        let bbox = BoundingBox::new(Vec3::from_xyz(-1.0, -1.0, -1.0), Vec3::from_xyz(1.0, 2.0, 1.0));
+        let sphere = Sphere::new(Vec3::from_xyz(-5.0, 0.0, 0.0), 1.0);
+        let contact = swept_circular_aabb(&sphere, &Vec3::from_xyz(6.0, 0.0, 0.0), &bbox).unwrap();
+        assert!((contact.toi - 0.5).abs() < 1e-9);
+        assert!((contact.normal - Vec3::from_xyz(-1.0, 0.0, 0.0)).length() < 1e-9);
+
+        let mut seed = 11;
+        for _ in 0..200 {
+            let sphere = Sphere::new(rand_vec3(&mut seed, 12.0), 0.2 + rand(&mut seed));
+            let vel = (rand_vec3(&mut seed, 3.0) - sphere.center) * (0.5 + rand(&mut seed));
+            let contact = swept_circular_aabb(&sphere, &vel, &bbox);
+            let brute = brute_toi(|t| bbox.pt_dist_sqrd(&(sphere.center + vel * t)).sqrt() - sphere.radius);
+            check_toi(contact.map(|c| c.toi), brute);
+        }
+    }
+
+    #[test]
+    fn aabb_pair() {
+        let a = BoundingBox::new(Vec2::from_xy(0.0, 0.0), Vec2::from_xy(1.0, 1.0));
+        let b = BoundingBox::new(Vec2::from_xy(5.0, 0.5), Vec2::from_xy(6.0, 3.0));
+        let contact = swept_aabb(&a, &Vec2::from_xy(8.0, 0.0), &b, &Vec2::zeros()).unwrap();
+        assert!((contact.toi - 0.5).abs() < 1e-9);
+        assert_eq!(contact.normal, Vec2::from_xy(-1.0, 0.0));
+        assert!((contact.pt - Vec2::from_xy(5.0, 0.75)).length() < 1e-9);
+        assert!(swept_aabb(&a, &Vec2::from_xy(8.0, 8.0), &b, &Vec2::zeros()).is_none());
+
+        let overlapped = BoundingBox::new(Vec2::from_xy(0.8, 0.0), Vec2::from_xy(3.0, 1.0));
+        let contact = swept_aabb(&a, &Vec2::zeros(), &overlapped, &Vec2::zeros()).unwrap();
+        assert_eq!(contact.toi, 0.0);
+        assert_eq!(contact.normal, Vec2::from_xy(-1.0, 0.0));
+    }
+
+    #[test]
+    fn conservative_advancement() {
+        // agrees with analytic results
+        let a = Sphere::new(Vec3::from_xyz(0.0, 0.0, 0.0), 1.0);
+        let b = Sphere::new(Vec3::from_xyz(10.0, 1.0, 0.0), 1.5);
+        let (va, vb) = (Vec3::from_xyz(12.0, 0.0, 0.0), Vec3::from_xyz(-2.0, 0.5, 0.0));
+        let expect = swept_circular(&a, &va, &b, &vb).unwrap();
+        let contact = time_of_impact(&a, &va, &b, &vb).unwrap();
+        assert!((contact.toi - expect.toi).abs() < 1e-6);
+        assert!((contact.pt - expect.pt).length() < 1e-3);
+        assert!((contact.normal - expect.normal).length() < 1e-3);
+
+        let a = AABB::from_min_max(Vec2::from_xy(0.0, 0.0), Vec2::from_xy(1.0, 1.0));
+        let b = AABB::from_min_max(Vec2::from_xy(5.0, 0.5), Vec2::from_xy(6.0, 3.0));
+        let vel = Vec2::from_xy(8.0, 1.0);
+        let expect = swept_aabb(&a.into(), &vel, &b.into(), &Vec2::zeros()).unwrap();
+        let contact = time_of_impact(&a, &vel, &b, &Vec2::zeros()).unwrap();
+        assert!((contact.toi - expect.toi).abs() < 1e-6);
+        assert!((contact.normal - expect.normal).length() < 1e-3);
+
+        // rotated box against triangle, compare with sampling
+        let mut obb = geom2d::OBB::new(Vec2::from_xy(-6.0, 0.0), Vec2::from_xy(1.0, 0.3));
+        obb.set_rotation(0.6);
+        let triangle = geom2d::Triangle2D::new([
+            Vec2::from_xy(2.0, -1.0),
+            Vec2::from_xy(4.0, 0.0),
+            Vec2::from_xy(2.5, 2.0),
+        ]);
+        let mut seed = 5;
+        for _ in 0..50 {
+            let vel = Vec2::from_xy(8.0 + rand(&mut seed) * 4.0, (rand(&mut seed) - 0.5) * 6.0);
+            let contact = time_of_impact(&obb, &vel, &triangle, &Vec2::zeros());
+            let brute = brute_toi(|t| {
+                let moved = gjk::Translated {
+                    shape: &obb,
+                    offset: vel * t,
+                };
+                gjk::distance(&moved, &triangle) - 1e-9
+            });
+            check_toi(contact.map(|c| c.toi), brute);
+        }
+
+        // already overlapping
+        let cube = Cube::from_min_max(Vec3::zeros(), Vec3::ones());
+        let contact = time_of_impact(&cube, &Vec3::x_axis(), &a_sphere(), &Vec3::zeros()).unwrap();
+        assert_eq!(contact.toi, 0.0);
+    }
+
+    fn a_sphere() -> Sphere {
+        Sphere::new(Vec3::from_xyz(0.5, 0.5, 0.5), 0.2)
+    }
+
+    #[test]
+    fn gjk_distance() {
+        let a = Sphere::new(Vec3::zeros(), 1.0);
+        let b = Cube::from_min_max(Vec3::from_xyz(3.0, -1.0, -1.0), Vec3::from_xyz(4.0, 1.0, 1.0));
+        let (pa, pb) = gjk::closest_pts(&a, &b).unwrap();
+        assert!((pa - Vec3::x_axis()).length() < 1e-4);
+        assert!((pb.x() - 3.0).abs() < 1e-6);
+        assert!((gjk::distance(&a, &b) - 2.0).abs() < 1e-6);
+        assert!(gjk::closest_pts(&a, &Sphere::new(Vec3::from_xyz(1.5, 0.0, 0.0), 1.0)).is_none());
+    }
+}
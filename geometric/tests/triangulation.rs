@@ -0,0 +1,143 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use geometric::contain2d::is_polygon_contain_pt;
+    use geometric::geom2d::Polygon;
+    use geometric::triangulation::*;
+    use math::matrix::*;
+    use math::precision::Real;
+    use std::f64::consts::PI;
+    use crate::common::rand;
+
+    fn rect(min: (Real, Real), max: (Real, Real)) -> Vec<Vec2> {
+        vec![
+            Vec2::from_xy(min.0, min.1),
+            Vec2::from_xy(max.0, min.1),
+            Vec2::from_xy(max.0, max.1),
+            Vec2::from_xy(min.0, max.1),
+        ]
+    }
+
+    fn star(n: usize, center: Vec2, inner: Real, outer: Real) -> Vec<Vec2> {
+        (0..n * 2)
+            .map(|i| {
+                let angle = PI * i as Real / n as Real;
+                let r = if i % 2 == 0 { outer } else { inner };
+                center + Vec2::from_xy(angle.cos(), angle.sin()) * r
+            })
+            .collect()
+    }
+
+    /// triangles are counter-clockwise, cover the region exactly once
+    fn check(outer: &[Vec2], holes: &[Vec<Vec2>]) {
+        let triangles = ear_clip(outer, holes);
+        let mut pts = outer.to_vec();
+        holes.iter().for_each(|h| pts.extend(h.iter()));
+        let tri_pts: Vec<[Vec2; 3]> = triangles.iter().map(|t| t.map(|i| pts[i])).collect();
+        assert_eq!(triangles.len(), pts.len() + 2 * holes.len() - 2);
+
+        let mut area = 0.0;
+        for [a, b, c] in &tri_pts {
+            let tri_area = (*b - *a).cross(&(*c - *a)) * 0.5;
+            assert!(tri_area >= 0.0);
+            area += tri_area;
+        }
+        let area_of = |ring: &[Vec2]| Polygon::new(ring.to_vec()).signed_area().abs();
+        let expect = area_of(outer) - holes.iter().map(|h| area_of(h)).sum::<Real>();
+        assert!((area - expect).abs() < 1e-9, "{} {}", area, expect);
+
+        let mut seed = 5;
+        for _ in 0..2000 {
+            let p = Vec2::from_xy(rand(&mut seed) * 8.0 - 4.0, rand(&mut seed) * 8.0 - 4.0);
+            let inside = is_polygon_contain_pt(&p, outer) && !holes.iter().any(|h| is_polygon_contain_pt(&p, h));
+            let covered = tri_pts
+                .iter()
+                .filter(|[a, b, c]| {
+                    (*b - *a).cross(&(p - *a)) > 0.0
+                        && (*c - *b).cross(&(p - *b)) > 0.0
+                        && (*a - *c).cross(&(p - *c)) > 0.0
+                })
+                .count();
+            assert!(covered <= 1);
+            // points too close to an edge may fall between two triangles
+            if inside {
+                let near_edge = tri_pts.iter().any(|t| {
+                    (0..3).any(|i| {
+                        let (a, b) = (t[i], t[(i + 1) % 3]);
+                        ((b - a).cross(&(p - a)) / (b - a).length()).abs() < 1e-9
+                    })
+                });
+                assert!(covered == 1 || near_edge);
+            } else {
+                assert_eq!(covered, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn simple_polygons() {
+        check(&rect((-1.0, -1.0), (2.0, 1.0)), &[]);
+        let mut clockwise = rect((-1.0, -1.0), (2.0, 1.0));
+        clockwise.reverse();
+        check(&clockwise, &[]);
+        check(&star(5, Vec2::from_xy(0.5, 0.0), 1.0, 3.0), &[]);
+
+        // comb with deep notches
+        let mut comb = vec![Vec2::from_xy(-3.0, -3.0), Vec2::from_xy(3.0, -3.0)];
+        for i in 0..4 {
+            let x = 3.0 - i as Real * 1.5;
+            comb.extend([
+                Vec2::from_xy(x, 3.0),
+                Vec2::from_xy(x - 0.75, 3.0),
+                Vec2::from_xy(x - 0.75, -2.0),
+            ]);
+        }
+        comb.push(Vec2::from_xy(-3.0, -2.0));
+        check(&comb, &[]);
+    }
+
+    #[test]
+    fn polygons_with_holes() {
+        let outer = rect((-3.0, -3.0), (3.0, 3.0));
+        check(&outer, &[rect((-1.0, -1.0), (1.0, 1.0))]);
+
+        let mut reversed = rect((1.0, -2.0), (2.0, 2.0));
+        reversed.reverse();
+        let holes = vec![
+            rect((-2.5, -2.5), (-1.0, -1.0)),
+            reversed,
+            star(4, Vec2::from_xy(-1.2, 1.5), 0.3, 0.9),
+            // same rightmost x as another hole
+            rect((-0.5, -2.5), (2.0, -2.2)),
+        ];
+        check(&outer, &holes);
+        check(
+            &star(7, Vec2::zeros(), 2.0, 3.5),
+            &[star(5, Vec2::zeros(), 0.5, 1.5)],
+        );
+
+        // degenerate holes are skipped but their points keep their indices
+        let square = rect((-1.0, -1.0), (1.0, 1.0));
+        assert_eq!(ear_clip(&square, &[vec![]]).len(), 2);
+        let small = rect((-0.5, -0.5), (0.5, 0.5));
+        let triangles = ear_clip(&square, &[vec![Vec2::zeros(), Vec2::x_axis()], small.clone()]);
+        assert_eq!(triangles.len(), 8);
+        assert!(triangles.iter().flatten().all(|i| *i < 4 || *i >= 6));
+        assert!(ear_clip(&square[..2], &[small]).is_empty());
+    }
+
+    #[test]
+    fn polygon_contain_pt() {
+        let mut star = star(5, Vec2::zeros(), 1.0, 3.0);
+        for _ in 0..2 {
+            assert!(is_polygon_contain_pt(&Vec2::zeros(), &star));
+            assert!(is_polygon_contain_pt(&Vec2::from_xy(2.5, 0.0), &star));
+            // between two spikes
+            assert!(!is_polygon_contain_pt(&Vec2::from_xy(1.5 * (PI / 5.0).cos(), 1.5 * (PI / 5.0).sin()), &star));
+            assert!(!is_polygon_contain_pt(&Vec2::from_xy(-4.0, 0.0), &star));
+            star.reverse();
+        }
+        assert!(!is_polygon_contain_pt(&Vec2::zeros(), &[]));
+    }
+}
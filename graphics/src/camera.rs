@@ -0,0 +1,209 @@
+//! camera looking from `position` to `target`, maps between world and screen
+//!
+//! screen coordinate is in pixels with origin at left-top corner and y going down,
+//! its z is depth in normalized device coordinate.
+
+use geometric::geom3d::{Ray3D, ViewFrustum};
+use math::cg::{create_look_at, create_ortho_project, create_persp_project, rotate_around_axis};
+use math::coord::Cartesian3D;
+use math::matrix::*;
+use math::precision::Real;
+
+/// keep camera away from poles so `up` never becomes parallel to view direction
+const MAX_PITCH: Real = std::f64::consts::FRAC_PI_2 - 1e-3;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Projection {
+    /// `half_fovy` is the vertical half angle
+    Perspective { half_fovy: Real },
+    /// `half_height` is half of the visible height in world unit
+    Orthographic { half_height: Real },
+}
+
+/// range of depth in normalized device coordinate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DepthRange {
+    /// `[-1, 1]`, used by OpenGL
+    NegOneToOne,
+    /// `[0, 1]`, used by Vulkan and DirectX
+    ZeroToOne,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Camera {
+    pub position: Vec3,
+    pub target: Vec3,
+    pub up: Vec3,
+    pub projection: Projection,
+    pub near: Real,
+    pub far: Real,
+    /// width and height of screen in pixels
+    pub viewport: Vec2,
+    pub depth_range: DepthRange,
+}
+
+impl Camera {
+    /// camera at origin looking at `-z`
+    pub fn new(projection: Projection, near: Real, far: Real, viewport: Vec2) -> Self {
+        Self {
+            position: Vec3::zeros(),
+            target: -Vec3::z_axis(),
+            up: Vec3::y_axis(),
+            projection,
+            near,
+            far,
+            viewport,
+            depth_range: DepthRange::NegOneToOne,
+        }
+    }
+
+    pub fn look_at(&mut self, position: Vec3, target: Vec3) {
+        self.position = position;
+        self.target = target;
+    }
+
+    pub fn aspect(&self) -> Real {
+        self.viewport.x() / self.viewport.y()
+    }
+
+    pub fn forward(&self) -> Vec3 {
+        (self.target - self.position).normalize()
+    }
+
+    pub fn right(&self) -> Vec3 {
+        self.forward().cross(&self.up).normalize()
+    }
+
+    /// camera frame in world, looks at `-z` with `y` up
+    pub fn frame(&self) -> Cartesian3D {
+        let z = -self.forward();
+        let x = self.right();
+        Cartesian3D::new(x, z.cross(&x), z, self.position)
+    }
+
+    /// rotate `dir` by `yaw` around `up` then by `pitch` around right axis, pitch is clamped so it never passes the poles
+    fn rotate_dir(&self, dir: &Vec3, yaw: Real, pitch: Real) -> Vec3 {
+        let up = self.up.normalize();
+        let dir = rotate_around_axis(dir, &up, yaw);
+        let cur_pitch = (dir.normalize().dot(&up)).clamp(-1.0, 1.0).asin();
+        let pitch = (cur_pitch + pitch).clamp(-MAX_PITCH, MAX_PITCH) - cur_pitch;
+        let right = dir.cross(&up).normalize();
+        rotate_around_axis(&dir, &right, pitch)
+    }
+
+    /// move around `target` keeping the distance, positive `pitch` goes up
+    pub fn orbit(&mut self, yaw: Real, pitch: Real) {
+        let offset = self.position - self.target;
+        self.position = self.target + self.rotate_dir(&offset, yaw, pitch);
+    }
+
+    /// move toward `target` by `factor` of the distance, `target` is never reached
+    pub fn zoom(&mut self, factor: Real) {
+        let offset = self.position - self.target;
+        self.position = self.target + offset * (1.0 - factor).max(1e-3);
+    }
+
+    /// turn the view direction around `position`, positive `pitch` looks up
+    pub fn fly_rotate(&mut self, yaw: Real, pitch: Real) {
+        let offset = self.target - self.position;
+        self.target = self.position + self.rotate_dir(&offset, yaw, pitch);
+    }
+
+    /// move both `position` and `target`, `offset` is (right, up, forward) in camera space
+    pub fn fly_move(&mut self, offset: &Vec3) {
+        let delta = self.right() * offset.x()
+            + self.up.normalize() * offset.y()
+            + self.forward() * offset.z();
+        self.position += delta;
+        self.target += delta;
+    }
+
+    pub fn view_mat(&self) -> Mat44 {
+        create_look_at(self.position, self.target, self.up)
+    }
+
+    pub fn proj_mat(&self) -> Mat44 {
+        let proj = match self.projection {
+            Projection::Perspective { half_fovy } => {
+                create_persp_project(self.near, self.far, half_fovy, self.aspect())
+            }
+            Projection::Orthographic { half_height } => {
+                let half_width = half_height * self.aspect();
+                create_ortho_project(-half_width, half_width, -half_height, half_height, self.far, self.near)
+            }
+        };
+        match self.depth_range {
+            DepthRange::NegOneToOne => proj,
+            // remap depth by `z' = (z + w) / 2`
+            #[rustfmt::skip]
+            DepthRange::ZeroToOne => Mat44::from_row(&[
+                1.0, 0.0, 0.0, 0.0,
+                0.0, 1.0, 0.0, 0.0,
+                0.0, 0.0, 0.5, 0.5,
+                0.0, 0.0, 0.0, 1.0,
+            ]) * proj,
+        }
+    }
+
+    pub fn view_proj_mat(&self) -> Mat44 {
+        self.proj_mat() * self.view_mat()
+    }
+
+    pub fn frustum(&self) -> ViewFrustum {
+        ViewFrustum::from_view_proj(&self.view_proj_mat(), self.depth_range == DepthRange::ZeroToOne)
+    }
+
+    /// `None` if `pt` is on or behind the eye plane of a perspective camera
+    pub fn world_to_screen(&self, pt: &Vec3) -> Option<Vec3> {
+        let clip = self.view_proj_mat() * Vec4::from(*pt);
+        if clip.w() <= 0.0 {
+            return None;
+        }
+        let ndc = clip.xyz() / clip.w();
+        Some(Vec3::from_xyz(
+            (ndc.x() + 1.0) * 0.5 * self.viewport.x(),
+            (1.0 - ndc.y()) * 0.5 * self.viewport.y(),
+            ndc.z(),
+        ))
+    }
+
+    /// inverse of `view_proj_mat`, `None` for a degenerate camera like `position == target` or `up` along the view
+    /// direction
+    pub fn inv_view_proj_mat(&self) -> Option<Mat44> {
+        let inv = self.view_proj_mat().inv()?;
+        let finite = (0..4).all(|x| (0..4).all(|y| inv.get(x, y).is_finite()));
+        finite.then_some(inv)
+    }
+
+    /// inverse of `world_to_screen`, `None` for a degenerate camera
+    pub fn screen_to_world(&self, screen: &Vec3) -> Option<Vec3> {
+        Some(Self::unproject(&self.inv_view_proj_mat()?, &self.viewport, screen))
+    }
+
+    fn unproject(inv_view_proj: &Mat44, viewport: &Vec2, screen: &Vec3) -> Vec3 {
+        let ndc = Vec4::from_xyzw(
+            screen.x() / viewport.x() * 2.0 - 1.0,
+            1.0 - screen.y() / viewport.y() * 2.0,
+            screen.z(),
+            1.0,
+        );
+        let world = *inv_view_proj * ndc;
+        world.xyz() / world.w()
+    }
+
+    /// ray from near plane through `pixel`, `None` for a degenerate camera
+    pub fn screen_ray(&self, pixel: &Vec2) -> Option<Ray3D> {
+        Some(self.screen_ray_with(&self.inv_view_proj_mat()?, pixel))
+    }
+
+    /// `screen_ray` with `inv_view_proj_mat` computed once by the caller
+    pub fn screen_ray_with(&self, inv_view_proj: &Mat44, pixel: &Vec2) -> Ray3D {
+        let (near_depth, far_depth) = match self.depth_range {
+            DepthRange::NegOneToOne => (-1.0, 1.0),
+            DepthRange::ZeroToOne => (0.0, 1.0),
+        };
+        let start = Self::unproject(inv_view_proj, &self.viewport, &Vec3::from_xyz(pixel.x(), pixel.y(), near_depth));
+        let end = Self::unproject(inv_view_proj, &self.viewport, &Vec3::from_xyz(pixel.x(), pixel.y(), far_depth));
+        Ray3D::new(start, end - start)
+    }
+}
@@ -0,0 +1,415 @@
+//! [zlib](https://www.rfc-editor.org/rfc/rfc1950)/[deflate](https://www.rfc-editor.org/rfc/rfc1951) streams
+//!
+//! compression writes stored blocks or a single fixed Huffman block with LZ77 matches from hash chains.
+//! decompression supports all block types.
+
+/// how `zlib_compress` encodes data
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    /// stored blocks, no compression
+    Store,
+    /// LZ77 matches coded with the fixed Huffman codes
+    Fixed,
+}
+
+const WINDOW_SIZE: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+/// candidates looked at per position, more compresses better but slower
+const MAX_CHAIN: usize = 64;
+const HASH_BITS: usize = 15;
+
+/// base and extra bits of length codes 257..=285
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+/// base and extra bits of distance codes 0..=29
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145,
+    8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+/// order of code length code lengths in a dynamic block header
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+pub fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 bytes is the most that can't overflow before the modulo
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+struct BitWriter {
+    out: Vec<u8>,
+    bits: u64,
+    count: u32,
+}
+
+impl BitWriter {
+    /// `value` least significant bit first
+    fn write(&mut self, value: u32, len: u32) {
+        self.bits |= (value as u64) << self.count;
+        self.count += len;
+        while self.count >= 8 {
+            self.out.push(self.bits as u8);
+            self.bits >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Huffman codes are packed most significant bit first
+    fn write_code(&mut self, code: u32, len: u32) {
+        let reversed = code.reverse_bits() >> (32 - len);
+        self.write(reversed, len);
+    }
+
+    fn align(&mut self) {
+        if self.count > 0 {
+            self.write(0, 8 - self.count);
+        }
+    }
+}
+
+/// fixed literal/length code of `symbol` and its length
+fn fixed_literal_code(symbol: u16) -> (u32, u32) {
+    let s = symbol as u32;
+    match symbol {
+        0..=143 => (0x30 + s, 8),
+        144..=255 => (0x190 + s - 144, 9),
+        256..=279 => (s - 256, 7),
+        _ => (0xC0 + s - 280, 8),
+    }
+}
+
+fn write_length(w: &mut BitWriter, len: usize) {
+    let code = LENGTH_BASE.iter().rposition(|base| *base as usize <= len).unwrap();
+    let (c, l) = fixed_literal_code(257 + code as u16);
+    w.write_code(c, l);
+    w.write((len - LENGTH_BASE[code] as usize) as u32, LENGTH_EXTRA[code] as u32);
+}
+
+fn write_distance(w: &mut BitWriter, dist: usize) {
+    let code = DIST_BASE.iter().rposition(|base| *base as usize <= dist).unwrap();
+    w.write_code(code as u32, 5);
+    w.write((dist - DIST_BASE[code] as usize) as u32, DIST_EXTRA[code] as u32);
+}
+
+fn hash(data: &[u8], i: usize) -> usize {
+    let v = (data[i] as u32) << 16 | (data[i + 1] as u32) << 8 | data[i + 2] as u32;
+    (v.wrapping_mul(2654435761) >> (32 - HASH_BITS)) as usize
+}
+
+/// add position `i` to the hash chains
+fn insert(data: &[u8], i: usize, head: &mut [usize], prev: &mut [usize]) {
+    if i + MIN_MATCH <= data.len() {
+        let h = hash(data, i);
+        prev[i % WINDOW_SIZE] = head[h];
+        head[h] = i;
+    }
+}
+
+/// raw deflate stream
+pub fn deflate(data: &[u8], compression: Compression) -> Vec<u8> {
+    let mut w = BitWriter {
+        out: Vec::new(),
+        bits: 0,
+        count: 0,
+    };
+    match compression {
+        Compression::Store => {
+            let mut chunks = data.chunks(65535).peekable();
+            if chunks.peek().is_none() {
+                w.write(1, 3);
+                w.align();
+                w.out.extend([0, 0, 0xFF, 0xFF]);
+            }
+            while let Some(chunk) = chunks.next() {
+                w.write(chunks.peek().is_none() as u32, 3);
+                w.align();
+                let len = chunk.len() as u16;
+                w.out.extend(len.to_le_bytes());
+                w.out.extend((!len).to_le_bytes());
+                w.out.extend(chunk);
+            }
+        }
+        Compression::Fixed => {
+            // final block, fixed codes
+            w.write(1, 1);
+            w.write(1, 2);
+            let mut head = vec![usize::MAX; 1 << HASH_BITS];
+            let mut prev = vec![usize::MAX; WINDOW_SIZE];
+
+            let mut i = 0;
+            while i < data.len() {
+                let (mut best_len, mut best_dist) = (0, 0);
+                if i + MIN_MATCH <= data.len() {
+                    let max_len = MAX_MATCH.min(data.len() - i);
+                    let mut candidate = head[hash(data, i)];
+                    let mut chain = 0;
+                    while candidate != usize::MAX && i - candidate <= WINDOW_SIZE && chain < MAX_CHAIN {
+                        let len = data[candidate..]
+                            .iter()
+                            .zip(&data[i..i + max_len])
+                            .take_while(|(a, b)| a == b)
+                            .count();
+                        if len > best_len {
+                            (best_len, best_dist) = (len, i - candidate);
+                            if len == max_len {
+                                break;
+                            }
+                        }
+                        let next = prev[candidate % WINDOW_SIZE];
+                        // slots are reused every window, a newer position means the chain ended
+                        if next == usize::MAX || next >= candidate {
+                            break;
+                        }
+                        candidate = next;
+                        chain += 1;
+                    }
+                }
+
+                if best_len >= MIN_MATCH {
+                    write_length(&mut w, best_len);
+                    write_distance(&mut w, best_dist);
+                    for k in i..i + best_len {
+                        insert(data, k, &mut head, &mut prev);
+                    }
+                    i += best_len;
+                } else {
+                    let (c, l) = fixed_literal_code(data[i] as u16);
+                    w.write_code(c, l);
+                    insert(data, i, &mut head, &mut prev);
+                    i += 1;
+                }
+            }
+            let (c, l) = fixed_literal_code(256);
+            w.write_code(c, l);
+            w.align();
+        }
+    }
+    w.out
+}
+
+/// deflate stream with zlib header and adler32 checksum
+pub fn zlib_compress(data: &[u8], compression: Compression) -> Vec<u8> {
+    // 32K window, no dictionary, check bits make the header a multiple of 31
+    let mut out = vec![0x78, 0x01];
+    out.extend(deflate(data, compression));
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit: u32,
+}
+
+impl BitReader<'_> {
+    fn bit(&mut self) -> Result<u32, &'static str> {
+        let byte = *self.data.get(self.pos).ok_or("unexpected end of deflate stream")?;
+        let b = (byte >> self.bit) & 1;
+        self.bit += 1;
+        if self.bit == 8 {
+            self.bit = 0;
+            self.pos += 1;
+        }
+        Ok(b as u32)
+    }
+
+    fn bits(&mut self, len: u32) -> Result<u32, &'static str> {
+        let mut v = 0;
+        for i in 0..len {
+            v |= self.bit()? << i;
+        }
+        Ok(v)
+    }
+
+    fn align(&mut self) {
+        if self.bit > 0 {
+            self.bit = 0;
+            self.pos += 1;
+        }
+    }
+}
+
+/// canonical Huffman code as symbol counts per length and symbols in code order
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self, &'static str> {
+        let mut counts = [0u16; 16];
+        for l in lengths {
+            counts[*l as usize] += 1;
+        }
+        counts[0] = 0;
+        // more codes than lengths can hold
+        let mut left: i32 = 1;
+        for count in &counts[1..] {
+            left = left * 2 - *count as i32;
+            if left < 0 {
+                return Err("over-subscribed Huffman code");
+            }
+        }
+        let mut symbols: Vec<u16> = (0..lengths.len() as u16).filter(|s| lengths[*s as usize] > 0).collect();
+        symbols.sort_by_key(|s| lengths[*s as usize]);
+        Ok(Self { counts, symbols })
+    }
+
+    /// walk down code lengths, codes of the same length are consecutive
+    fn decode(&self, r: &mut BitReader) -> Result<u16, &'static str> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..16 {
+            code |= r.bit()? as i32;
+            let count = self.counts[len] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("invalid Huffman code")
+    }
+}
+
+fn fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    (Huffman::new(&lengths).unwrap(), Huffman::new(&[5; 30]).unwrap())
+}
+
+fn dynamic_codes(r: &mut BitReader) -> Result<(Huffman, Huffman), &'static str> {
+    let nlen = r.bits(5)? as usize + 257;
+    let ndist = r.bits(5)? as usize + 1;
+    let ncode = r.bits(4)? as usize + 4;
+    let mut code_lengths = [0u8; 19];
+    for i in CODE_LENGTH_ORDER.iter().take(ncode) {
+        code_lengths[*i] = r.bits(3)? as u8;
+    }
+    let code_length_code = Huffman::new(&code_lengths)?;
+
+    let mut lengths = Vec::with_capacity(nlen + ndist);
+    while lengths.len() < nlen + ndist {
+        let (value, repeat) = match code_length_code.decode(r)? {
+            s @ 0..=15 => (s as u8, 1),
+            16 => (*lengths.last().ok_or("repeat without previous length")?, 3 + r.bits(2)?),
+            17 => (0, 3 + r.bits(3)?),
+            _ => (0, 11 + r.bits(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+    if lengths.len() > nlen + ndist {
+        return Err("too many code lengths");
+    }
+    if lengths[256] == 0 {
+        return Err("missing end of block code");
+    }
+    Ok((Huffman::new(&lengths[..nlen])?, Huffman::new(&lengths[nlen..])?))
+}
+
+const TOO_LONG: &str = "decompressed data is longer than expected";
+
+/// raw deflate stream, also returns the number of bytes consumed.
+/// fails once output grows past `max_len`
+pub fn inflate(data: &[u8], max_len: usize) -> Result<(Vec<u8>, usize), &'static str> {
+    let mut r = BitReader { data, pos: 0, bit: 0 };
+    let mut out: Vec<u8> = Vec::new();
+    loop {
+        let last = r.bit()? == 1;
+        match r.bits(2)? {
+            0 => {
+                r.align();
+                let header = data.get(r.pos..r.pos + 4).ok_or("unexpected end of deflate stream")?;
+                let len = u16::from_le_bytes([header[0], header[1]]);
+                if len != !u16::from_le_bytes([header[2], header[3]]) {
+                    return Err("stored block length mismatch");
+                }
+                r.pos += 4;
+                let block = data.get(r.pos..r.pos + len as usize).ok_or("unexpected end of deflate stream")?;
+                if block.len() > max_len - out.len() {
+                    return Err(TOO_LONG);
+                }
+                out.extend(block);
+                r.pos += len as usize;
+            }
+            kind @ (1 | 2) => {
+                let (literal, distance) = if kind == 1 { fixed_codes() } else { dynamic_codes(&mut r)? };
+                loop {
+                    let symbol = literal.decode(&mut r)? as usize;
+                    if symbol < 256 {
+                        if out.len() == max_len {
+                            return Err(TOO_LONG);
+                        }
+                        out.push(symbol as u8);
+                        continue;
+                    }
+                    if symbol == 256 {
+                        break;
+                    }
+                    let code = symbol - 257;
+                    if code >= LENGTH_BASE.len() {
+                        return Err("invalid length code");
+                    }
+                    let len = LENGTH_BASE[code] as usize + r.bits(LENGTH_EXTRA[code] as u32)? as usize;
+                    let code = distance.decode(&mut r)? as usize;
+                    if code >= DIST_BASE.len() {
+                        return Err("invalid distance code");
+                    }
+                    let dist = DIST_BASE[code] as usize + r.bits(DIST_EXTRA[code] as u32)? as usize;
+                    if dist > out.len() {
+                        return Err("distance before start of data");
+                    }
+                    if len > max_len - out.len() {
+                        return Err(TOO_LONG);
+                    }
+                    // copies may overlap themselves
+                    let start = out.len() - dist;
+                    for k in 0..len {
+                        out.push(out[start + k]);
+                    }
+                }
+            }
+            _ => return Err("invalid block type"),
+        }
+        if last {
+            break;
+        }
+    }
+    r.align();
+    Ok((out, r.pos))
+}
+
+/// fails once output grows past `max_len`
+pub fn zlib_decompress(data: &[u8], max_len: usize) -> Result<Vec<u8>, &'static str> {
+    if data.len() < 6 {
+        return Err("unexpected end of zlib stream");
+    }
+    let (cmf, flg) = (data[0], data[1]);
+    if cmf & 0x0F != 8 || cmf >> 4 > 7 || !(cmf as u16 * 256 + flg as u16).is_multiple_of(31) {
+        return Err("invalid zlib header");
+    }
+    if flg & 0x20 != 0 {
+        return Err("preset dictionary is not supported");
+    }
+    let (out, used) = inflate(&data[2..], max_len)?;
+    let checksum = data.get(2 + used..2 + used + 4).ok_or("unexpected end of zlib stream")?;
+    if u32::from_be_bytes(checksum.try_into().unwrap()) != adler32(&out) {
+        return Err("adler32 mismatch");
+    }
+    Ok(out)
+}
@@ -0,0 +1,302 @@
+//! [glTF 2.0](https://registry.khronos.org/glTF/specs/2.0/glTF-2.0.html) writer, `.gltf` with a `.bin` buffer or a single `.glb`
+//!
+//! every node has one mesh with one primitive, faces are TRIANGLES and line strips are LINE_STRIP.
+//! `color` becomes the base color of an unlit-looking(non metallic, fully rough) material.
+//! JSON has no NaN or infinity, so a scene with any non-finite value is rejected.
+
+use std::fmt::Write as _;
+use std::path::Path;
+use std::{fmt, fs, io};
+
+use crate::mesh_generate::{FaceDisplayData, LineStripDisplayData};
+use math::coord::Cartesian3D;
+use math::matrix::*;
+
+#[derive(Debug)]
+pub enum GltfError {
+    Io(io::Error),
+    /// a node has a NaN or infinite transform, color or vertex, or a vertex too large for f32
+    NonFinite(String),
+}
+
+impl fmt::Display for GltfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GltfError::Io(e) => write!(f, "io error: {}", e),
+            GltfError::NonFinite(name) => write!(f, "node {:?} has a non-finite value", name),
+        }
+    }
+}
+
+impl std::error::Error for GltfError {}
+
+impl From<io::Error> for GltfError {
+    fn from(e: io::Error) -> Self {
+        GltfError::Io(e)
+    }
+}
+
+pub enum GltfMesh {
+    Faces(FaceDisplayData),
+    Lines(LineStripDisplayData),
+}
+
+pub struct GltfNode {
+    pub name: String,
+    /// vertices are in the local space of this frame
+    pub transform: Cartesian3D,
+    pub mesh: GltfMesh,
+}
+
+#[derive(Default)]
+pub struct GltfScene {
+    pub nodes: Vec<GltfNode>,
+}
+
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+const MODE_LINE_STRIP: u32 = 3;
+const MODE_TRIANGLES: u32 = 4;
+
+const GLB_MAGIC: u32 = 0x4654_6C67;
+const GLB_CHUNK_JSON: u32 = 0x4E4F_534A;
+const GLB_CHUNK_BIN: u32 = 0x004E_4942;
+
+/// binary buffer and the json objects describing it
+#[derive(Default)]
+struct Builder {
+    bin: Vec<u8>,
+    views: Vec<String>,
+    accessors: Vec<String>,
+}
+
+impl Builder {
+    /// one buffer view per accessor, all components are 4 bytes so views stay aligned
+    fn push_view(&mut self, bytes: Vec<u8>, target: u32) -> usize {
+        let view = format!(
+            r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":{}}}"#,
+            self.bin.len(),
+            bytes.len(),
+            target
+        );
+        self.bin.extend(bytes);
+        self.views.push(view);
+        self.views.len() - 1
+    }
+
+    /// positions also get min/max which glTF requires
+    fn push_vec3(&mut self, values: &[Vec3], bounds: bool) -> usize {
+        let mut bytes = Vec::with_capacity(values.len() * 12);
+        let (mut min, mut max) = ([f32::INFINITY; 3], [f32::NEG_INFINITY; 3]);
+        for v in values {
+            for i in 0..3 {
+                let c = v[i] as f32;
+                bytes.extend(c.to_le_bytes());
+                min[i] = min[i].min(c);
+                max[i] = max[i].max(c);
+            }
+        }
+        let view = self.push_view(bytes, ARRAY_BUFFER);
+        let mut accessor = format!(
+            r#"{{"bufferView":{},"componentType":{},"count":{},"type":"VEC3""#,
+            view,
+            FLOAT,
+            values.len()
+        );
+        if bounds {
+            write!(accessor, r#","min":{},"max":{}"#, json_array(&min), json_array(&max)).unwrap();
+        }
+        accessor.push('}');
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    fn push_indices(&mut self, indices: &[u32]) -> usize {
+        let bytes = indices.iter().flat_map(|i| i.to_le_bytes()).collect();
+        let view = self.push_view(bytes, ELEMENT_ARRAY_BUFFER);
+        self.accessors.push(format!(
+            r#"{{"bufferView":{},"componentType":{},"count":{},"type":"SCALAR"}}"#,
+            view,
+            UNSIGNED_INT,
+            indices.len()
+        ));
+        self.accessors.len() - 1
+    }
+}
+
+fn json_array<T: ToString>(values: &[T]) -> String {
+    let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+    format!("[{}]", values.join(","))
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// column major, as glTF expects
+#[rustfmt::skip]
+fn node_matrix(frame: &Cartesian3D) -> String {
+    let (x, y, z, p) = (frame.x_axis(), frame.y_axis(), frame.z_axis(), frame.position());
+    json_array(&[
+        x.x(), x.y(), x.z(), 0.0,
+        y.x(), y.y(), y.z(), 0.0,
+        z.x(), z.y(), z.z(), 0.0,
+        p.x(), p.y(), p.z(), 1.0,
+    ])
+}
+
+fn material(name: &str, color: &Vec4) -> String {
+    let mut material = format!(
+        r#"{{"name":{},"pbrMetallicRoughness":{{"baseColorFactor":{},"metallicFactor":0,"roughnessFactor":1}}"#,
+        json_string(name),
+        json_array(&[color.x(), color.y(), color.z(), color.w()])
+    );
+    if color.w() < 1.0 {
+        material.push_str(r#","alphaMode":"BLEND""#);
+    }
+    material.push('}');
+    material
+}
+
+/// every number written for `node` is finite, vertex data is written as f32 so it has to stay finite after conversion
+fn check_finite(node: &GltfNode) -> Result<(), GltfError> {
+    let frame = &node.transform;
+    let axes = [frame.x_axis(), frame.y_axis(), frame.z_axis(), frame.position()];
+    let (color, vertices, normals): (&Vec4, &[Vec3], &[Vec3]) = match &node.mesh {
+        GltfMesh::Faces(data) => (&data.color, &data.vertices, &data.normals),
+        GltfMesh::Lines(data) => (&data.color, &data.vertices, &[]),
+    };
+    let finite = axes.iter().all(|v| (0..3).all(|i| v[i].is_finite()))
+        && (0..4).all(|i| color[i].is_finite())
+        && vertices.iter().chain(normals).all(|v| (0..3).all(|i| (v[i] as f32).is_finite()));
+    if finite {
+        Ok(())
+    } else {
+        Err(GltfError::NonFinite(node.name.clone()))
+    }
+}
+
+impl GltfScene {
+    /// json and binary buffer, `bin_uri` is the buffer location written into the json, `None` for glb
+    fn build(&self, bin_uri: Option<&str>) -> Result<(String, Vec<u8>), GltfError> {
+        let mut builder = Builder::default();
+        let (mut nodes, mut meshes, mut materials) = (Vec::new(), Vec::new(), Vec::new());
+        for node in &self.nodes {
+            check_finite(node)?;
+            let mut json = format!(r#"{{"name":{},"matrix":{}"#, json_string(&node.name), node_matrix(&node.transform));
+            let primitive = match &node.mesh {
+                GltfMesh::Faces(data) if !data.vertices.is_empty() && !data.indices.is_empty() => {
+                    let position = builder.push_vec3(&data.vertices, true);
+                    let mut attributes = format!(r#""POSITION":{}"#, position);
+                    if data.normals.len() == data.vertices.len() {
+                        write!(attributes, r#","NORMAL":{}"#, builder.push_vec3(&data.normals, false)).unwrap();
+                    }
+                    let indices = builder.push_indices(&data.indices);
+                    Some((attributes, Some(indices), MODE_TRIANGLES, data.color))
+                }
+                GltfMesh::Lines(data) if data.vertices.len() >= 2 => {
+                    let position = builder.push_vec3(&data.vertices, true);
+                    Some((format!(r#""POSITION":{}"#, position), None, MODE_LINE_STRIP, data.color))
+                }
+                // glTF doesn't allow empty accessors, keep the node for its transform
+                _ => None,
+            };
+            if let Some((attributes, indices, mode, color)) = primitive {
+                let mut json_primitive = format!(r#"{{"attributes":{{{}}}"#, attributes);
+                if let Some(indices) = indices {
+                    write!(json_primitive, r#","indices":{}"#, indices).unwrap();
+                }
+                write!(json_primitive, r#","material":{},"mode":{}}}"#, materials.len(), mode).unwrap();
+                materials.push(material(&node.name, &color));
+                meshes.push(format!(r#"{{"name":{},"primitives":[{}]}}"#, json_string(&node.name), json_primitive));
+                write!(json, r#","mesh":{}"#, meshes.len() - 1).unwrap();
+            }
+            json.push('}');
+            nodes.push(json);
+        }
+
+        let scene_nodes: Vec<usize> = (0..nodes.len()).collect();
+        let mut json = format!(
+            r#"{{"asset":{{"version":"2.0","generator":"graphics"}},"scene":0,"scenes":[{{"nodes":{}}}],"nodes":[{}]"#,
+            json_array(&scene_nodes),
+            nodes.join(",")
+        );
+        if !meshes.is_empty() {
+            let mut buffer = format!(r#"{{"byteLength":{}"#, builder.bin.len());
+            if let Some(uri) = bin_uri {
+                write!(buffer, r#","uri":{}"#, json_string(uri)).unwrap();
+            }
+            buffer.push('}');
+            write!(
+                json,
+                r#","meshes":[{}],"materials":[{}],"accessors":[{}],"bufferViews":[{}],"buffers":[{}]"#,
+                meshes.join(","),
+                materials.join(","),
+                builder.accessors.join(","),
+                builder.views.join(","),
+                buffer
+            )
+            .unwrap();
+        }
+        json.push('}');
+        Ok((json, builder.bin))
+    }
+
+    /// json of the scene with its buffer at `bin_uri`, and the buffer content
+    pub fn to_gltf(&self, bin_uri: &str) -> Result<(String, Vec<u8>), GltfError> {
+        self.build(Some(bin_uri))
+    }
+
+    /// json and buffer in one binary container
+    pub fn to_glb(&self) -> Result<Vec<u8>, GltfError> {
+        let (json, mut bin) = self.build(None)?;
+        let mut json = json.into_bytes();
+        // chunks are 4 byte aligned, json pads with spaces and bin with zeros
+        json.resize(json.len().next_multiple_of(4), b' ');
+        bin.resize(bin.len().next_multiple_of(4), 0);
+
+        let mut total = 12 + 8 + json.len();
+        if !bin.is_empty() {
+            total += 8 + bin.len();
+        }
+        let mut out = Vec::with_capacity(total);
+        for v in [GLB_MAGIC, 2, total as u32, json.len() as u32, GLB_CHUNK_JSON] {
+            out.extend(v.to_le_bytes());
+        }
+        out.extend(json);
+        if !bin.is_empty() {
+            for v in [bin.len() as u32, GLB_CHUNK_BIN] {
+                out.extend(v.to_le_bytes());
+            }
+            out.extend(bin);
+        }
+        Ok(out)
+    }
+
+    /// writes the buffer next to `filename` with `bin` extension
+    pub fn write_gltf(&self, filename: &str) -> Result<(), GltfError> {
+        let bin_path = Path::new(filename).with_extension("bin");
+        let bin_name = bin_path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        let (json, bin) = self.to_gltf(&bin_name)?;
+        fs::write(filename, json)?;
+        fs::write(bin_path, bin)?;
+        Ok(())
+    }
+
+    pub fn write_glb(&self, filename: &str) -> Result<(), GltfError> {
+        fs::write(filename, self.to_glb()?)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,513 @@
+//! [half-edge](https://en.wikipedia.org/wiki/Doubly_connected_edge_list) triangle mesh with adjacency and local editing
+//!
+//! every edge is a pair of twin half-edges, boundary half-edges have no face and link into boundary loops,
+//! so twins always exist and one-ring circulation never breaks. the mesh is always manifold.
+//! removed elements leave empty slots, ids of the others stay valid.
+
+use std::collections::HashMap;
+
+use crate::mesh_generate::FaceDisplayData;
+use math::matrix::*;
+use math::precision::Real;
+
+#[derive(Clone, Copy, Debug)]
+pub struct HalfEdge {
+    /// vertex it points to
+    pub vertex: usize,
+    pub twin: usize,
+    pub next: usize,
+    pub prev: usize,
+    /// `None` on boundary
+    pub face: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Vertex {
+    pub position: Vec3,
+    /// an outgoing half-edge, the boundary one if vertex is on boundary. `None` if isolated
+    pub half_edge: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Face {
+    pub half_edge: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Mesh {
+    vertices: Vec<Option<Vertex>>,
+    half_edges: Vec<Option<HalfEdge>>,
+    faces: Vec<Option<Face>>,
+}
+
+impl Mesh {
+    /// triangles are counter-clockwise, degenerated ones are skipped.
+    /// fails if the triangles don't form a manifold with consistent orientation
+    pub fn from_triangles(positions: Vec<Vec3>, triangles: &[[usize; 3]]) -> Result<Self, &'static str> {
+        let mut mesh = Self {
+            vertices: positions
+                .into_iter()
+                .map(|position| Some(Vertex { position, half_edge: None }))
+                .collect(),
+            ..Default::default()
+        };
+
+        let mut directed: HashMap<(usize, usize), usize> = HashMap::new();
+        for [a, b, c] in triangles.iter().copied() {
+            if [a, b, c].iter().any(|v| *v >= mesh.vertices.len()) {
+                return Err("invalid vertex index");
+            }
+            if a == b || b == c || c == a {
+                continue;
+            }
+            let face = mesh.faces.len();
+            let first = mesh.half_edges.len();
+            mesh.faces.push(Some(Face { half_edge: first }));
+            for (i, (from, to)) in [(a, b), (b, c), (c, a)].into_iter().enumerate() {
+                if directed.insert((from, to), first + i).is_some() {
+                    return Err("non-manifold edge");
+                }
+                mesh.half_edges.push(Some(HalfEdge {
+                    vertex: to,
+                    twin: usize::MAX,
+                    next: first + (i + 1) % 3,
+                    prev: first + (i + 2) % 3,
+                    face: Some(face),
+                }));
+                mesh.vertices[from].as_mut().unwrap().half_edge = Some(first + i);
+            }
+        }
+
+        // pair twins, missing ones are boundary half-edges
+        let mut boundary_from: HashMap<usize, usize> = HashMap::new();
+        for ((from, to), h) in &directed {
+            match directed.get(&(*to, *from)) {
+                Some(twin) => mesh.he_mut(*h).twin = *twin,
+                None => {
+                    let twin = mesh.half_edges.len();
+                    mesh.half_edges.push(Some(HalfEdge {
+                        vertex: *from,
+                        twin: *h,
+                        next: usize::MAX,
+                        prev: usize::MAX,
+                        face: None,
+                    }));
+                    mesh.he_mut(*h).twin = twin;
+                    if boundary_from.insert(*to, twin).is_some() {
+                        return Err("non-manifold vertex");
+                    }
+                    mesh.vertices[*to].as_mut().unwrap().half_edge = Some(twin);
+                }
+            }
+        }
+        for h in boundary_from.values().copied().collect::<Vec<_>>() {
+            let next = boundary_from[&mesh.he(h).vertex];
+            mesh.he_mut(h).next = next;
+            mesh.he_mut(next).prev = h;
+        }
+
+        // fans around a vertex must be a single one
+        let mut incident = vec![0; mesh.vertices.len()];
+        for h in mesh.half_edges() {
+            if mesh.he(h).face.is_some() {
+                incident[mesh.he(h).vertex] += 1;
+            }
+        }
+        for v in mesh.vertices() {
+            if mesh.vertex_faces(v).count() != incident[v] {
+                return Err("non-manifold vertex");
+            }
+        }
+        Ok(mesh)
+    }
+
+    /// vertices at the same position(within `1e-9` of mesh size) are welded, so hard edges and uv seams don't split the surface.
+    /// fails like `from_triangles`, or if indices don't form whole triangles
+    pub fn from_display_data(data: &FaceDisplayData) -> Result<Self, &'static str> {
+        let extent = data
+            .vertices
+            .iter()
+            .flat_map(|v| (0..3).map(|i| v[i].abs()))
+            .fold(0.0, Real::max);
+        let quantum = extent.max(Real::MIN_POSITIVE) * 1e-9;
+        let mut ids: HashMap<[i64; 3], usize> = HashMap::new();
+        let mut positions = Vec::new();
+        let welded: Vec<usize> = data
+            .vertices
+            .iter()
+            .map(|v| {
+                let key = [0, 1, 2].map(|i| (v[i] / quantum).round() as i64);
+                *ids.entry(key).or_insert_with(|| {
+                    positions.push(*v);
+                    positions.len() - 1
+                })
+            })
+            .collect();
+        if !data.indices.len().is_multiple_of(3) {
+            return Err("index count is not a multiple of 3");
+        }
+        let mut triangles: Vec<[usize; 3]> = Vec::with_capacity(data.indices.len() / 3);
+        for t in data.indices.chunks_exact(3) {
+            if t.iter().any(|i| *i as usize >= welded.len()) {
+                return Err("invalid vertex index");
+            }
+            triangles.push([0, 1, 2].map(|i| welded[t[i] as usize]));
+        }
+        Self::from_triangles(positions, &triangles)
+    }
+
+    /// smooth shaded by area weighted normals, without uvs
+    pub fn to_display_data(&self, color: Vec4) -> FaceDisplayData {
+        let mut remap = vec![usize::MAX; self.vertices.len()];
+        let mut vertices = Vec::new();
+        for v in self.vertices() {
+            remap[v] = vertices.len();
+            vertices.push(self.position(v));
+        }
+
+        let mut normals = vec![Vec3::zeros(); vertices.len()];
+        let mut indices: Vec<u32> = Vec::new();
+        for f in self.faces() {
+            let t = self.face_vertices(f).map(|v| remap[v]);
+            let normal = (vertices[t[1]] - vertices[t[0]]).cross(&(vertices[t[2]] - vertices[t[0]]));
+            for i in t {
+                normals[i] += normal;
+                indices.push(i as u32);
+            }
+        }
+        let normals = normals
+            .into_iter()
+            .map(|n| if n.length_sqrd() > 0.0 { n.normalize() } else { n })
+            .collect();
+
+        FaceDisplayData {
+            vertices,
+            normals,
+            indices,
+            color,
+            uvs: None,
+            tangents: None,
+        }
+    }
+
+    fn he(&self, h: usize) -> &HalfEdge {
+        self.half_edges[h].as_ref().unwrap()
+    }
+
+    fn he_mut(&mut self, h: usize) -> &mut HalfEdge {
+        self.half_edges[h].as_mut().unwrap()
+    }
+
+    pub fn vertex(&self, v: usize) -> &Vertex {
+        self.vertices[v].as_ref().unwrap()
+    }
+
+    pub fn half_edge(&self, h: usize) -> &HalfEdge {
+        self.he(h)
+    }
+
+    pub fn face(&self, f: usize) -> &Face {
+        self.faces[f].as_ref().unwrap()
+    }
+
+    pub fn position(&self, v: usize) -> Vec3 {
+        self.vertex(v).position
+    }
+
+    pub fn set_position(&mut self, v: usize, position: Vec3) {
+        self.vertices[v].as_mut().unwrap().position = position;
+    }
+
+    /// ids of vertices not removed
+    pub fn vertices(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.vertices.len()).filter(|v| self.vertices[*v].is_some())
+    }
+
+    pub fn half_edges(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.half_edges.len()).filter(|h| self.half_edges[*h].is_some())
+    }
+
+    pub fn faces(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.faces.len()).filter(|f| self.faces[*f].is_some())
+    }
+
+    /// one half-edge of every edge
+    pub fn edges(&self) -> impl Iterator<Item = usize> + '_ {
+        self.half_edges().filter(|h| *h < self.he(*h).twin)
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertices().count()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.half_edges().count() / 2
+    }
+
+    pub fn face_count(&self) -> usize {
+        self.faces().count()
+    }
+
+    /// vertex the half-edge starts from
+    pub fn origin(&self, h: usize) -> usize {
+        self.he(self.he(h).twin).vertex
+    }
+
+    /// half-edges going out of `v` in counter-clockwise order, starting from the boundary one if any
+    pub fn outgoing(&self, v: usize) -> impl Iterator<Item = usize> + '_ {
+        let start = self.vertex(v).half_edge;
+        let mut cur = start;
+        std::iter::from_fn(move || {
+            let h = cur?;
+            let next = self.he(self.he(h).prev).twin;
+            cur = if Some(next) == start { None } else { Some(next) };
+            Some(h)
+        })
+    }
+
+    /// one-ring neighbors of `v`
+    pub fn vertex_neighbors(&self, v: usize) -> impl Iterator<Item = usize> + '_ {
+        self.outgoing(v).map(|h| self.he(h).vertex)
+    }
+
+    pub fn vertex_faces(&self, v: usize) -> impl Iterator<Item = usize> + '_ {
+        self.outgoing(v).filter_map(|h| self.he(h).face)
+    }
+
+    pub fn valence(&self, v: usize) -> usize {
+        self.outgoing(v).count()
+    }
+
+    /// counter-clockwise
+    pub fn face_vertices(&self, f: usize) -> [usize; 3] {
+        let h = self.face(f).half_edge;
+        let next = self.he(h).next;
+        [self.origin(h), self.he(h).vertex, self.he(next).vertex]
+    }
+
+    pub fn find_half_edge(&self, from: usize, to: usize) -> Option<usize> {
+        self.outgoing(from).find(|h| self.he(*h).vertex == to)
+    }
+
+    /// whether the half-edge or its twin has no face
+    pub fn is_boundary_edge(&self, h: usize) -> bool {
+        self.he(h).face.is_none() || self.he(self.he(h).twin).face.is_none()
+    }
+
+    /// isolated vertex counts as boundary
+    pub fn is_boundary_vertex(&self, v: usize) -> bool {
+        self.vertex(v).half_edge.is_none_or(|h| self.he(h).face.is_none())
+    }
+
+    /// vertices of every boundary loop, in the order of boundary half-edges
+    pub fn boundary_loops(&self) -> Vec<Vec<usize>> {
+        let mut visited = vec![false; self.half_edges.len()];
+        let mut loops = Vec::new();
+        for start in self.half_edges() {
+            if visited[start] || self.he(start).face.is_some() {
+                continue;
+            }
+            let mut boundary = Vec::new();
+            let mut h = start;
+            while !visited[h] {
+                visited[h] = true;
+                boundary.push(self.origin(h));
+                h = self.he(h).next;
+            }
+            loops.push(boundary);
+        }
+        loops
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.half_edges().all(|h| self.he(h).face.is_some())
+    }
+
+    /// `V - E + F`, `2` for a closed surface of genus 0
+    pub fn euler_characteristic(&self) -> i64 {
+        self.vertex_count() as i64 - self.edge_count() as i64 + self.face_count() as i64
+    }
+
+    /// keep a boundary half-edge as the outgoing one of `v` if there is any, starting search from `h`
+    fn reset_outgoing(&mut self, v: usize, h: usize) {
+        self.vertices[v].as_mut().unwrap().half_edge = Some(h);
+        let boundary = self.outgoing(v).find(|h| self.he(*h).face.is_none());
+        self.vertices[v].as_mut().unwrap().half_edge = Some(boundary.unwrap_or(h));
+    }
+
+    fn link(&mut self, h: usize, next: usize) {
+        self.he_mut(h).next = next;
+        self.he_mut(next).prev = h;
+    }
+
+    fn new_half_edge_pair(&mut self, from: usize, to: usize) -> usize {
+        let h = self.half_edges.len();
+        for (vertex, twin) in [(to, h + 1), (from, h)] {
+            self.half_edges.push(Some(HalfEdge {
+                vertex,
+                twin,
+                next: usize::MAX,
+                prev: usize::MAX,
+                face: None,
+            }));
+        }
+        h
+    }
+
+    /// replace the edge of `h` by the other diagonal of its two triangles.
+    /// returns `false` and leaves mesh unchanged for boundary edges or if the new edge already exists
+    pub fn flip_edge(&mut self, h: usize) -> bool {
+        let t = self.he(h).twin;
+        let (Some(f0), Some(f1)) = (self.he(h).face, self.he(t).face) else {
+            return false;
+        };
+        let (hn, tn) = (self.he(h).next, self.he(t).next);
+        let (hnn, tnn) = (self.he(hn).next, self.he(tn).next);
+        let (a, b) = (self.origin(h), self.he(h).vertex);
+        let (c, d) = (self.he(hn).vertex, self.he(tn).vertex);
+        if c == d || self.find_half_edge(c, d).is_some() {
+            return false;
+        }
+
+        // triangles (a, b, c) and (b, a, d) become (c, a, d) and (d, b, c)
+        self.he_mut(h).vertex = c;
+        self.he_mut(t).vertex = d;
+        self.link(hnn, tn);
+        self.link(tn, h);
+        self.link(h, hnn);
+        self.link(tnn, hn);
+        self.link(hn, t);
+        self.link(t, tnn);
+        self.he_mut(tn).face = Some(f0);
+        self.he_mut(hn).face = Some(f1);
+        self.faces[f0] = Some(Face { half_edge: h });
+        self.faces[f1] = Some(Face { half_edge: t });
+        if self.vertex(a).half_edge == Some(h) {
+            self.reset_outgoing(a, tn);
+        }
+        if self.vertex(b).half_edge == Some(t) {
+            self.reset_outgoing(b, hn);
+        }
+        true
+    }
+
+    /// split triangle `(from, to, opposite)` of half-edge `h` by a new edge `(mid, opposite)`, `h` already ends at `mid`
+    fn split_face(&mut self, h: usize, mid: usize) {
+        let Some(face) = self.he(h).face else {
+            return;
+        };
+        let e = self.he(h).next;
+        let en = self.he(e).next;
+        let enn = self.he(en).next;
+        let opposite = self.he(en).vertex;
+
+        let x = self.new_half_edge_pair(mid, opposite);
+        let y = x + 1;
+        let new_face = self.faces.len();
+        self.faces.push(Some(Face { half_edge: e }));
+        self.faces[face] = Some(Face { half_edge: h });
+        self.link(h, x);
+        self.link(x, enn);
+        self.link(e, en);
+        self.link(en, y);
+        self.link(y, e);
+        self.he_mut(x).face = Some(face);
+        for i in [e, en, y] {
+            self.he_mut(i).face = Some(new_face);
+        }
+    }
+
+    /// insert a vertex at `position` on the edge of `h` and connect it to opposite vertices, returns the new vertex
+    pub fn split_edge(&mut self, h: usize, position: Vec3) -> usize {
+        let t = self.he(h).twin;
+        let (a, b) = (self.origin(h), self.he(h).vertex);
+        let m = self.vertices.len();
+        self.vertices.push(Some(Vertex { position, half_edge: None }));
+
+        // a -> b and b -> a become a -> m -> b and b -> m -> a
+        let e = self.new_half_edge_pair(m, b);
+        let f = e + 1;
+        let (hn, tn) = (self.he(h).next, self.he(t).next);
+        self.he_mut(f).vertex = a;
+        self.he_mut(h).vertex = m;
+        self.he_mut(t).vertex = m;
+        self.he_mut(h).twin = f;
+        self.he_mut(f).twin = h;
+        self.he_mut(t).twin = e;
+        self.he_mut(e).twin = t;
+        self.he_mut(e).face = self.he(h).face;
+        self.he_mut(f).face = self.he(t).face;
+        self.link(e, hn);
+        self.link(h, e);
+        self.link(f, tn);
+        self.link(t, f);
+
+        // quads around `m` are split into triangles
+        self.split_face(h, m);
+        self.split_face(t, m);
+        self.reset_outgoing(m, e);
+        m
+    }
+
+    /// merge the end of `h` into its start, which moves to `position`. returns `false` and leaves mesh unchanged
+    /// if the collapse would make the mesh non-manifold or fold a triangle flat
+    pub fn collapse_edge(&mut self, h: usize, position: Vec3) -> bool {
+        let t = self.he(h).twin;
+        let (a, b) = (self.origin(h), self.he(h).vertex);
+        // vertex opposite to the edge in the face of a half-edge
+        let opposite = |mesh: &Self, h: usize| mesh.he(h).face.map(|_| mesh.he(mesh.he(h).next).vertex);
+        let opposites: Vec<usize> = [opposite(self, h), opposite(self, t)].into_iter().flatten().collect();
+
+        // link condition, common neighbors are exactly the opposite vertices
+        let a_ring: Vec<usize> = self.vertex_neighbors(a).collect();
+        let common = self.vertex_neighbors(b).filter(|v| a_ring.contains(v)).count();
+        if common != opposites.len() {
+            return false;
+        }
+        if !self.is_boundary_edge(h) && self.is_boundary_vertex(a) && self.is_boundary_vertex(b) {
+            return false;
+        }
+        // opposite vertex loses an edge, it must keep a proper fan
+        for v in &opposites {
+            let min = if self.is_boundary_vertex(*v) { 2 } else { 3 };
+            if self.valence(*v) <= min {
+                return false;
+            }
+        }
+
+        let incoming: Vec<usize> = self.outgoing(b).map(|o| self.he(o).twin).collect();
+        let mut survivor = None;
+        for side in [h, t] {
+            if let Some(face) = self.he(side).face {
+                // the two other edges of the triangle become one
+                let n = self.he(side).next;
+                let nn = self.he(n).next;
+                let (tw1, tw2) = (self.he(n).twin, self.he(nn).twin);
+                self.he_mut(tw1).twin = tw2;
+                self.he_mut(tw2).twin = tw1;
+                let c = self.he(n).vertex;
+                self.faces[face] = None;
+                self.half_edges[n] = None;
+                self.half_edges[nn] = None;
+                self.reset_outgoing(c, tw1);
+                survivor = Some(survivor.unwrap_or(tw2));
+            } else {
+                let (prev, next) = (self.he(side).prev, self.he(side).next);
+                self.link(prev, next);
+                survivor = Some(survivor.unwrap_or(next));
+            }
+        }
+        for i in incoming {
+            if let Some(he) = self.half_edges[i].as_mut() {
+                he.vertex = a;
+            }
+        }
+        self.half_edges[h] = None;
+        self.half_edges[t] = None;
+        self.vertices[b] = None;
+
+        self.reset_outgoing(a, survivor.unwrap());
+        self.set_position(a, position);
+        true
+    }
+}
@@ -0,0 +1,143 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use math::matrix::*;
+use math::precision::Real;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageError {
+    /// pixel count is not `width * height`
+    SizeMismatch { expected: usize, actual: usize },
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImageError::SizeMismatch { expected, actual } => {
+                write!(f, "expected {} pixels, got {}", expected, actual)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+/// row major pixel buffer, `(0, 0)` is the top left pixel
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image<T> {
+    width: usize,
+    height: usize,
+    data: Vec<T>,
+}
+
+impl<T: Clone> Image<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Self {
+            width,
+            height,
+            data: vec![value; width * height],
+        }
+    }
+
+    pub fn fill(&mut self, value: T) {
+        self.data.fill(value);
+    }
+}
+
+impl<T> Image<T> {
+    /// pixels from `f(x, y)`
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(usize, usize) -> T) -> Self {
+        let data = (0..width * height).map(|i| f(i % width, i / width)).collect();
+        Self { width, height, data }
+    }
+
+    /// `data` is row major and must have `width * height` pixels
+    pub fn from_data(width: usize, height: usize, data: Vec<T>) -> Result<Self, ImageError> {
+        if data.len() != width * height {
+            return Err(ImageError::SizeMismatch {
+                expected: width * height,
+                actual: data.len(),
+            });
+        }
+        Ok(Self { width, height, data })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn data(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn data_mut(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    pub fn into_data(self) -> Vec<T> {
+        self.data
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.data[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        (x < self.width && y < self.height).then(|| &self.data[x + y * self.width])
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Image<U> {
+        Image {
+            width: self.width,
+            height: self.height,
+            data: self.data.iter().map(f).collect(),
+        }
+    }
+}
+
+/// pixel at `(x, y)`
+impl<T> Index<(usize, usize)> for Image<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        assert!(x < self.width && y < self.height, "pixel out of image");
+        &self.data[x + y * self.width]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Image<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        assert!(x < self.width && y < self.height, "pixel out of image");
+        &mut self.data[x + y * self.width]
+    }
+}
+
+/// channel in `[0, 1]` to 8 bits
+fn to_u8(v: Real) -> u8 {
+    (v.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+impl Image<Vec3> {
+    /// opaque 8 bit RGBA, channels are clamped to `[0, 1]`
+    pub fn to_rgba8(&self) -> Image<[u8; 4]> {
+        self.map(|c| [to_u8(c.x()), to_u8(c.y()), to_u8(c.z()), 255])
+    }
+}
+
+impl Image<Vec4> {
+    /// 8 bit RGBA, channels are clamped to `[0, 1]`
+    pub fn to_rgba8(&self) -> Image<[u8; 4]> {
+        self.map(|c| [to_u8(c.x()), to_u8(c.y()), to_u8(c.z()), to_u8(c.w())])
+    }
+}
+
+impl Image<[u8; 4]> {
+    /// channels in `[0, 1]`
+    pub fn to_colors(&self) -> Image<Vec4> {
+        self.map(|p| Vec4::from_xyzw(p[0] as Real, p[1] as Real, p[2] as Real, p[3] as Real) / 255.0)
+    }
+}
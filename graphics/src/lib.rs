@@ -1,2 +1,14 @@
+pub mod camera;
+pub mod deflate;
+pub mod gltf;
+pub mod half_edge;
+pub mod image;
 pub mod mesh_generate;
+pub mod obj;
+pub mod ply;
+pub mod png;
 pub mod ppm;
+pub mod raster;
+pub mod ray_trace;
+pub mod stl;
+pub mod svg;
@@ -1,13 +1,113 @@
 use std::f64::consts::PI;
 
-use geometric::{geom2d::Circle, geom3d::*};
+use geometric::{geom2d::{self, Circle}, geom3d::*, triangulation::ear_clip};
 use math::{coord::Cartesian3D, matrix::*, precision::Real};
 
 pub struct FaceDisplayData {
     pub vertices: Vec<Vec3>,
+    /// one normal per vertex, vertices are duplicated along hard edges
     pub normals: Vec<Vec3>,
     pub indices: Vec<u32>,
     pub color: Vec4,
+    /// texture coordinate per vertex
+    pub uvs: Option<Vec<Vec2>>,
+    /// tangent per vertex in MikkTSpace convention, bitangent is `w * normal.cross(tangent.xyz)`
+    pub tangents: Option<Vec<Vec4>>,
+}
+
+impl FaceDisplayData {
+    /// mesh with all attributes, tangents are derived from normals and uvs
+    pub fn new(
+        vertices: Vec<Vec3>,
+        normals: Vec<Vec3>,
+        uvs: Vec<Vec2>,
+        indices: Vec<u32>,
+        color: Vec4,
+    ) -> Self {
+        let mut data = Self {
+            vertices,
+            normals,
+            indices,
+            color,
+            uvs: Some(uvs),
+            tangents: None,
+        };
+        data.compute_tangents();
+        data
+    }
+
+    /// merge `other` into self, an attribute channel is kept only if both meshes have it
+    pub fn append(&mut self, other: FaceDisplayData) {
+        let base = self.vertices.len() as u32;
+        self.vertices.extend(other.vertices);
+        self.normals.extend(other.normals);
+        self.indices.extend(other.indices.iter().map(|i| *i + base));
+        self.uvs = match (self.uvs.take(), other.uvs) {
+            (Some(mut a), Some(b)) => {
+                a.extend(b);
+                Some(a)
+            }
+            _ => None,
+        };
+        self.tangents = match (self.tangents.take(), other.tangents) {
+            (Some(mut a), Some(b)) => {
+                a.extend(b);
+                Some(a)
+            }
+            _ => None,
+        };
+    }
+
+    /// accumulate uv derivatives of triangles weighted by corner angle, then orthogonalize against normals.
+    /// does nothing without uvs
+    pub fn compute_tangents(&mut self) {
+        const EPSILON: Real = 1e-12;
+        let Some(uvs) = &self.uvs else {
+            return;
+        };
+
+        let mut tangents = vec![Vec3::zeros(); self.vertices.len()];
+        let mut bitangents = vec![Vec3::zeros(); self.vertices.len()];
+        for t in self.indices.chunks(3) {
+            let [i0, i1, i2] = [t[0] as usize, t[1] as usize, t[2] as usize];
+            let (e1, e2) = (self.vertices[i1] - self.vertices[i0], self.vertices[i2] - self.vertices[i0]);
+            let (d1, d2) = (uvs[i1] - uvs[i0], uvs[i2] - uvs[i0]);
+            let det = d1.x() * d2.y() - d2.x() * d1.y();
+            if det.abs() <= EPSILON || e1.cross(&e2).length_sqrd() <= EPSILON * EPSILON {
+                continue;
+            }
+            let tangent = ((e1 * d2.y() - e2 * d1.y()) / det).normalize();
+            let bitangent = ((e2 * d1.x() - e1 * d2.x()) / det).normalize();
+
+            for (i, a, b) in [(i0, i1, i2), (i1, i2, i0), (i2, i0, i1)] {
+                let (u, v) = (self.vertices[a] - self.vertices[i], self.vertices[b] - self.vertices[i]);
+                let cos = u.dot(&v) / (u.length() * v.length());
+                let angle = cos.clamp(-1.0, 1.0).acos();
+                if angle.is_finite() {
+                    tangents[i] += tangent * angle;
+                    bitangents[i] += bitangent * angle;
+                }
+            }
+        }
+
+        let tangents = tangents
+            .iter()
+            .zip(&bitangents)
+            .zip(&self.normals)
+            .map(|((t, b), n)| {
+                let t = *t - *n * n.dot(t);
+                // vertices only used by degenerated triangles get any tangent
+                let t = if t.length_sqrd() > EPSILON {
+                    t.normalize()
+                } else {
+                    perpendicular_axes(n).0
+                };
+                let w = if n.cross(&t).dot(b) < 0.0 { -1.0 } else { 1.0 };
+                Vec4::from_xyzw(t.x(), t.y(), t.z(), w)
+            })
+            .collect();
+        self.tangents = Some(tangents);
+    }
 }
 
 pub struct LineStripDisplayData {
@@ -15,29 +115,91 @@ pub struct LineStripDisplayData {
     pub color: Vec4,
 }
 
+/// planar polygon(may be concave), uvs are the polygon bounding rectangle on its plane mapped to `[0, 1]`.
+/// faces are counter-clockwise seen from the side points go around counter-clockwise
 pub fn polygon_to_display_data(polygon: &Polygon, color: Vec4) -> Result<FaceDisplayData, &str> {
     if polygon.points.len() < 3 {
         return Err("invalid polygon");
     }
 
-    let vertices = polygon.points.clone();
-    let normal = (vertices[1] - vertices[0])
-        .cross(&(vertices[2] - vertices[1]))
-        .normalize();
+    let Some((normal, triangles)) = triangulate_polygon(&polygon.points) else {
+        return Err("invalid polygon");
+    };
+    let u_axis = (polygon.points[1] - polygon.points[0]).normalize();
+    let v_axis = normal.cross(&u_axis);
+    Ok(planar_to_display_data(polygon.points.clone(), &triangles, u_axis, v_axis, color))
+}
+
+/// normal(by Newell's method, robust for concave polygons) and counter-clockwise triangles of a planar polygon.
+/// `None` if the polygon has no area
+pub(crate) fn triangulate_polygon(points: &[Vec3]) -> Option<(Vec3, Vec<[usize; 3]>)> {
+    let n = points.len();
+    let normal = (0..n)
+        .map(|i| points[i].cross(&points[(i + 1) % n]))
+        .fold(Vec3::zeros(), |acc, v| acc + v);
+    if n < 3 || normal.length_sqrd() <= Real::EPSILON * Real::EPSILON {
+        return None;
+    }
+    let normal = normal.normalize();
+    if n == 3 {
+        return Some((normal, vec![[0, 1, 2]]));
+    }
+
+    let u_axis = (points[1] - points[0]).normalize();
+    let v_axis = normal.cross(&u_axis);
+    let projected: Vec<Vec2> = points
+        .iter()
+        .map(|p| Vec2::from_xy((*p - points[0]).dot(&u_axis), (*p - points[0]).dot(&v_axis)))
+        .collect();
+    Some((normal, ear_clip(&projected, &[])))
+}
+
+/// flat face of `vertices` triangulated by `triangles`, facing `u_axis × v_axis` whatever the triangle orientation is.
+/// uvs are the bounding rectangle in `(u_axis, v_axis)` mapped to `[0, 1]`
+fn planar_to_display_data(
+    vertices: Vec<Vec3>,
+    triangles: &[[usize; 3]],
+    u_axis: Vec3,
+    v_axis: Vec3,
+    color: Vec4,
+) -> FaceDisplayData {
+    if vertices.is_empty() {
+        return empty_display_data(color);
+    }
+    let normal = u_axis.cross(&v_axis).normalize();
     let mut indices: Vec<u32> = Vec::new();
-    let mut normals: Vec<Vec3> = Vec::new();
+    for [a, b, c] in triangles {
+        let facing = (vertices[*b] - vertices[*a]).cross(&(vertices[*c] - vertices[*a])).dot(&normal);
+        if facing >= 0.0 {
+            indices.extend([*a as u32, *b as u32, *c as u32].iter());
+        } else {
+            indices.extend([*a as u32, *c as u32, *b as u32].iter());
+        }
+    }
 
-    for i in 1..vertices.len() as u32 - 1 {
-        indices.extend([0, i, i + 1].iter());
-        normals.push(normal);
+    let projected: Vec<Vec2> = vertices
+        .iter()
+        .map(|p| Vec2::from_xy(p.dot(&u_axis), p.dot(&v_axis)))
+        .collect();
+    let mut min = projected[0];
+    let mut max = projected[0];
+    for p in &projected {
+        for i in 0..2 {
+            min[i] = min[i].min(p[i]);
+            max[i] = max[i].max(p[i]);
+        }
     }
+    let size = max - min;
+    let uvs = projected
+        .iter()
+        .map(|p| {
+            let p = *p - min;
+            Vec2::from_xy(p.x() / size.x().max(Real::EPSILON), p.y() / size.y().max(Real::EPSILON))
+        })
+        .collect();
 
-    Ok(FaceDisplayData {
-        vertices,
-        normals,
-        indices,
-        color,
-    })
+    let normals = vec![normal; vertices.len()];
+    FaceDisplayData::new(vertices, normals, uvs, indices, color)
 }
 
 pub fn polyline_to_display_data(polyline: &Vec<Vec3>, color: Vec4) -> Result<LineStripDisplayData, &str> {
@@ -161,81 +323,85 @@ pub fn cylinder_to_display_data(cylinder: &Cylinder, color: Vec4, slice: u32) ->
 }
 
 pub fn cone_to_display_data(cone: &Cone, color: Vec4, slice: u32) -> FaceDisplayData {
-    let mut circle_data = circle_to_display_data(
-        &Circle {
-            center: Vec2::from_xy(cone.bottom.x(), cone.bottom.z()),
-            radius: cone.bottom_radius,
+    truncatedcone_to_display_data(
+        &TruncatedCone {
+            bottom: cone.bottom,
+            bottom_radius: cone.bottom_radius,
+            top_radius: 0.0,
+            dir: cone.dir,
+            height: cone.height,
         },
         color,
         slice,
-    );
-    let top = cone.bottom + cone.dir * cone.height;
-
-    let mut vertices: Vec<Vec3> = Vec::new();
-    let mut indices: Vec<u32> = Vec::new();
-
-    vertices.append(&mut circle_data.vertices);
-    vertices.push(top);
-    indices.append(&mut circle_data.indices);
-
-    let last_idx = vertices.len() as u32 - 1;
-    for i in 0..slice {
-        indices.extend([last_idx, i, (i + 1) % slice].iter());
-    }
-
-    // TODO: normals not calculated
-    FaceDisplayData {
-        vertices,
-        normals: vec![],
-        indices,
-        color,
-    }
+    )
 }
 
+/// side has smooth normals and cylindrical uvs, caps are separated by hard edges and have planar uvs.
+/// apex of cone is split for each slice so every side column keeps its own normal
 pub fn truncatedcone_to_display_data(
     cone: &TruncatedCone,
     color: Vec4,
     slice: u32,
 ) -> FaceDisplayData {
-    let mut bottom = origin_circle_to_display_data(cone.bottom_radius, color, slice);
-    let top = origin_circle_to_display_data(cone.top_radius, color, slice);
+    let (x_axis, y_axis) = perpendicular_axes(&cone.dir);
+    let top = cone.bottom + cone.dir * cone.height;
 
-    let mut vertices: Vec<Vec3> = Vec::new();
-    let mut indices: Vec<u32> = Vec::new();
+    let mut data = grid_to_display_data(2, slice, color, |row, theta| {
+        let radial = x_axis * theta.cos() + y_axis * theta.sin();
+        let (center, radius) = if row == 0 {
+            (cone.bottom, cone.bottom_radius)
+        } else {
+            (top, cone.top_radius)
+        };
+        let normal = (radial * cone.height + cone.dir * (cone.bottom_radius - cone.top_radius)).normalize();
+        (center + radial * radius, normal)
+    });
 
-    vertices.append(&mut bottom.vertices);
-    indices.append(&mut bottom.indices);
-    vertices.append(
-        &mut top
-            .vertices
-            .iter()
-            .map(|v| *v + Vec3::z_axis() * cone.height)
-            .collect(),
-    );
-    indices.append(&mut top.indices.iter().map(|i| *i + slice).collect());
+    for (center, radius, normal) in [
+        (cone.bottom, cone.bottom_radius, -cone.dir),
+        (top, cone.top_radius, cone.dir),
+    ] {
+        if radius > 0.0 {
+            data.append(disc_to_display_data(center, x_axis, y_axis, normal, radius, slice, color));
+        }
+    }
+    data
+}
 
+/// disc with vertices at the same angles as `grid_to_display_data` around `x_axis` and `y_axis`, facing `normal`
+fn disc_to_display_data(
+    center: Vec3,
+    x_axis: Vec3,
+    y_axis: Vec3,
+    normal: Vec3,
+    radius: Real,
+    slice: u32,
+    color: Vec4,
+) -> FaceDisplayData {
+    let flip = x_axis.cross(&y_axis).dot(&normal) < 0.0;
+    let mut vertices = vec![center];
+    let mut uvs = vec![Vec2::from_xy(0.5, 0.5)];
     for i in 0..slice {
-        indices.extend([i, i + slice, (i + 1) % slice].iter());
-        indices.extend([i + 1, i + slice, (i + slice + 1) % (slice * 2)].iter());
+        let theta = 2.0 * PI * i as Real / slice as Real;
+        let (cos, sin) = (theta.cos(), theta.sin());
+        vertices.push(center + (x_axis * cos + y_axis * sin) * radius);
+        // mirror u on back face so uvs are not flipped seen from outside
+        let u = if flip { -cos } else { cos };
+        uvs.push(Vec2::from_xy(0.5 + 0.5 * u, 0.5 + 0.5 * sin));
     }
 
-    let x_axis = (geometric::misc::get_arbitrary_from_plane(&Vec3::z_axis(), &cone.bottom)
-        - cone.bottom)
-        .normalize();
-    let y_axis = cone.dir.cross(&x_axis).normalize();
-    let x_axis = y_axis.cross(&cone.dir).normalize();
-
-    let cart = Cartesian3D::new(x_axis, y_axis, cone.dir, cone.bottom);
-    for v in &mut vertices {
-        *v = cart.transform(*v);
+    let mut indices: Vec<u32> = Vec::new();
+    for i in 0..slice {
+        let (a, b) = (i + 1, (i + 1) % slice + 1);
+        if flip {
+            indices.extend([0, b, a].iter());
+        } else {
+            indices.extend([0, a, b].iter());
+        }
     }
 
-    FaceDisplayData {
-        vertices,
-        normals: vec![],
-        indices,
-        color,
-    }
+    let normals = vec![normal; vertices.len()];
+    FaceDisplayData::new(vertices, normals, uvs, indices, color)
 }
 
 pub fn cylinderlike_to_display_data(
@@ -342,4 +508,675 @@ pub fn polar_conic_arc_to_display_data(arc: &ConicArcInPolar, color: Vec4, slice
     }).collect();
 
     polyline_to_display_data(&points, color).unwrap()
-}
\ No newline at end of file
+}
+
+/// two unit vectors perpendicular to `dir`, `(x, y, dir)` is right-handed
+fn perpendicular_axes(dir: &Vec3) -> (Vec3, Vec3) {
+    let helper = if dir.x().abs() < 0.9 {
+        Vec3::x_axis()
+    } else {
+        Vec3::y_axis()
+    };
+    let x_axis = helper.cross(dir).normalize();
+    (x_axis, dir.cross(&x_axis))
+}
+
+/// grid surface closed around theta, `vertex(row, theta)` gives position and normal. the last column repeats the first one
+/// so uvs(`u` along theta, `v` along rows) have a seam there.
+/// faces are counter-clockwise seen from outside if `theta direction × row direction` points outside
+fn grid_to_display_data(
+    rows: u32,
+    slice: u32,
+    color: Vec4,
+    vertex: impl Fn(u32, Real) -> (Vec3, Vec3),
+) -> FaceDisplayData {
+    let mut vertices: Vec<Vec3> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut uvs: Vec<Vec2> = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+
+    for row in 0..rows {
+        for col in 0..=slice {
+            let (v, n) = vertex(row, 2.0 * PI * col as Real / slice as Real);
+            vertices.push(v);
+            normals.push(n);
+            uvs.push(Vec2::from_xy(col as Real / slice as Real, row as Real / (rows - 1) as Real));
+        }
+    }
+
+    let width = slice + 1;
+    for row in 0..rows - 1 {
+        for col in 0..slice {
+            let a = row * width + col;
+            let b = a + width;
+            indices.extend([a, a + 1, b + 1].iter());
+            indices.extend([a, b + 1, b].iter());
+        }
+    }
+
+    FaceDisplayData::new(vertices, normals, uvs, indices, color)
+}
+
+/// `stack` is the number of rings on each hemisphere
+pub fn capsule_to_display_data(
+    capsule: &Capsule,
+    color: Vec4,
+    slice: u32,
+    stack: u32,
+) -> FaceDisplayData {
+    let dir = (capsule.end - capsule.start).normalize();
+    let (x_axis, y_axis) = perpendicular_axes(&dir);
+
+    grid_to_display_data((stack + 1) * 2, slice, color, |row, theta| {
+        let (center, phi) = if row <= stack {
+            (capsule.start, -PI / 2.0 + PI / 2.0 * row as Real / stack as Real)
+        } else {
+            (capsule.end, PI / 2.0 * (row - stack - 1) as Real / stack as Real)
+        };
+        let normal =
+            (x_axis * theta.cos() + y_axis * theta.sin()) * phi.cos() + dir * phi.sin();
+        (center + normal * capsule.radius, normal)
+    })
+}
+
+pub fn ellipsoid_to_display_data(
+    ellipsoid: &Ellipsoid,
+    color: Vec4,
+    slice: u32,
+    stack: u32,
+) -> FaceDisplayData {
+    let cart = ellipsoid.get_coord();
+    let r = ellipsoid.radii;
+
+    grid_to_display_data(stack + 1, slice, color, |row, theta| {
+        let phi = -PI / 2.0 + PI * row as Real / stack as Real;
+        let unit = Vec3::from_xyz(phi.cos() * theta.cos(), phi.cos() * theta.sin(), phi.sin());
+        let local = Vec3::from_xyz(unit.x() * r.x(), unit.y() * r.y(), unit.z() * r.z());
+        let normal = (0..3)
+            .fold(Vec3::zeros(), |acc, i| acc + ellipsoid.axes[i] * (unit[i] / r[i]))
+            .normalize();
+        (cart.transform(local), normal)
+    })
+}
+
+/// `slice` goes around the torus axis, `stack` goes around the tube
+pub fn torus_to_display_data(torus: &Torus, color: Vec4, slice: u32, stack: u32) -> FaceDisplayData {
+    let (x_axis, y_axis) = perpendicular_axes(&torus.normal);
+
+    grid_to_display_data(stack + 1, slice, color, |row, theta| {
+        let phi = 2.0 * PI * row as Real / stack as Real;
+        let radial = x_axis * theta.cos() + y_axis * theta.sin();
+        let normal = radial * phi.cos() + torus.normal * phi.sin();
+        (
+            torus.center + radial * torus.major_radius + normal * torus.minor_radius,
+            normal,
+        )
+    })
+}
+
+/// sphere grid pushed out to the inner box corners, flat faces are covered by triangles crossing the axes
+pub fn rounded_box_to_display_data(
+    rounded: &RoundedBox,
+    color: Vec4,
+    slice: u32,
+    stack: u32,
+) -> FaceDisplayData {
+    const EPSILON: Real = 1e-9;
+
+    grid_to_display_data(stack + 1, slice, color, |row, theta| {
+        let phi = -PI / 2.0 + PI * row as Real / stack as Real;
+        let normal = Vec3::from_xyz(phi.cos() * theta.cos(), phi.cos() * theta.sin(), phi.sin());
+        let mut corner = rounded.center;
+        for i in 0..3 {
+            if normal[i].abs() > EPSILON {
+                corner[i] += rounded.half_len[i] * normal[i].signum();
+            }
+        }
+        (corner + normal * rounded.radius, normal)
+    })
+}
+
+/// quad `center ± u ± v` split into `segments` x `segments` cells, facing `u.cross(v)`
+fn quad_to_display_data(center: Vec3, u: Vec3, v: Vec3, segments: u32, color: Vec4) -> FaceDisplayData {
+    let normal = u.cross(&v).normalize();
+    let mut vertices: Vec<Vec3> = Vec::new();
+    let mut uvs: Vec<Vec2> = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+
+    for row in 0..=segments {
+        for col in 0..=segments {
+            let (s, t) = (col as Real / segments as Real, row as Real / segments as Real);
+            vertices.push(center + u * (2.0 * s - 1.0) + v * (2.0 * t - 1.0));
+            uvs.push(Vec2::from_xy(s, t));
+        }
+    }
+
+    let width = segments + 1;
+    for row in 0..segments {
+        for col in 0..segments {
+            let a = row * width + col;
+            let b = a + width;
+            indices.extend([a, a + 1, b + 1, a, b + 1, b].iter());
+        }
+    }
+
+    let normals = vec![normal; vertices.len()];
+    FaceDisplayData::new(vertices, normals, uvs, indices, color)
+}
+
+/// faces of box with right-handed `axes`, each face has its own vertices so normals stay flat
+fn box_to_display_data(center: Vec3, axes: [Vec3; 3], half_len: Vec3, segments: u32, color: Vec4) -> FaceDisplayData {
+    let mut data: Option<FaceDisplayData> = None;
+    for i in 0..3 {
+        let u = axes[(i + 1) % 3] * half_len[(i + 1) % 3];
+        let v = axes[(i + 2) % 3] * half_len[(i + 2) % 3];
+        for sign in [1.0, -1.0] {
+            let face_center = center + axes[i] * (sign * half_len[i]);
+            // swap sides on back face to keep counter-clockwise seen from outside
+            let (u, v) = if sign > 0.0 { (u, v) } else { (v, u) };
+            let face = quad_to_display_data(face_center, u, v, segments, color);
+            match &mut data {
+                Some(data) => data.append(face),
+                None => data = Some(face),
+            }
+        }
+    }
+    data.unwrap()
+}
+
+/// square patch of the boundary plane, facing outside
+pub fn half_space_to_display_data(half_space: &HalfSpace, color: Vec4, half_size: Real) -> FaceDisplayData {
+    let (x_axis, y_axis) = perpendicular_axes(&half_space.normal);
+    quad_to_display_data(half_space.pt, x_axis * half_size, y_axis * half_size, 1, color)
+}
+
+/// slab cut to a square of `half_size` around `slab.pt`
+pub fn slab_to_display_data(slab: &Slab, color: Vec4, half_size: Real) -> FaceDisplayData {
+    let (x_axis, y_axis) = perpendicular_axes(&slab.normal);
+    box_to_display_data(
+        slab.pt,
+        [x_axis, y_axis, slab.normal],
+        Vec3::from_xyz(half_size, half_size, slab.half_thickness),
+        1,
+        color,
+    )
+}
+
+/// box with each face split into `segments` x `segments` cells
+pub fn cube_to_display_data(cube: &Cube, color: Vec4, segments: u32) -> FaceDisplayData {
+    box_to_display_data(
+        cube.center,
+        [Vec3::x_axis(), Vec3::y_axis(), Vec3::z_axis()],
+        cube.half_len,
+        segments,
+        color,
+    )
+}
+
+/// `axes` of obb must be right-handed
+pub fn obb_to_display_data(obb: &OBB, color: Vec4, segments: u32) -> FaceDisplayData {
+    box_to_display_data(obb.center, obb.axes, obb.half_len, segments, color)
+}
+
+/// square grid of the plane around `plane.pt`, facing `plane.normal`
+pub fn plane_to_display_data(plane: &Plane, color: Vec4, half_size: Real, segments: u32) -> FaceDisplayData {
+    let (x_axis, y_axis) = perpendicular_axes(&plane.normal.normalize());
+    quad_to_display_data(plane.pt, x_axis * half_size, y_axis * half_size, segments, color)
+}
+
+/// uv sphere, `stack` is the number of rings from pole to pole
+pub fn sphere_to_display_data(sphere: &Sphere, color: Vec4, slice: u32, stack: u32) -> FaceDisplayData {
+    grid_to_display_data(stack + 1, slice, color, |row, theta| {
+        let phi = -PI / 2.0 + PI * row as Real / stack as Real;
+        let normal = Vec3::from_xyz(phi.cos() * theta.cos(), phi.cos() * theta.sin(), phi.sin());
+        (sphere.center + normal * sphere.radius, normal)
+    })
+}
+
+const GOLDEN_RATIO: Real = 1.618033988749895;
+
+#[rustfmt::skip]
+const ICOSAHEDRON_VERTICES: [[Real; 3]; 12] = [
+    [-1.0, GOLDEN_RATIO, 0.0], [1.0, GOLDEN_RATIO, 0.0], [-1.0, -GOLDEN_RATIO, 0.0], [1.0, -GOLDEN_RATIO, 0.0],
+    [0.0, -1.0, GOLDEN_RATIO], [0.0, 1.0, GOLDEN_RATIO], [0.0, -1.0, -GOLDEN_RATIO], [0.0, 1.0, -GOLDEN_RATIO],
+    [GOLDEN_RATIO, 0.0, -1.0], [GOLDEN_RATIO, 0.0, 1.0], [-GOLDEN_RATIO, 0.0, -1.0], [-GOLDEN_RATIO, 0.0, 1.0],
+];
+
+/// counter-clockwise seen from outside
+#[rustfmt::skip]
+const ICOSAHEDRON_FACES: [[usize; 3]; 20] = [
+    [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+    [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+    [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+    [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+];
+
+/// icosahedron with each face split into `4^subdivision` triangles, projected onto sphere.
+/// uvs are spherical, vertices are duplicated on the `u` seam and at poles.
+/// edges of the bare icosahedron cross the poles, so uvs are only usable with `subdivision >= 1`
+pub fn icosphere_to_display_data(sphere: &Sphere, color: Vec4, subdivision: u32) -> FaceDisplayData {
+    let mut dirs: Vec<Vec3> = ICOSAHEDRON_VERTICES
+        .iter()
+        .map(|[x, y, z]| Vec3::from_xyz(*x, *y, *z).normalize())
+        .collect();
+    let mut triangles = ICOSAHEDRON_FACES.to_vec();
+
+    for _ in 0..subdivision {
+        // share midpoint of each edge between its two triangles
+        let mut midpoints = std::collections::HashMap::new();
+        let mut midpoint = |a: usize, b: usize, dirs: &mut Vec<Vec3>| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                dirs.push((dirs[a] + dirs[b]).normalize());
+                dirs.len() - 1
+            })
+        };
+        triangles = triangles
+            .iter()
+            .flat_map(|[a, b, c]| {
+                let ab = midpoint(*a, *b, &mut dirs);
+                let bc = midpoint(*b, *c, &mut dirs);
+                let ca = midpoint(*c, *a, &mut dirs);
+                [[*a, ab, ca], [*b, bc, ab], [*c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    const POLE_EPSILON: Real = 1e-9;
+    let u_of = |d: &Vec3| 0.5 + d.y().atan2(d.x()) / (2.0 * PI);
+    let v_of = |d: &Vec3| 0.5 + d.z().clamp(-1.0, 1.0).asin() / PI;
+    let is_pole = |d: &Vec3| d.x().abs() < POLE_EPSILON && d.y().abs() < POLE_EPSILON;
+
+    let mut vertices: Vec<Vec3> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut uvs: Vec<Vec2> = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+    let mut cache = std::collections::HashMap::new();
+    for tri in &triangles {
+        let mut us = tri.map(|i| u_of(&dirs[i]));
+        // wrap triangles crossing the seam to the right side
+        let non_pole: Vec<usize> = (0..3).filter(|k| !is_pole(&dirs[tri[*k]])).collect();
+        let max_u = non_pole.iter().map(|k| us[*k]).fold(0.0, Real::max);
+        for k in &non_pole {
+            if max_u - us[*k] > 0.5 {
+                us[*k] += 1.0;
+            }
+        }
+        // pole takes the middle of the other two so its triangle is not twisted
+        for k in 0..3 {
+            if is_pole(&dirs[tri[k]]) {
+                us[k] = non_pole.iter().map(|j| us[*j]).sum::<Real>() / non_pole.len() as Real;
+            }
+        }
+
+        for k in 0..3 {
+            let key = (tri[k], (us[k] * 1e9).round() as i64);
+            let index = *cache.entry(key).or_insert_with(|| {
+                let d = dirs[tri[k]];
+                vertices.push(sphere.center + d * sphere.radius);
+                normals.push(d);
+                uvs.push(Vec2::from_xy(us[k], v_of(&d)));
+                vertices.len() as u32 - 1
+            });
+            indices.push(index);
+        }
+    }
+
+    FaceDisplayData::new(vertices, normals, uvs, indices, color)
+}
+
+/// annulus in xy plane facing `+z`, same as `circle_to_display_data`. uvs are planar over the outer circle
+pub fn annulus_to_display_data(annulus: &geom2d::Annulus, color: Vec4, slice: u32) -> FaceDisplayData {
+    let center = Vec3::from(annulus.center);
+    let mut vertices: Vec<Vec3> = Vec::new();
+    let mut uvs: Vec<Vec2> = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+    for radius in [annulus.inner_radius, annulus.outer_radius] {
+        for i in 0..slice {
+            let theta = 2.0 * PI * i as Real / slice as Real;
+            let (cos, sin) = (theta.cos(), theta.sin());
+            vertices.push(center + Vec3::from_xyz(cos, sin, 0.0) * radius);
+            let scale = 0.5 * radius / annulus.outer_radius;
+            uvs.push(Vec2::from_xy(0.5 + cos * scale, 0.5 + sin * scale));
+        }
+    }
+    for i in 0..slice {
+        let next = (i + 1) % slice;
+        indices.extend([i, slice + i, slice + next, i, slice + next, next].iter());
+    }
+
+    let normals = vec![Vec3::z_axis(); vertices.len()];
+    FaceDisplayData::new(vertices, normals, uvs, indices, color)
+}
+
+/// arrow from `start` to `end`, shaft with `radius` and a cone head twice as wide
+pub fn arrow_to_display_data(start: Vec3, end: Vec3, radius: Real, color: Vec4, slice: u32) -> FaceDisplayData {
+    let len = (end - start).length();
+    let dir = (end - start) / len;
+    let head_len = (radius * 6.0).min(len * 0.5);
+
+    let mut data = cylinder_to_display_data(
+        &Cylinder {
+            bottom: start,
+            dir,
+            height: len - head_len,
+            radius,
+        },
+        color,
+        slice,
+    );
+    data.append(cone_to_display_data(
+        &Cone {
+            bottom: end - dir * head_len,
+            bottom_radius: radius * 2.0,
+            dir,
+            height: head_len,
+        },
+        color,
+        slice,
+    ));
+    data
+}
+
+/// arrows of `frame` axes colored red, green and blue
+pub fn axis_gizmo_to_display_data(frame: &Cartesian3D, length: Real, slice: u32) -> [FaceDisplayData; 3] {
+    let radius = length * 0.02;
+    let origin = frame.position();
+    [
+        (frame.x_axis(), Vec4::from_xyzw(1.0, 0.0, 0.0, 1.0)),
+        (frame.y_axis(), Vec4::from_xyzw(0.0, 1.0, 0.0, 1.0)),
+        (frame.z_axis(), Vec4::from_xyzw(0.0, 0.0, 1.0, 1.0)),
+    ]
+    .map(|(axis, color)| arrow_to_display_data(origin, origin + axis * length, radius, color, slice))
+}
+
+/// no vertices, returned for input too short to make a surface
+fn empty_display_data(color: Vec4) -> FaceDisplayData {
+    FaceDisplayData::new(Vec::new(), Vec::new(), Vec::new(), Vec::new(), color)
+}
+
+/// side surface through `layers`, every layer is the same ring of points placed differently.
+/// each ring edge gets its own strip so edges between them are hard, `smooth` shares vertices around the ring instead.
+/// `closed` joins the last ring point to the first, `wrap` means the last layer coincides with the first one.
+/// faces are counter-clockwise seen from outside if `ring direction × layer direction` points outside
+fn loft_to_display_data(layers: &[Vec<Vec3>], closed: bool, smooth: bool, wrap: bool, color: Vec4) -> FaceDisplayData {
+    let (m, n) = (layers.len(), layers.first().map_or(0, |l| l.len()));
+    if m < 2 || n < 2 {
+        return empty_display_data(color);
+    }
+    let edge_count = if closed { n } else { n - 1 };
+    // direction across layers at layer `k`, with `pt(layer)` picking the point on a layer
+    let along = |k: usize, pt: &dyn Fn(usize) -> Vec3| {
+        let prev = if k > 0 { k - 1 } else if wrap { m - 2 } else { k };
+        let next = if k + 1 < m { k + 1 } else if wrap { 1 } else { k };
+        pt(next) - pt(prev)
+    };
+
+    let mut lengths = vec![0.0];
+    for j in 0..edge_count {
+        let len = (layers[0][(j + 1) % n] - layers[0][j]).length();
+        lengths.push(lengths[j] + len);
+    }
+    let total = lengths[edge_count].max(Real::EPSILON);
+    let v_at = |k: usize| k as Real / (m - 1) as Real;
+
+    let mut vertices: Vec<Vec3> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut uvs: Vec<Vec2> = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+    // quad between columns `a` and `b` of consecutive layers, `width` vertices per layer.
+    // triangles collapsed by points on a revolving axis are dropped
+    let mut add_quads = |vertices: &Vec<Vec3>, base: usize, width: usize, a: usize, b: usize| {
+        for k in 0..m - 1 {
+            let at = |layer: usize, col: usize| base + layer * width + col;
+            for t in [[at(k, a), at(k, b), at(k + 1, b)], [at(k, a), at(k + 1, b), at(k + 1, a)]] {
+                let [p0, p1, p2] = t.map(|i| vertices[i]);
+                if p0 != p1 && p1 != p2 && p2 != p0 {
+                    indices.extend(t.map(|i| i as u32).iter());
+                }
+            }
+        }
+    };
+
+    if smooth {
+        let base = vertices.len();
+        for (k, layer) in layers.iter().enumerate() {
+            for (col, length) in lengths.iter().enumerate() {
+                let j = col % n;
+                let (prev, next) = match (j, closed) {
+                    (0, false) => (0, 1),
+                    (_, false) if j == n - 1 => (j - 1, j),
+                    _ => ((j + n - 1) % n, (j + 1) % n),
+                };
+                let normal = (layer[next] - layer[prev]).cross(&along(k, &|l| layers[l][j]));
+                vertices.push(layer[j]);
+                normals.push(normal.normalize());
+                uvs.push(Vec2::from_xy(length / total, v_at(k)));
+            }
+        }
+        for j in 0..edge_count {
+            add_quads(&vertices, base, edge_count + 1, j, j + 1);
+        }
+    } else {
+        for j in 0..edge_count {
+            let (a, b) = (j, (j + 1) % n);
+            let base = vertices.len();
+            let mut strip_normals = Vec::new();
+            for (k, layer) in layers.iter().enumerate() {
+                let mid = |l: usize| (layers[l][a] + layers[l][b]) * 0.5;
+                strip_normals.push((layer[b] - layer[a]).cross(&along(k, &mid)));
+                vertices.extend([layer[a], layer[b]].iter());
+                uvs.push(Vec2::from_xy(lengths[j] / total, v_at(k)));
+                uvs.push(Vec2::from_xy(lengths[j + 1] / total, v_at(k)));
+            }
+            // an edge shrunk to a point on some layer takes the normal of its neighbour layer
+            for k in (0..m).chain((0..m).rev()) {
+                if strip_normals[k].length_sqrd() <= Real::EPSILON * Real::EPSILON {
+                    let neighbour = if k + 1 < m { strip_normals[k + 1] } else { strip_normals[k - 1] };
+                    strip_normals[k] = neighbour;
+                }
+            }
+            for normal in strip_normals {
+                let normal = normal.normalize();
+                normals.extend([normal, normal].iter());
+            }
+            add_quads(&vertices, base, 2, 0, 1);
+        }
+    }
+
+    FaceDisplayData::new(vertices, normals, uvs, indices, color)
+}
+
+/// copy of `pts` going around counter-clockwise, or clockwise if not `ccw`
+fn oriented_pts(pts: &[Vec2], ccw: bool) -> Vec<Vec2> {
+    let mut pts = pts.to_vec();
+    if (geom2d::Polygon::new(pts.clone()).signed_area() > 0.0) != ccw {
+        pts.reverse();
+    }
+    pts
+}
+
+/// `pts` moved toward their left side by `dist`, corners are mitered
+fn inset_pts(pts: &[Vec2], dist: Real) -> Vec<Vec2> {
+    let n = pts.len();
+    let left = |a: &Vec2, b: &Vec2| {
+        let d = (*b - *a).normalize();
+        Vec2::from_xy(-d.y(), d.x())
+    };
+    (0..n)
+        .map(|i| {
+            let n1 = left(&pts[(i + n - 1) % n], &pts[i]);
+            let n2 = left(&pts[i], &pts[(i + 1) % n]);
+            let miter = (n1 + n2).normalize();
+            pts[i] + miter * (dist / miter.dot(&n1))
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Extrusion {
+    pub height: Real,
+    /// scale of the top outline relative to the bottom one around profile origin, `1` for straight walls
+    pub taper: Real,
+    /// size of the chamfer around both caps, `0` for none. it must be smaller than features of the outline
+    pub bevel: Real,
+}
+
+impl Extrusion {
+    pub fn new(height: Real) -> Self {
+        Self {
+            height,
+            taper: 1.0,
+            bevel: 0.0,
+        }
+    }
+}
+
+/// solid made by moving `outline` with `holes` along z axis of `frame`, profile lies on xy plane of `frame`.
+/// rings can have any orientation
+pub fn extrude_to_display_data(
+    outline: &geom2d::Polygon,
+    holes: &[geom2d::Polygon],
+    extrusion: &Extrusion,
+    frame: &Cartesian3D,
+    color: Vec4,
+) -> FaceDisplayData {
+    let mut rings = vec![oriented_pts(&outline.points, true)];
+    rings.extend(holes.iter().map(|h| oriented_pts(&h.points, false)));
+    let triangles = ear_clip(&rings[0], &rings[1..]);
+
+    let (h, b) = (extrusion.height, extrusion.bevel);
+    // (height, inset) of each layer
+    let layers = if b > 0.0 {
+        vec![(0.0, b), (b, 0.0), (h - b, 0.0), (h, b)]
+    } else {
+        vec![(0.0, 0.0), (h, 0.0)]
+    };
+    let place = |ring: &[Vec2], (z, inset): (Real, Real)| -> Vec<Vec3> {
+        let scale = 1.0 + (extrusion.taper - 1.0) * z / h;
+        inset_pts(ring, inset)
+            .iter()
+            .map(|p| frame.transform(Vec3::from_xyz(p.x() * scale, p.y() * scale, z)))
+            .collect()
+    };
+
+    let cap = |layer: (Real, Real), u_axis: Vec3, v_axis: Vec3| {
+        let vertices = rings.iter().flat_map(|r| place(r, layer)).collect();
+        planar_to_display_data(vertices, &triangles, u_axis, v_axis, color)
+    };
+    let mut data = cap(layers[0], frame.y_axis(), frame.x_axis());
+    data.append(cap(layers[layers.len() - 1], frame.x_axis(), frame.y_axis()));
+    for ring in &rings {
+        // a strip per layer pair keeps bevel edges hard
+        for pair in layers.windows(2) {
+            let strip = [place(ring, pair[0]), place(ring, pair[1])];
+            data.append(loft_to_display_data(&strip, true, false, false, color));
+        }
+    }
+    data
+}
+
+/// surface made by revolving `profile` around z axis of `frame` by `angle` in radians, starting from its x axis.
+/// profile points are `(radius, height)`, faces are on the right side walking along the profile.
+/// a partial revolution is capped by the profile closed as a polygon, a profile starting and ending on the axis gives a solid.
+/// empty for less than 2 profile points or no slice
+pub fn lathe_to_display_data(
+    profile: &[Vec2],
+    angle: Real,
+    slice: u32,
+    frame: &Cartesian3D,
+    color: Vec4,
+) -> FaceDisplayData {
+    if profile.len() < 2 || slice == 0 {
+        return empty_display_data(color);
+    }
+    let full = angle >= 2.0 * PI - 1e-9;
+    let angle = if full { 2.0 * PI } else { angle };
+    // walk profile backward so `ring direction × layer direction` is the right side of it
+    let ring: Vec<Vec2> = profile.iter().rev().copied().collect();
+    let radial = |theta: Real| Vec3::from_xyz(theta.cos(), theta.sin(), 0.0);
+    let place = |theta: Real| -> Vec<Vec3> {
+        ring.iter()
+            .map(|p| frame.transform(radial(theta) * p.x() + Vec3::z_axis() * p.y()))
+            .collect()
+    };
+
+    let layers: Vec<Vec<Vec3>> = (0..=slice)
+        .map(|k| place(angle * k as Real / slice as Real))
+        .collect();
+    let mut data = loft_to_display_data(&layers, false, false, full, color);
+    if !full {
+        let to_world = |v: Vec3| frame.x_axis() * v.x() + frame.y_axis() * v.y() + frame.z_axis() * v.z();
+        let triangles = ear_clip(&ring, &[]);
+        let (start, end) = (radial(0.0), radial(angle));
+        data.append(planar_to_display_data(place(0.0), &triangles, to_world(start), frame.z_axis(), color));
+        data.append(planar_to_display_data(place(angle), &triangles, frame.z_axis(), to_world(end), color));
+    }
+    data
+}
+
+/// frames along `path` with z axis being its tangent, twisting as little as possible, none for less than 2 points.
+/// computed by [double reflection](https://www.microsoft.com/en-us/research/wp-content/uploads/2016/12/Computation-of-rotation-minimizing-frames.pdf)
+pub fn rotation_minimizing_frames(path: &[Vec3]) -> Vec<Cartesian3D> {
+    let n = path.len();
+    if n < 2 {
+        return Vec::new();
+    }
+    let tangents: Vec<Vec3> = (0..n)
+        .map(|i| (path[(i + 1).min(n - 1)] - path[i.saturating_sub(1)]).normalize())
+        .collect();
+
+    let mut x_axis = perpendicular_axes(&tangents[0]).0;
+    let mut frames = Vec::new();
+    for i in 0..n {
+        let t = tangents[i];
+        frames.push(Cartesian3D::new(x_axis, t.cross(&x_axis), t, path[i]));
+        if i + 1 == n {
+            break;
+        }
+
+        let v1 = path[i + 1] - path[i];
+        let c1 = v1.dot(&v1);
+        let x_l = x_axis - v1 * (2.0 / c1 * v1.dot(&x_axis));
+        let t_l = t - v1 * (2.0 / c1 * v1.dot(&t));
+        let v2 = tangents[i + 1] - t_l;
+        let c2 = v2.dot(&v2);
+        x_axis = if c2 > Real::EPSILON {
+            x_l - v2 * (2.0 / c2 * v2.dot(&x_l))
+        } else {
+            x_l
+        };
+    }
+    frames
+}
+
+/// solid made by moving `profile` along `path` in its rotation minimizing frames, ends are capped.
+/// `smooth` shades the profile as a smooth curve instead of a polygon.
+/// the path should bend slowly compared to profile size, or the surface folds. empty for less than 2 path points
+/// or 3 profile points
+pub fn sweep_to_display_data(profile: &geom2d::Polygon, path: &[Vec3], smooth: bool, color: Vec4) -> FaceDisplayData {
+    if path.len() < 2 || profile.points.len() < 3 {
+        return empty_display_data(color);
+    }
+    let ring = oriented_pts(&profile.points, true);
+    let frames = rotation_minimizing_frames(path);
+    let place = |frame: &Cartesian3D| -> Vec<Vec3> {
+        ring.iter()
+            .map(|p| frame.transform(Vec3::from_xyz(p.x(), p.y(), 0.0)))
+            .collect()
+    };
+
+    let layers: Vec<Vec<Vec3>> = frames.iter().map(place).collect();
+    let mut data = loft_to_display_data(&layers, true, smooth, false, color);
+    let triangles = ear_clip(&ring, &[]);
+    let (first, last) = (&frames[0], &frames[frames.len() - 1]);
+    data.append(planar_to_display_data(place(first), &triangles, first.y_axis(), first.x_axis(), color));
+    data.append(planar_to_display_data(place(last), &triangles, last.x_axis(), last.y_axis(), color));
+    data
+}
+
+/// round tube of `radius` along `path`
+pub fn tube_to_display_data(path: &[Vec3], radius: Real, color: Vec4, slice: u32) -> FaceDisplayData {
+    let circle = origin_circle_to_display_data(radius, color, slice);
+    let profile = geom2d::Polygon::new(circle.vertices.iter().map(|v| Vec2::from_xy(v.x(), v.y())).collect());
+    sweep_to_display_data(&profile, path, true, color)
+}
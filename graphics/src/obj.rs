@@ -0,0 +1,391 @@
+//! [Wavefront OBJ](https://paulbourke.net/dataformats/obj/) and MTL reader/writer for `FaceDisplayData`
+//!
+//! supports positions, normals, uvs, polygon faces(triangulated on import), groups/objects and diffuse colors of materials.
+//! every group uses one material, a `usemtl` inside a group starts a new group with the same name.
+//! statements other than those are ignored.
+
+use std::collections::HashMap;
+use std::fmt::{self, Write as _};
+use std::path::Path;
+use std::{fs, io};
+
+use crate::mesh_generate::{triangulate_polygon, FaceDisplayData};
+use math::matrix::*;
+use math::precision::Real;
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io(io::Error),
+    /// a number can't be parsed, `line` starts from 1
+    InvalidNumber { line: usize },
+    /// a statement has fewer values than required
+    MissingValue { line: usize },
+    /// a face refers to an element which doesn't exist
+    IndexOutOfRange { line: usize, index: i64 },
+    /// a face has fewer than 3 vertices or a malformed vertex
+    InvalidFace { line: usize },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ObjError::Io(e) => write!(f, "io error: {}", e),
+            ObjError::InvalidNumber { line } => write!(f, "invalid number at line {}", line),
+            ObjError::MissingValue { line } => write!(f, "missing value at line {}", line),
+            ObjError::IndexOutOfRange { line, index } => write!(f, "index {} out of range at line {}", index, line),
+            ObjError::InvalidFace { line } => write!(f, "invalid face at line {}", line),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+impl From<io::Error> for ObjError {
+    fn from(e: io::Error) -> Self {
+        ObjError::Io(e)
+    }
+}
+
+pub struct ObjGroup {
+    pub name: String,
+    pub material: Option<String>,
+    /// color is the diffuse color of material, white if there is none
+    pub data: FaceDisplayData,
+}
+
+#[derive(Default)]
+pub struct ObjModel {
+    pub groups: Vec<ObjGroup>,
+}
+
+/// `Kd` is rgb, `d`(or `1 - Tr`) is alpha
+pub fn parse_mtl(src: &str) -> Result<HashMap<String, Vec4>, ObjError> {
+    let mut materials = HashMap::new();
+    let mut current: Option<String> = None;
+    for (i, line) in src.lines().enumerate() {
+        let line_no = i + 1;
+        let mut tokens = statement(line);
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+        match keyword {
+            "newmtl" => {
+                let name = rest(line, keyword).ok_or(ObjError::MissingValue { line: line_no })?;
+                materials.insert(name.to_string(), Vec4::ones());
+                current = Some(name.to_string());
+            }
+            "Kd" | "d" | "Tr" => {
+                let Some(color) = current.as_ref().and_then(|name| materials.get_mut(name)) else {
+                    continue;
+                };
+                let values = parse_reals(tokens, line_no)?;
+                match (keyword, values.as_slice()) {
+                    ("Kd", [r, g, b, ..]) => *color = Vec4::from_xyzw(*r, *g, *b, color.w()),
+                    ("d", [d, ..]) => color[3] = *d,
+                    ("Tr", [tr, ..]) => color[3] = 1.0 - *tr,
+                    _ => return Err(ObjError::MissingValue { line: line_no }),
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(materials)
+}
+
+/// `materials` maps names used by `usemtl` to colors, unknown names get white
+pub fn parse_obj(src: &str, materials: &HashMap<String, Vec4>) -> Result<ObjModel, ObjError> {
+    let mut positions: Vec<Vec3> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut uvs: Vec<Vec2> = Vec::new();
+    let mut model = ObjModel::default();
+    let mut group = GroupBuilder::new("default".to_string(), None);
+
+    for (i, line) in src.lines().enumerate() {
+        let line_no = i + 1;
+        let mut tokens = statement(line);
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+        match keyword {
+            "v" | "vn" => {
+                let values = parse_reals(tokens, line_no)?;
+                let [x, y, z, ..] = values[..] else {
+                    return Err(ObjError::MissingValue { line: line_no });
+                };
+                let v = Vec3::from_xyz(x, y, z);
+                if keyword == "v" {
+                    positions.push(v);
+                } else {
+                    normals.push(v.normalize());
+                }
+            }
+            "vt" => {
+                let values = parse_reals(tokens, line_no)?;
+                let [u, v, ..] = values[..] else {
+                    return Err(ObjError::MissingValue { line: line_no });
+                };
+                uvs.push(Vec2::from_xy(u, v));
+            }
+            "f" => {
+                let counts = [positions.len(), uvs.len(), normals.len()];
+                let corners = tokens
+                    .map(|t| parse_face_vertex(t, counts, line_no))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if corners.len() < 3 {
+                    return Err(ObjError::InvalidFace { line: line_no });
+                }
+                group.add_face(&corners, &positions, &uvs, &normals);
+            }
+            "g" | "o" => {
+                let name = rest(line, keyword).unwrap_or("default").to_string();
+                let material = group.material.clone();
+                let previous = std::mem::replace(&mut group, GroupBuilder::new(name, material));
+                previous.finish(materials, &mut model);
+            }
+            "usemtl" => {
+                let material = rest(line, keyword).ok_or(ObjError::MissingValue { line: line_no })?;
+                let name = group.name.clone();
+                let previous = std::mem::replace(&mut group, GroupBuilder::new(name, Some(material.to_string())));
+                previous.finish(materials, &mut model);
+            }
+            _ => {}
+        }
+    }
+    group.finish(materials, &mut model);
+    Ok(model)
+}
+
+/// load `filename` and material libraries it refers to, which are relative to it
+pub fn read_obj(filename: &str) -> Result<ObjModel, ObjError> {
+    let src = fs::read_to_string(filename)?;
+    let dir = Path::new(filename).parent().unwrap_or(Path::new(""));
+    let mut materials = HashMap::new();
+    for line in src.lines() {
+        if statement(line).next() == Some("mtllib") {
+            for lib in statement(line).skip(1) {
+                materials.extend(parse_mtl(&fs::read_to_string(dir.join(lib))?)?);
+            }
+        }
+    }
+    parse_obj(&src, &materials)
+}
+
+impl ObjModel {
+    /// one material per group named after it, `mtllib` is written if given
+    pub fn to_obj(&self, mtllib: Option<&str>) -> String {
+        let mut out = String::new();
+        if let Some(lib) = mtllib {
+            writeln!(out, "mtllib {}", lib).unwrap();
+        }
+        let (mut v_base, mut vt_base) = (1, 1);
+        for (i, group) in self.groups.iter().enumerate() {
+            let data = &group.data;
+            writeln!(out, "g {}", group.name).unwrap();
+            writeln!(out, "usemtl {}", self.material_name(i)).unwrap();
+            for v in &data.vertices {
+                writeln!(out, "v {} {} {}", v.x(), v.y(), v.z()).unwrap();
+            }
+            if let Some(uvs) = &data.uvs {
+                for uv in uvs {
+                    writeln!(out, "vt {} {}", uv.x(), uv.y()).unwrap();
+                }
+            }
+            for n in &data.normals {
+                writeln!(out, "vn {} {} {}", n.x(), n.y(), n.z()).unwrap();
+            }
+            for t in data.indices.chunks(3) {
+                write!(out, "f").unwrap();
+                for i in t {
+                    // positions and normals share indices
+                    match data.uvs {
+                        Some(_) => write!(out, " {0}/{1}/{0}", v_base + i, vt_base + i).unwrap(),
+                        None => write!(out, " {0}//{0}", v_base + i).unwrap(),
+                    }
+                }
+                writeln!(out).unwrap();
+            }
+            v_base += data.vertices.len() as u32;
+            vt_base += data.uvs.as_ref().map_or(0, |uvs| uvs.len() as u32);
+        }
+        out
+    }
+
+    pub fn to_mtl(&self) -> String {
+        let mut out = String::new();
+        for (i, group) in self.groups.iter().enumerate() {
+            let c = group.data.color;
+            writeln!(out, "newmtl {}", self.material_name(i)).unwrap();
+            writeln!(out, "Kd {} {} {}", c.x(), c.y(), c.z()).unwrap();
+            writeln!(out, "d {}", c.w()).unwrap();
+        }
+        out
+    }
+
+    /// write `filename` and the material library next to it with extension `mtl`
+    pub fn write_to_file(&self, filename: &str) -> Result<(), io::Error> {
+        let mtl_path = Path::new(filename).with_extension("mtl");
+        // mtl file is next to obj file, so the name alone is enough
+        let mtl_name = mtl_path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        fs::write(filename, self.to_obj(Some(&mtl_name)))?;
+        fs::write(mtl_path, self.to_mtl())
+    }
+
+    /// groups sharing a material name but not color get distinct names
+    fn material_name(&self, i: usize) -> String {
+        let group = &self.groups[i];
+        match &group.material {
+            Some(name) => {
+                let clash = self.groups[..i]
+                    .iter()
+                    .any(|g| g.material.as_ref() == Some(name) && g.data.color != group.data.color);
+                if clash {
+                    format!("{}_{}", name, i)
+                } else {
+                    name.clone()
+                }
+            }
+            None => format!("{}_{}", group.name, i),
+        }
+    }
+}
+
+/// tokens of a line without comment
+fn statement(line: &str) -> std::str::SplitWhitespace<'_> {
+    line.split('#').next().unwrap_or("").split_whitespace()
+}
+
+/// text after keyword, names may contain spaces
+fn rest<'a>(line: &'a str, keyword: &str) -> Option<&'a str> {
+    let text = line.split('#').next().unwrap_or("").trim_start();
+    let text = text[keyword.len()..].trim();
+    (!text.is_empty()).then_some(text)
+}
+
+fn parse_reals<'a>(tokens: impl Iterator<Item = &'a str>, line: usize) -> Result<Vec<Real>, ObjError> {
+    tokens
+        .map(|t| t.parse::<Real>().map_err(|_| ObjError::InvalidNumber { line }))
+        .collect()
+}
+
+/// `v`, `v/vt`, `v//vn` or `v/vt/vn`, returns 0-based indices. negative index counts from the last element
+fn parse_face_vertex(
+    token: &str,
+    counts: [usize; 3],
+    line: usize,
+) -> Result<(usize, Option<usize>, Option<usize>), ObjError> {
+    let parts: Vec<&str> = token.split('/').collect();
+    if parts.len() > 3 || parts[0].is_empty() {
+        return Err(ObjError::InvalidFace { line });
+    }
+    let mut indices = [None; 3];
+    for (k, part) in parts.iter().enumerate() {
+        if part.is_empty() {
+            continue;
+        }
+        let index: i64 = part.parse().map_err(|_| ObjError::InvalidNumber { line })?;
+        let count = counts[k] as i64;
+        let resolved = if index > 0 { index - 1 } else { count + index };
+        if index == 0 || resolved < 0 || resolved >= count {
+            return Err(ObjError::IndexOutOfRange { line, index });
+        }
+        indices[k] = Some(resolved as usize);
+    }
+    Ok((indices[0].unwrap(), indices[1], indices[2]))
+}
+
+struct GroupBuilder {
+    name: String,
+    material: Option<String>,
+    vertices: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<Option<Vec2>>,
+    indices: Vec<u32>,
+    /// vertices are shared by corners with the same (position, uv, normal)
+    shared: HashMap<(usize, Option<usize>, usize), u32>,
+}
+
+impl GroupBuilder {
+    fn new(name: String, material: Option<String>) -> Self {
+        Self {
+            name,
+            material,
+            vertices: Vec::new(),
+            normals: Vec::new(),
+            uvs: Vec::new(),
+            indices: Vec::new(),
+            shared: HashMap::new(),
+        }
+    }
+
+    fn add_face(
+        &mut self,
+        corners: &[(usize, Option<usize>, Option<usize>)],
+        positions: &[Vec3],
+        uvs: &[Vec2],
+        normals: &[Vec3],
+    ) {
+        let pts: Vec<Vec3> = corners.iter().map(|c| positions[c.0]).collect();
+        // a face without area is kept, corners without normal get any one
+        let (face_normal, triangles) = triangulate_polygon(&pts)
+            .unwrap_or_else(|| (Vec3::z_axis(), (1..pts.len() - 1).map(|i| [0, i, i + 1]).collect()));
+
+        let ids: Vec<u32> = corners
+            .iter()
+            .map(|(v, vt, vn)| {
+                let mut push = |normal: Vec3| {
+                    self.vertices.push(positions[*v]);
+                    self.normals.push(normal);
+                    self.uvs.push(vt.map(|t| uvs[t]));
+                    self.vertices.len() as u32 - 1
+                };
+                match vn {
+                    Some(vn) => match self.shared.get(&(*v, *vt, *vn)) {
+                        Some(id) => *id,
+                        None => {
+                            let id = push(normals[*vn]);
+                            self.shared.insert((*v, *vt, *vn), id);
+                            id
+                        }
+                    },
+                    // flat shaded, not shared with other faces
+                    None => push(face_normal),
+                }
+            })
+            .collect();
+
+        for t in triangles {
+            self.indices.extend(t.iter().map(|i| ids[*i]));
+        }
+    }
+
+    /// groups without faces are dropped
+    fn finish(self, materials: &HashMap<String, Vec4>, model: &mut ObjModel) {
+        if self.indices.is_empty() {
+            return;
+        }
+        let color = self
+            .material
+            .as_ref()
+            .and_then(|m| materials.get(m))
+            .copied()
+            .unwrap_or(Vec4::ones());
+        let data = if self.uvs.iter().all(|uv| uv.is_some()) {
+            let uvs = self.uvs.into_iter().flatten().collect();
+            FaceDisplayData::new(self.vertices, self.normals, uvs, self.indices, color)
+        } else {
+            FaceDisplayData {
+                vertices: self.vertices,
+                normals: self.normals,
+                indices: self.indices,
+                color,
+                uvs: None,
+                tangents: None,
+            }
+        };
+        model.groups.push(ObjGroup {
+            name: self.name,
+            material: self.material,
+            data,
+        });
+    }
+}
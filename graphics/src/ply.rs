@@ -0,0 +1,526 @@
+//! [PLY](https://paulbourke.net/dataformats/ply/) reader/writer, ASCII and binary in both byte orders
+//!
+//! `vertex` and `face` elements map onto `FaceDisplayData`, other elements are skipped.
+//! vertices are never duplicated so every extra per-vertex property(colors, confidence...) stays aligned with them.
+
+use std::fmt::{self, Write as _};
+use std::{fs, io};
+
+use crate::mesh_generate::{triangulate_polygon, FaceDisplayData};
+use math::matrix::*;
+use math::precision::Real;
+
+#[derive(Debug)]
+pub enum PlyError {
+    Io(io::Error),
+    /// malformed header, `line` starts from 1
+    InvalidHeader { line: usize },
+    /// a required vertex property like `x` is not declared
+    MissingProperty { name: String },
+    /// an ASCII value can't be parsed or a count/index is not a non-negative integer
+    InvalidNumber,
+    /// data ends before all declared elements are read
+    UnexpectedEof,
+    /// a face refers to a vertex which doesn't exist
+    IndexOutOfRange { index: i64 },
+    /// a face has fewer than 3 vertices
+    InvalidFace,
+}
+
+impl fmt::Display for PlyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlyError::Io(e) => write!(f, "io error: {}", e),
+            PlyError::InvalidHeader { line } => write!(f, "invalid header at line {}", line),
+            PlyError::MissingProperty { name } => write!(f, "missing vertex property {}", name),
+            PlyError::InvalidNumber => write!(f, "invalid number"),
+            PlyError::UnexpectedEof => write!(f, "unexpected end of data"),
+            PlyError::IndexOutOfRange { index } => write!(f, "vertex index {} out of range", index),
+            PlyError::InvalidFace => write!(f, "face with fewer than 3 vertices"),
+        }
+    }
+}
+
+impl std::error::Error for PlyError {}
+
+impl From<io::Error> for PlyError {
+    fn from(e: io::Error) -> Self {
+        PlyError::Io(e)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlyFormat {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+impl PlyFormat {
+    fn name(self) -> &'static str {
+        match self {
+            PlyFormat::Ascii => "ascii",
+            PlyFormat::BinaryLittleEndian => "binary_little_endian",
+            PlyFormat::BinaryBigEndian => "binary_big_endian",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlyType {
+    Char,
+    UChar,
+    Short,
+    UShort,
+    Int,
+    UInt,
+    Float,
+    Double,
+}
+
+impl PlyType {
+    /// also accepts the sized names like `uint8` and `float32`
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "char" | "int8" => Some(PlyType::Char),
+            "uchar" | "uint8" => Some(PlyType::UChar),
+            "short" | "int16" => Some(PlyType::Short),
+            "ushort" | "uint16" => Some(PlyType::UShort),
+            "int" | "int32" => Some(PlyType::Int),
+            "uint" | "uint32" => Some(PlyType::UInt),
+            "float" | "float32" => Some(PlyType::Float),
+            "double" | "float64" => Some(PlyType::Double),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            PlyType::Char => "char",
+            PlyType::UChar => "uchar",
+            PlyType::Short => "short",
+            PlyType::UShort => "ushort",
+            PlyType::Int => "int",
+            PlyType::UInt => "uint",
+            PlyType::Float => "float",
+            PlyType::Double => "double",
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            PlyType::Char | PlyType::UChar => 1,
+            PlyType::Short | PlyType::UShort => 2,
+            PlyType::Int | PlyType::UInt | PlyType::Float => 4,
+            PlyType::Double => 8,
+        }
+    }
+}
+
+/// per-vertex scalar property other than position, normal and uv
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlyProperty {
+    pub name: String,
+    pub ty: PlyType,
+    /// one value per vertex
+    pub values: Vec<Real>,
+}
+
+pub struct PlyMesh {
+    /// normals are smoothed from faces if the file has none, color is white
+    pub data: FaceDisplayData,
+    pub properties: Vec<PlyProperty>,
+}
+
+impl PlyMesh {
+    pub fn property(&self, name: &str) -> Option<&PlyProperty> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+
+    /// `red`, `green`, `blue` and optional `alpha` in `[0, 1]`, integer channels are divided by their max value
+    pub fn vertex_colors(&self) -> Option<Vec<Vec4>> {
+        let channel = |name: &str| {
+            self.property(name).map(|p| {
+                let scale = match p.ty {
+                    PlyType::UChar => 255.0,
+                    PlyType::UShort => 65535.0,
+                    _ => 1.0,
+                };
+                p.values.iter().map(|v| v / scale).collect::<Vec<Real>>()
+            })
+        };
+        let (r, g, b) = (channel("red")?, channel("green")?, channel("blue")?);
+        let a = channel("alpha");
+        Some(
+            (0..self.data.vertices.len())
+                .map(|i| Vec4::from_xyzw(r[i], g[i], b[i], a.as_ref().map_or(1.0, |a| a[i])))
+                .collect(),
+        )
+    }
+
+    /// positions, normals and uvs are written as doubles, faces as `uchar` counts and `int` indices.
+    /// every property must have one value per vertex
+    pub fn to_bytes(&self, format: PlyFormat) -> Vec<u8> {
+        let data = &self.data;
+        let has_normals = data.normals.len() == data.vertices.len();
+        let mut header = String::new();
+        writeln!(header, "ply").unwrap();
+        writeln!(header, "format {} 1.0", format.name()).unwrap();
+        writeln!(header, "element vertex {}", data.vertices.len()).unwrap();
+        let mut names = vec!["x", "y", "z"];
+        if has_normals {
+            names.extend(["nx", "ny", "nz"]);
+        }
+        if data.uvs.is_some() {
+            names.extend(["s", "t"]);
+        }
+        for name in names {
+            writeln!(header, "property double {}", name).unwrap();
+        }
+        for p in &self.properties {
+            writeln!(header, "property {} {}", p.ty.name(), p.name).unwrap();
+        }
+        writeln!(header, "element face {}", data.indices.len() / 3).unwrap();
+        writeln!(header, "property list uchar int vertex_indices").unwrap();
+        writeln!(header, "end_header").unwrap();
+
+        let mut out = header.into_bytes();
+        for i in 0..data.vertices.len() {
+            let mut values: Vec<(PlyType, Real)> = Vec::new();
+            let mut push = |v: Vec<Real>| values.extend(v.into_iter().map(|v| (PlyType::Double, v)));
+            let p = data.vertices[i];
+            push(vec![p.x(), p.y(), p.z()]);
+            if has_normals {
+                let n = data.normals[i];
+                push(vec![n.x(), n.y(), n.z()]);
+            }
+            if let Some(uvs) = &data.uvs {
+                push(vec![uvs[i].x(), uvs[i].y()]);
+            }
+            values.extend(self.properties.iter().map(|p| (p.ty, p.values[i])));
+            write_element(&mut out, format, &values);
+        }
+        for t in data.indices.chunks(3) {
+            let mut values = vec![(PlyType::UChar, 3.0)];
+            values.extend(t.iter().map(|i| (PlyType::Int, *i as Real)));
+            write_element(&mut out, format, &values);
+        }
+        out
+    }
+
+    pub fn write_to_file(&self, filename: &str, format: PlyFormat) -> Result<(), io::Error> {
+        fs::write(filename, self.to_bytes(format))
+    }
+}
+
+impl From<FaceDisplayData> for PlyMesh {
+    fn from(data: FaceDisplayData) -> Self {
+        PlyMesh {
+            data,
+            properties: Vec::new(),
+        }
+    }
+}
+
+enum PropertyKind {
+    Scalar(PlyType),
+    List(PlyType, PlyType),
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<(String, PropertyKind)>,
+}
+
+struct Header {
+    format: PlyFormat,
+    elements: Vec<Element>,
+    /// byte offset where data starts
+    body: usize,
+}
+
+fn parse_header(bytes: &[u8]) -> Result<Header, PlyError> {
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    let mut pos = 0;
+    let mut line_no = 0;
+    loop {
+        let end = bytes[pos..]
+            .iter()
+            .position(|b| *b == b'\n')
+            .map(|i| pos + i)
+            .ok_or(PlyError::UnexpectedEof)?;
+        let line = String::from_utf8_lossy(&bytes[pos..end]);
+        pos = end + 1;
+        line_no += 1;
+
+        let invalid = PlyError::InvalidHeader { line: line_no };
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if line_no == 1 {
+            if tokens != ["ply"] {
+                return Err(invalid);
+            }
+            continue;
+        }
+        match tokens.as_slice() {
+            ["format", name, _version] => {
+                format = Some(match *name {
+                    "ascii" => PlyFormat::Ascii,
+                    "binary_little_endian" => PlyFormat::BinaryLittleEndian,
+                    "binary_big_endian" => PlyFormat::BinaryBigEndian,
+                    _ => return Err(invalid),
+                });
+            }
+            // a second vertex element would make its columns misaligned with the first one
+            ["element", "vertex", _] if elements.iter().any(|e| e.name == "vertex") => return Err(invalid),
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count.parse().map_err(|_| invalid)?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count_ty, item_ty, name] => {
+                let kind = match (PlyType::from_name(count_ty), PlyType::from_name(item_ty)) {
+                    (Some(c), Some(i)) => PropertyKind::List(c, i),
+                    _ => return Err(invalid),
+                };
+                elements.last_mut().ok_or(invalid)?.properties.push((name.to_string(), kind));
+            }
+            ["property", ty, name] => {
+                let ty = PlyType::from_name(ty).ok_or(PlyError::InvalidHeader { line: line_no })?;
+                let kind = PropertyKind::Scalar(ty);
+                elements.last_mut().ok_or(invalid)?.properties.push((name.to_string(), kind));
+            }
+            ["end_header"] => break,
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            _ => return Err(invalid),
+        }
+    }
+    Ok(Header {
+        format: format.ok_or(PlyError::InvalidHeader { line: line_no })?,
+        elements,
+        body: pos,
+    })
+}
+
+enum Reader<'a> {
+    Ascii(std::str::SplitAsciiWhitespace<'a>),
+    Binary { bytes: &'a [u8], pos: usize, big_endian: bool },
+}
+
+impl Reader<'_> {
+    fn read(&mut self, ty: PlyType) -> Result<Real, PlyError> {
+        match self {
+            Reader::Ascii(tokens) => tokens
+                .next()
+                .ok_or(PlyError::UnexpectedEof)?
+                .parse()
+                .map_err(|_| PlyError::InvalidNumber),
+            Reader::Binary { bytes, pos, big_endian } => {
+                let size = ty.size();
+                let src = bytes.get(*pos..*pos + size).ok_or(PlyError::UnexpectedEof)?;
+                *pos += size;
+                // decode everything as little endian
+                let mut raw = [0u8; 8];
+                raw[..size].copy_from_slice(src);
+                if *big_endian {
+                    raw[..size].reverse();
+                }
+                let [b0, b1, b2, b3, ..] = raw;
+                Ok(match ty {
+                    PlyType::Char => b0 as i8 as Real,
+                    PlyType::UChar => b0 as Real,
+                    PlyType::Short => i16::from_le_bytes([b0, b1]) as Real,
+                    PlyType::UShort => u16::from_le_bytes([b0, b1]) as Real,
+                    PlyType::Int => i32::from_le_bytes([b0, b1, b2, b3]) as Real,
+                    PlyType::UInt => u32::from_le_bytes([b0, b1, b2, b3]) as Real,
+                    PlyType::Float => f32::from_le_bytes([b0, b1, b2, b3]) as Real,
+                    PlyType::Double => f64::from_le_bytes(raw) as Real,
+                })
+            }
+        }
+    }
+
+    fn read_index(&mut self, ty: PlyType) -> Result<usize, PlyError> {
+        let v = self.read(ty)?;
+        if v < 0.0 || v.fract() != 0.0 {
+            return Err(PlyError::InvalidNumber);
+        }
+        Ok(v as usize)
+    }
+
+    fn read_list(&mut self, count_ty: PlyType, item_ty: PlyType) -> Result<Vec<Real>, PlyError> {
+        let count = self.read_index(count_ty)?;
+        (0..count).map(|_| self.read(item_ty)).collect()
+    }
+}
+
+fn write_element(out: &mut Vec<u8>, format: PlyFormat, values: &[(PlyType, Real)]) {
+    if format == PlyFormat::Ascii {
+        let line: Vec<String> = values
+            .iter()
+            .map(|(ty, v)| match ty {
+                PlyType::Float => (*v as f32).to_string(),
+                PlyType::Double => v.to_string(),
+                _ => (v.round() as i64).to_string(),
+            })
+            .collect();
+        out.extend(line.join(" ").bytes());
+        out.push(b'\n');
+        return;
+    }
+
+    for (ty, v) in values {
+        let mut raw = match ty {
+            PlyType::Char => (*v as i8).to_le_bytes().to_vec(),
+            PlyType::UChar => (*v as u8).to_le_bytes().to_vec(),
+            PlyType::Short => (*v as i16).to_le_bytes().to_vec(),
+            PlyType::UShort => (*v as u16).to_le_bytes().to_vec(),
+            PlyType::Int => (*v as i32).to_le_bytes().to_vec(),
+            PlyType::UInt => (*v as u32).to_le_bytes().to_vec(),
+            PlyType::Float => (*v as f32).to_le_bytes().to_vec(),
+            PlyType::Double => v.to_le_bytes().to_vec(),
+        };
+        if format == PlyFormat::BinaryBigEndian {
+            raw.reverse();
+        }
+        out.extend(raw);
+    }
+}
+
+/// polygon faces are triangulated
+pub fn parse_ply(bytes: &[u8]) -> Result<PlyMesh, PlyError> {
+    let header = parse_header(bytes)?;
+    let body = &bytes[header.body..];
+    let mut reader = match header.format {
+        PlyFormat::Ascii => {
+            let text = std::str::from_utf8(body).map_err(|_| PlyError::InvalidNumber)?;
+            Reader::Ascii(text.split_ascii_whitespace())
+        }
+        format => Reader::Binary {
+            bytes: body,
+            pos: 0,
+            big_endian: format == PlyFormat::BinaryBigEndian,
+        },
+    };
+
+    let mut columns: Vec<(String, PlyType, Vec<Real>)> = Vec::new();
+    let mut faces: Vec<Vec<Real>> = Vec::new();
+    for element in &header.elements {
+        let is_vertex = element.name == "vertex";
+        let is_face = element.name == "face";
+        if is_vertex {
+            for (name, kind) in &element.properties {
+                if let PropertyKind::Scalar(ty) = kind {
+                    columns.push((name.clone(), *ty, Vec::new()));
+                }
+            }
+        }
+        for _ in 0..element.count {
+            let mut column = 0;
+            for (name, kind) in &element.properties {
+                match kind {
+                    PropertyKind::Scalar(ty) => {
+                        let v = reader.read(*ty)?;
+                        if is_vertex {
+                            columns[column].2.push(v);
+                            column += 1;
+                        }
+                    }
+                    PropertyKind::List(count_ty, item_ty) => {
+                        let list = reader.read_list(*count_ty, *item_ty)?;
+                        if is_face && (name == "vertex_indices" || name == "vertex_index") {
+                            faces.push(list);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(name) = ["x", "y", "z"].iter().find(|n| !columns.iter().any(|c| c.0 == **n)) {
+        return Err(PlyError::MissingProperty { name: name.to_string() });
+    }
+    let xyz = take_columns(&mut columns, &["x", "y", "z"]).unwrap();
+    let vertices: Vec<Vec3> = (0..xyz[0].len())
+        .map(|i| Vec3::from_xyz(xyz[0][i], xyz[1][i], xyz[2][i]))
+        .collect();
+    let normals = take_columns(&mut columns, &["nx", "ny", "nz"])
+        .map(|n| (0..vertices.len()).map(|i| Vec3::from_xyz(n[0][i], n[1][i], n[2][i])).collect::<Vec<_>>());
+    let uvs = [["s", "t"], ["u", "v"], ["texture_u", "texture_v"]]
+        .iter()
+        .find_map(|names| take_columns(&mut columns, names))
+        .map(|uv| (0..vertices.len()).map(|i| Vec2::from_xy(uv[0][i], uv[1][i])).collect::<Vec<_>>());
+
+    let mut indices: Vec<u32> = Vec::new();
+    for face in &faces {
+        let mut corners = Vec::with_capacity(face.len());
+        for v in face {
+            if *v < 0.0 || *v as usize >= vertices.len() || v.fract() != 0.0 {
+                return Err(PlyError::IndexOutOfRange { index: *v as i64 });
+            }
+            corners.push(*v as usize);
+        }
+        if corners.len() < 3 {
+            return Err(PlyError::InvalidFace);
+        }
+        let points: Vec<Vec3> = corners.iter().map(|i| vertices[*i]).collect();
+        let triangles = triangulate_polygon(&points)
+            .map(|(_, t)| t)
+            .unwrap_or_else(|| (1..corners.len() - 1).map(|i| [0, i, i + 1]).collect());
+        for t in triangles {
+            indices.extend(t.iter().map(|i| corners[*i] as u32));
+        }
+    }
+
+    let normals = normals.unwrap_or_else(|| smooth_normals(&vertices, &indices));
+    let data = FaceDisplayData {
+        vertices,
+        normals,
+        indices,
+        color: Vec4::ones(),
+        uvs,
+        tangents: None,
+    };
+    let properties = columns
+        .into_iter()
+        .map(|(name, ty, values)| PlyProperty { name, ty, values })
+        .collect();
+    Ok(PlyMesh { data, properties })
+}
+
+pub fn read_ply(filename: &str) -> Result<PlyMesh, PlyError> {
+    parse_ply(&fs::read(filename)?)
+}
+
+/// remove the columns of `names` if all of them exist
+fn take_columns(columns: &mut Vec<(String, PlyType, Vec<Real>)>, names: &[&str]) -> Option<Vec<Vec<Real>>> {
+    if !names.iter().all(|n| columns.iter().any(|c| c.0 == *n)) {
+        return None;
+    }
+    Some(
+        names
+            .iter()
+            .map(|n| {
+                let i = columns.iter().position(|c| c.0 == *n).unwrap();
+                columns.remove(i).2
+            })
+            .collect(),
+    )
+}
+
+/// area weighted face normals accumulated at vertices
+fn smooth_normals(vertices: &[Vec3], indices: &[u32]) -> Vec<Vec3> {
+    let mut normals = vec![Vec3::zeros(); vertices.len()];
+    for t in indices.chunks(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| t[i] as usize);
+        let normal = (vertices[b] - vertices[a]).cross(&(vertices[c] - vertices[a]));
+        for i in [a, b, c] {
+            normals[i] += normal;
+        }
+    }
+    normals
+        .into_iter()
+        .map(|n| if n.length_sqrd() > 0.0 { n.normalize() } else { n })
+        .collect()
+}
@@ -0,0 +1,376 @@
+//! [PNG](https://www.w3.org/TR/png/) encoder and decoder backed by 8 bit RGBA images
+//!
+//! the encoder writes 8 bit RGB, or RGBA if any pixel is not opaque. the decoder reads every standard color type and bit
+//! depth, interlaced or not; 16 bit samples are truncated to 8 bits and `tRNS` becomes alpha.
+
+use std::{fmt, fs, io};
+
+use crate::deflate::{zlib_compress, zlib_decompress, Compression};
+use crate::image::Image;
+
+#[derive(Debug)]
+pub enum PngError {
+    Io(io::Error),
+    /// data doesn't start with the PNG signature
+    InvalidSignature,
+    /// a chunk is truncated or its CRC doesn't match
+    InvalidChunk,
+    /// `IHDR` is missing, not first or has invalid values
+    InvalidHeader,
+    /// a valid but not supported feature, like an unknown critical chunk
+    Unsupported(&'static str),
+    /// image data can't be decompressed or doesn't match the header
+    InvalidData(&'static str),
+}
+
+impl fmt::Display for PngError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PngError::Io(e) => write!(f, "io error: {}", e),
+            PngError::InvalidSignature => write!(f, "not a PNG file"),
+            PngError::InvalidChunk => write!(f, "truncated or corrupted chunk"),
+            PngError::InvalidHeader => write!(f, "invalid IHDR"),
+            PngError::Unsupported(what) => write!(f, "unsupported: {}", what),
+            PngError::InvalidData(what) => write!(f, "invalid image data: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for PngError {}
+
+impl From<io::Error> for PngError {
+    fn from(e: io::Error) -> Self {
+        PngError::Io(e)
+    }
+}
+
+/// filter applied to every scanline before compression
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PngFilter {
+    None,
+    Sub,
+    Up,
+    Average,
+    Paeth,
+    /// per scanline, the filter with the least sum of absolute values
+    Adaptive,
+}
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+const COLOR_GRAY: u8 = 0;
+const COLOR_RGB: u8 = 2;
+const COLOR_PALETTE: u8 = 3;
+const COLOR_GRAY_ALPHA: u8 = 4;
+const COLOR_RGBA: u8 = 6;
+
+/// `(x, y)` offset and step of the 7 Adam7 passes
+const ADAM7: [(usize, usize, usize, usize); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+const CRC_TABLE: [u32; 256] = crc_table();
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 { 0xEDB8_8320 ^ (c >> 1) } else { c >> 1 };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+}
+
+pub fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |c, b| CRC_TABLE[((c ^ *b as u32) & 0xFF) as usize] ^ (c >> 8))
+}
+
+fn push_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = ((p - a as i16).abs(), (p - b as i16).abs(), (p - c as i16).abs());
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// `row` filtered by filter type `kind`, `prev` is the unfiltered previous row(zeros for the first one)
+fn filter_row(kind: u8, row: &[u8], prev: &[u8], bpp: usize, out: &mut Vec<u8>) {
+    out.push(kind);
+    for i in 0..row.len() {
+        let a = if i >= bpp { row[i - bpp] } else { 0 };
+        let c = if i >= bpp { prev[i - bpp] } else { 0 };
+        let b = prev[i];
+        let predict = match kind {
+            0 => 0,
+            1 => a,
+            2 => b,
+            3 => ((a as u16 + b as u16) / 2) as u8,
+            _ => paeth(a, b, c),
+        };
+        out.push(row[i].wrapping_sub(predict));
+    }
+}
+
+/// reverse `filter_row` in place
+fn unfilter_row(kind: u8, row: &mut [u8], prev: &[u8], bpp: usize) -> Result<(), PngError> {
+    if kind > 4 {
+        return Err(PngError::InvalidData("unknown filter type"));
+    }
+    for i in 0..row.len() {
+        let a = if i >= bpp { row[i - bpp] } else { 0 };
+        let c = if i >= bpp { prev[i - bpp] } else { 0 };
+        let b = prev[i];
+        let predict = match kind {
+            0 => 0,
+            1 => a,
+            2 => b,
+            3 => ((a as u16 + b as u16) / 2) as u8,
+            _ => paeth(a, b, c),
+        };
+        row[i] = row[i].wrapping_add(predict);
+    }
+    Ok(())
+}
+
+/// opaque images are written as RGB
+pub fn encode_png(image: &Image<[u8; 4]>, compression: Compression, filter: PngFilter) -> Vec<u8> {
+    let opaque = image.data().iter().all(|p| p[3] == 255);
+    let (color, bpp) = if opaque { (COLOR_RGB, 3) } else { (COLOR_RGBA, 4) };
+
+    let mut header = Vec::with_capacity(13);
+    header.extend((image.width() as u32).to_be_bytes());
+    header.extend((image.height() as u32).to_be_bytes());
+    // bit depth, color type, compression, filter method, no interlace
+    header.extend([8, color, 0, 0, 0]);
+
+    let stride = image.width() * bpp;
+    let mut filtered = Vec::with_capacity((stride + 1) * image.height());
+    let mut prev = vec![0u8; stride];
+    let mut candidate = Vec::with_capacity(stride + 1);
+    for y in 0..image.height() {
+        let row: Vec<u8> = image.row(y).iter().flat_map(|p| p[..bpp].to_vec()).collect();
+        match filter {
+            PngFilter::Adaptive => {
+                let mut best: Option<(u64, Vec<u8>)> = None;
+                for kind in 0..5 {
+                    candidate.clear();
+                    filter_row(kind, &row, &prev, bpp, &mut candidate);
+                    let cost = candidate[1..].iter().map(|b| (*b as i8).unsigned_abs() as u64).sum();
+                    if best.as_ref().is_none_or(|(c, _)| cost < *c) {
+                        best = Some((cost, candidate.clone()));
+                    }
+                }
+                filtered.extend(best.unwrap().1);
+            }
+            _ => filter_row(filter as u8, &row, &prev, bpp, &mut filtered),
+        }
+        prev = row;
+    }
+
+    let mut out = SIGNATURE.to_vec();
+    push_chunk(&mut out, b"IHDR", &header);
+    push_chunk(&mut out, b"IDAT", &zlib_compress(&filtered, compression));
+    push_chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+/// adaptive filters and fixed Huffman codes
+pub fn write_png(image: &Image<[u8; 4]>, filename: &str) -> Result<(), io::Error> {
+    fs::write(filename, encode_png(image, Compression::Fixed, PngFilter::Adaptive))
+}
+
+struct Header {
+    width: usize,
+    height: usize,
+    depth: u8,
+    color: u8,
+    interlaced: bool,
+}
+
+impl Header {
+    fn parse(data: &[u8]) -> Result<Self, PngError> {
+        if data.len() != 13 {
+            return Err(PngError::InvalidHeader);
+        }
+        let width = u32::from_be_bytes(data[0..4].try_into().unwrap()) as usize;
+        let height = u32::from_be_bytes(data[4..8].try_into().unwrap()) as usize;
+        let (depth, color) = (data[8], data[9]);
+        let valid_depth = match color {
+            COLOR_GRAY => matches!(depth, 1 | 2 | 4 | 8 | 16),
+            COLOR_PALETTE => matches!(depth, 1 | 2 | 4 | 8),
+            COLOR_RGB | COLOR_GRAY_ALPHA | COLOR_RGBA => matches!(depth, 8 | 16),
+            _ => false,
+        };
+        if width == 0 || height == 0 || !valid_depth || data[10] != 0 || data[11] != 0 || data[12] > 1 {
+            return Err(PngError::InvalidHeader);
+        }
+        Ok(Self {
+            width,
+            height,
+            depth,
+            color,
+            interlaced: data[12] == 1,
+        })
+    }
+
+    fn channels(&self) -> usize {
+        match self.color {
+            COLOR_RGB => 3,
+            COLOR_GRAY_ALPHA => 2,
+            COLOR_RGBA => 4,
+            _ => 1,
+        }
+    }
+}
+
+/// `index`th sample of an unfiltered scanline
+fn sample(row: &[u8], index: usize, depth: u8) -> u16 {
+    match depth {
+        16 => u16::from_be_bytes([row[index * 2], row[index * 2 + 1]]),
+        8 => row[index] as u16,
+        _ => {
+            let bit = index * depth as usize;
+            let shift = 8 - depth as usize - bit % 8;
+            ((row[bit / 8] >> shift) & ((1 << depth) - 1)) as u16
+        }
+    }
+}
+
+fn to_8bit(v: u16, depth: u8) -> u8 {
+    match depth {
+        16 => (v >> 8) as u8,
+        8 => v as u8,
+        _ => (v as u32 * 255 / ((1 << depth) - 1)) as u8,
+    }
+}
+
+pub fn decode_png(bytes: &[u8]) -> Result<Image<[u8; 4]>, PngError> {
+    if !bytes.starts_with(&SIGNATURE) {
+        return Err(PngError::InvalidSignature);
+    }
+    let mut header: Option<Header> = None;
+    let (mut palette, mut transparency, mut compressed) = (Vec::new(), Vec::new(), Vec::new());
+    let mut pos = SIGNATURE.len();
+    loop {
+        let len = bytes.get(pos..pos + 4).ok_or(PngError::InvalidChunk)?;
+        let len = u32::from_be_bytes(len.try_into().unwrap()) as usize;
+        let chunk = bytes.get(pos + 4..pos + 8 + len).ok_or(PngError::InvalidChunk)?;
+        let crc = bytes.get(pos + 8 + len..pos + 12 + len).ok_or(PngError::InvalidChunk)?;
+        if crc32(chunk) != u32::from_be_bytes(crc.try_into().unwrap()) {
+            return Err(PngError::InvalidChunk);
+        }
+        pos += 12 + len;
+
+        let (kind, data) = chunk.split_at(4);
+        if header.is_none() && kind != b"IHDR" {
+            return Err(PngError::InvalidHeader);
+        }
+        match kind {
+            b"IHDR" if header.is_none() => header = Some(Header::parse(data)?),
+            b"IHDR" => return Err(PngError::InvalidHeader),
+            b"PLTE" => palette = data.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect(),
+            b"tRNS" => transparency = data.to_vec(),
+            b"IDAT" => compressed.extend(data),
+            b"IEND" => break,
+            // ancillary chunks have a lowercase first letter and can be skipped
+            _ if kind[0].is_ascii_lowercase() => {}
+            _ => return Err(PngError::Unsupported("critical chunk")),
+        }
+    }
+    let header = header.ok_or(PngError::InvalidHeader)?;
+    if header.color == COLOR_PALETTE && palette.is_empty() {
+        return Err(PngError::InvalidData("missing palette"));
+    }
+
+    let channels = header.channels();
+    let bits_per_pixel = channels * header.depth as usize;
+    let bpp = bits_per_pixel.div_ceil(8);
+    let passes: &[(usize, usize, usize, usize)] = if header.interlaced { &ADAM7 } else { &[(0, 0, 1, 1)] };
+    // (pass, width, height, stride), empty passes have no scanlines
+    let pass_sizes: Vec<_> = passes
+        .iter()
+        .map(|pass @ (x0, y0, dx, dy)| {
+            let width = (header.width + dx - 1 - x0) / dx;
+            let height = (header.height + dy - 1 - y0) / dy;
+            (*pass, width, height, (width * bits_per_pixel).div_ceil(8))
+        })
+        .filter(|(_, width, height, _)| *width > 0 && *height > 0)
+        .collect();
+    // filter byte and samples of every scanline, checked before anything of image size is allocated
+    let expected = pass_sizes
+        .iter()
+        .try_fold(0usize, |sum, (_, _, height, stride)| sum.checked_add((stride + 1).checked_mul(*height)?))
+        .ok_or(PngError::InvalidHeader)?;
+    let data = zlib_decompress(&compressed, expected).map_err(PngError::InvalidData)?;
+    if data.len() < expected {
+        return Err(PngError::InvalidData("not enough scanlines"));
+    }
+    // sample value of a fully transparent gray or rgb color
+    let key: Option<Vec<u16>> = match header.color {
+        COLOR_GRAY | COLOR_RGB if transparency.len() == channels * 2 => {
+            Some(transparency.chunks(2).map(|c| u16::from_be_bytes([c[0], c[1]])).collect())
+        }
+        _ => None,
+    };
+
+    let mut image = Image::new(header.width, header.height, [0u8; 4]);
+    let mut pos = 0;
+    for ((x0, y0, dx, dy), width, height, stride) in pass_sizes {
+        let mut prev = vec![0u8; stride];
+        for y in 0..height {
+            let kind = data[pos];
+            let mut row = data[pos + 1..pos + 1 + stride].to_vec();
+            pos += 1 + stride;
+            unfilter_row(kind, &mut row, &prev, bpp)?;
+
+            for x in 0..width {
+                let s: Vec<u16> = (0..channels).map(|c| sample(&row, x * channels + c, header.depth)).collect();
+                let v = |c: usize| to_8bit(s[c], header.depth);
+                let opaque = if key.as_ref() == Some(&s) { 0 } else { 255 };
+                let pixel = match header.color {
+                    COLOR_GRAY => [v(0), v(0), v(0), opaque],
+                    COLOR_RGB => [v(0), v(1), v(2), opaque],
+                    COLOR_GRAY_ALPHA => [v(0), v(0), v(0), v(1)],
+                    COLOR_RGBA => [v(0), v(1), v(2), v(3)],
+                    _ => {
+                        let i = s[0] as usize;
+                        let [r, g, b] = *palette.get(i).ok_or(PngError::InvalidData("palette index out of range"))?;
+                        [r, g, b, transparency.get(i).copied().unwrap_or(255)]
+                    }
+                };
+                image[(x0 + x * dx, y0 + y * dy)] = pixel;
+            }
+            prev = row;
+        }
+    }
+    Ok(image)
+}
+
+pub fn read_png(filename: &str) -> Result<Image<[u8; 4]>, PngError> {
+    decode_png(&fs::read(filename)?)
+}
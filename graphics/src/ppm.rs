@@ -1,47 +1,406 @@
+//! [Netpbm](https://netpbm.sourceforge.net/doc/) images: PBM(P1/P4), PGM(P2/P5) and PPM(P3/P6)
+//!
+//! samples are normalized to `[0, 1]` on read, maxval up to 65535 is supported(2 bytes big endian per binary sample).
+//! PBM has no maxval, its 1 is black and read as 0.
+
 use math::matrix::*;
-use std::{
-    fs,
-    io::{self, Write},
-};
+use math::precision::Real;
+use std::io::Write;
+use std::{fmt, fs, io};
+
+use crate::image::Image;
+
+#[derive(Debug)]
+pub enum PnmError {
+    Io(io::Error),
+    /// not starting with `P1` to `P6`
+    InvalidMagic,
+    /// width, height or maxval is missing or not a number
+    InvalidHeader,
+    /// maxval must be in `1..=65535`
+    InvalidMaxval(u32),
+    /// an ASCII sample is not a number or a sample is above maxval
+    InvalidSample,
+    /// data ends before all pixels are read
+    UnexpectedEof,
+}
+
+impl fmt::Display for PnmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PnmError::Io(e) => write!(f, "io error: {}", e),
+            PnmError::InvalidMagic => write!(f, "not a netpbm image"),
+            PnmError::InvalidHeader => write!(f, "invalid header"),
+            PnmError::InvalidMaxval(v) => write!(f, "invalid maxval {}", v),
+            PnmError::InvalidSample => write!(f, "invalid sample"),
+            PnmError::UnexpectedEof => write!(f, "unexpected end of data"),
+        }
+    }
+}
+
+impl std::error::Error for PnmError {}
+
+impl From<io::Error> for PnmError {
+    fn from(e: io::Error) -> Self {
+        PnmError::Io(e)
+    }
+}
+
+/// variants are named after their magic numbers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PnmFormat {
+    /// ASCII bitmap
+    P1,
+    /// ASCII graymap
+    P2,
+    /// ASCII pixmap
+    P3,
+    /// binary bitmap, 8 pixels per byte
+    P4,
+    /// binary graymap
+    P5,
+    /// binary pixmap
+    P6,
+}
+
+impl PnmFormat {
+    fn from_magic(magic: &[u8]) -> Option<Self> {
+        match magic {
+            b"P1" => Some(PnmFormat::P1),
+            b"P2" => Some(PnmFormat::P2),
+            b"P3" => Some(PnmFormat::P3),
+            b"P4" => Some(PnmFormat::P4),
+            b"P5" => Some(PnmFormat::P5),
+            b"P6" => Some(PnmFormat::P6),
+            _ => None,
+        }
+    }
+
+    fn magic(self) -> &'static str {
+        match self {
+            PnmFormat::P1 => "P1",
+            PnmFormat::P2 => "P2",
+            PnmFormat::P3 => "P3",
+            PnmFormat::P4 => "P4",
+            PnmFormat::P5 => "P5",
+            PnmFormat::P6 => "P6",
+        }
+    }
+
+    pub fn is_binary(self) -> bool {
+        matches!(self, PnmFormat::P4 | PnmFormat::P5 | PnmFormat::P6)
+    }
+
+    pub fn is_bitmap(self) -> bool {
+        matches!(self, PnmFormat::P1 | PnmFormat::P4)
+    }
+
+    pub fn is_rgb(self) -> bool {
+        matches!(self, PnmFormat::P3 | PnmFormat::P6)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PnmImage {
+    Gray(Image<Real>),
+    Rgb(Image<Vec3>),
+}
+
+impl PnmImage {
+    pub fn width(&self) -> usize {
+        match self {
+            PnmImage::Gray(image) => image.width(),
+            PnmImage::Rgb(image) => image.width(),
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        match self {
+            PnmImage::Gray(image) => image.height(),
+            PnmImage::Rgb(image) => image.height(),
+        }
+    }
+
+    /// gray is replicated to rgb
+    pub fn to_rgb(&self) -> Image<Vec3> {
+        match self {
+            PnmImage::Gray(image) => image.map(|v| Vec3::from_xyz(*v, *v, *v)),
+            PnmImage::Rgb(image) => image.clone(),
+        }
+    }
+
+    /// rgb is converted by Rec. 709 luma
+    pub fn to_gray(&self) -> Image<Real> {
+        match self {
+            PnmImage::Gray(image) => image.clone(),
+            PnmImage::Rgb(image) => image.map(|c| 0.2126 * c.x() + 0.7152 * c.y() + 0.0722 * c.z()),
+        }
+    }
+}
+
+pub struct Pnm {
+    pub format: PnmFormat,
+    /// ignored by bitmaps
+    pub maxval: u16,
+    /// gray for bitmaps and graymaps, rgb for pixmaps
+    pub image: PnmImage,
+}
+
+/// header tokens and ASCII samples separated by whitespace and `#` comments
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Cursor<'_> {
+    fn skip_space(&mut self) {
+        while let Some(b) = self.bytes.get(self.pos) {
+            match b {
+                b'#' => {
+                    while self.bytes.get(self.pos).is_some_and(|b| *b != b'\n' && *b != b'\r') {
+                        self.pos += 1;
+                    }
+                }
+                b if b.is_ascii_whitespace() => self.pos += 1,
+                _ => break,
+            }
+        }
+    }
+
+    /// digits of at most `max_len`, a bitmap sample is a single digit which needs no separator
+    fn number(&mut self, max_len: usize) -> Result<Option<u32>, PnmError> {
+        self.skip_space();
+        let start = self.pos;
+        while self.pos - start < max_len && self.bytes.get(self.pos).is_some_and(|b| b.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return match self.bytes.get(self.pos) {
+                None => Err(PnmError::UnexpectedEof),
+                Some(_) => Ok(None),
+            };
+        }
+        // too many digits overflow
+        Ok(std::str::from_utf8(&self.bytes[start..self.pos]).unwrap().parse().ok())
+    }
+
+    fn header_value(&mut self) -> Result<u32, PnmError> {
+        match self.number(usize::MAX) {
+            Ok(Some(v)) => Ok(v),
+            _ => Err(PnmError::InvalidHeader),
+        }
+    }
+
+    fn sample(&mut self, maxval: u32, max_len: usize) -> Result<u32, PnmError> {
+        match self.number(max_len)? {
+            Some(v) if v <= maxval => Ok(v),
+            _ => Err(PnmError::InvalidSample),
+        }
+    }
+}
+
+pub fn parse_pnm(bytes: &[u8]) -> Result<Pnm, PnmError> {
+    let format = bytes.get(..2).and_then(PnmFormat::from_magic).ok_or(PnmError::InvalidMagic)?;
+    let mut cursor = Cursor { bytes, pos: 2 };
+    let width = cursor.header_value()? as usize;
+    let height = cursor.header_value()? as usize;
+    let maxval = if format.is_bitmap() {
+        1
+    } else {
+        match cursor.header_value()? {
+            v @ 1..=65535 => v,
+            v => return Err(PnmError::InvalidMaxval(v)),
+        }
+    };
+    let channels = if format.is_rgb() { 3 } else { 1 };
+    let count = width
+        .checked_mul(height)
+        .and_then(|n| n.checked_mul(channels))
+        .ok_or(PnmError::InvalidHeader)?;
+
+    let samples: Vec<u32> = if !format.is_binary() {
+        let max_len = if format.is_bitmap() { 1 } else { usize::MAX };
+        (0..count).map(|_| cursor.sample(maxval, max_len)).collect::<Result<_, _>>()?
+    } else {
+        // a single whitespace separates header and data
+        let body = bytes.get(cursor.pos + 1..).ok_or(PnmError::UnexpectedEof)?;
+        if format == PnmFormat::P4 {
+            let stride = width.div_ceil(8);
+            if body.len() < stride.checked_mul(height).ok_or(PnmError::InvalidHeader)? {
+                return Err(PnmError::UnexpectedEof);
+            }
+            // rows are padded to whole bytes, the first pixel is the most significant bit
+            (0..count)
+                .map(|i| {
+                    let (x, y) = (i % width, i / width);
+                    (body[y * stride + x / 8] >> (7 - x % 8)) as u32 & 1
+                })
+                .collect()
+        } else {
+            let size = if maxval < 256 { 1 } else { 2 };
+            if body.len() < count.checked_mul(size).ok_or(PnmError::InvalidHeader)? {
+                return Err(PnmError::UnexpectedEof);
+            }
+            let samples: Vec<u32> = match size {
+                1 => body[..count].iter().map(|b| *b as u32).collect(),
+                _ => body[..count * 2].chunks(2).map(|c| u16::from_be_bytes([c[0], c[1]]) as u32).collect(),
+            };
+            if samples.iter().any(|s| *s > maxval) {
+                return Err(PnmError::InvalidSample);
+            }
+            samples
+        }
+    };
+
+    let value = |s: u32| {
+        if format.is_bitmap() {
+            1.0 - s as Real
+        } else {
+            s as Real / maxval as Real
+        }
+    };
+    let image = if format.is_rgb() {
+        let pixels = samples
+            .chunks(3)
+            .map(|c| Vec3::from_xyz(value(c[0]), value(c[1]), value(c[2])))
+            .collect();
+        PnmImage::Rgb(Image::from_data(width, height, pixels).unwrap())
+    } else {
+        PnmImage::Gray(Image::from_data(width, height, samples.into_iter().map(value).collect()).unwrap())
+    };
+    Ok(Pnm {
+        format,
+        maxval: maxval as u16,
+        image,
+    })
+}
+
+pub fn read_pnm(filename: &str) -> Result<Pnm, PnmError> {
+    parse_pnm(&fs::read(filename)?)
+}
+
+impl Pnm {
+    /// 8 bit image in `format`
+    pub fn new(image: PnmImage, format: PnmFormat) -> Self {
+        Self {
+            format,
+            maxval: 255,
+            image,
+        }
+    }
+
+    /// image is converted if its channels don't match format, values are clamped to `[0, 1]`.
+    /// bitmap pixels below 0.5 are black, maxval of 0 is taken as 1
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let format = self.format;
+        let maxval = if format.is_bitmap() { 1 } else { self.maxval.max(1) as u32 };
+        let quantize = |v: Real| -> u32 {
+            if format.is_bitmap() {
+                (v < 0.5) as u32
+            } else {
+                (v.clamp(0.0, 1.0) * maxval as Real).round() as u32
+            }
+        };
+        let samples: Vec<u32> = if format.is_rgb() {
+            let image = self.image.to_rgb();
+            image.data().iter().flat_map(|c| [c.x(), c.y(), c.z()].map(quantize)).collect()
+        } else {
+            self.image.to_gray().data().iter().map(|v| quantize(*v)).collect()
+        };
+        let (width, height) = (self.image.width(), self.image.height());
+
+        let mut out = format!("{}\n{} {}\n", format.magic(), width, height);
+        if !format.is_bitmap() {
+            out += &format!("{}\n", maxval);
+        }
+        let mut out = out.into_bytes();
+        match format {
+            PnmFormat::P4 => {
+                for row in samples.chunks(width.max(1)).take(height) {
+                    for byte in row.chunks(8) {
+                        out.push(byte.iter().enumerate().fold(0u8, |acc, (i, s)| acc | ((*s as u8) << (7 - i))));
+                    }
+                }
+            }
+            PnmFormat::P5 | PnmFormat::P6 if maxval < 256 => out.extend(samples.iter().map(|s| *s as u8)),
+            PnmFormat::P5 | PnmFormat::P6 => out.extend(samples.iter().flat_map(|s| (*s as u16).to_be_bytes())),
+            _ => {
+                // plain formats should keep lines within 70 characters
+                let mut line = String::new();
+                for s in samples {
+                    let s = s.to_string();
+                    if !line.is_empty() && line.len() + 1 + s.len() > 70 {
+                        out.extend(line.bytes());
+                        out.push(b'\n');
+                        line.clear();
+                    }
+                    if !line.is_empty() {
+                        line.push(' ');
+                    }
+                    line += &s;
+                }
+                out.extend(line.bytes());
+                out.push(b'\n');
+            }
+        }
+        out
+    }
+
+    pub fn write_to_file(&self, filename: &str) -> Result<(), io::Error> {
+        fs::write(filename, self.to_bytes())
+    }
+}
 
 pub struct PPM {
-    data: Vec<Vec3>,
-    w: usize,
-    h: usize,
+    image: Image<Vec3>,
 }
 
 impl PPM {
     pub fn new(w: usize, h: usize) -> Self {
         Self {
-            data: vec![Vec3::zeros(); w * h],
-            w,
-            h,
+            image: Image::new(w, h, Vec3::zeros()),
         }
     }
 
+    /// any netpbm image, gray ones are converted to rgb
+    pub fn read_from_file(filename: &str) -> Result<Self, PnmError> {
+        Ok(Self {
+            image: read_pnm(filename)?.image.to_rgb(),
+        })
+    }
+
     pub fn set_pixel(&mut self, x: usize, y: usize, pixel: Vec3) {
-        self.data[x + y * self.w] = pixel;
+        self.image[(x, y)] = pixel;
     }
 
     pub fn get_pixel(&self, x: usize, y: usize) -> Vec3 {
-        self.data[x + y * self.w]
+        self.image[(x, y)]
     }
 
     pub fn width(&self) -> usize {
-        self.w
+        self.image.width()
     }
 
     pub fn height(&self) -> usize {
-        self.h
+        self.image.height()
+    }
+
+    pub fn image(&self) -> &Image<Vec3> {
+        &self.image
+    }
+
+    fn to_pnm(&self, format: PnmFormat) -> Pnm {
+        Pnm::new(PnmImage::Rgb(self.image.clone()), format)
     }
 
+    /// ASCII P3 with one row per line, channels are truncated to 8 bits unlike `Pnm` which rounds
     pub fn write_to_file(&self, filename: &str) -> Result<(), io::Error> {
-        let mut file = fs::File::create(filename)?;
+        let mut file = io::BufWriter::new(fs::File::create(filename)?);
         writeln!(&mut file, "P3")?;
-        writeln!(&mut file, "{} {}", self.w, self.h)?;
+        writeln!(&mut file, "{} {}", self.width(), self.height())?;
         writeln!(&mut file, "255")?;
-        for y in 0..self.h {
-            for x in 0..self.w {
+        for y in 0..self.height() {
+            for x in 0..self.width() {
                 let color = self.get_pixel(x, y) * 255.0;
                 write!(
                     &mut file,
@@ -51,8 +410,19 @@ impl PPM {
                     color.z().clamp(0.0, 255.0) as u8
                 )?;
             }
-            writeln!(&mut file, "")?;
+            writeln!(&mut file)?;
         }
-        Ok(())
+        file.flush()
+    }
+
+    /// binary P6, much smaller and faster than `write_to_file`
+    pub fn write_binary_to_file(&self, filename: &str) -> Result<(), io::Error> {
+        self.to_pnm(PnmFormat::P6).write_to_file(filename)
+    }
+}
+
+impl From<Image<Vec3>> for PPM {
+    fn from(image: Image<Vec3>) -> Self {
+        Self { image }
     }
 }
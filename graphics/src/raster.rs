@@ -0,0 +1,194 @@
+//! CPU triangle rasterizer for `FaceDisplayData`
+//!
+//! triangles are clipped against the near plane in clip space, depth tested per pixel center and shaded with one
+//! directional light. clip space follows `math::cg` projections(depth in `[-1, 1]`), front faces are counter-clockwise
+//! in normalized device coordinate.
+
+use math::cg::Berycentric;
+use math::matrix::*;
+use math::precision::Real;
+
+use crate::image::Image;
+use crate::mesh_generate::FaceDisplayData;
+use crate::ppm::PPM;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CullMode {
+    None,
+    Back,
+    Front,
+}
+
+/// vertex after transform, attributes are interpolated perspective correctly
+#[derive(Clone, Copy, Debug)]
+struct ClipVertex {
+    clip: Vec4,
+    normal: Vec3,
+}
+
+impl ClipVertex {
+    fn lerp(&self, other: &Self, t: Real) -> Self {
+        Self {
+            clip: self.clip + (other.clip - self.clip) * t,
+            normal: self.normal + (other.normal - self.normal) * t,
+        }
+    }
+
+    /// distance to the near plane `z = -w`, non-negative if in front of it
+    fn near_distance(&self) -> Real {
+        self.clip.z() + self.clip.w()
+    }
+}
+
+/// Sutherland-Hodgman against the near plane, leaves a convex polygon of at most 4 vertices
+fn clip_near(triangle: [ClipVertex; 3]) -> Vec<ClipVertex> {
+    let mut out = Vec::with_capacity(4);
+    for i in 0..3 {
+        let (a, b) = (triangle[i], triangle[(i + 1) % 3]);
+        let (da, db) = (a.near_distance(), b.near_distance());
+        if da >= 0.0 {
+            out.push(a);
+        }
+        if (da >= 0.0) != (db >= 0.0) {
+            out.push(a.lerp(&b, da / (da - db)));
+        }
+    }
+    out
+}
+
+pub struct Rasterizer {
+    pub cull: CullMode,
+    /// direction toward a directional light in world, `None` draws the unlit base color
+    pub light: Option<Vec3>,
+    /// brightness of faces turned away from the light
+    pub ambient: Real,
+    color: Image<Vec3>,
+    depth: Image<Real>,
+}
+
+impl Rasterizer {
+    /// back faces culled, lit from the upper right front
+    pub fn new(width: usize, height: usize, background: Vec3) -> Self {
+        Self {
+            cull: CullMode::Back,
+            light: Some(Vec3::from_xyz(1.0, 2.0, 3.0).normalize()),
+            ambient: 0.2,
+            color: Image::new(width, height, background),
+            depth: Image::new(width, height, Real::INFINITY),
+        }
+    }
+
+    pub fn clear(&mut self, background: Vec3) {
+        self.color.fill(background);
+        self.depth.fill(Real::INFINITY);
+    }
+
+    pub fn color(&self) -> &Image<Vec3> {
+        &self.color
+    }
+
+    /// depth in normalized device coordinate, infinity where nothing is drawn
+    pub fn depth(&self) -> &Image<Real> {
+        &self.depth
+    }
+
+    pub fn to_ppm(&self) -> PPM {
+        PPM::from(self.color.clone())
+    }
+
+    /// draw `data` placed by `model`, `view_proj` maps world to clip space.
+    /// alpha of `data.color` is ignored
+    pub fn draw(&mut self, data: &FaceDisplayData, model: &Mat44, view_proj: &Mat44) {
+        let mvp = *view_proj * *model;
+        // normals go through the inverse transpose, a singular model can only flatten the mesh
+        let normal_mat = model.inv().map_or(*model, |m| m.transpose());
+        let vertices: Vec<ClipVertex> = (0..data.vertices.len())
+            .map(|i| {
+                let normal = data.normals.get(i).map_or(Vec3::zeros(), |n| {
+                    let n = normal_mat * Vec4::from_xyzw(n.x(), n.y(), n.z(), 0.0);
+                    n.xyz()
+                });
+                ClipVertex {
+                    clip: mvp * Vec4::from(data.vertices[i]),
+                    normal,
+                }
+            })
+            .collect();
+
+        for t in data.indices.chunks_exact(3) {
+            let polygon = clip_near([0, 1, 2].map(|i| vertices[t[i] as usize]));
+            for i in 1..polygon.len().saturating_sub(1) {
+                self.draw_triangle([polygon[0], polygon[i], polygon[i + 1]], &data.color.xyz());
+            }
+        }
+    }
+
+    fn draw_triangle(&mut self, triangle: [ClipVertex; 3], base_color: &Vec3) {
+        let (width, height) = (self.color.width() as Real, self.color.height() as Real);
+        let inv_w = triangle.map(|v| 1.0 / v.clip.w());
+        // screen has y going down, depth stays in normalized device coordinate
+        let screen = [0, 1, 2].map(|i| {
+            let ndc = triangle[i].clip.xyz() * inv_w[i];
+            Vec3::from_xyz((ndc.x() + 1.0) * 0.5 * width, (1.0 - ndc.y()) * 0.5 * height, ndc.z())
+        });
+        let pts = screen.map(|s| s.xy());
+
+        // counter-clockwise in normalized device coordinate is clockwise on screen
+        let area = (pts[1] - pts[0]).cross(&(pts[2] - pts[0]));
+        let front = area < 0.0;
+        let culled = match self.cull {
+            CullMode::None => false,
+            CullMode::Back => !front,
+            CullMode::Front => front,
+        };
+        if area == 0.0 || !area.is_finite() || culled {
+            return;
+        }
+
+        let min_x = pts.iter().map(|p| p.x()).fold(Real::INFINITY, Real::min).max(0.0) as usize;
+        let min_y = pts.iter().map(|p| p.y()).fold(Real::INFINITY, Real::min).max(0.0) as usize;
+        let max_x = pts.iter().map(|p| p.x()).fold(Real::NEG_INFINITY, Real::max).ceil().min(width) as usize;
+        let max_y = pts.iter().map(|p| p.y()).fold(Real::NEG_INFINITY, Real::max).ceil().min(height) as usize;
+        let sign = area.signum();
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let p = Vec2::from_xy(x as Real + 0.5, y as Real + 0.5);
+                let inside = (0..3).all(|i| {
+                    let (a, b) = (pts[i], pts[(i + 1) % 3]);
+                    (b - a).cross(&(p - a)) * sign >= 0.0
+                });
+                if !inside {
+                    continue;
+                }
+
+                // screen space weights, then perspective correct ones by 1/w
+                let bary = Berycentric::new(&pts, p);
+                let screen_weights = [bary.alpha(), bary.beta(), bary.gamma()];
+                let depth: Real = (0..3).map(|i| screen_weights[i] * screen[i].z()).sum();
+                if !(-1.0..=1.0).contains(&depth) || depth >= self.depth[(x, y)] {
+                    continue;
+                }
+                let persp = [0, 1, 2].map(|i| screen_weights[i] * inv_w[i]);
+                let total: Real = persp.iter().sum();
+                let normal = (0..3).fold(Vec3::zeros(), |acc, i| acc + triangle[i].normal * (persp[i] / total));
+
+                self.depth[(x, y)] = depth;
+                self.color[(x, y)] = self.shade(base_color, &normal, front);
+            }
+        }
+    }
+
+    /// Lambert, back faces are lit as seen from behind
+    fn shade(&self, base_color: &Vec3, normal: &Vec3, front: bool) -> Vec3 {
+        let Some(light) = self.light else {
+            return *base_color;
+        };
+        if normal.length_sqrd() == 0.0 {
+            return *base_color;
+        }
+        let normal = if front { normal.normalize() } else { -normal.normalize() };
+        let diffuse = normal.dot(&light.normalize()).max(0.0);
+        *base_color * (self.ambient + (1.0 - self.ambient) * diffuse)
+    }
+}
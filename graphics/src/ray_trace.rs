@@ -0,0 +1,468 @@
+//! CPU path tracer over `geometric` primitives
+//!
+//! spheres are hit by `ray_sphere_intersect_param`, triangle meshes go through a `BVH` of `Triangle3D`.
+//! diffuse surfaces sample area lights directly(next event estimation), so emission is only added on camera rays
+//! and after specular bounces. every pixel has its own random sequence, the image doesn't depend on thread count.
+
+use std::f64::consts::PI;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use geometric::bvh::BVH;
+use geometric::geom3d::{Ray3D, Sphere, Triangle3D};
+use geometric::intersect3d::ray_sphere_intersect_param;
+use math::matrix::*;
+use math::precision::Real;
+
+use crate::camera::Camera;
+use crate::image::Image;
+use crate::mesh_generate::FaceDisplayData;
+use crate::ppm::PPM;
+
+/// offset of secondary rays from the surface they leave
+const RAY_EPSILON: Real = 1e-5;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Material {
+    /// ideal diffuse
+    Lambertian { albedo: Vec3 },
+    /// mirror, `fuzz` in `[0, 1]` blurs the reflection
+    Metal { albedo: Vec3, fuzz: Real },
+    /// glass-like, `ior` is the index of refraction inside relative to outside
+    Dielectric { ior: Real },
+    /// area light emitting `radiance` from its front side, reflects nothing
+    Emissive { radiance: Vec3 },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RenderSettings {
+    pub samples: u32,
+    /// bounces after the camera ray
+    pub max_depth: u32,
+    /// 0 uses all available cores
+    pub threads: usize,
+    pub seed: u64,
+    /// output is `radiance^(1/gamma)`, 1 keeps it linear
+    pub gamma: Real,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            samples: 16,
+            max_depth: 8,
+            threads: 0,
+            seed: 0,
+            gamma: 2.2,
+        }
+    }
+}
+
+/// xorshift64* seeded through splitmix64
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        // xorshift gets stuck at zero
+        Self((z ^ (z >> 31)) | 1)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// uniform in `[0, 1)`
+    fn next_real(&mut self) -> Real {
+        (self.next_u64() >> 11) as Real / (1u64 << 53) as Real
+    }
+
+    fn unit_vector(&mut self) -> Vec3 {
+        let z = 1.0 - 2.0 * self.next_real();
+        let r = (1.0 - z * z).max(0.0).sqrt();
+        let phi = 2.0 * PI * self.next_real();
+        Vec3::from_xyz(r * phi.cos(), r * phi.sin(), z)
+    }
+
+    /// cosine weighted direction in the hemisphere around unit `normal`
+    fn cosine_hemisphere(&mut self, normal: &Vec3) -> Vec3 {
+        let phi = 2.0 * PI * self.next_real();
+        let r2 = self.next_real();
+        let r = r2.sqrt();
+        let (tangent, bitangent) = orthonormal_basis(normal);
+        tangent * (r * phi.cos()) + bitangent * (r * phi.sin()) + *normal * (1.0 - r2).sqrt()
+    }
+}
+
+/// [Duff et al.](https://graphics.pixar.com/library/OrthonormalB/paper.pdf), `normal` must be unit
+fn orthonormal_basis(normal: &Vec3) -> (Vec3, Vec3) {
+    let sign = Real::copysign(1.0, normal.z());
+    let a = -1.0 / (sign + normal.z());
+    let b = normal.x() * normal.y() * a;
+    (
+        Vec3::from_xyz(1.0 + sign * normal.x() * normal.x() * a, sign * b, -sign * normal.x()),
+        Vec3::from_xyz(b, sign + normal.y() * normal.y() * a, -normal.y()),
+    )
+}
+
+fn reflect(dir: &Vec3, normal: &Vec3) -> Vec3 {
+    *dir - *normal * (2.0 * dir.dot(normal))
+}
+
+/// Schlick's approximation of Fresnel reflectance, `eta` is the ratio of indices of refraction
+fn reflectance(cos_i: Real, eta: Real) -> Real {
+    let r0 = ((1.0 - eta) / (1.0 + eta)).powi(2);
+    r0 + (1.0 - r0) * (1.0 - cos_i).powi(5)
+}
+
+struct Mesh {
+    bvh: BVH<Triangle3D, 3>,
+    /// vertex normals per triangle, empty for flat shading
+    normals: Vec<[Vec3; 3]>,
+    /// running sum of triangle areas, picks triangles of a light proportional to area
+    area_cdf: Vec<Real>,
+}
+
+impl Mesh {
+    fn triangle(&self, index: usize) -> &Triangle3D {
+        self.bvh.get(index).unwrap()
+    }
+
+    /// counter-clockwise normal and the interpolated vertex normal at `pt`
+    fn normals_at(&self, index: usize, pt: &Vec3) -> (Vec3, Vec3) {
+        let t = self.triangle(index);
+        let cross = (t[1] - t[0]).cross(&(t[2] - t[0]));
+        let normal = cross.normalize();
+        let Some(normals) = self.normals.get(index) else {
+            return (normal, normal);
+        };
+        let area = cross.length();
+        let shading = (0..3).fold(Vec3::zeros(), |acc, i| {
+            let (b, c) = (t[(i + 1) % 3], t[(i + 2) % 3]);
+            acc + normals[i] * ((b - *pt).cross(&(c - *pt)).dot(&normal) / area)
+        });
+        if shading.length_sqrd() == 0.0 {
+            (normal, normal)
+        } else {
+            (normal, shading.normalize())
+        }
+    }
+}
+
+enum Shape {
+    Sphere(Sphere),
+    Mesh(Mesh),
+}
+
+impl Shape {
+    /// nearest hit within `max_t`, returns (ray param, triangle index)
+    fn ray_cast(&self, ray: &Ray3D, max_t: Real) -> Option<(Real, usize)> {
+        match self {
+            Shape::Sphere(sphere) => ray_sphere_intersect_param(ray, sphere)
+                .map(|(t, _)| (t, 0))
+                .filter(|(t, _)| *t <= max_t),
+            Shape::Mesh(mesh) => mesh.bvh.ray_cast(ray, max_t).map(|(index, t)| (t, index)),
+        }
+    }
+
+    fn is_occluding(&self, ray: &Ray3D, max_t: Real) -> bool {
+        match self {
+            Shape::Sphere(_) => self.ray_cast(ray, max_t).is_some(),
+            Shape::Mesh(mesh) => mesh.bvh.ray_cast_any(ray, max_t).is_some(),
+        }
+    }
+
+    /// uniform point on the surface, returns (point, outward normal, pdf per area)
+    fn sample_surface(&self, rng: &mut Rng) -> (Vec3, Vec3, Real) {
+        match self {
+            Shape::Sphere(sphere) => {
+                let normal = rng.unit_vector();
+                let area = 4.0 * PI * sphere.radius * sphere.radius;
+                (sphere.center + normal * sphere.radius, normal, 1.0 / area)
+            }
+            Shape::Mesh(mesh) => {
+                let total = *mesh.area_cdf.last().unwrap();
+                let u = rng.next_real() * total;
+                let index = mesh.area_cdf.partition_point(|a| *a <= u).min(mesh.area_cdf.len() - 1);
+                let t = mesh.triangle(index);
+                let s = rng.next_real().sqrt();
+                let r = rng.next_real();
+                let pt = t[0] * (1.0 - s) + t[1] * (s * (1.0 - r)) + t[2] * (s * r);
+                let normal = (t[1] - t[0]).cross(&(t[2] - t[0])).normalize();
+                (pt, normal, 1.0 / total)
+            }
+        }
+    }
+}
+
+struct Object {
+    shape: Shape,
+    material: Material,
+}
+
+struct Hit {
+    pt: Vec3,
+    /// geometric normal on the side the ray comes from
+    normal: Vec3,
+    /// interpolated normal on the same side as `normal`
+    shading: Vec3,
+    /// ray comes from the front(outside)
+    front: bool,
+    object: usize,
+}
+
+pub struct Scene {
+    /// radiance of rays leaving the scene
+    pub background: Vec3,
+    objects: Vec<Object>,
+    /// indices of emissive objects
+    lights: Vec<usize>,
+}
+
+impl Scene {
+    pub fn new(background: Vec3) -> Self {
+        Self {
+            background,
+            objects: Vec::new(),
+            lights: Vec::new(),
+        }
+    }
+
+    pub fn add_sphere(&mut self, sphere: Sphere, material: Material) {
+        self.add_object(Shape::Sphere(sphere), material);
+    }
+
+    /// mesh placed by `model`, front faces are counter-clockwise.
+    /// closed meshes are needed for dielectric
+    pub fn add_mesh(&mut self, data: &FaceDisplayData, model: &Mat44, material: Material) {
+        // normals go through the inverse transpose, a singular model can only flatten the mesh
+        let normal_mat = model.inv().map_or(*model, |m| m.transpose());
+        let vertices: Vec<Vec3> = data
+            .vertices
+            .iter()
+            .map(|v| (*model * Vec4::from(*v)).xyz())
+            .collect();
+        let normals: Vec<Vec3> = data
+            .normals
+            .iter()
+            .map(|n| (normal_mat * Vec4::from_xyzw(n.x(), n.y(), n.z(), 0.0)).xyz())
+            .collect();
+
+        let mut triangles = Vec::new();
+        let mut triangle_normals = Vec::new();
+        let mut area_cdf = Vec::new();
+        let mut total = 0.0;
+        for t in data.indices.chunks_exact(3) {
+            let pts = [0, 1, 2].map(|i| vertices[t[i] as usize]);
+            total += (pts[1] - pts[0]).cross(&(pts[2] - pts[0])).length() * 0.5;
+            area_cdf.push(total);
+            triangles.push(Triangle3D::new(pts));
+            if normals.len() == vertices.len() {
+                triangle_normals.push([0, 1, 2].map(|i| normals[t[i] as usize]));
+            }
+        }
+        if triangles.is_empty() {
+            return;
+        }
+
+        let mesh = Mesh {
+            bvh: BVH::build(triangles),
+            normals: triangle_normals,
+            area_cdf,
+        };
+        self.add_object(Shape::Mesh(mesh), material);
+    }
+
+    fn add_object(&mut self, shape: Shape, material: Material) {
+        if matches!(material, Material::Emissive { .. }) {
+            self.lights.push(self.objects.len());
+        }
+        self.objects.push(Object { shape, material });
+    }
+
+    fn hit(&self, ray: &Ray3D) -> Option<Hit> {
+        let mut max_t = Real::INFINITY;
+        let mut nearest = None;
+        for (i, object) in self.objects.iter().enumerate() {
+            if let Some((t, index)) = object.shape.ray_cast(ray, max_t) {
+                max_t = t;
+                nearest = Some((i, index));
+            }
+        }
+
+        let (object, index) = nearest?;
+        let pt = ray.start + ray.dir * max_t;
+        let (outward, shading) = match &self.objects[object].shape {
+            Shape::Sphere(sphere) => {
+                let normal = (pt - sphere.center) / sphere.radius;
+                (normal, normal)
+            }
+            Shape::Mesh(mesh) => mesh.normals_at(index, &pt),
+        };
+        let front = ray.dir.dot(&outward) < 0.0;
+        let (normal, shading) = if front { (outward, shading) } else { (-outward, -shading) };
+        // interpolated normals may bend past the silhouette
+        let shading = if shading.dot(&normal) > 0.0 { shading } else { normal };
+        Some(Hit {
+            pt,
+            normal,
+            shading,
+            front,
+            object,
+        })
+    }
+
+    fn is_occluded(&self, ray: &Ray3D, max_t: Real) -> bool {
+        self.objects.iter().any(|o| o.shape.is_occluding(ray, max_t))
+    }
+
+    /// ray leaving `hit` toward `dir`, offset to the side it goes
+    fn spawn_ray(hit: &Hit, dir: Vec3) -> Ray3D {
+        let offset = if dir.dot(&hit.normal) > 0.0 { hit.normal } else { -hit.normal };
+        Ray3D::new(hit.pt + offset * RAY_EPSILON, dir)
+    }
+
+    /// reflected radiance of a diffuse surface from one random point on one random light
+    fn direct_light(&self, hit: &Hit, albedo: &Vec3, rng: &mut Rng) -> Vec3 {
+        if self.lights.is_empty() {
+            return Vec3::zeros();
+        }
+        let count = self.lights.len();
+        let light = &self.objects[self.lights[((rng.next_real() * count as Real) as usize).min(count - 1)]];
+        let Material::Emissive { radiance } = light.material else {
+            unreachable!("lights are emissive")
+        };
+
+        let (pt, normal, pdf) = light.shape.sample_surface(rng);
+        let to_light = pt - hit.pt;
+        let dist_sqrd = to_light.length_sqrd();
+        let dist = dist_sqrd.sqrt();
+        let dir = to_light / dist;
+        let cos_surface = dir.dot(&hit.shading);
+        let cos_light = -dir.dot(&normal);
+        if cos_surface <= 0.0 || cos_light <= 0.0 || dir.dot(&hit.normal) <= 0.0 {
+            return Vec3::zeros();
+        }
+        // stop short of the light so it doesn't shadow itself
+        if self.is_occluded(&Self::spawn_ray(hit, dir), dist * (1.0 - 1e-4)) {
+            return Vec3::zeros();
+        }
+        albedo.mul_each(radiance) * (cos_surface * cos_light * count as Real / (PI * dist_sqrd * pdf))
+    }
+
+    fn trace(&self, mut ray: Ray3D, max_depth: u32, rng: &mut Rng) -> Vec3 {
+        let mut radiance = Vec3::zeros();
+        let mut throughput = Vec3::ones();
+        // camera rays and specular bounces see emission, diffuse ones already sampled it
+        let mut specular = true;
+        for _ in 0..=max_depth {
+            let Some(hit) = self.hit(&ray) else {
+                return radiance + throughput.mul_each(self.background);
+            };
+
+            let dir = match self.objects[hit.object].material {
+                Material::Emissive { radiance: emitted } => {
+                    if specular && hit.front {
+                        radiance += throughput.mul_each(emitted);
+                    }
+                    return radiance;
+                }
+                Material::Lambertian { albedo } => {
+                    radiance += throughput.mul_each(self.direct_light(&hit, &albedo, rng));
+                    throughput = throughput.mul_each(albedo);
+                    specular = false;
+                    rng.cosine_hemisphere(&hit.shading)
+                }
+                Material::Metal { albedo, fuzz } => {
+                    throughput = throughput.mul_each(albedo);
+                    specular = true;
+                    (reflect(&ray.dir, &hit.shading) + rng.unit_vector() * fuzz).normalize()
+                }
+                Material::Dielectric { ior } => {
+                    specular = true;
+                    let eta = if hit.front { 1.0 / ior } else { ior };
+                    let cos_i = (-ray.dir.dot(&hit.shading)).min(1.0);
+                    let sin_t_sqrd = eta * eta * (1.0 - cos_i * cos_i);
+                    if sin_t_sqrd > 1.0 || rng.next_real() < reflectance(cos_i, eta) {
+                        reflect(&ray.dir, &hit.shading)
+                    } else {
+                        let perp = (ray.dir + hit.shading * cos_i) * eta;
+                        perp - hit.shading * (1.0 - sin_t_sqrd).sqrt()
+                    }
+                }
+            };
+
+            // only refraction may cross the surface
+            let crossing = dir.dot(&hit.normal) < 0.0;
+            let refracting = matches!(self.objects[hit.object].material, Material::Dielectric { .. });
+            if crossing && !refracting {
+                return radiance;
+            }
+            ray = Self::spawn_ray(&hit, dir);
+        }
+        radiance
+    }
+
+    /// image of `camera.viewport` size, samples are jittered within pixels, a degenerate camera gives a black image
+    pub fn render(&self, camera: &Camera, settings: &RenderSettings) -> PPM {
+        let width = camera.viewport.x().round() as usize;
+        let height = camera.viewport.y().round() as usize;
+        let Some(inv_view_proj) = camera.inv_view_proj_mat() else {
+            return PPM::from(Image::new(width, height, Vec3::zeros()));
+        };
+        let threads = match settings.threads {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        }
+        .clamp(1, height.max(1));
+        let samples = settings.samples.max(1);
+
+        let render_row = |y: usize| -> Vec<Vec3> {
+            (0..width)
+                .map(|x| {
+                    let pixel = (y * width + x) as u64;
+                    let mut rng = Rng::new(settings.seed ^ pixel.wrapping_mul(0xD1B5_4A32_D192_ED03));
+                    let mut sum = Vec3::zeros();
+                    for _ in 0..samples {
+                        let jitter = Vec2::from_xy(x as Real + rng.next_real(), y as Real + rng.next_real());
+                        let ray = camera.screen_ray_with(&inv_view_proj, &jitter);
+                        sum += self.trace(ray, settings.max_depth, &mut rng);
+                    }
+                    let mean = sum / samples as Real;
+                    let encode = |v: Real| v.max(0.0).powf(1.0 / settings.gamma);
+                    Vec3::from_xyz(encode(mean.x()), encode(mean.y()), encode(mean.z()))
+                })
+                .collect()
+        };
+
+        let mut image = Image::new(width, height, Vec3::zeros());
+        let next_row = AtomicUsize::new(0);
+        thread::scope(|s| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| {
+                    s.spawn(|| {
+                        let mut rows = Vec::new();
+                        loop {
+                            let y = next_row.fetch_add(1, Ordering::Relaxed);
+                            if y >= height {
+                                break rows;
+                            }
+                            rows.push((y, render_row(y)));
+                        }
+                    })
+                })
+                .collect();
+            for worker in workers {
+                for (y, row) in worker.join().unwrap() {
+                    image.data_mut()[y * width..(y + 1) * width].copy_from_slice(&row);
+                }
+            }
+        });
+        PPM::from(image)
+    }
+}
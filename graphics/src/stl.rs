@@ -0,0 +1,235 @@
+//! [STL](https://en.wikipedia.org/wiki/STL_(file_format)) reader/writer, both ASCII and binary
+//!
+//! STL is a triangle soup with one normal per facet, so every facet gets its own three vertices sharing the facet normal.
+
+use std::fmt::{self, Write as _};
+use std::{fs, io};
+
+use crate::mesh_generate::FaceDisplayData;
+use math::matrix::*;
+use math::precision::Real;
+
+#[derive(Debug)]
+pub enum StlError {
+    Io(io::Error),
+    /// a number can't be parsed, `line` starts from 1
+    InvalidNumber { line: usize },
+    /// ASCII keyword is not the expected one
+    UnexpectedToken { line: usize },
+    /// data ends in the middle of a facet
+    UnexpectedEof,
+}
+
+impl fmt::Display for StlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StlError::Io(e) => write!(f, "io error: {}", e),
+            StlError::InvalidNumber { line } => write!(f, "invalid number at line {}", line),
+            StlError::UnexpectedToken { line } => write!(f, "unexpected token at line {}", line),
+            StlError::UnexpectedEof => write!(f, "unexpected end of data"),
+        }
+    }
+}
+
+impl std::error::Error for StlError {}
+
+impl From<io::Error> for StlError {
+    fn from(e: io::Error) -> Self {
+        StlError::Io(e)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StlFormat {
+    Ascii,
+    Binary,
+}
+
+const BINARY_HEADER_LEN: usize = 80;
+const BINARY_FACET_LEN: usize = 50;
+
+/// binary if the size matches its facet count, otherwise ASCII if it starts with `solid`.
+/// a zero facet normal is replaced by the one from vertices
+pub fn parse_stl(bytes: &[u8]) -> Result<FaceDisplayData, StlError> {
+    let binary_len = bytes
+        .get(BINARY_HEADER_LEN..BINARY_HEADER_LEN + 4)
+        .map(|c| BINARY_HEADER_LEN + 4 + u32::from_le_bytes(c.try_into().unwrap()) as usize * BINARY_FACET_LEN);
+    if binary_len != Some(bytes.len()) && bytes.trim_ascii_start().starts_with(b"solid") {
+        parse_ascii(&String::from_utf8_lossy(bytes))
+    } else {
+        parse_binary(bytes)
+    }
+}
+
+pub fn read_stl(filename: &str) -> Result<FaceDisplayData, StlError> {
+    parse_stl(&fs::read(filename)?)
+}
+
+/// facet normals come from triangle vertices, not from vertex normals
+pub fn write_stl(data: &FaceDisplayData, filename: &str, format: StlFormat) -> Result<(), io::Error> {
+    match format {
+        StlFormat::Ascii => fs::write(filename, to_ascii_stl(data, "mesh")),
+        StlFormat::Binary => fs::write(filename, to_binary_stl(data)),
+    }
+}
+
+fn facets(data: &FaceDisplayData) -> impl Iterator<Item = (Vec3, [Vec3; 3])> + '_ {
+    data.indices.chunks(3).map(|t| {
+        let pts = [0, 1, 2].map(|i| data.vertices[t[i] as usize]);
+        let normal = (pts[1] - pts[0]).cross(&(pts[2] - pts[0]));
+        let normal = if normal.length_sqrd() > 0.0 { normal.normalize() } else { normal };
+        (normal, pts)
+    })
+}
+
+pub fn to_ascii_stl(data: &FaceDisplayData, name: &str) -> String {
+    let mut out = String::new();
+    writeln!(out, "solid {}", name).unwrap();
+    for (n, pts) in facets(data) {
+        writeln!(out, "facet normal {} {} {}", n.x(), n.y(), n.z()).unwrap();
+        writeln!(out, "outer loop").unwrap();
+        for p in pts {
+            writeln!(out, "vertex {} {} {}", p.x(), p.y(), p.z()).unwrap();
+        }
+        writeln!(out, "endloop").unwrap();
+        writeln!(out, "endfacet").unwrap();
+    }
+    writeln!(out, "endsolid {}", name).unwrap();
+    out
+}
+
+/// values are stored as `f32`
+pub fn to_binary_stl(data: &FaceDisplayData) -> Vec<u8> {
+    let mut out = vec![0u8; BINARY_HEADER_LEN];
+    out.extend((data.indices.len() as u32 / 3).to_le_bytes());
+    for (n, pts) in facets(data) {
+        for v in [n, pts[0], pts[1], pts[2]] {
+            for i in 0..3 {
+                out.extend((v[i] as f32).to_le_bytes());
+            }
+        }
+        // attribute byte count
+        out.extend(0u16.to_le_bytes());
+    }
+    out
+}
+
+/// one facet from its normal and vertices
+fn push_facet(
+    normal: Vec3,
+    pts: [Vec3; 3],
+    vertices: &mut Vec<Vec3>,
+    normals: &mut Vec<Vec3>,
+    indices: &mut Vec<u32>,
+) {
+    let normal = if normal.length_sqrd() > 0.0 {
+        normal.normalize()
+    } else {
+        let n = (pts[1] - pts[0]).cross(&(pts[2] - pts[0]));
+        if n.length_sqrd() > 0.0 { n.normalize() } else { Vec3::z_axis() }
+    };
+    for p in pts {
+        indices.push(vertices.len() as u32);
+        vertices.push(p);
+        normals.push(normal);
+    }
+}
+
+fn to_display_data(vertices: Vec<Vec3>, normals: Vec<Vec3>, indices: Vec<u32>) -> FaceDisplayData {
+    FaceDisplayData {
+        vertices,
+        normals,
+        indices,
+        color: Vec4::ones(),
+        uvs: None,
+        tangents: None,
+    }
+}
+
+fn parse_binary(bytes: &[u8]) -> Result<FaceDisplayData, StlError> {
+    let count = bytes
+        .get(BINARY_HEADER_LEN..BINARY_HEADER_LEN + 4)
+        .ok_or(StlError::UnexpectedEof)?;
+    let count = u32::from_le_bytes(count.try_into().unwrap()) as usize;
+    let body = &bytes[BINARY_HEADER_LEN + 4..];
+    if body.len() < count * BINARY_FACET_LEN {
+        return Err(StlError::UnexpectedEof);
+    }
+
+    let (mut vertices, mut normals, mut indices) = (Vec::new(), Vec::new(), Vec::new());
+    for facet in body.chunks_exact(BINARY_FACET_LEN).take(count) {
+        let v = |k: usize| {
+            let f = |i: usize| {
+                let at = (k * 3 + i) * 4;
+                f32::from_le_bytes(facet[at..at + 4].try_into().unwrap()) as Real
+            };
+            Vec3::from_xyz(f(0), f(1), f(2))
+        };
+        push_facet(v(0), [v(1), v(2), v(3)], &mut vertices, &mut normals, &mut indices);
+    }
+    Ok(to_display_data(vertices, normals, indices))
+}
+
+/// whitespace separated tokens with their line numbers
+struct Tokens<'a> {
+    iter: Box<dyn Iterator<Item = (usize, &'a str)> + 'a>,
+}
+
+impl<'a> Tokens<'a> {
+    fn next(&mut self) -> Result<(usize, &'a str), StlError> {
+        self.iter.next().ok_or(StlError::UnexpectedEof)
+    }
+
+    fn expect(&mut self, keyword: &str) -> Result<(), StlError> {
+        let (line, t) = self.next()?;
+        if t == keyword {
+            Ok(())
+        } else {
+            Err(StlError::UnexpectedToken { line })
+        }
+    }
+
+    fn vec3(&mut self) -> Result<Vec3, StlError> {
+        let mut v = Vec3::zeros();
+        for i in 0..3 {
+            let (line, t) = self.next()?;
+            v[i] = t.parse().map_err(|_| StlError::InvalidNumber { line })?;
+        }
+        Ok(v)
+    }
+}
+
+fn parse_ascii(src: &str) -> Result<FaceDisplayData, StlError> {
+    // `solid` is followed by an optional name on the same line
+    let mut tokens = Tokens {
+        iter: Box::new(
+            src.lines()
+                .enumerate()
+                .skip_while(|(_, line)| line.trim().is_empty())
+                .skip(1)
+                .flat_map(|(i, line)| line.split_whitespace().map(move |t| (i + 1, t))),
+        ),
+    };
+
+    let (mut vertices, mut normals, mut indices) = (Vec::new(), Vec::new(), Vec::new());
+    loop {
+        match tokens.next()? {
+            (_, "endsolid") => break,
+            (_, "facet") => {}
+            (line, _) => return Err(StlError::UnexpectedToken { line }),
+        }
+        tokens.expect("normal")?;
+        let normal = tokens.vec3()?;
+        tokens.expect("outer")?;
+        tokens.expect("loop")?;
+        let mut pts = [Vec3::zeros(); 3];
+        for p in &mut pts {
+            tokens.expect("vertex")?;
+            *p = tokens.vec3()?;
+        }
+        tokens.expect("endloop")?;
+        tokens.expect("endfacet")?;
+        push_facet(normal, pts, &mut vertices, &mut normals, &mut indices);
+    }
+    Ok(to_display_data(vertices, normals, indices))
+}
@@ -0,0 +1,433 @@
+//! [SVG](https://www.w3.org/TR/SVG11/) writer for `geom2d` shapes
+//!
+//! a document maps a viewport in world coordinate onto an image of `width` pixels, shapes are grouped into layers(Inkscape
+//! layers when opened there). lines and rays are clipped to the viewport, unbounded conics are sampled until they leave it.
+//! stroke widths, point radii and font sizes are in pixels, everything else is in world units.
+
+use std::f64::consts::PI;
+use std::fmt::Write as _;
+use std::{fs, io};
+
+use geometric::geom2d::*;
+use math::matrix::*;
+use math::precision::Real;
+
+/// segments of a sampled hyperbola branch or parabola
+const CONIC_SAMPLES: usize = 128;
+
+#[derive(Clone, Copy, Debug)]
+pub struct SvgStyle {
+    pub stroke: Option<Vec4>,
+    pub stroke_width: Real,
+    pub fill: Option<Vec4>,
+    /// dash and gap length in pixels
+    pub dash: Option<(Real, Real)>,
+}
+
+impl Default for SvgStyle {
+    /// 1 pixel black stroke without fill
+    fn default() -> Self {
+        Self {
+            stroke: Some(Vec4::from_xyzw(0.0, 0.0, 0.0, 1.0)),
+            stroke_width: 1.0,
+            fill: None,
+            dash: None,
+        }
+    }
+}
+
+impl SvgStyle {
+    pub fn stroke(color: Vec4, width: Real) -> Self {
+        Self {
+            stroke: Some(color),
+            stroke_width: width,
+            ..Default::default()
+        }
+    }
+
+    pub fn fill(color: Vec4) -> Self {
+        Self {
+            stroke: None,
+            fill: Some(color),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_fill(mut self, color: Vec4) -> Self {
+        self.fill = Some(color);
+        self
+    }
+
+    pub fn with_dash(mut self, dash: Real, gap: Real) -> Self {
+        self.dash = Some((dash, gap));
+        self
+    }
+
+    fn attributes(&self, fill: bool) -> String {
+        let mut out = String::new();
+        match self.fill.filter(|_| fill) {
+            Some(c) => write!(out, r#"fill="{}""#, color(&c)).unwrap(),
+            None => out.push_str(r#"fill="none""#),
+        }
+        if let Some(c) = self.fill.filter(|c| fill && c.w() < 1.0) {
+            write!(out, r#" fill-opacity="{}""#, num(c.w())).unwrap();
+        }
+        if let Some(c) = self.stroke {
+            write!(out, r#" stroke="{}" stroke-width="{}""#, color(&c), num(self.stroke_width)).unwrap();
+            if c.w() < 1.0 {
+                write!(out, r#" stroke-opacity="{}""#, num(c.w())).unwrap();
+            }
+            if let Some((dash, gap)) = self.dash {
+                write!(out, r#" stroke-dasharray="{} {}""#, num(dash), num(gap)).unwrap();
+            }
+        }
+        out
+    }
+}
+
+/// rgb in `[0, 1]` to css color
+fn color(c: &Vec4) -> String {
+    let channel = |v: Real| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", channel(c.x()), channel(c.y()), channel(c.z()))
+}
+
+/// at most 3 decimals without trailing zeros
+fn num(v: Real) -> String {
+    let s = format!("{:.3}", v);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" { "0".to_string() } else { s.to_string() }
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// mapping from world to image coordinate
+#[derive(Clone, Copy, Debug)]
+struct View {
+    viewport: AABB,
+    scale: Real,
+    y_up: bool,
+}
+
+impl View {
+    fn map(&self, p: &Vec2) -> Vec2 {
+        let (min, max) = (self.viewport.min(), self.viewport.max());
+        let y = if self.y_up { max.y() - p.y() } else { p.y() - min.y() };
+        Vec2::from_xy((p.x() - min.x()) * self.scale, y * self.scale)
+    }
+
+    fn points(&self, pts: &[Vec2]) -> String {
+        let pts: Vec<String> = pts
+            .iter()
+            .map(|p| {
+                let p = self.map(p);
+                format!("{},{}", num(p.x()), num(p.y()))
+            })
+            .collect();
+        pts.join(" ")
+    }
+
+    /// rotation angle in degrees as seen in the image
+    fn degrees(&self, radians: Real) -> Real {
+        let deg = radians.to_degrees();
+        if self.y_up { -deg } else { deg }
+    }
+
+    /// parameter range of `start + dir * t` inside the viewport, [Liang-Barsky](https://en.wikipedia.org/wiki/Liang%E2%80%93Barsky_algorithm)
+    fn clip(&self, start: &Vec2, dir: &Vec2, mut t0: Real, mut t1: Real) -> Option<(Real, Real)> {
+        let (min, max) = (self.viewport.min(), self.viewport.max());
+        for i in 0..2 {
+            if dir[i] == 0.0 {
+                if start[i] < min[i] || start[i] > max[i] {
+                    return None;
+                }
+                continue;
+            }
+            let a = (min[i] - start[i]) / dir[i];
+            let b = (max[i] - start[i]) / dir[i];
+            t0 = t0.max(a.min(b));
+            t1 = t1.min(a.max(b));
+        }
+        (t0 <= t1).then_some((t0, t1))
+    }
+
+    /// distance from `p` to the farthest viewport corner, a curve point farther than it is outside
+    fn reach(&self, p: &Vec2) -> Real {
+        let (min, max) = (self.viewport.min(), self.viewport.max());
+        [min, max, Vec2::from_xy(min.x(), max.y()), Vec2::from_xy(max.x(), min.y())]
+            .iter()
+            .map(|c| (*c - *p).length())
+            .fold(0.0, Real::max)
+    }
+}
+
+pub struct SvgLayer {
+    pub name: String,
+    pub visible: bool,
+    view: View,
+    elements: Vec<String>,
+}
+
+impl SvgLayer {
+    fn push(&mut self, element: String) {
+        self.elements.push(element);
+    }
+
+    fn linear(&mut self, l: &Linear2D, t0: Real, t1: Real, style: &SvgStyle) {
+        if let Some((t0, t1)) = self.view.clip(&l.start, &l.dir, t0, t1) {
+            self.segment_pts(&(l.start + l.dir * t0), &(l.start + l.dir * t1), style);
+        }
+    }
+
+    fn segment_pts(&mut self, a: &Vec2, b: &Vec2, style: &SvgStyle) {
+        let (a, b) = (self.view.map(a), self.view.map(b));
+        self.push(format!(
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" {}/>"#,
+            num(a.x()),
+            num(a.y()),
+            num(b.x()),
+            num(b.y()),
+            style.attributes(false)
+        ));
+    }
+
+    pub fn line(&mut self, line: &Line2D, style: &SvgStyle) {
+        self.linear(line, Real::NEG_INFINITY, Real::INFINITY, style);
+    }
+
+    pub fn ray(&mut self, ray: &Ray2D, style: &SvgStyle) {
+        self.linear(ray, 0.0, Real::INFINITY, style);
+    }
+
+    pub fn segment(&mut self, seg: &Segment2D, style: &SvgStyle) {
+        self.linear(seg, 0.0, seg.len, style);
+    }
+
+    pub fn polyline(&mut self, pts: &[Vec2], style: &SvgStyle) {
+        let element = format!(r#"<polyline points="{}" {}/>"#, self.view.points(pts), style.attributes(false));
+        self.push(element);
+    }
+
+    pub fn polygon(&mut self, polygon: &Polygon, style: &SvgStyle) {
+        let element = format!(r#"<polygon points="{}" {}/>"#, self.view.points(&polygon.points), style.attributes(true));
+        self.push(element);
+    }
+
+    pub fn triangle(&mut self, tri: &Triangle2D, style: &SvgStyle) {
+        self.polygon(&Polygon::new(tri.pts.to_vec()), style);
+    }
+
+    pub fn aabb(&mut self, aabb: &AABB, style: &SvgStyle) {
+        let (min, max) = (aabb.min(), aabb.max());
+        let corners = vec![min, Vec2::from_xy(max.x(), min.y()), max, Vec2::from_xy(min.x(), max.y())];
+        self.polygon(&Polygon::new(corners), style);
+    }
+
+    pub fn obb(&mut self, obb: &OBB, style: &SvgStyle) {
+        let (x, y) = (obb.x_axis() * obb.half_size.x(), obb.y_axis() * obb.half_size.y());
+        let c = obb.center;
+        self.polygon(&Polygon::new(vec![c - x - y, c + x - y, c + x + y, c - x + y]), style);
+    }
+
+    pub fn circle(&mut self, circle: &Circle, style: &SvgStyle) {
+        let c = self.view.map(&circle.center);
+        self.push(format!(
+            r#"<circle cx="{}" cy="{}" r="{}" {}/>"#,
+            num(c.x()),
+            num(c.y()),
+            num(circle.radius * self.view.scale),
+            style.attributes(true)
+        ));
+    }
+
+    /// a full turn is drawn as a circle, otherwise as an open arc
+    pub fn arc(&mut self, arc: &CircleArc, style: &SvgStyle) {
+        if arc.sweep() >= 2.0 * PI - CircleArc::ANGLE_EPSILON {
+            self.circle(&arc.circle(), style);
+            return;
+        }
+        let (a, b) = (self.view.map(&arc.start_pt()), self.view.map(&arc.end_pt()));
+        let r = num(arc.radius * self.view.scale);
+        let large = (arc.sweep() > PI) as u8;
+        // counter-clockwise in world is counter-clockwise on screen only when y is flipped
+        let sweep = (!self.view.y_up) as u8;
+        self.push(format!(
+            r#"<path d="M {} {} A {} {} 0 {} {} {} {}" {}/>"#,
+            num(a.x()),
+            num(a.y()),
+            r,
+            r,
+            large,
+            sweep,
+            num(b.x()),
+            num(b.y()),
+            style.attributes(false)
+        ));
+    }
+
+    pub fn ellipse(&mut self, ellipse: &Ellipse, style: &SvgStyle) {
+        let c = self.view.map(&ellipse.position);
+        let (cx, cy) = (num(c.x()), num(c.y()));
+        self.push(format!(
+            r#"<ellipse cx="{}" cy="{}" rx="{}" ry="{}" transform="rotate({} {} {})" {}/>"#,
+            cx,
+            cy,
+            num(ellipse.a * self.view.scale),
+            num(ellipse.b * self.view.scale),
+            num(self.view.degrees(ellipse.rotation)),
+            cx,
+            cy,
+            style.attributes(true)
+        ));
+    }
+
+    /// hyperbola gets both branches, open curves are sampled until they leave the viewport
+    pub fn conic(&mut self, conic: &Conic, style: &SvgStyle) {
+        let reach = self.view.reach(&conic.position());
+        let sample = |f: &dyn Fn(Real) -> Vec2, range: Real| -> Vec<Vec2> {
+            (0..=CONIC_SAMPLES)
+                .map(|i| f(-range + 2.0 * range * i as Real / CONIC_SAMPLES as Real))
+                .map(|p| conic.to_world(&p))
+                .collect()
+        };
+        match conic {
+            Conic::Ellipse(e) => self.ellipse(e, style),
+            Conic::Hyperbola(h) => {
+                // |x| = a * cosh(t) beyond reach is outside
+                let range = (reach / h.a).max(1.0).acosh();
+                for sign in [1.0, -1.0] {
+                    let branch = sample(&|t: Real| Vec2::from_xy(sign * h.a * t.cosh(), h.b * t.sinh()), range);
+                    self.polyline(&branch, style);
+                }
+            }
+            Conic::Parabola(p) => {
+                let curve = sample(&|y: Real| Vec2::from_xy(y * y / (2.0 * p.p), y), reach);
+                self.polyline(&curve, style);
+            }
+        }
+    }
+
+    /// dot of `radius` pixels
+    pub fn point(&mut self, p: &Vec2, radius: Real, color: Vec4) {
+        let c = self.view.map(p);
+        self.push(format!(
+            r#"<circle cx="{}" cy="{}" r="{}" {}/>"#,
+            num(c.x()),
+            num(c.y()),
+            num(radius),
+            SvgStyle::fill(color).attributes(true)
+        ));
+    }
+
+    /// text with its baseline starting at `p`, `size` in pixels
+    pub fn label(&mut self, p: &Vec2, text: &str, size: Real, color: Vec4) {
+        let c = self.view.map(p);
+        self.push(format!(
+            r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" {}>{}</text>"#,
+            num(c.x()),
+            num(c.y()),
+            num(size),
+            SvgStyle::fill(color).attributes(true),
+            escape(text)
+        ));
+    }
+}
+
+pub struct SvgDocument {
+    pub width: u32,
+    pub height: u32,
+    pub background: Option<Vec4>,
+    view: View,
+    layers: Vec<SvgLayer>,
+}
+
+impl SvgDocument {
+    /// image of `width` pixels showing `viewport`, height follows its aspect ratio.
+    /// with `y_up` y axis points up as in a plot, otherwise down as in screen coordinate.
+    /// error if the viewport is empty in either axis or not finite, or `width` is zero
+    pub fn new(viewport: AABB, width: u32, y_up: bool) -> Result<Self, &'static str> {
+        let size = viewport.size();
+        if !(size.x() > 0.0 && size.y() > 0.0 && size.x().is_finite() && size.y().is_finite()) {
+            return Err("degenerate viewport");
+        }
+        if width == 0 {
+            return Err("zero width");
+        }
+        let scale = width as Real / size.x();
+        Ok(Self {
+            width,
+            height: (size.y() * scale).round().max(1.0) as u32,
+            background: None,
+            view: View { viewport, scale, y_up },
+            layers: Vec::new(),
+        })
+    }
+
+    /// layer of `name`, created on top of the others if it doesn't exist
+    pub fn layer(&mut self, name: &str) -> &mut SvgLayer {
+        let i = match self.layers.iter().position(|l| l.name == name) {
+            Some(i) => i,
+            None => {
+                self.layers.push(SvgLayer {
+                    name: name.to_string(),
+                    visible: true,
+                    view: self.view,
+                    elements: Vec::new(),
+                });
+                self.layers.len() - 1
+            }
+        };
+        &mut self.layers[i]
+    }
+
+    pub fn layers(&self) -> &[SvgLayer] {
+        &self.layers
+    }
+
+    pub fn to_svg(&self) -> String {
+        let mut out = String::new();
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+            self.width, self.height
+        )
+        .unwrap();
+        if let Some(c) = self.background {
+            writeln!(out, r#"<rect width="100%" height="100%" {}/>"#, SvgStyle::fill(c).attributes(true)).unwrap();
+        }
+        // names can be anything, ids have to be unique XML names
+        for (i, layer) in self.layers.iter().enumerate() {
+            let hidden = if layer.visible { "" } else { r#" style="display:none""# };
+            writeln!(
+                out,
+                r#"<g inkscape:groupmode="layer" inkscape:label="{}" id="layer{}"{}>"#,
+                escape(&layer.name),
+                i + 1,
+                hidden
+            )
+            .unwrap();
+            for element in &layer.elements {
+                writeln!(out, "{}", element).unwrap();
+            }
+            writeln!(out, "</g>").unwrap();
+        }
+        writeln!(out, "</svg>").unwrap();
+        out
+    }
+
+    pub fn write_to_file(&self, filename: &str) -> Result<(), io::Error> {
+        fs::write(filename, self.to_svg())
+    }
+}
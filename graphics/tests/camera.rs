@@ -0,0 +1,142 @@
+#[cfg(test)]
+mod test {
+    use geometric::geom3d::Frustum;
+    use graphics::camera::*;
+    use math::matrix::*;
+    use math::precision::Real;
+
+    fn cameras() -> Vec<Camera> {
+        let viewport = Vec2::from_xy(800.0, 600.0);
+        let mut result = Vec::new();
+        for projection in [
+            Projection::Perspective { half_fovy: 0.5 },
+            Projection::Orthographic { half_height: 3.0 },
+        ] {
+            for depth_range in [DepthRange::NegOneToOne, DepthRange::ZeroToOne] {
+                let mut camera = Camera::new(projection, 0.5, 50.0, viewport);
+                camera.depth_range = depth_range;
+                camera.look_at(Vec3::from_xyz(3.0, 2.0, 5.0), Vec3::from_xyz(0.0, 1.0, -1.0));
+                result.push(camera);
+            }
+        }
+        result
+    }
+
+    fn near_depth(camera: &Camera) -> Real {
+        match camera.depth_range {
+            DepthRange::NegOneToOne => -1.0,
+            DepthRange::ZeroToOne => 0.0,
+        }
+    }
+
+    #[test]
+    fn screen_world_round_trip() {
+        for camera in cameras() {
+            let center = camera.world_to_screen(&camera.target).unwrap();
+            assert!((center.x() - 400.0).abs() < 1e-9 && (center.y() - 300.0).abs() < 1e-9);
+
+            let near = camera.position + camera.forward() * camera.near;
+            let far = camera.position + camera.forward() * camera.far;
+            assert!((camera.world_to_screen(&near).unwrap().z() - near_depth(&camera)).abs() < 1e-9);
+            assert!((camera.world_to_screen(&far).unwrap().z() - 1.0).abs() < 1e-9);
+
+            let pt = camera.target + Vec3::from_xyz(0.7, -0.4, 0.3);
+            let screen = camera.world_to_screen(&pt).unwrap();
+            assert!((camera.screen_to_world(&screen).unwrap() - pt).length() < 1e-9);
+
+            // right and up on screen
+            let moved = camera.world_to_screen(&(camera.target + camera.right() * 0.1)).unwrap();
+            assert!(moved.x() > center.x());
+            let moved = camera.world_to_screen(&(camera.target + camera.up * 0.1)).unwrap();
+            assert!(moved.y() < center.y());
+        }
+    }
+
+    #[test]
+    fn projection_extent() {
+        let camera = cameras()[0];
+        let dist = 10.0;
+        let half_h = dist * (0.5 as Real).tan();
+        let frame = camera.frame();
+        let corner = camera.position + camera.forward() * dist
+            - frame.x_axis() * (half_h * camera.aspect())
+            + frame.y_axis() * half_h;
+        let screen = camera.world_to_screen(&corner).unwrap();
+        assert!(screen.x().abs() < 1e-9 && screen.y().abs() < 1e-9);
+        assert!(camera.world_to_screen(&(camera.position - camera.forward())).is_none());
+
+        let ortho = cameras()[2];
+        let frame = ortho.frame();
+        let corner = ortho.target + frame.x_axis() * (3.0 * ortho.aspect()) - frame.y_axis() * 3.0;
+        let screen = ortho.world_to_screen(&corner).unwrap();
+        assert!((screen.x() - 800.0).abs() < 1e-9 && (screen.y() - 600.0).abs() < 1e-9);
+
+        // frustum agrees with the one placed at camera frame
+        let placed = Frustum::new(camera.near, camera.far, 0.5, camera.aspect()).placed(&frame);
+        for (a, b) in placed.corners.iter().zip(&camera.frustum().corners) {
+            assert!((*a - *b).length() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn picking_ray() {
+        for camera in cameras() {
+            let pixel = Vec2::from_xy(123.0, 456.0);
+            let ray = camera.screen_ray(&pixel).unwrap();
+            let start = camera.world_to_screen(&ray.start).unwrap();
+            assert!((start.z() - near_depth(&camera)).abs() < 1e-9);
+            for t in [0.0, 1.0, 10.0, 40.0] {
+                let screen = camera.world_to_screen(&(ray.start + ray.dir * t)).unwrap();
+                assert!((screen.x() - pixel.x()).abs() < 1e-6 && (screen.y() - pixel.y()).abs() < 1e-6);
+            }
+            assert!(ray.dir.dot(&camera.forward()) > 0.0);
+        }
+
+        let camera = cameras()[0];
+        let ray = camera.screen_ray(&Vec2::from_xy(400.0, 300.0)).unwrap();
+        assert!((ray.dir - camera.forward()).length() < 1e-9);
+
+        // eye at target, and up along the view direction
+        let mut degenerate = cameras()[0];
+        degenerate.target = degenerate.position;
+        assert!(degenerate.inv_view_proj_mat().is_none());
+        assert!(degenerate.screen_ray(&Vec2::from_xy(400.0, 300.0)).is_none());
+        let mut degenerate = cameras()[0];
+        degenerate.up = degenerate.forward();
+        assert!(degenerate.screen_to_world(&Vec3::from_xyz(400.0, 300.0, 0.0)).is_none());
+        assert!(degenerate.screen_ray(&Vec2::from_xy(400.0, 300.0)).is_none());
+    }
+
+    #[test]
+    fn controls() {
+        let mut camera = cameras()[0];
+        let dist = (camera.position - camera.target).length();
+        camera.orbit(0.3, 0.2);
+        assert!(((camera.position - camera.target).length() - dist).abs() < 1e-9);
+
+        let height = camera.position.y();
+        camera.orbit(0.0, 0.1);
+        assert!(camera.position.y() > height);
+        // pitch stops before the pole
+        camera.orbit(0.0, 10.0);
+        assert!(camera.forward().dot(&camera.up).abs() < 1.0 - 1e-7);
+        assert!(camera.forward().y() < 0.0);
+
+        camera.zoom(0.5);
+        assert!(((camera.position - camera.target).length() - dist * 0.5).abs() < 1e-9);
+
+        let mut camera = cameras()[0];
+        let forward = camera.forward();
+        camera.fly_rotate(0.0, 0.2);
+        assert!(camera.forward().y() > forward.y());
+        // yaw keeps pitch
+        let pitch = camera.forward().y();
+        camera.fly_rotate(0.5, 0.0);
+        assert!((camera.forward().y() - pitch).abs() < 1e-12);
+
+        let (position, target) = (camera.position, camera.target);
+        camera.fly_move(&Vec3::from_xyz(0.0, 0.0, 2.0));
+        assert!((camera.position - position - camera.forward() * 2.0).length() < 1e-9);
+        assert!((camera.target - target - camera.forward() * 2.0).length() < 1e-9);
+    }
+}
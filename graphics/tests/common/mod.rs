@@ -0,0 +1,15 @@
+//! helpers shared by integration tests, each test binary uses a different subset
+#![allow(dead_code)]
+
+use math::precision::Real;
+
+/// next state of a linear congruential generator, high bits are the most random
+pub fn lcg_next(seed: &mut u64) -> u64 {
+    *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    *seed
+}
+
+/// deterministic pseudo random number in [0, 1)
+pub fn rand(seed: &mut u64) -> Real {
+    (lcg_next(seed) >> 11) as Real / (1u64 << 53) as Real
+}
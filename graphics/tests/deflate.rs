@@ -0,0 +1,74 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use graphics::deflate::*;
+    use crate::common::lcg_next;
+
+    // python zlib at level 9, dynamic Huffman codes
+    const ZLIB_DYNAMIC: &str = "78daedd0b701c2401000b09e296e04f23e0463f2837993a6870168aea053af4a75dbc4b5dfad0eb1eccae31c9bf28c61ecfbd3e516e5de7451bfe0b878bf625dda41fda147293d4ee9494a4f537a96d2f3943668d0a04183060d1a3468d0a04183060d1a3468d0a04183060d1a3468d0a04183060d1a3468f03f831ffcfcc3df";
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap()).collect()
+    }
+
+    fn text() -> Vec<u8> {
+        (0..200)
+            .flat_map(|i| format!("the quick brown fox {} jumps over the lazy dog\n", i % 7).into_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut seed = 1u64;
+        let noise: Vec<u8> = (0..70000).map(|_| (lcg_next(&mut seed) >> 60) as u8).collect();
+        let runs: Vec<u8> = (0..100000).map(|i| (i / 1000) as u8).collect();
+        for data in [Vec::new(), vec![7], text(), noise, runs] {
+            for compression in [Compression::Store, Compression::Fixed] {
+                let compressed = zlib_compress(&data, compression);
+                assert_eq!(zlib_decompress(&compressed, usize::MAX).unwrap(), data);
+            }
+        }
+        assert!(zlib_compress(&text(), Compression::Fixed).len() < text().len() / 10);
+    }
+
+    #[test]
+    fn dynamic_codes() {
+        assert_eq!(zlib_decompress(&hex(ZLIB_DYNAMIC), usize::MAX).unwrap(), text());
+    }
+
+    #[test]
+    fn checksum() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11E60398);
+        assert_eq!(adler32(&[]), 1);
+        assert_eq!(adler32(&[255; 100000]), 0x149A302C);
+    }
+
+    #[test]
+    fn malformed() {
+        let valid = zlib_compress(&text(), Compression::Fixed);
+        let mut checksum = valid.clone();
+        *checksum.last_mut().unwrap() ^= 1;
+        assert_eq!(zlib_decompress(&checksum, usize::MAX), Err("adler32 mismatch"));
+        assert_eq!(zlib_decompress(&valid[..valid.len() / 2], usize::MAX), Err("unexpected end of deflate stream"));
+        assert_eq!(zlib_decompress(&[0x78, 0x02, 3, 0, 0, 0, 0, 1], usize::MAX), Err("invalid zlib header"));
+        // reserved block type 3
+        assert_eq!(inflate(&[0x07], usize::MAX).err(), Some("invalid block type"));
+        // stored block with wrong complement length
+        assert_eq!(inflate(&[0x01, 1, 0, 0, 0, 42], usize::MAX).err(), Some("stored block length mismatch"));
+    }
+
+    #[test]
+    fn output_limit() {
+        let too_long = Err("decompressed data is longer than expected");
+        for compression in [Compression::Store, Compression::Fixed] {
+            let compressed = zlib_compress(&text(), compression);
+            assert_eq!(zlib_decompress(&compressed, text().len()).unwrap(), text());
+            assert_eq!(zlib_decompress(&compressed, text().len() - 1), too_long);
+        }
+        // small stream expanding to megabytes stops at the limit
+        let bomb = zlib_compress(&vec![0; 1 << 22], Compression::Fixed);
+        assert!(bomb.len() < 1 << 16);
+        assert_eq!(zlib_decompress(&bomb, 1000), too_long);
+    }
+}
@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod test {
+    use geometric::geom3d::*;
+    use graphics::gltf::*;
+    use graphics::mesh_generate::*;
+    use math::coord::Cartesian3D;
+    use math::matrix::*;
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    fn f32_at(bytes: &[u8], at: usize) -> f32 {
+        f32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    fn scene() -> GltfScene {
+        let cube = Cube::from_min_max(Vec3::zeros(), Vec3::from_xyz(1.0, 2.0, 3.0));
+        let frame = Cartesian3D::new(Vec3::y_axis(), Vec3::z_axis(), Vec3::x_axis(), Vec3::from_xyz(5.0, 6.0, 7.0));
+        GltfScene {
+            nodes: vec![
+                GltfNode {
+                    name: "box \"a\"".to_string(),
+                    transform: frame,
+                    mesh: GltfMesh::Faces(cube_to_display_data(&cube, Vec4::from_xyzw(1.0, 0.5, 0.25, 0.5), 1)),
+                },
+                GltfNode {
+                    name: "path".to_string(),
+                    transform: Cartesian3D::default(),
+                    mesh: GltfMesh::Lines(LineStripDisplayData {
+                        vertices: vec![Vec3::zeros(), Vec3::x_axis(), Vec3::from_xyz(1.0, 1.0, 0.0)],
+                        color: Vec4::ones(),
+                    }),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn gltf() {
+        let scene = scene();
+        let (json, bin) = scene.to_gltf("scene.bin").unwrap();
+        assert!(json.contains(r#""version":"2.0""#));
+        assert!(json.contains(&format!(r#""buffers":[{{"byteLength":{},"uri":"scene.bin"}}]"#, bin.len())));
+        assert!(json.contains(r#""name":"box \"a\"""#));
+        assert!(json.contains(r#""matrix":[0,1,0,0,0,0,1,0,1,0,0,0,5,6,7,1]"#));
+        assert!(json.contains(r#""baseColorFactor":[1,0.5,0.25,0.5]"#));
+        assert!(json.contains(r#""alphaMode":"BLEND""#));
+        assert!(json.contains(r#""min":[0,0,0],"max":[1,2,3]"#));
+        assert!(json.contains(r#""mode":4"#));
+        assert!(json.contains(r#""attributes":{"POSITION":3},"material":1,"mode":3"#));
+        assert_eq!(json.matches('{').count(), json.matches('}').count());
+
+        // positions, normals and indices of the box come first, then the line strip
+        let GltfMesh::Faces(data) = &scene.nodes[0].mesh else { unreachable!() };
+        let n = data.vertices.len();
+        for (i, v) in data.vertices.iter().enumerate() {
+            for k in 0..3 {
+                assert_eq!(f32_at(&bin, i * 12 + k * 4), v[k] as f32);
+            }
+        }
+        for (i, v) in data.normals.iter().enumerate() {
+            assert_eq!(f32_at(&bin, (n + i) * 12), v.x() as f32);
+        }
+        for (i, index) in data.indices.iter().enumerate() {
+            assert_eq!(u32_at(&bin, n * 24 + i * 4), *index);
+        }
+        assert_eq!(bin.len(), n * 24 + data.indices.len() * 4 + 3 * 12);
+    }
+
+    #[test]
+    fn glb() {
+        let scene = scene();
+        let glb = scene.to_glb().unwrap();
+        let (json, bin) = scene.to_gltf("unused.bin").unwrap();
+        assert_eq!(u32_at(&glb, 0), 0x46546C67);
+        assert_eq!(u32_at(&glb, 4), 2);
+        assert_eq!(u32_at(&glb, 8) as usize, glb.len());
+
+        let json_len = u32_at(&glb, 12) as usize;
+        assert_eq!(json_len % 4, 0);
+        assert_eq!(&glb[16..20], b"JSON");
+        let glb_json = std::str::from_utf8(&glb[20..20 + json_len]).unwrap();
+        assert!(!glb_json.contains("uri"));
+        assert_eq!(glb_json.trim_end(), json.replace(r#","uri":"unused.bin""#, ""));
+
+        let at = 20 + json_len;
+        let bin_len = u32_at(&glb, at) as usize;
+        assert_eq!(bin_len % 4, 0);
+        assert_eq!(&glb[at + 4..at + 8], b"BIN\0");
+        assert_eq!(&glb[at + 8..at + 8 + bin.len()], &bin[..]);
+        assert_eq!(at + 8 + bin_len, glb.len());
+    }
+
+    #[test]
+    fn empty() {
+        let mut scene = GltfScene::default();
+        let glb = scene.to_glb().unwrap();
+        assert_eq!(glb.len(), 20 + u32_at(&glb, 12) as usize);
+        scene.nodes.push(GltfNode {
+            name: "empty".to_string(),
+            transform: Cartesian3D::default(),
+            mesh: GltfMesh::Lines(LineStripDisplayData {
+                vertices: Vec::new(),
+                color: Vec4::ones(),
+            }),
+        });
+        let (json, bin) = scene.to_gltf("empty.bin").unwrap();
+        assert!(bin.is_empty());
+        assert!(json.contains(r#""nodes":[{"name":"empty","matrix":[1,0,0,0,0,1,0,0,0,0,1,0,0,0,0,1]}]"#));
+        assert!(!json.contains("buffers"));
+    }
+
+    #[test]
+    fn non_finite() {
+        let nan = Vec3::from_xyz(0.0, f64::NAN, 0.0);
+        let mut broken = [scene(), scene(), scene(), scene()];
+        let GltfMesh::Faces(data) = &mut broken[0].nodes[0].mesh else { unreachable!() };
+        data.vertices[1] = nan;
+        let GltfMesh::Lines(data) = &mut broken[1].nodes[1].mesh else { unreachable!() };
+        // finite but beyond f32
+        data.vertices[0] = Vec3::from_xyz(1e300, 0.0, 0.0);
+        broken[2].nodes[0].transform = Cartesian3D::new(Vec3::x_axis(), Vec3::y_axis(), Vec3::z_axis(), nan);
+        let GltfMesh::Faces(data) = &mut broken[3].nodes[0].mesh else { unreachable!() };
+        data.color = Vec4::from_xyzw(1.0, f64::INFINITY, 1.0, 1.0);
+
+        for (scene, name) in broken.iter().zip(["box \"a\"", "path", "box \"a\"", "box \"a\""]) {
+            assert!(matches!(scene.to_gltf("scene.bin"), Err(GltfError::NonFinite(n)) if n == name));
+            assert!(matches!(scene.to_glb(), Err(GltfError::NonFinite(_))));
+        }
+        let path = std::env::temp_dir().join("graphics_test_non_finite.glb");
+        assert!(matches!(broken[0].write_glb(path.to_str().unwrap()), Err(GltfError::NonFinite(_))));
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,230 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use geometric::geom2d::Annulus;
+    use geometric::geom3d::*;
+    use graphics::half_edge::*;
+    use graphics::mesh_generate::*;
+    use math::matrix::*;
+    use math::precision::Real;
+    use crate::common::rand;
+
+    /// links are consistent, every face is a counter-clockwise triangle and every vertex fan is a single one
+    fn check_invariants(mesh: &Mesh) {
+        for h in mesh.half_edges() {
+            let he = mesh.half_edge(h);
+            assert_eq!(mesh.half_edge(he.twin).twin, h);
+            assert_ne!(he.twin, h);
+            assert_eq!(mesh.half_edge(he.next).prev, h);
+            assert_eq!(mesh.origin(he.next), he.vertex);
+            assert_eq!(mesh.half_edge(he.next).face, he.face);
+            assert_ne!(mesh.origin(h), he.vertex);
+            if he.face.is_some() {
+                assert_eq!(mesh.half_edge(mesh.half_edge(he.next).next).next, h);
+            }
+        }
+        for f in mesh.faces() {
+            let h = mesh.face(f).half_edge;
+            assert_eq!(mesh.half_edge(h).face, Some(f));
+            let [a, b, c] = mesh.face_vertices(f);
+            assert!(a != b && b != c && c != a);
+        }
+        for v in mesh.vertices() {
+            let out: Vec<usize> = mesh.outgoing(v).collect();
+            assert!(out.iter().all(|h| mesh.origin(*h) == v));
+            let boundary = out.iter().filter(|h| mesh.half_edge(**h).face.is_none()).count();
+            assert!(boundary <= 1);
+            assert_eq!(mesh.is_boundary_vertex(v), boundary == 1 || out.is_empty());
+            let total = mesh.half_edges().filter(|h| mesh.origin(*h) == v).count();
+            assert_eq!(out.len(), total);
+        }
+    }
+
+    fn volume(mesh: &Mesh) -> Real {
+        mesh.faces()
+            .map(|f| {
+                let [a, b, c] = mesh.face_vertices(f).map(|v| mesh.position(v));
+                a.dot(&b.cross(&c)) / 6.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn topology() {
+        let color = Vec4::ones();
+        let cube = Cube::from_min_max(Vec3::zeros(), Vec3::from_xyz(1.0, 2.0, 3.0));
+        let mesh = Mesh::from_display_data(&cube_to_display_data(&cube, color, 1)).unwrap();
+        check_invariants(&mesh);
+        assert_eq!((mesh.vertex_count(), mesh.edge_count(), mesh.face_count()), (8, 18, 12));
+        assert_eq!(mesh.euler_characteristic(), 2);
+        assert!(mesh.is_closed() && mesh.boundary_loops().is_empty());
+        assert!((volume(&mesh) - 6.0).abs() < 1e-9);
+        let valence: usize = mesh.vertices().map(|v| mesh.valence(v)).sum();
+        assert_eq!(valence, 36);
+
+        let data = mesh.to_display_data(color);
+        assert_eq!((data.vertices.len(), data.indices.len()), (8, 36));
+        let corner = data.vertices.iter().position(|v| v.length() < 1e-9).unwrap();
+        assert!((data.normals[corner] + Vec3::ones().normalize()).length() < 0.5);
+        let round_trip = Mesh::from_display_data(&data).unwrap();
+        assert!((volume(&round_trip) - 6.0).abs() < 1e-9);
+
+        let torus = Torus::new(Vec3::zeros(), Vec3::z_axis(), 2.0, 0.5);
+        let mesh = Mesh::from_display_data(&torus_to_display_data(&torus, color, 16, 8)).unwrap();
+        check_invariants(&mesh);
+        assert_eq!(mesh.euler_characteristic(), 0);
+        assert!(mesh.vertices().all(|v| mesh.valence(v) == 6));
+
+        let plane = Plane::new(Vec3::z_axis(), Vec3::zeros());
+        let mesh = Mesh::from_display_data(&plane_to_display_data(&plane, color, 1.0, 4)).unwrap();
+        check_invariants(&mesh);
+        assert_eq!(mesh.euler_characteristic(), 1);
+        let loops = mesh.boundary_loops();
+        assert_eq!(loops.len(), 1);
+        assert_eq!(loops[0].len(), 16);
+        assert_eq!(mesh.vertices().filter(|v| !mesh.is_boundary_vertex(*v)).count(), 9);
+
+        let annulus = Annulus::new(Vec2::zeros(), 1.0, 2.0);
+        let mesh = Mesh::from_display_data(&annulus_to_display_data(&annulus, color, 24)).unwrap();
+        check_invariants(&mesh);
+        assert_eq!(mesh.euler_characteristic(), 0);
+        let mut lens: Vec<usize> = mesh.boundary_loops().iter().map(|l| l.len()).collect();
+        lens.sort();
+        assert_eq!(lens, vec![24, 24]);
+    }
+
+    #[test]
+    fn non_manifold() {
+        let pts = vec![
+            Vec3::zeros(),
+            Vec3::x_axis(),
+            Vec3::y_axis(),
+            Vec3::z_axis(),
+            -Vec3::y_axis(),
+            -Vec3::x_axis(),
+        ];
+        // three triangles on one edge
+        assert!(Mesh::from_triangles(pts.clone(), &[[0, 1, 2], [1, 0, 3], [1, 0, 4]]).is_err());
+        // faces with opposite orientations
+        assert!(Mesh::from_triangles(pts.clone(), &[[0, 1, 2], [0, 1, 3]]).is_err());
+        // bow tie touching at a vertex
+        assert!(Mesh::from_triangles(pts.clone(), &[[0, 1, 2], [0, 5, 4]]).is_err());
+        assert!(Mesh::from_triangles(pts.clone(), &[[0, 1, 9]]).is_err());
+
+        let mut data = FaceDisplayData {
+            vertices: pts.clone(),
+            normals: Vec::new(),
+            indices: vec![0, 1, 2],
+            color: Vec4::ones(),
+            uvs: None,
+            tangents: None,
+        };
+        assert!(Mesh::from_display_data(&data).is_ok());
+        data.indices = vec![0, 1, 6];
+        assert_eq!(Mesh::from_display_data(&data).err(), Some("invalid vertex index"));
+        data.indices = vec![0, 1, 2, 2];
+        assert_eq!(Mesh::from_display_data(&data).err(), Some("index count is not a multiple of 3"));
+
+        let mesh = Mesh::from_triangles(pts, &[[0, 1, 2], [2, 5, 0], [0, 0, 3]]).unwrap();
+        check_invariants(&mesh);
+        assert_eq!(mesh.face_count(), 2);
+        assert!(mesh.vertex(3).half_edge.is_none() && mesh.is_boundary_vertex(3));
+        let mut ring: Vec<usize> = mesh.vertex_neighbors(0).collect();
+        ring.sort();
+        assert_eq!(ring, vec![1, 2, 5]);
+    }
+
+    #[test]
+    fn flip() {
+        let pts = vec![
+            Vec3::zeros(),
+            Vec3::x_axis(),
+            Vec3::from_xyz(1.0, 1.0, 0.0),
+            Vec3::y_axis(),
+        ];
+        let mut mesh = Mesh::from_triangles(pts, &[[0, 1, 2], [0, 2, 3]]).unwrap();
+        let diagonal = mesh.find_half_edge(0, 2).unwrap();
+        assert!(!mesh.flip_edge(mesh.find_half_edge(0, 1).unwrap()));
+        assert!(mesh.flip_edge(diagonal));
+        check_invariants(&mesh);
+        assert!(mesh.find_half_edge(0, 2).is_none());
+        assert!(mesh.find_half_edge(1, 3).is_some() && mesh.find_half_edge(3, 1).is_some());
+        assert_eq!(mesh.valence(0), 2);
+        for f in mesh.faces() {
+            let [a, b, c] = mesh.face_vertices(f).map(|v| mesh.position(v));
+            assert!((b - a).cross(&(c - a)).z() > 0.0);
+        }
+        // flipping back restores the diagonal
+        assert!(mesh.flip_edge(diagonal));
+        check_invariants(&mesh);
+        assert!(mesh.find_half_edge(0, 2).is_some());
+
+        // a tetrahedron edge can't flip, the other diagonal is already an edge
+        let tetra = vec![Vec3::zeros(), Vec3::x_axis(), Vec3::y_axis(), Vec3::z_axis()];
+        let mut mesh = Mesh::from_triangles(tetra, &[[0, 2, 1], [0, 1, 3], [1, 2, 3], [0, 3, 2]]).unwrap();
+        assert!(mesh.is_closed());
+        assert!(mesh.edges().collect::<Vec<_>>().into_iter().all(|h| !mesh.flip_edge(h)));
+    }
+
+    #[test]
+    fn split_and_collapse() {
+        let color = Vec4::ones();
+        let sphere = Sphere::new(Vec3::zeros(), 1.0);
+        let mut mesh = Mesh::from_display_data(&icosphere_to_display_data(&sphere, color, 1)).unwrap();
+        let (v, e, f) = (mesh.vertex_count(), mesh.edge_count(), mesh.face_count());
+        let edges: Vec<usize> = mesh.edges().collect();
+        for h in &edges {
+            let mid = (mesh.position(mesh.origin(*h)) + mesh.position(mesh.half_edge(*h).vertex)) * 0.5;
+            let m = mesh.split_edge(*h, mid);
+            assert_eq!(mesh.valence(m), 4);
+        }
+        check_invariants(&mesh);
+        let n = edges.len();
+        assert_eq!((mesh.vertex_count(), mesh.edge_count(), mesh.face_count()), (v + n, e + 3 * n, f + 2 * n));
+        assert_eq!(mesh.euler_characteristic(), 2);
+
+        // collapse random edges until most of them are rejected
+        let mut seed = 3;
+        let mut collapsed = 0;
+        for _ in 0..2000 {
+            let edges: Vec<usize> = mesh.edges().collect();
+            let h = edges[(rand(&mut seed) * edges.len() as Real) as usize];
+            let (a, b) = (mesh.origin(h), mesh.half_edge(h).vertex);
+            let mid = (mesh.position(a) + mesh.position(b)) * 0.5;
+            if mesh.collapse_edge(h, mid) {
+                collapsed += 1;
+                assert!(mesh.position(a) == mid);
+                check_invariants(&mesh);
+                assert_eq!(mesh.euler_characteristic(), 2);
+            }
+        }
+        assert!(collapsed > 50, "{}", collapsed);
+        assert_eq!(mesh.face_count(), 2 * mesh.vertex_count() - 4);
+
+        // collapses on an open grid keep its boundary a single loop
+        let plane = Plane::new(Vec3::z_axis(), Vec3::zeros());
+        let mut mesh = Mesh::from_display_data(&plane_to_display_data(&plane, color, 1.0, 6)).unwrap();
+        let mut collapsed = 0;
+        for _ in 0..500 {
+            let edges: Vec<usize> = mesh.edges().collect();
+            let h = edges[(rand(&mut seed) * edges.len() as Real) as usize];
+            let pos = mesh.position(mesh.origin(h));
+            if mesh.collapse_edge(h, pos) {
+                collapsed += 1;
+                check_invariants(&mesh);
+                assert_eq!(mesh.euler_characteristic(), 1);
+                assert_eq!(mesh.boundary_loops().len(), 1);
+            }
+        }
+        assert!(collapsed > 10, "{}", collapsed);
+
+        // splitting a boundary edge adds one triangle
+        let h = mesh.half_edges().find(|h| mesh.half_edge(*h).face.is_none()).unwrap();
+        let f = mesh.face_count();
+        let m = mesh.split_edge(h, mesh.position(mesh.origin(h)) * 0.5 + mesh.position(mesh.half_edge(h).vertex) * 0.5);
+        check_invariants(&mesh);
+        assert!(mesh.is_boundary_vertex(m));
+        assert_eq!(mesh.face_count(), f + 1);
+    }
+}
@@ -0,0 +1,30 @@
+#[cfg(test)]
+mod test {
+    use graphics::image::{Image, ImageError};
+
+    #[test]
+    fn pixels() {
+        let mut image = Image::from_fn(3, 2, |x, y| x + y * 10);
+        assert_eq!(image.data(), &[0, 1, 2, 10, 11, 12]);
+        assert_eq!(image.row(1), &[10, 11, 12]);
+        assert_eq!(image[(2, 1)], 12);
+        assert_eq!(image.get(2, 1), Some(&12));
+        assert_eq!(image.get(3, 0), None);
+        assert_eq!(image.get(0, 2), None);
+
+        image[(0, 1)] = 7;
+        assert_eq!(image.map(|v| v * 2).row(1), &[14, 22, 24]);
+        image.fill(1);
+        assert!(image.data().iter().all(|v| *v == 1));
+
+        assert_eq!(Image::new(2, 2, 'a'), Image::from_data(2, 2, vec!['a'; 4]).unwrap());
+        assert_eq!(Image::from_data(2, 2, vec![0; 3]), Err(ImageError::SizeMismatch { expected: 4, actual: 3 }));
+    }
+
+    #[test]
+    #[should_panic]
+    fn out_of_image() {
+        let image = Image::new(2, 2, 0);
+        let _ = image[(2, 0)];
+    }
+}
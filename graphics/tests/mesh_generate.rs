@@ -0,0 +1,496 @@
+#[cfg(test)]
+mod test {
+    use geometric::contain3d::*;
+    use geometric::geom3d::*;
+    use graphics::mesh_generate::*;
+    use math::matrix::*;
+    use math::precision::Real;
+    use geometric::geom2d::Annulus;
+    use std::collections::HashMap;
+    use std::f64::consts::PI;
+
+    /// volume enclosed by mesh, positive if faces are counter-clockwise seen from outside
+    fn signed_volume(data: &FaceDisplayData) -> Real {
+        data.indices
+            .chunks(3)
+            .map(|t| {
+                let (a, b, c) = (
+                    data.vertices[t[0] as usize],
+                    data.vertices[t[1] as usize],
+                    data.vertices[t[2] as usize],
+                );
+                a.dot(&b.cross(&c)) / 6.0
+            })
+            .sum()
+    }
+
+    /// vertices are on surface and normals point outside
+    fn check_surface(data: &FaceDisplayData, contain: impl Fn(&Vec3) -> bool) {
+        assert_eq!(data.vertices.len(), data.normals.len());
+        for (v, n) in data.vertices.iter().zip(&data.normals) {
+            assert!(contain(&(*v - *n * 1e-6)));
+            assert!(!contain(&(*v + *n * 1e-6)));
+        }
+    }
+
+    fn check_volume(data: &FaceDisplayData, expect: Real) {
+        let volume = signed_volume(data);
+        assert!(volume > 0.0 && (volume - expect).abs() < expect * 0.01, "{} {}", volume, expect);
+    }
+
+    #[test]
+    fn primitives() {
+        let color = Vec4::ones();
+
+        let capsule = Capsule::new(Vec3::from_xyz(1.0, 0.0, 0.0), Vec3::from_xyz(1.0, 2.0, 3.0), 0.5);
+        let data = capsule_to_display_data(&capsule, color, 64, 16);
+        check_surface(&data, |p| is_capsule_contain_pt(p, &capsule));
+        let len = (capsule.end - capsule.start).length();
+        check_volume(&data, PI * 0.25 * len + 4.0 / 3.0 * PI * 0.125);
+
+        let axes = [
+            Vec3::from_xyz(1.0, 2.0, 2.0) / 3.0,
+            Vec3::from_xyz(2.0, 1.0, -2.0) / 3.0,
+            Vec3::from_xyz(-2.0, 2.0, -1.0) / 3.0,
+        ];
+        let ellipsoid = Ellipsoid::new(Vec3::from_xyz(0.0, 1.0, 0.0), Vec3::from_xyz(3.0, 1.0, 2.0), axes);
+        let data = ellipsoid_to_display_data(&ellipsoid, color, 64, 32);
+        check_surface(&data, |p| is_ellipsoid_contain_pt(p, &ellipsoid));
+        check_volume(&data, 4.0 / 3.0 * PI * 6.0);
+
+        let torus = Torus::new(Vec3::from_xyz(0.0, 0.0, 1.0), axes[0], 2.0, 0.5);
+        let data = torus_to_display_data(&torus, color, 64, 32);
+        check_surface(&data, |p| is_torus_contain_pt(p, &torus));
+        check_volume(&data, 2.0 * PI * PI * 2.0 * 0.25);
+
+        let rounded = RoundedBox::new(Vec3::from_xyz(1.0, 0.0, 0.0), Vec3::from_xyz(1.0, 0.5, 1.5), 0.3);
+        let data = rounded_box_to_display_data(&rounded, color, 64, 32);
+        check_surface(&data, |p| is_rounded_box_contain_pt(p, &rounded));
+        let (a, b, c, r) = (2.0, 1.0, 3.0, 0.3);
+        check_volume(
+            &data,
+            a * b * c + 2.0 * r * (a * b + b * c + c * a) + PI * r * r * (a + b + c) + 4.0 / 3.0 * PI * r * r * r,
+        );
+
+        let slab = Slab::new(axes[2], Vec3::zeros(), 0.5);
+        let data = slab_to_display_data(&slab, color, 2.0);
+        check_volume(&data, 16.0);
+        for v in &data.vertices {
+            assert!((slab.signed_dist(v).abs() - 0.5).abs() < 1e-9);
+        }
+
+        let half_space = HalfSpace::new(axes[1], Vec3::from_xyz(1.0, 1.0, 1.0));
+        let data = half_space_to_display_data(&half_space, color, 2.0);
+        for t in data.indices.chunks(3) {
+            let (a, b, c) = (
+                data.vertices[t[0] as usize],
+                data.vertices[t[1] as usize],
+                data.vertices[t[2] as usize],
+            );
+            assert!((b - a).cross(&(c - a)).normalize().dot(&half_space.normal) > 1.0 - 1e-9);
+        }
+    }
+
+    /// normals and tangents are unit and orthogonal, tangents follow uv gradient and normals agree with winding
+    fn check_attributes(data: &FaceDisplayData) {
+        let n = data.vertices.len();
+        let uvs = data.uvs.as_ref().unwrap();
+        let tangents = data.tangents.as_ref().unwrap();
+        assert!(data.normals.len() == n && uvs.len() == n && tangents.len() == n);
+
+        for (normal, t) in data.normals.iter().zip(tangents) {
+            let tangent = t.xyz();
+            assert!((normal.length() - 1.0).abs() < 1e-9);
+            assert!((tangent.length() - 1.0).abs() < 1e-9);
+            assert!(normal.dot(&tangent).abs() < 1e-9);
+            assert!(t.w() == 1.0 || t.w() == -1.0);
+        }
+
+        for t in data.indices.chunks(3) {
+            let [i0, i1, i2] = [t[0] as usize, t[1] as usize, t[2] as usize];
+            let (e1, e2) = (data.vertices[i1] - data.vertices[i0], data.vertices[i2] - data.vertices[i0]);
+            let face_normal = e1.cross(&e2);
+            if face_normal.length() < 1e-9 {
+                continue;
+            }
+            for i in [i0, i1, i2] {
+                assert!(face_normal.dot(&data.normals[i]) > 0.0);
+            }
+
+            // position changes along tangent when u grows
+            let (d1, d2) = (uvs[i1] - uvs[i0], uvs[i2] - uvs[i0]);
+            let det = d1.x() * d2.y() - d2.x() * d1.y();
+            if det.abs() < 1e-9 {
+                continue;
+            }
+            let dp_du = (e1 * d2.y() - e2 * d1.y()) / det;
+            let dp_dv = (e2 * d1.x() - e1 * d2.x()) / det;
+            for i in [i0, i1, i2] {
+                let tangent = tangents[i].xyz();
+                assert!(tangent.dot(&dp_du) > 0.0);
+                let bitangent = data.normals[i].cross(&tangent) * tangents[i].w();
+                assert!(bitangent.dot(&dp_dv) > 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn attributes() {
+        let color = Vec4::ones();
+        let dir = Vec3::from_xyz(1.0, 2.0, 2.0) / 3.0;
+
+        let cylinder = Cylinder {
+            bottom: Vec3::from_xyz(0.0, 1.0, 0.0),
+            dir,
+            height: 2.0,
+            radius: 0.5,
+        };
+        let data = cylinder_to_display_data(&cylinder, color, 64);
+        check_attributes(&data);
+        check_volume(&data, PI * 0.25 * 2.0);
+
+        let cone = Cone {
+            bottom: Vec3::zeros(),
+            bottom_radius: 1.0,
+            dir,
+            height: 3.0,
+        };
+        let data = cone_to_display_data(&cone, color, 64);
+        check_attributes(&data);
+        check_volume(&data, PI * 3.0 / 3.0);
+        // side normals lean toward apex
+        let side_normal = data.normals[0];
+        assert!((side_normal.dot(&dir) - (1.0 / 10.0 as Real).sqrt()).abs() < 1e-9);
+
+        let truncated = TruncatedCone {
+            bottom: Vec3::zeros(),
+            bottom_radius: 2.0,
+            top_radius: 1.0,
+            dir,
+            height: 3.0,
+        };
+        let data = truncatedcone_to_display_data(&truncated, color, 64);
+        check_attributes(&data);
+        check_volume(&data, PI * 3.0 / 3.0 * (4.0 + 2.0 + 1.0));
+
+        let polygon = Polygon {
+            points: vec![
+                Vec3::from_xyz(0.0, 0.0, 1.0),
+                Vec3::from_xyz(2.0, 0.0, 1.0),
+                Vec3::from_xyz(2.0, 1.0, 2.0),
+                Vec3::from_xyz(0.0, 1.0, 2.0),
+            ],
+        };
+        let data = polygon_to_display_data(&polygon, color).unwrap();
+        check_attributes(&data);
+        for uv in data.uvs.as_ref().unwrap() {
+            assert!((0..2).all(|i| uv[i] >= 0.0 && uv[i] <= 1.0));
+        }
+
+        let capsule = Capsule::new(Vec3::zeros(), dir, 0.5);
+        check_attributes(&capsule_to_display_data(&capsule, color, 32, 8));
+        let axes = [dir, Vec3::from_xyz(2.0, 1.0, -2.0) / 3.0, Vec3::from_xyz(-2.0, 2.0, -1.0) / 3.0];
+        let ellipsoid = Ellipsoid::new(Vec3::zeros(), Vec3::from_xyz(3.0, 1.0, 2.0), axes);
+        check_attributes(&ellipsoid_to_display_data(&ellipsoid, color, 32, 16));
+        let torus = Torus::new(Vec3::zeros(), dir, 2.0, 0.5);
+        check_attributes(&torus_to_display_data(&torus, color, 32, 16));
+        let rounded = RoundedBox::new(Vec3::zeros(), Vec3::from_xyz(1.0, 0.5, 1.5), 0.3);
+        check_attributes(&rounded_box_to_display_data(&rounded, color, 32, 16));
+        check_attributes(&slab_to_display_data(&Slab::new(dir, Vec3::zeros(), 0.5), color, 2.0));
+        check_attributes(&half_space_to_display_data(&HalfSpace::new(dir, Vec3::zeros()), color, 2.0));
+    }
+
+    /// weld vertices by position, then count how many times each directed edge is used by non-degenerated triangles
+    fn directed_edges(data: &FaceDisplayData) -> HashMap<(usize, usize), usize> {
+        let mut ids: HashMap<[i64; 3], usize> = HashMap::new();
+        let welded: Vec<usize> = data
+            .vertices
+            .iter()
+            .map(|v| {
+                let key = [0, 1, 2].map(|i| (v[i] * 1e6).round() as i64);
+                let next = ids.len();
+                *ids.entry(key).or_insert(next)
+            })
+            .collect();
+
+        let mut edges = HashMap::new();
+        for t in data.indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| welded[t[i] as usize]);
+            if a == b || b == c || c == a {
+                continue;
+            }
+            for edge in [(a, b), (b, c), (c, a)] {
+                *edges.entry(edge).or_insert(0) += 1;
+            }
+        }
+        edges
+    }
+
+    /// every edge is shared by exactly two triangles with opposite directions
+    fn check_watertight(data: &FaceDisplayData) {
+        let edges = directed_edges(data);
+        for ((a, b), count) in &edges {
+            assert_eq!(*count, 1);
+            assert_eq!(edges.get(&(*b, *a)), Some(&1));
+        }
+    }
+
+    /// surface with boundary, returns number of boundary edges
+    fn check_manifold_with_boundary(data: &FaceDisplayData) -> usize {
+        let edges = directed_edges(data);
+        assert!(edges.values().all(|c| *c == 1));
+        edges.keys().filter(|(a, b)| !edges.contains_key(&(*b, *a))).count()
+    }
+
+    #[test]
+    fn watertight() {
+        let color = Vec4::ones();
+        let dir = Vec3::from_xyz(1.0, 2.0, 2.0) / 3.0;
+        let axes = [dir, Vec3::from_xyz(2.0, 1.0, -2.0) / 3.0, Vec3::from_xyz(-2.0, 2.0, -1.0) / 3.0];
+
+        let sphere = Sphere::new(Vec3::from_xyz(1.0, 0.0, -1.0), 2.0);
+        let data = sphere_to_display_data(&sphere, color, 64, 32);
+        check_watertight(&data);
+        check_attributes(&data);
+        check_volume(&data, 4.0 / 3.0 * PI * 8.0);
+
+        for subdivision in 0..4 {
+            let data = icosphere_to_display_data(&sphere, color, subdivision);
+            check_watertight(&data);
+            if subdivision > 0 {
+                check_attributes(&data);
+            }
+            check_surface(&data, |p| (*p - sphere.center).length() <= sphere.radius);
+            for uv in data.uvs.as_ref().unwrap() {
+                assert!(uv.y() >= 0.0 && uv.y() <= 1.0);
+            }
+        }
+        check_volume(&icosphere_to_display_data(&sphere, color, 4), 4.0 / 3.0 * PI * 8.0);
+
+        let cube = Cube::from_min_max(Vec3::zeros(), Vec3::from_xyz(1.0, 2.0, 3.0));
+        let data = cube_to_display_data(&cube, color, 3);
+        check_watertight(&data);
+        check_attributes(&data);
+        check_volume(&data, 6.0);
+        assert_eq!(data.indices.len(), 6 * 9 * 6);
+
+        let obb = OBB::new(Vec3::from_xyz(1.0, 1.0, 1.0), Vec3::from_xyz(0.5, 1.0, 1.5), axes);
+        let data = obb_to_display_data(&obb, color, 2);
+        check_watertight(&data);
+        check_volume(&data, 6.0);
+
+        let capsule = Capsule::new(Vec3::zeros(), dir * 2.0, 0.5);
+        check_watertight(&capsule_to_display_data(&capsule, color, 32, 8));
+        let torus = Torus::new(Vec3::zeros(), dir, 2.0, 0.5);
+        check_watertight(&torus_to_display_data(&torus, color, 32, 16));
+        let ellipsoid = Ellipsoid::new(Vec3::zeros(), Vec3::from_xyz(3.0, 1.0, 2.0), axes);
+        check_watertight(&ellipsoid_to_display_data(&ellipsoid, color, 32, 16));
+        let rounded = RoundedBox::new(Vec3::zeros(), Vec3::from_xyz(1.0, 0.5, 1.5), 0.3);
+        check_watertight(&rounded_box_to_display_data(&rounded, color, 32, 16));
+        check_watertight(&slab_to_display_data(&Slab::new(dir, Vec3::zeros(), 0.5), color, 2.0));
+        let cylinder = Cylinder {
+            bottom: Vec3::zeros(),
+            dir,
+            height: 2.0,
+            radius: 0.5,
+        };
+        check_watertight(&cylinder_to_display_data(&cylinder, color, 32));
+        let cone = Cone {
+            bottom: Vec3::zeros(),
+            bottom_radius: 1.0,
+            dir,
+            height: 3.0,
+        };
+        check_watertight(&cone_to_display_data(&cone, color, 32));
+
+        let data = arrow_to_display_data(Vec3::zeros(), dir * 3.0, 0.1, color, 64);
+        check_watertight(&data);
+        check_attributes(&data);
+        let volume = PI * 0.01 * 2.4 + PI * 0.04 * 0.6 / 3.0;
+        check_volume(&data, volume);
+
+        let frame = math::coord::Cartesian3D::new(axes[0], axes[1], axes[2], Vec3::from_xyz(1.0, 2.0, 3.0));
+        let gizmo = axis_gizmo_to_display_data(&frame, 2.0, 16);
+        for (i, data) in gizmo.iter().enumerate() {
+            check_watertight(data);
+            assert_eq!(data.color[i], 1.0);
+            let tip = data.vertices.iter().map(|v| (*v - frame.position()).dot(&axes[i])).fold(0.0, Real::max);
+            assert!((tip - 2.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn open_surfaces() {
+        let color = Vec4::ones();
+
+        let plane = Plane::new(Vec3::from_xyz(0.0, 0.6, 0.8), Vec3::from_xyz(1.0, 2.0, 3.0));
+        let data = plane_to_display_data(&plane, color, 2.0, 4);
+        check_attributes(&data);
+        assert_eq!(check_manifold_with_boundary(&data), 16);
+        for (v, n) in data.vertices.iter().zip(&data.normals) {
+            assert!((*v - plane.pt).dot(&plane.normal).abs() < 1e-9);
+            assert!((*n - plane.normal).length() < 1e-9);
+        }
+
+        let annulus = Annulus::new(Vec2::from_xy(1.0, 1.0), 1.0, 2.0);
+        let data = annulus_to_display_data(&annulus, color, 32);
+        check_attributes(&data);
+        assert_eq!(check_manifold_with_boundary(&data), 64);
+        let area: Real = data
+            .indices
+            .chunks(3)
+            .map(|t| {
+                let [a, b, c] = [0, 1, 2].map(|i| data.vertices[t[i] as usize]);
+                (b - a).cross(&(c - a)).z() * 0.5
+            })
+            .sum();
+        let expect = 0.5 * 32.0 * (2.0 * PI / 32.0).sin() * 3.0;
+        assert!((area - expect).abs() < 1e-9);
+    }
+
+    fn rect(min: (Real, Real), max: (Real, Real)) -> geometric::geom2d::Polygon {
+        geometric::geom2d::Polygon::new(vec![
+            Vec2::from_xy(min.0, min.1),
+            Vec2::from_xy(max.0, min.1),
+            Vec2::from_xy(max.0, max.1),
+            Vec2::from_xy(min.0, max.1),
+        ])
+    }
+
+    #[test]
+    fn profile_builders() {
+        let color = Vec4::ones();
+        let dir = Vec3::from_xyz(1.0, 2.0, 2.0) / 3.0;
+        let axes = [dir, Vec3::from_xyz(2.0, 1.0, -2.0) / 3.0, Vec3::from_xyz(-2.0, 2.0, -1.0) / 3.0];
+        let frame = math::coord::Cartesian3D::new(axes[1], axes[2], axes[0], Vec3::from_xyz(1.0, 2.0, 3.0));
+
+        // concave polygon
+        let l_shape = Polygon {
+            points: [(0.0, 0.0), (2.0, 0.0), (2.0, 1.0), (1.0, 1.0), (1.0, 2.0), (0.0, 2.0)]
+                .iter()
+                .map(|(x, y)| frame.transform(Vec3::from_xyz(*x, *y, 0.0)))
+                .collect(),
+        };
+        let data = polygon_to_display_data(&l_shape, color).unwrap();
+        check_attributes(&data);
+        assert_eq!(check_manifold_with_boundary(&data), 6);
+        for n in &data.normals {
+            assert!((*n - frame.z_axis()).length() < 1e-9);
+        }
+
+        // hole is given counter-clockwise, orientation is fixed
+        let outline = rect((-1.0, -1.0), (1.0, 1.0));
+        let holes = [rect((-0.5, -0.25), (0.5, 0.25))];
+        let data = extrude_to_display_data(&outline, &holes, &Extrusion::new(3.0), &frame, color);
+        check_watertight(&data);
+        check_attributes(&data);
+        check_volume(&data, 3.0 * 3.5);
+        for v in &data.vertices {
+            let h = (*v - frame.position()).dot(&frame.z_axis());
+            assert!(h > -1e-9 && h < 3.0 + 1e-9);
+        }
+
+        let tapered = Extrusion {
+            taper: 0.5,
+            ..Extrusion::new(3.0)
+        };
+        let data = extrude_to_display_data(&outline, &holes, &tapered, &frame, color);
+        check_watertight(&data);
+        check_attributes(&data);
+        check_volume(&data, 3.5 * 3.0 * (1.0 + 0.5 + 0.25) / 3.0);
+
+        let beveled = Extrusion {
+            bevel: 0.2,
+            ..Extrusion::new(3.0)
+        };
+        let data = extrude_to_display_data(&outline, &[], &beveled, &frame, color);
+        check_watertight(&data);
+        check_attributes(&data);
+        let chamfer = 0.2 / 3.0 * (4.0 + 2.56 + (4.0 * 2.56 as Real).sqrt());
+        check_volume(&data, 4.0 * 2.6 + 2.0 * chamfer);
+
+        // faceted cylinder, profile walks around the solid clockwise
+        let profile = [
+            Vec2::from_xy(0.0, 0.0),
+            Vec2::from_xy(1.0, 0.0),
+            Vec2::from_xy(1.0, 2.0),
+            Vec2::from_xy(0.0, 2.0),
+        ];
+        let slice = 32;
+        let prism = 0.5 * slice as Real * (2.0 * PI / slice as Real).sin() * 2.0;
+        let data = lathe_to_display_data(&profile, 2.0 * PI, slice, &frame, color);
+        check_watertight(&data);
+        check_attributes(&data);
+        check_volume(&data, prism);
+        let data = lathe_to_display_data(&profile, PI, slice / 2, &frame, color);
+        check_watertight(&data);
+        check_attributes(&data);
+        check_volume(&data, prism * 0.5);
+
+        // helix, frames are orthonormal with z along the path
+        let path: Vec<Vec3> = (0..=200)
+            .map(|i| {
+                let t = i as Real / 200.0 * 4.0 * PI;
+                Vec3::from_xyz(3.0 * t.cos(), 3.0 * t.sin(), t * 0.5)
+            })
+            .collect();
+        let frames = rotation_minimizing_frames(&path);
+        for (i, f) in frames.iter().enumerate() {
+            assert!((f.x_axis().length() - 1.0).abs() < 1e-9);
+            assert!(f.x_axis().dot(&f.z_axis()).abs() < 1e-9);
+            assert!((f.x_axis().cross(&f.y_axis()) - f.z_axis()).length() < 1e-9);
+            let tangent = (path[(i + 1).min(200)] - path[i.saturating_sub(1)]).normalize();
+            assert!((f.z_axis() - tangent).length() < 1e-9);
+        }
+        let length: Real = path.windows(2).map(|w| (w[1] - w[0]).length()).sum();
+        let square = rect((-0.2, -0.2), (0.2, 0.2));
+        let data = sweep_to_display_data(&square, &path, false, color);
+        check_watertight(&data);
+        check_attributes(&data);
+        check_volume(&data, 0.16 * length);
+
+        // frames of a planar curve never twist out of its plane
+        let arc: Vec<Vec3> = (0..=50)
+            .map(|i| {
+                let t = i as Real / 50.0 * PI;
+                Vec3::from_xyz(2.0 * t.cos(), 0.0, 2.0 * t.sin())
+            })
+            .collect();
+        let frames = rotation_minimizing_frames(&arc);
+        let out_of_plane = frames[0].x_axis().y();
+        for f in &frames {
+            assert!((f.x_axis().y() - out_of_plane).abs() < 1e-9);
+        }
+
+        let data = tube_to_display_data(&arc, 0.3, color, 32);
+        check_watertight(&data);
+        check_attributes(&data);
+        let length: Real = arc.windows(2).map(|w| (w[1] - w[0]).length()).sum();
+        check_volume(&data, 0.5 * 32.0 * (2.0 * PI / 32.0).sin() * 0.09 * length);
+    }
+
+    #[test]
+    fn empty_inputs() {
+        let color = Vec4::ones();
+        let frame = math::coord::Cartesian3D::new(Vec3::x_axis(), Vec3::y_axis(), Vec3::z_axis(), Vec3::zeros());
+        let square = rect((-0.2, -0.2), (0.2, 0.2));
+        let pt = [Vec3::zeros()];
+        let line = [Vec3::zeros(), Vec3::z_axis()];
+        let nothing = geometric::geom2d::Polygon::new(Vec::new());
+
+        assert!(rotation_minimizing_frames(&[]).is_empty());
+        assert!(rotation_minimizing_frames(&pt).is_empty());
+        assert!(sweep_to_display_data(&square, &[], false, color).indices.is_empty());
+        assert!(sweep_to_display_data(&square, &pt, true, color).indices.is_empty());
+        assert!(sweep_to_display_data(&nothing, &line, false, color).indices.is_empty());
+        assert!(tube_to_display_data(&[], 0.3, color, 16).indices.is_empty());
+        assert!(lathe_to_display_data(&[], PI, 8, &frame, color).indices.is_empty());
+        assert!(lathe_to_display_data(&[Vec2::from_xy(1.0, 0.0)], PI, 8, &frame, color).indices.is_empty());
+        assert!(lathe_to_display_data(&[Vec2::zeros(), Vec2::x_axis()], PI, 0, &frame, color).indices.is_empty());
+        assert!(extrude_to_display_data(&nothing, &[], &Extrusion::new(1.0), &frame, color).indices.is_empty());
+        let extrusion = Extrusion::new(1.0);
+        let solid = extrude_to_display_data(&square, &[], &extrusion, &frame, color);
+        let holed = extrude_to_display_data(&square, std::slice::from_ref(&nothing), &extrusion, &frame, color);
+        assert_eq!(holed.indices, solid.indices);
+    }
+}
@@ -0,0 +1,170 @@
+#[cfg(test)]
+mod test {
+    use geometric::geom3d::*;
+    use graphics::mesh_generate::*;
+    use graphics::obj::*;
+    use math::matrix::*;
+    use math::precision::Real;
+    use std::collections::HashMap;
+
+    fn triangle_area(data: &FaceDisplayData) -> Real {
+        data.indices
+            .chunks(3)
+            .map(|t| {
+                let [a, b, c] = [0, 1, 2].map(|i| data.vertices[t[i] as usize]);
+                (b - a).cross(&(c - a)).length() * 0.5
+            })
+            .sum()
+    }
+
+    #[test]
+    fn round_trip() {
+        let sphere = Sphere::new(Vec3::from_xyz(1.0, 2.0, 3.0), 1.5);
+        let cube = Cube::from_min_max(Vec3::zeros(), Vec3::from_xyz(1.0, 2.0, 3.0));
+        let model = ObjModel {
+            groups: vec![
+                ObjGroup {
+                    name: "ball".to_string(),
+                    material: Some("red".to_string()),
+                    data: sphere_to_display_data(&sphere, Vec4::from_xyzw(1.0, 0.0, 0.0, 0.5), 16, 8),
+                },
+                ObjGroup {
+                    name: "box".to_string(),
+                    material: None,
+                    data: cube_to_display_data(&cube, Vec4::from_xyzw(0.2, 0.4, 0.6, 1.0), 2),
+                },
+                ObjGroup {
+                    name: "no uv".to_string(),
+                    material: Some("red".to_string()),
+                    data: without_uvs(cube_to_display_data(&cube, Vec4::ones(), 1)),
+                },
+            ],
+        };
+
+        let materials = parse_mtl(&model.to_mtl()).unwrap();
+        assert_eq!(materials.len(), 3);
+        let loaded = parse_obj(&model.to_obj(None), &materials).unwrap();
+        assert_eq!(loaded.groups.len(), 3);
+        for (a, b) in model.groups.iter().zip(&loaded.groups) {
+            assert_eq!(a.name, b.name);
+            assert_eq!(a.data.color, b.data.color);
+            // vertices are ordered by first use, compare triangle corners
+            assert_eq!(a.data.indices.len(), b.data.indices.len());
+            assert_eq!(a.data.uvs.is_some(), b.data.uvs.is_some());
+            assert_eq!(a.data.tangents.is_some(), b.data.tangents.is_some());
+            for (i, j) in a.data.indices.iter().zip(&b.data.indices) {
+                let (i, j) = (*i as usize, *j as usize);
+                assert!(a.data.vertices[i] == b.data.vertices[j]);
+                assert!((a.data.normals[i] - b.data.normals[j]).length() < 1e-12);
+                if let (Some(u), Some(v)) = (&a.data.uvs, &b.data.uvs) {
+                    assert!(u[i] == v[j]);
+                }
+            }
+        }
+
+        let dir = std::env::temp_dir().join("graphics_obj_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("model.obj");
+        model.write_to_file(path.to_str().unwrap()).unwrap();
+        assert!(dir.join("model.mtl").exists());
+        let loaded = read_obj(path.to_str().unwrap()).unwrap();
+        assert_eq!(loaded.groups[0].data.color, model.groups[0].data.color);
+        assert_eq!(loaded.groups[2].data.color, Vec4::ones());
+        assert!(matches!(read_obj(dir.join("missing.obj").to_str().unwrap()), Err(ObjError::Io(_))));
+    }
+
+    fn without_uvs(data: FaceDisplayData) -> FaceDisplayData {
+        FaceDisplayData {
+            uvs: None,
+            tangents: None,
+            ..data
+        }
+    }
+
+    #[test]
+    fn polygons_and_groups() {
+        let src = "
+            # unit square, concave pentagon and a triangle with relative indices
+            mtllib scene.mtl
+            v 0 0 0
+            v 1 0 0
+            v 1 1 0
+            v 0 1 0
+            vt 0 0
+            vt 1 0
+            vt 1 1
+            vt 0 1
+            vn 0 0 2
+            o square
+            usemtl blue
+            f 1/1/1 2/2/1 3/3/1 4/4/1
+            g arrow head
+            v 0 0 1
+            v 4 0 1
+            v 2 1 1
+            v 4 2 1
+            v 0 2 1
+            f 5 6 7 8 9 # concave
+            usemtl missing
+            s off
+            f -3 -2 -1
+        ";
+        let mtl = "
+            newmtl blue
+            Kd 0 0 1
+            Tr 0.25
+            newmtl unused
+            Kd 1 1 1
+        ";
+        let materials = parse_mtl(mtl).unwrap();
+        let model = parse_obj(src, &materials).unwrap();
+        let names: Vec<&str> = model.groups.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, vec!["square", "arrow head", "arrow head"]);
+
+        let square = &model.groups[0];
+        assert_eq!(square.material.as_deref(), Some("blue"));
+        assert_eq!(square.data.color, Vec4::from_xyzw(0.0, 0.0, 1.0, 0.75));
+        assert_eq!(square.data.vertices.len(), 4);
+        assert_eq!(square.data.indices.len(), 6);
+        assert!(square.data.normals.iter().all(|n| *n == Vec3::z_axis()));
+        assert!(square.data.tangents.is_some());
+        assert!((triangle_area(&square.data) - 1.0).abs() < 1e-12);
+
+        let head = &model.groups[1];
+        assert_eq!(head.material.as_deref(), Some("blue"));
+        assert!(head.data.uvs.is_none());
+        assert_eq!(head.data.indices.len(), 9);
+        assert!((triangle_area(&head.data) - 6.0).abs() < 1e-12);
+        assert!(head.data.normals.iter().all(|n| *n == Vec3::z_axis()));
+
+        let tail = &model.groups[2];
+        assert_eq!(tail.data.color, Vec4::ones());
+        assert_eq!(tail.data.vertices, vec![Vec3::from_xyz(2.0, 1.0, 1.0), Vec3::from_xyz(4.0, 2.0, 1.0), Vec3::from_xyz(0.0, 2.0, 1.0)]);
+    }
+
+    #[test]
+    fn malformed() {
+        let materials = HashMap::new();
+        let parse = |src: &str| parse_obj(src, &materials).err().unwrap();
+        assert!(matches!(parse("v 1 2 x"), ObjError::InvalidNumber { line: 1 }));
+        assert!(matches!(parse("v 1 2"), ObjError::MissingValue { line: 1 }));
+        assert!(matches!(parse("vt 1"), ObjError::MissingValue { line: 1 }));
+        let vertices = "v 0 0 0\nv 1 0 0\nv 0 1 0\n";
+        assert!(matches!(parse(&format!("{}f 1 2 4", vertices)), ObjError::IndexOutOfRange { line: 4, index: 4 }));
+        assert!(matches!(parse(&format!("{}f 1 2 0", vertices)), ObjError::IndexOutOfRange { line: 4, index: 0 }));
+        assert!(matches!(parse(&format!("{}f -4 2 3", vertices)), ObjError::IndexOutOfRange { line: 4, index: -4 }));
+        assert!(matches!(parse(&format!("{}f 1/1 2 3", vertices)), ObjError::IndexOutOfRange { line: 4, index: 1 }));
+        assert!(matches!(parse(&format!("{}f 1 2", vertices)), ObjError::InvalidFace { line: 4 }));
+        assert!(matches!(parse(&format!("{}f 1 2 /3", vertices)), ObjError::InvalidFace { line: 4 }));
+        assert!(matches!(parse(&format!("{}f 1 2 3/1/1/1", vertices)), ObjError::InvalidFace { line: 4 }));
+        assert!(matches!(parse(&format!("{}f 1 2 a", vertices)), ObjError::InvalidNumber { line: 4 }));
+        assert!(matches!(parse("usemtl"), ObjError::MissingValue { line: 1 }));
+        assert!(matches!(parse_mtl("newmtl a\nKd 1 x 1").err().unwrap(), ObjError::InvalidNumber { line: 2 }));
+        assert!(matches!(parse_mtl("newmtl a\nKd 1").err().unwrap(), ObjError::MissingValue { line: 2 }));
+
+        // nothing to read is not an error
+        assert!(parse_obj("", &materials).unwrap().groups.is_empty());
+        let err = parse("v 1 2 x");
+        assert_eq!(err.to_string(), "invalid number at line 1");
+    }
+}
@@ -0,0 +1,145 @@
+#[cfg(test)]
+mod test {
+    use geometric::geom3d::*;
+    use graphics::mesh_generate::*;
+    use graphics::ply::*;
+    use math::matrix::*;
+    use math::precision::Real;
+
+    #[test]
+    fn round_trip() {
+        let cube = Cube::from_min_max(Vec3::zeros(), Vec3::from_xyz(1.0, 2.0, 3.0));
+        let data = cube_to_display_data(&cube, Vec4::ones(), 2);
+        let n = data.vertices.len();
+        let channel = |k: usize| PlyProperty {
+            name: ["red", "green", "blue"][k].to_string(),
+            ty: PlyType::UChar,
+            values: (0..n).map(|i| ((i * 37 + k * 91) % 256) as Real).collect(),
+        };
+        let confidence = PlyProperty {
+            name: "confidence".to_string(),
+            ty: PlyType::Float,
+            values: (0..n).map(|i| i as Real * 0.25).collect(),
+        };
+        let mut mesh = PlyMesh::from(data);
+        mesh.properties = vec![channel(0), channel(1), channel(2), confidence];
+
+        for format in [PlyFormat::Ascii, PlyFormat::BinaryLittleEndian, PlyFormat::BinaryBigEndian] {
+            let loaded = parse_ply(&mesh.to_bytes(format)).unwrap();
+            assert_eq!(loaded.data.vertices, mesh.data.vertices);
+            assert_eq!(loaded.data.normals, mesh.data.normals);
+            assert_eq!(loaded.data.uvs, mesh.data.uvs);
+            assert_eq!(loaded.data.indices, mesh.data.indices);
+            assert_eq!(loaded.properties, mesh.properties);
+
+            let colors = loaded.vertex_colors().unwrap();
+            assert_eq!(colors.len(), n);
+            assert_eq!(colors[1], Vec4::from_xyzw(37.0 / 255.0, 128.0 / 255.0, 219.0 / 255.0, 1.0));
+        }
+    }
+
+    #[test]
+    fn polygons_and_other_elements() {
+        let src = "ply
+format ascii 1.0
+comment a quad and a triangle
+element vertex 5
+property float x
+property float y
+property float z
+property uint8 red
+property uint8 green
+property uint8 blue
+element face 2
+property list uchar int vertex_index
+property uchar flags
+element edge 1
+property int vertex1
+property int vertex2
+end_header
+0 0 0 255 0 0
+1 0 0 0 255 0
+1 1 0 0 0 255
+0 1 0 255 255 255
+0.5 0.5 1 0 0 0
+4 0 1 2 3 7
+3 0 1 4 0
+0 1
+";
+        let mesh = parse_ply(src.as_bytes()).unwrap();
+        assert_eq!(mesh.data.vertices.len(), 5);
+        assert_eq!(mesh.data.indices.len(), 9);
+        assert!(mesh.data.uvs.is_none());
+        // no normals in the file, the quad corner not shared with the triangle faces +z
+        assert!((mesh.data.normals[2] - Vec3::z_axis()).length() < 1e-9);
+        assert_eq!(mesh.vertex_colors().unwrap()[3], Vec4::ones());
+        assert!(mesh.property("x").is_none());
+    }
+
+    #[test]
+    fn malformed() {
+        let header = "ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\nproperty float y\nproperty float z\nelement face 1\nproperty list uchar int vertex_indices\nend_header\n";
+        let vertices = "0 0 0\n1 0 0\n0 1 0\n";
+        assert!(parse_ply(format!("{}{}3 0 1 2\n", header, vertices).as_bytes()).is_ok());
+        assert!(matches!(
+            parse_ply(format!("{}{}3 0 1 3\n", header, vertices).as_bytes()),
+            Err(PlyError::IndexOutOfRange { index: 3 })
+        ));
+        assert!(matches!(
+            parse_ply(format!("{}{}2 0 1\n", header, vertices).as_bytes()),
+            Err(PlyError::InvalidFace)
+        ));
+        assert!(matches!(
+            parse_ply(format!("{}{}3 0 1\n", header, vertices).as_bytes()),
+            Err(PlyError::UnexpectedEof)
+        ));
+        assert!(matches!(
+            parse_ply(format!("{}0 0 0\n1 a 0\n", header).as_bytes()),
+            Err(PlyError::InvalidNumber)
+        ));
+        assert!(matches!(
+            parse_ply(header.replace("float y", "real y").as_bytes()),
+            Err(PlyError::InvalidHeader { line: 5 })
+        ));
+        assert!(matches!(
+            parse_ply(header.replace("ply\n", "plx\n").as_bytes()),
+            Err(PlyError::InvalidHeader { line: 1 })
+        ));
+        assert!(matches!(
+            parse_ply(format!("{}{}3 0 1 2\n", header.replace(" z\n", " w\n"), vertices).as_bytes()),
+            Err(PlyError::MissingProperty { .. })
+        ));
+        let twice = header.replace("element face", "element vertex 1\nproperty float x\nelement face");
+        assert!(matches!(
+            parse_ply(format!("{}{}0\n3 0 1 2\n", twice, vertices).as_bytes()),
+            Err(PlyError::InvalidHeader { line: 7 })
+        ));
+
+        let binary = header.replace("ascii", "binary_little_endian");
+        let mut bytes = binary.into_bytes();
+        bytes.extend([0u8; 4 * 9 - 1]);
+        assert!(matches!(parse_ply(&bytes), Err(PlyError::UnexpectedEof)));
+        assert!(matches!(read_ply("/nonexistent/file.ply"), Err(PlyError::Io(_))));
+    }
+
+    #[test]
+    fn huge_element_count() {
+        // counts come from the header and must not be trusted for allocation
+        for format in ["ascii", "binary_little_endian"] {
+            let header = format!(
+                "ply\nformat {} 1.0\nelement vertex 18446744073709551615\nproperty float x\nproperty float y\nproperty float z\nend_header\n",
+                format
+            );
+            assert!(parse_ply(header.as_bytes()).is_err());
+            let list = header.replace("property float z", "property float z\nproperty list uint int extra");
+            let mut bytes = list.into_bytes();
+            if format == "ascii" {
+                bytes.extend(b"0 0 0 4294967295 ");
+            } else {
+                bytes.extend([0u8; 12]);
+                bytes.extend([0xFF; 4]);
+            }
+            assert!(parse_ply(&bytes).is_err());
+        }
+    }
+}
@@ -0,0 +1,179 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use graphics::deflate::{zlib_compress, Compression};
+    use graphics::image::Image;
+    use graphics::png::*;
+    use math::matrix::*;
+    use crate::common::lcg_next;
+
+    // generated by an independent encoder(python zlib), covering every color type, sub-byte and 16 bit depths,
+    // tRNS, Adam7 and all filter types
+    const PALETTE: &str = "89504e470d0a1a0a0000000d494844520000000500000003020300000026582d6b0000000c504c5445ff000000ff000000fffffffffb0060f60000000374524e530080ffecf7b3180000000f74455874436f6d6d656e740066697874757265970fc658000000114944415478da63906660dc789e29ab010008f20289fc2288ba0000000049454e44ae426082";
+    const GRAY16_INTERLACED: &str = "89504e470d0a1a0a0000000d4948445200000009000000071000000001d867d4690000000274524e533c8ce16e7bcc0000000f74455874436f6d6d656e740066697874757265970fc658000000734944415478da636060b8758031378189fd42a9c1a309cc661b3a77b0705c60bfc0c0fcc26a46a1c7f21ff7563072ef30df60bec10c8899a42382383a76eccf601612913d2307842c2c2f2090415d20f6c09482d30a8c8c5f6422a48150068aa52398608a40901942b287092c105000423809004842308176b0b9680000000049454e44ae426082";
+    const RGB_INTERLACED: &str = "89504e470d0a1a0a0000000d494844520000000a000000090802000001f3c31ad20000000f74455874436f6d6d656e740066697874757265970fc658000000bb4944415478da758ebd6d03310c46df49b2aca3cf3f3054e4eb52aa567d45fa3437841648e7213c8487f0101ecbba43902ec00308f223f108f08081572f5f6e015f5e9f81c6c2a543815bcf4b4bbd73b444fbf0548ac2acb82835d9ba0e09a65f32ccacc33b3cfb75aeae10fe70d44ea41af544bd7a6b216bcc3a641db3ce61cd896070a23f81c81a8adc2cbf2834ed6e8a77ed1f4a4f8d03dfdde8ffa3abd8846173a64d3b6de6cb2ae707d3ce144d7b53328d26331d4c93e9f806606519a71d4c4e620000000049454e44ae426082";
+    const GRAY_ALPHA: &str = "89504e470d0a1a0a0000000d4948445200000003000000020804000000377dae910000000f74455874436f6d6d656e740066697874757265970fc658000000164944415478da6360304a313a61c4c8f02b8521850100187f03866edcd14d0000000049454e44ae426082";
+    const GRAY1: &str = "89504e470d0a1a0a0000000d494844520000000a000000020100000000491a707d0000000f74455874436f6d6d656e740066697874757265970fc6580000000e4944415478da630875605c750d0004dc0217d6fefcaa0000000049454e44ae426082";
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap()).collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut seed = 3;
+        // smooth gradients with noise, so filters and matches both matter
+        let opaque = Image::from_fn(37, 23, |x, y| {
+            let noise = ((lcg_next(&mut seed) >> 33) % 4) as u8;
+            [(x * 6) as u8 + noise, (y * 11) as u8, ((x * y) % 256) as u8, 255]
+        });
+        let mut translucent = opaque.clone();
+        translucent[(3, 4)][3] = 0;
+        translucent[(36, 22)][3] = 128;
+
+        for image in [&opaque, &translucent] {
+            for compression in [Compression::Store, Compression::Fixed] {
+                for filter in [
+                    PngFilter::None,
+                    PngFilter::Sub,
+                    PngFilter::Up,
+                    PngFilter::Average,
+                    PngFilter::Paeth,
+                    PngFilter::Adaptive,
+                ] {
+                    let bytes = encode_png(image, compression, filter);
+                    // color type in IHDR
+                    assert_eq!(bytes[25], if image == &opaque { 2 } else { 6 });
+                    assert_eq!(&decode_png(&bytes).unwrap(), image);
+                }
+            }
+        }
+
+        let stored = encode_png(&opaque, Compression::Store, PngFilter::None).len();
+        let compressed = encode_png(&opaque, Compression::Fixed, PngFilter::Adaptive).len();
+        assert!(compressed < stored * 2 / 3);
+
+        let one = Image::new(1, 1, [1, 2, 3, 4]);
+        assert_eq!(decode_png(&encode_png(&one, Compression::Fixed, PngFilter::Paeth)).unwrap(), one);
+    }
+
+    #[test]
+    fn color_types() {
+        let palette = decode_png(&hex(PALETTE)).unwrap();
+        let colors = [[255, 0, 0, 0], [0, 255, 0, 128], [0, 0, 255, 255], [255, 255, 255, 255]];
+        assert_eq!(palette, Image::from_fn(5, 3, |x, y| colors[(x + 2 * y) % 4]));
+
+        let gray = decode_png(&hex(GRAY16_INTERLACED)).unwrap();
+        let expected = Image::from_fn(9, 7, |x, y| {
+            let v = x * 7000 + y * 500;
+            let g = (v >> 8) as u8;
+            [g, g, g, if v == 15500 { 0 } else { 255 }]
+        });
+        assert_eq!(gray, expected);
+
+        let rgb = decode_png(&hex(RGB_INTERLACED)).unwrap();
+        assert_eq!(rgb, Image::from_fn(10, 9, |x, y| [(x * 20) as u8, (y * 25) as u8, (x * y % 256) as u8, 255]));
+
+        let gray_alpha = decode_png(&hex(GRAY_ALPHA)).unwrap();
+        assert_eq!(gray_alpha, Image::from_fn(3, 2, |x, y| {
+            let g = (x * 100) as u8;
+            [g, g, g, (y * 200 + 50) as u8]
+        }));
+
+        let bits = decode_png(&hex(GRAY1)).unwrap();
+        assert_eq!(bits, Image::from_fn(10, 2, |x, y| {
+            let g = ((x + y) % 2 * 255) as u8;
+            [g, g, g, 255]
+        }));
+    }
+
+    #[test]
+    fn colors() {
+        let image = Image::from_fn(2, 1, |x, _| Vec3::from_xyz(x as f64, 0.5, 2.0));
+        let rgba = image.to_rgba8();
+        assert_eq!(rgba.data(), &[[0, 128, 255, 255], [255, 128, 255, 255]]);
+        assert_eq!(rgba.to_colors()[(1, 0)], Vec4::from_xyzw(1.0, 128.0 / 255.0, 1.0, 1.0));
+        assert_eq!(rgba.to_colors().to_rgba8(), rgba);
+    }
+
+    #[test]
+    fn malformed() {
+        let valid = encode_png(&Image::new(2, 2, [9, 9, 9, 255]), Compression::Fixed, PngFilter::Sub);
+        assert!(matches!(decode_png(&valid[1..]), Err(PngError::InvalidSignature)));
+        assert!(matches!(decode_png(&valid[..valid.len() - 1]), Err(PngError::InvalidChunk)));
+
+        let mut corrupted = valid.clone();
+        corrupted[30] ^= 1;
+        assert!(matches!(decode_png(&corrupted), Err(PngError::InvalidChunk)));
+
+        // chunk with a valid CRC but broken content
+        let rebuild = |kind: &[u8; 4], data: &[u8]| {
+            let mut out = valid[..8].to_vec();
+            out.extend((data.len() as u32).to_be_bytes());
+            out.extend(kind);
+            out.extend(data);
+            let crc = crc32(&out[12..]);
+            out.extend(crc.to_be_bytes());
+            out
+        };
+        let mut ihdr = valid[16..29].to_vec();
+        ihdr[8] = 3; // 3 bit depth
+        assert!(matches!(decode_png(&rebuild(b"IHDR", &ihdr)), Err(PngError::InvalidHeader)));
+        assert!(matches!(decode_png(&rebuild(b"IDAT", &[])), Err(PngError::InvalidHeader)));
+
+        let with = |kind: &[u8; 4], data: &[u8]| {
+            let mut out = rebuild(b"IHDR", &valid[16..29]);
+            let chunk = rebuild(kind, data);
+            out.extend(&chunk[8..]);
+            out.extend(&valid[33..]);
+            out
+        };
+        assert!(decode_png(&with(b"abCD", &[1, 2])).is_ok());
+        assert!(matches!(decode_png(&with(b"ABCD", &[])), Err(PngError::Unsupported(_))));
+        assert!(matches!(decode_png(&with(b"IDAT", &[1, 2, 3])), Err(PngError::InvalidData(_))));
+
+        // a palette image without PLTE
+        ihdr[8] = 8;
+        ihdr[9] = 3;
+        let mut no_palette = rebuild(b"IHDR", &ihdr);
+        no_palette.extend(&valid[33..]);
+        assert!(matches!(decode_png(&no_palette), Err(PngError::InvalidData("missing palette"))));
+        assert!(matches!(read_png("/nonexistent/file.png"), Err(PngError::Io(_))));
+    }
+
+    #[test]
+    fn huge_size() {
+        let valid = encode_png(&Image::new(1, 1, [9, 9, 9, 255]), Compression::Fixed, PngFilter::None);
+        let resized = |width: u32, height: u32| {
+            let mut out = valid.clone();
+            out[16..20].copy_from_slice(&width.to_be_bytes());
+            out[20..24].copy_from_slice(&height.to_be_bytes());
+            let crc = crc32(&out[12..29]);
+            out[29..33].copy_from_slice(&crc.to_be_bytes());
+            out
+        };
+        assert!(decode_png(&resized(1, 1)).is_ok());
+        // the tiny IDAT can't hold the scanlines, nothing of image size is allocated
+        for (width, height) in [(0x7FFFFFFF, 0x7FFFFFFF), (100_000, 100_000), (0x7FFFFFFF, 1)] {
+            assert!(matches!(
+                decode_png(&resized(width, height)),
+                Err(PngError::InvalidData(_) | PngError::InvalidHeader)
+            ));
+        }
+
+        // IDAT inflating far beyond one scanline
+        let idat = zlib_compress(&vec![0; 1 << 22], Compression::Fixed);
+        let mut bomb = valid[..33].to_vec();
+        bomb.extend((idat.len() as u32).to_be_bytes());
+        bomb.extend(b"IDAT");
+        bomb.extend(&idat);
+        let crc = crc32(&bomb[37..]);
+        bomb.extend(crc.to_be_bytes());
+        bomb.extend(&valid[valid.len() - 12..]);
+        assert!(matches!(
+            decode_png(&bomb),
+            Err(PngError::InvalidData("decompressed data is longer than expected"))
+        ));
+    }
+}
@@ -0,0 +1,163 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use graphics::image::Image;
+    use graphics::ppm::*;
+    use math::matrix::*;
+    use math::precision::Real;
+    use crate::common::rand;
+
+    fn quantized(seed: &mut u64, maxval: u16) -> Real {
+        (rand(seed) * maxval as Real).round() / maxval as Real
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut seed = 7;
+        let (w, h) = (13, 5);
+        for maxval in [1, 255, 256, 1000, 65535] {
+            let gray = Image::from_fn(w, h, |_, _| quantized(&mut seed, maxval));
+            let rgb = Image::from_fn(w, h, |_, _| {
+                Vec3::from_xyz(quantized(&mut seed, maxval), quantized(&mut seed, maxval), quantized(&mut seed, maxval))
+            });
+            for (format, image) in [
+                (PnmFormat::P2, PnmImage::Gray(gray.clone())),
+                (PnmFormat::P5, PnmImage::Gray(gray.clone())),
+                (PnmFormat::P3, PnmImage::Rgb(rgb.clone())),
+                (PnmFormat::P6, PnmImage::Rgb(rgb.clone())),
+            ] {
+                let pnm = Pnm { format, maxval, image };
+                let bytes = pnm.to_bytes();
+                if format.is_binary() {
+                    let channels = if format.is_rgb() { 3 } else { 1 };
+                    let size = if maxval < 256 { 1 } else { 2 };
+                    assert_eq!(bytes.len(), format!("P5\n{} {}\n{}\n", w, h, maxval).len() + w * h * channels * size);
+                } else {
+                    assert!(bytes.split(|b| *b == b'\n').all(|line| line.len() <= 70));
+                }
+                let loaded = parse_pnm(&bytes).unwrap();
+                assert_eq!(loaded.format, format);
+                assert_eq!(loaded.maxval, maxval);
+                assert_eq!(loaded.image, pnm.image);
+            }
+        }
+
+        let bits = Image::from_fn(w, h, |x, y| ((x * 3 + y) % 4 == 0) as u8 as Real);
+        for format in [PnmFormat::P1, PnmFormat::P4] {
+            let pnm = Pnm::new(PnmImage::Gray(bits.clone()), format);
+            let bytes = pnm.to_bytes();
+            if format == PnmFormat::P4 {
+                assert_eq!(bytes.len(), format!("P4\n{} {}\n", w, h).len() + 2 * h);
+            }
+            let loaded = parse_pnm(&bytes).unwrap();
+            assert_eq!(loaded.format, format);
+            assert_eq!(loaded.image, pnm.image);
+        }
+    }
+
+    #[test]
+    fn conversion() {
+        let rgb = Image::from_fn(2, 1, |x, _| Vec3::from_xyz(x as Real, 0.5, 1.0));
+        let gray = parse_pnm(&Pnm::new(PnmImage::Rgb(rgb), PnmFormat::P5).to_bytes()).unwrap();
+        let PnmImage::Gray(gray) = gray.image else { panic!("graymap should be gray") };
+        assert_eq!(gray[(0, 0)], (255.0 * (0.7152 * 0.5 + 0.0722) as Real).round() / 255.0);
+
+        let rgb = parse_pnm(&Pnm::new(PnmImage::Gray(gray.clone()), PnmFormat::P6).to_bytes()).unwrap();
+        assert_eq!(rgb.image.to_rgb()[(1, 0)], Vec3::ones() * gray[(1, 0)]);
+
+        // out of range values are clamped
+        let image = PnmImage::Gray(Image::from_data(2, 1, vec![-1.0, 2.0]).unwrap());
+        let loaded = parse_pnm(&Pnm::new(image, PnmFormat::P2).to_bytes()).unwrap();
+        assert_eq!(loaded.image, PnmImage::Gray(Image::from_data(2, 1, vec![0.0, 1.0]).unwrap()));
+    }
+
+    #[test]
+    fn comments_and_whitespace() {
+        let src = b"P2 # graymap\n# size\n3\t2\r\n  # maxval\n 4 # after maxval\n0 1 2\n# between rows\n3 4\n\n 0\n";
+        let pnm = parse_pnm(src).unwrap();
+        let expected: Vec<Real> = [0.0, 1.0, 2.0, 3.0, 4.0, 0.0].iter().map(|v| v / 4.0).collect();
+        assert_eq!(pnm.image, PnmImage::Gray(Image::from_data(3, 2, expected).unwrap()));
+
+        // bitmap digits need no separator
+        let pnm = parse_pnm(b"P1\n#c\n4 2\n0110\n1 0 0 1").unwrap();
+        let expected = vec![1.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0];
+        assert_eq!(pnm.image, PnmImage::Gray(Image::from_data(4, 2, expected).unwrap()));
+
+        // binary data right after a single whitespace, even if it looks like a comment or a space
+        let pnm = parse_pnm(b"P5 # c\n2 1 255\n# ").unwrap();
+        assert_eq!(pnm.image, PnmImage::Gray(Image::from_data(2, 1, vec![35.0 / 255.0, 32.0 / 255.0]).unwrap()));
+    }
+
+    #[test]
+    fn malformed() {
+        assert!(matches!(parse_pnm(b"P7\n1 1\n255\n0"), Err(PnmError::InvalidMagic)));
+        assert!(matches!(parse_pnm(b"P"), Err(PnmError::InvalidMagic)));
+        assert!(matches!(parse_pnm(b"P2\n1 x\n255\n0"), Err(PnmError::InvalidHeader)));
+        assert!(matches!(parse_pnm(b"P2\n1 1"), Err(PnmError::InvalidHeader)));
+        assert!(matches!(parse_pnm(b"P2\n1 1\n0\n0"), Err(PnmError::InvalidMaxval(0))));
+        assert!(matches!(parse_pnm(b"P5\n1 1\n65536\n00"), Err(PnmError::InvalidMaxval(65536))));
+        assert!(matches!(parse_pnm(b"P2\n2 1\n255\n0 256"), Err(PnmError::InvalidSample)));
+        assert!(matches!(parse_pnm(b"P3\n1 1\n255\n0 a 0"), Err(PnmError::InvalidSample)));
+        assert!(matches!(parse_pnm(b"P1\n2 1\n0 2"), Err(PnmError::InvalidSample)));
+        assert!(matches!(parse_pnm(b"P5\n2 1\n15\n\x01\x10"), Err(PnmError::InvalidSample)));
+        assert!(matches!(parse_pnm(b"P3\n1 1\n255\n0 0"), Err(PnmError::UnexpectedEof)));
+        assert!(matches!(parse_pnm(b"P6\n1 1\n255\n\0\0"), Err(PnmError::UnexpectedEof)));
+        assert!(matches!(parse_pnm(b"P5\n2 1\n1000\n\0\0\0"), Err(PnmError::UnexpectedEof)));
+        assert!(matches!(parse_pnm(b"P4\n9 1\n\0"), Err(PnmError::UnexpectedEof)));
+        assert!(matches!(read_pnm("/nonexistent/file.ppm"), Err(PnmError::Io(_))));
+    }
+
+    #[test]
+    fn huge_size() {
+        // sample count overflows
+        assert!(matches!(parse_pnm(b"P6 4294967295 4294967295 255\n"), Err(PnmError::InvalidHeader)));
+        assert!(matches!(parse_pnm(b"P3 4294967295 4294967295 255\n"), Err(PnmError::InvalidHeader)));
+        // byte count overflows
+        assert!(matches!(parse_pnm(b"P5 4294967295 4294967295 65535\n"), Err(PnmError::InvalidHeader)));
+        // fits but the body is missing, nothing is allocated for it
+        assert!(matches!(parse_pnm(b"P6 65535 65535 255\n\0"), Err(PnmError::UnexpectedEof)));
+        assert!(matches!(parse_pnm(b"P2 65535 65535 255\n0"), Err(PnmError::UnexpectedEof)));
+        assert!(matches!(parse_pnm(b"P4 4294967295 4294967295\n"), Err(PnmError::UnexpectedEof)));
+    }
+
+    #[test]
+    fn ppm_files() {
+        let mut ppm = PPM::new(4, 3);
+        for y in 0..ppm.height() {
+            for x in 0..ppm.width() {
+                ppm.set_pixel(x, y, Vec3::from_xyz(x as Real / 3.0, y as Real / 2.0, 1.0));
+            }
+        }
+        let dir = std::env::temp_dir();
+        for (name, binary) in [("graphics_test_ascii.ppm", false), ("graphics_test_binary.ppm", true)] {
+            let path = dir.join(name);
+            let path = path.to_str().unwrap();
+            if binary {
+                ppm.write_binary_to_file(path).unwrap();
+            } else {
+                ppm.write_to_file(path).unwrap();
+            }
+            let loaded = PPM::read_from_file(path).unwrap();
+            std::fs::remove_file(path).unwrap();
+            assert_eq!(loaded.width(), 4);
+            assert_eq!(loaded.height(), 3);
+            for y in 0..3 {
+                for x in 0..4 {
+                    assert!((loaded.get_pixel(x, y) - ppm.get_pixel(x, y)).length() <= 1.0 / 255.0);
+                }
+            }
+        }
+
+        // ASCII output truncates channels
+        let mut ppm = PPM::new(2, 1);
+        ppm.set_pixel(0, 0, Vec3::from_xyz(0.999, 0.5, -1.0));
+        ppm.set_pixel(1, 0, Vec3::from_xyz(2.0, 0.1, 0.0));
+        let path = dir.join("graphics_test_truncate.ppm");
+        let path = path.to_str().unwrap();
+        ppm.write_to_file(path).unwrap();
+        let text = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(text, "P3\n2 1\n255\n254 127 0  255 25 0  \n");
+    }
+}
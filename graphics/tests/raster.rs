@@ -0,0 +1,244 @@
+#[cfg(test)]
+mod test {
+    use graphics::camera::*;
+    use graphics::mesh_generate::FaceDisplayData;
+    use graphics::raster::*;
+    use math::matrix::*;
+    use math::precision::Real;
+
+    const WIDTH: usize = 64;
+    const HEIGHT: usize = 48;
+
+    fn camera() -> Camera {
+        let mut camera = Camera::new(
+            Projection::Perspective { half_fovy: 0.5 },
+            0.5,
+            50.0,
+            Vec2::from_xy(WIDTH as Real, HEIGHT as Real),
+        );
+        camera.look_at(Vec3::from_xyz(0.0, 0.0, 5.0), Vec3::zeros());
+        camera
+    }
+
+    fn triangle(vertices: [Vec3; 3], normals: [Vec3; 3], color: Vec4) -> FaceDisplayData {
+        FaceDisplayData {
+            vertices: vertices.to_vec(),
+            normals: normals.to_vec(),
+            indices: vec![0, 1, 2],
+            color,
+            uvs: None,
+            tangents: None,
+        }
+    }
+
+    /// square in `z = 0` facing `+z`
+    fn square(half: Real, color: Vec4) -> FaceDisplayData {
+        FaceDisplayData {
+            vertices: vec![
+                Vec3::from_xyz(-half, -half, 0.0),
+                Vec3::from_xyz(half, -half, 0.0),
+                Vec3::from_xyz(half, half, 0.0),
+                Vec3::from_xyz(-half, half, 0.0),
+            ],
+            normals: vec![Vec3::z_axis(); 4],
+            indices: vec![0, 1, 2, 0, 2, 3],
+            color,
+            uvs: None,
+            tangents: None,
+        }
+    }
+
+    fn translation(offset: Vec3) -> Mat44 {
+        #[rustfmt::skip]
+        let m = Mat44::from_row(&[
+            1.0, 0.0, 0.0, offset.x(),
+            0.0, 1.0, 0.0, offset.y(),
+            0.0, 0.0, 1.0, offset.z(),
+            0.0, 0.0, 0.0, 1.0,
+        ]);
+        m
+    }
+
+    fn unlit() -> Rasterizer {
+        let mut raster = Rasterizer::new(WIDTH, HEIGHT, Vec3::zeros());
+        raster.light = None;
+        raster
+    }
+
+    #[test]
+    fn square_covers_center() {
+        let camera = camera();
+        let mut raster = unlit();
+        let red = Vec4::from_xyzw(1.0, 0.0, 0.0, 1.0);
+        raster.draw(&square(1.0, red), &Mat44::identity(), &camera.view_proj_mat());
+
+        let (cx, cy) = (WIDTH / 2, HEIGHT / 2);
+        assert_eq!(raster.color()[(cx, cy)], red.xyz());
+        assert_eq!(raster.color()[(0, 0)], Vec3::zeros());
+        assert_eq!(raster.depth()[(0, 0)], Real::INFINITY);
+
+        let expected = camera.world_to_screen(&Vec3::zeros()).unwrap().z();
+        assert!((raster.depth()[(cx, cy)] - expected).abs() < 1e-9);
+
+        // covered pixels are exactly those whose center projects inside the square
+        let corner = camera.world_to_screen(&Vec3::from_xyz(1.0, 1.0, 0.0)).unwrap();
+        let half_x = corner.x() - WIDTH as Real / 2.0;
+        let half_y = HEIGHT as Real / 2.0 - corner.y();
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                let dx = (x as Real + 0.5 - WIDTH as Real / 2.0).abs();
+                let dy = (y as Real + 0.5 - HEIGHT as Real / 2.0).abs();
+                if dx < half_x - 1e-6 && dy < half_y - 1e-6 {
+                    assert_eq!(raster.color()[(x, y)], red.xyz());
+                } else if dx > half_x + 1e-6 || dy > half_y + 1e-6 {
+                    assert_eq!(raster.color()[(x, y)], Vec3::zeros());
+                }
+            }
+        }
+
+        let ppm = raster.to_ppm();
+        assert_eq!((ppm.width(), ppm.height()), (WIDTH, HEIGHT));
+        assert_eq!(ppm.get_pixel(cx, cy), red.xyz());
+    }
+
+    #[test]
+    fn nearer_wins_in_any_order() {
+        let camera = camera();
+        let red = Vec4::from_xyzw(1.0, 0.0, 0.0, 1.0);
+        let blue = Vec4::from_xyzw(0.0, 0.0, 1.0, 1.0);
+        let near = translation(Vec3::from_xyz(0.5, 0.0, 1.0));
+        let far = translation(Vec3::from_xyz(-0.5, 0.0, -1.0));
+
+        let mut first = unlit();
+        first.draw(&square(1.0, red), &near, &camera.view_proj_mat());
+        first.draw(&square(1.0, blue), &far, &camera.view_proj_mat());
+        let mut second = unlit();
+        second.draw(&square(1.0, blue), &far, &camera.view_proj_mat());
+        second.draw(&square(1.0, red), &near, &camera.view_proj_mat());
+
+        assert_eq!(first.color(), second.color());
+        assert_eq!(first.depth(), second.depth());
+        assert_eq!(first.color()[(WIDTH / 2, HEIGHT / 2)], red.xyz());
+
+        // only the far square reaches the left part
+        let left = camera.world_to_screen(&Vec3::from_xyz(-1.2, 0.0, -1.0)).unwrap();
+        assert_eq!(first.color()[(left.x() as usize, left.y() as usize)], blue.xyz());
+
+        first.clear(Vec3::zeros());
+        assert!(first.color().data().iter().all(|c| *c == Vec3::zeros()));
+        assert!(first.depth().data().iter().all(|d| *d == Real::INFINITY));
+    }
+
+    #[test]
+    fn cull_by_winding() {
+        let camera = camera();
+        let white = Vec4::from_xyzw(1.0, 1.0, 1.0, 1.0);
+        let front = square(1.0, white);
+        let mut back = square(1.0, white);
+        back.indices = vec![0, 2, 1, 0, 3, 2];
+        let center = (WIDTH / 2, HEIGHT / 2);
+
+        for (mode, front_drawn, back_drawn) in [
+            (CullMode::Back, true, false),
+            (CullMode::Front, false, true),
+            (CullMode::None, true, true),
+        ] {
+            for (data, drawn) in [(&front, front_drawn), (&back, back_drawn)] {
+                let mut raster = unlit();
+                raster.cull = mode;
+                raster.draw(data, &Mat44::identity(), &camera.view_proj_mat());
+                assert_eq!(raster.color()[center] == white.xyz(), drawn);
+            }
+        }
+    }
+
+    #[test]
+    fn back_faces_lit_from_behind() {
+        let camera = camera();
+        let mut back = square(1.0, Vec4::from_xyzw(1.0, 1.0, 1.0, 1.0));
+        back.indices = vec![0, 2, 1, 0, 3, 2];
+        // normals point to the viewer, flipped for a back face they face away from the light
+        let mut raster = Rasterizer::new(WIDTH, HEIGHT, Vec3::zeros());
+        raster.cull = CullMode::None;
+        raster.light = Some(Vec3::z_axis());
+        raster.draw(&back, &Mat44::identity(), &camera.view_proj_mat());
+        let c = raster.color()[(WIDTH / 2, HEIGHT / 2)];
+        assert!((c.x() - raster.ambient).abs() < 1e-9);
+    }
+
+    #[test]
+    fn perspective_correct_normals() {
+        let camera = camera();
+        let vertices = [
+            Vec3::from_xyz(-2.0, -1.0, 3.0),
+            Vec3::from_xyz(2.0, -1.0, -8.0),
+            Vec3::from_xyz(0.0, 2.0, -2.0),
+        ];
+        let normals = [
+            Vec3::from_xyz(0.0, 0.0, 1.0),
+            Vec3::from_xyz(0.8, 0.0, 0.6),
+            Vec3::from_xyz(0.0, 0.6, 0.8),
+        ];
+        let color = Vec4::from_xyzw(1.0, 0.5, 0.25, 1.0);
+        let light = Vec3::from_xyz(1.0, 1.0, 2.0).normalize();
+        let mut raster = Rasterizer::new(WIDTH, HEIGHT, Vec3::zeros());
+        raster.light = Some(light);
+        raster.draw(&triangle(vertices, normals, color), &Mat44::identity(), &camera.view_proj_mat());
+
+        let plane_normal = (vertices[1] - vertices[0]).cross(&(vertices[2] - vertices[0]));
+        let area = plane_normal.length();
+        let mut checked = 0;
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                if raster.depth()[(x, y)] == Real::INFINITY {
+                    continue;
+                }
+                let ray = camera.screen_ray(&Vec2::from_xy(x as Real + 0.5, y as Real + 0.5)).unwrap();
+                let t = (vertices[0] - ray.start).dot(&plane_normal) / ray.dir.dot(&plane_normal);
+                let hit = ray.start + ray.dir * t;
+                let weights = [0, 1, 2].map(|i| {
+                    let (b, c) = (vertices[(i + 1) % 3], vertices[(i + 2) % 3]);
+                    (b - hit).cross(&(c - hit)).length() / area
+                });
+                let normal = (0..3).fold(Vec3::zeros(), |acc, i| acc + normals[i] * weights[i]).normalize();
+                let shade = raster.ambient + (1.0 - raster.ambient) * normal.dot(&light).max(0.0);
+                let expected = color.xyz() * shade;
+                assert!((raster.color()[(x, y)] - expected).length() < 1e-6);
+                checked += 1;
+            }
+        }
+        assert!(checked > 100);
+    }
+
+    #[test]
+    fn clip_near_plane() {
+        let camera = camera();
+        // floor reaching far behind the camera
+        let floor = triangle(
+            [
+                Vec3::from_xyz(-20.0, -1.0, 40.0),
+                Vec3::from_xyz(20.0, -1.0, 40.0),
+                Vec3::from_xyz(0.0, -1.0, -20.0),
+            ],
+            [Vec3::y_axis(); 3],
+            Vec4::from_xyzw(0.0, 1.0, 0.0, 1.0),
+        );
+        let mut raster = unlit();
+        raster.draw(&floor, &Mat44::identity(), &camera.view_proj_mat());
+
+        // floor below the eye fills the bottom row and never the top one
+        assert!(raster.color().row(HEIGHT - 1).iter().all(|c| *c == Vec3::y_axis()));
+        assert!(raster.color().row(0).iter().all(|c| *c == Vec3::zeros()));
+        assert!(raster
+            .depth()
+            .data()
+            .iter()
+            .all(|d| *d == Real::INFINITY || (-1.0..=1.0).contains(d)));
+
+        // depth still matches the camera where the floor is visible
+        let pt = Vec3::from_xyz(0.0, -1.0, 0.0);
+        let screen = camera.world_to_screen(&pt).unwrap();
+        let depth = raster.depth()[(screen.x() as usize, screen.y() as usize)];
+        assert!((depth - screen.z()).abs() < 1e-2);
+    }
+}
@@ -0,0 +1,180 @@
+#[cfg(test)]
+mod test {
+    use geometric::geom3d::Sphere;
+    use graphics::camera::*;
+    use graphics::mesh_generate::FaceDisplayData;
+    use graphics::ray_trace::*;
+    use math::matrix::*;
+    use math::precision::Real;
+
+    fn camera(size: usize, half_fovy: Real, position: Vec3, target: Vec3) -> Camera {
+        let mut camera = Camera::new(
+            Projection::Perspective { half_fovy },
+            0.1,
+            100.0,
+            Vec2::from_xy(size as Real, size as Real),
+        );
+        camera.look_at(position, target);
+        camera
+    }
+
+    fn linear(samples: u32, max_depth: u32) -> RenderSettings {
+        RenderSettings {
+            samples,
+            max_depth,
+            gamma: 1.0,
+            ..Default::default()
+        }
+    }
+
+    /// square in `y = 0` facing `+y`
+    fn floor(half: Real) -> FaceDisplayData {
+        FaceDisplayData {
+            vertices: vec![
+                Vec3::from_xyz(-half, 0.0, half),
+                Vec3::from_xyz(half, 0.0, half),
+                Vec3::from_xyz(half, 0.0, -half),
+                Vec3::from_xyz(-half, 0.0, -half),
+            ],
+            normals: vec![Vec3::y_axis(); 4],
+            indices: vec![0, 1, 2, 0, 2, 3],
+            color: Vec4::from_xyzw(1.0, 1.0, 1.0, 1.0),
+            uvs: None,
+            tangents: None,
+        }
+    }
+
+    fn center_pixel(ppm: &graphics::ppm::PPM) -> Vec3 {
+        ppm.get_pixel(ppm.width() / 2, ppm.height() / 2)
+    }
+
+    #[test]
+    fn empty_scene_is_background() {
+        let background = Vec3::from_xyz(0.25, 0.5, 1.0);
+        let scene = Scene::new(background);
+        let ppm = scene.render(&camera(8, 0.5, Vec3::z_axis() * 5.0, Vec3::zeros()), &linear(2, 4));
+        assert_eq!((ppm.width(), ppm.height()), (8, 8));
+        assert!(ppm.image().data().iter().all(|c| (*c - background).length() < 1e-12));
+
+        // eye at target
+        let ppm = scene.render(&camera(8, 0.5, Vec3::zeros(), Vec3::zeros()), &linear(2, 4));
+        assert_eq!((ppm.width(), ppm.height()), (8, 8));
+        assert!(ppm.image().data().iter().all(|c| *c == Vec3::zeros()));
+    }
+
+    #[test]
+    fn lambertian_under_uniform_sky() {
+        // rays leaving a convex diffuse surface never come back, every sample sees albedo * sky
+        let mut scene = Scene::new(Vec3::ones());
+        let albedo = Vec3::from_xyz(0.2, 0.5, 0.8);
+        scene.add_sphere(Sphere::new(Vec3::zeros(), 1.0), Material::Lambertian { albedo });
+        let ppm = scene.render(&camera(9, 0.1, Vec3::z_axis() * 5.0, Vec3::zeros()), &linear(4, 4));
+        assert!((center_pixel(&ppm) - albedo).length() < 1e-9);
+    }
+
+    #[test]
+    fn dielectric_keeps_energy() {
+        let mut scene = Scene::new(Vec3::ones());
+        scene.add_sphere(Sphere::new(Vec3::zeros(), 1.0), Material::Dielectric { ior: 1.5 });
+        let ppm = scene.render(&camera(9, 0.3, Vec3::z_axis() * 5.0, Vec3::zeros()), &linear(8, 64));
+        assert!(ppm.image().data().iter().all(|c| (*c - Vec3::ones()).length() < 1e-9));
+    }
+
+    #[test]
+    fn mirror_reflects_sky() {
+        let mut scene = Scene::new(Vec3::from_xyz(0.5, 0.5, 0.5));
+        let albedo = Vec3::from_xyz(0.9, 0.6, 0.3);
+        scene.add_mesh(&floor(10.0), &Mat44::identity(), Material::Metal { albedo, fuzz: 0.0 });
+        // a light hidden from the reflection doesn't change a perfect mirror
+        scene.add_sphere(
+            Sphere::new(Vec3::from_xyz(0.0, 3.0, -20.0), 0.5),
+            Material::Emissive {
+                radiance: Vec3::ones() * 10.0,
+            },
+        );
+        let ppm = scene.render(&camera(9, 0.1, Vec3::from_xyz(0.0, 2.0, 4.0), Vec3::zeros()), &linear(4, 4));
+        assert!((center_pixel(&ppm) - albedo * 0.5).length() < 1e-9);
+    }
+
+    #[test]
+    fn sphere_light_on_floor() {
+        // irradiance right below a sphere light is `pi * radiance * (r / d)^2`
+        let (radius, height, emitted, albedo) = (0.5, 2.0, 5.0, 0.8);
+        let mut scene = Scene::new(Vec3::zeros());
+        scene.add_mesh(
+            &floor(50.0),
+            &Mat44::identity(),
+            Material::Lambertian {
+                albedo: Vec3::ones() * albedo,
+            },
+        );
+        scene.add_sphere(
+            Sphere::new(Vec3::y_axis() * height, radius),
+            Material::Emissive {
+                radiance: Vec3::ones() * emitted,
+            },
+        );
+
+        let settings = RenderSettings {
+            threads: 2,
+            ..linear(16384, 1)
+        };
+        let ppm = scene.render(&camera(3, 0.001, Vec3::from_xyz(4.0, 1.0, 4.0), Vec3::zeros()), &settings);
+        let expected = albedo * emitted * (radius / height).powi(2);
+        let c = center_pixel(&ppm);
+        for v in [c.x(), c.y(), c.z()] {
+            assert!((v - expected).abs() < expected * 0.05, "{} vs {}", v, expected);
+        }
+
+        // looking at the light itself
+        let ppm = scene.render(&camera(3, 0.001, Vec3::from_xyz(0.0, 2.0, 5.0), Vec3::y_axis() * height), &linear(1, 1));
+        assert!((center_pixel(&ppm) - Vec3::ones() * emitted).length() < 1e-9);
+    }
+
+    #[test]
+    fn same_image_on_any_thread_count() {
+        let mut scene = Scene::new(Vec3::from_xyz(0.1, 0.1, 0.2));
+        scene.add_mesh(
+            &floor(5.0),
+            &Mat44::identity(),
+            Material::Lambertian {
+                albedo: Vec3::from_xyz(0.7, 0.7, 0.7),
+            },
+        );
+        scene.add_sphere(
+            Sphere::new(Vec3::from_xyz(-1.0, 1.0, 0.0), 1.0),
+            Material::Metal {
+                albedo: Vec3::from_xyz(0.9, 0.8, 0.6),
+                fuzz: 0.2,
+            },
+        );
+        scene.add_sphere(Sphere::new(Vec3::from_xyz(1.2, 0.7, 0.5), 0.7), Material::Dielectric { ior: 1.5 });
+        scene.add_sphere(
+            Sphere::new(Vec3::from_xyz(0.0, 4.0, 0.0), 0.5),
+            Material::Emissive {
+                radiance: Vec3::ones() * 20.0,
+            },
+        );
+
+        let camera = camera(24, 0.6, Vec3::from_xyz(0.0, 2.0, 6.0), Vec3::from_xyz(0.0, 0.8, 0.0));
+        let render = |threads| {
+            let settings = RenderSettings {
+                samples: 4,
+                threads,
+                ..Default::default()
+            };
+            scene.render(&camera, &settings)
+        };
+        let single = render(1);
+        assert_eq!(single.image(), render(3).image());
+        assert_eq!(single.image(), render(0).image());
+        assert!(single.image().data().iter().all(|c| c.x().is_finite() && c.x() >= 0.0));
+
+        let other_seed = RenderSettings {
+            samples: 4,
+            seed: 7,
+            ..Default::default()
+        };
+        assert_ne!(single.image(), scene.render(&camera, &other_seed).image());
+    }
+}
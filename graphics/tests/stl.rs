@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod test {
+    use geometric::geom3d::*;
+    use graphics::mesh_generate::*;
+    use graphics::stl::*;
+    use math::matrix::*;
+
+    fn corners(data: &FaceDisplayData) -> Vec<Vec3> {
+        data.indices.iter().map(|i| data.vertices[*i as usize]).collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let sphere = Sphere::new(Vec3::from_xyz(1.0, 2.0, 3.0), 1.5);
+        let data = sphere_to_display_data(&sphere, Vec4::ones(), 12, 6);
+        let facets = data.indices.len() / 3;
+
+        let ascii = parse_stl(to_ascii_stl(&data, "ball").as_bytes()).unwrap();
+        assert_eq!(corners(&ascii), corners(&data));
+
+        let bytes = to_binary_stl(&data);
+        assert_eq!(bytes.len(), 84 + facets * 50);
+        let binary = parse_stl(&bytes).unwrap();
+        assert_eq!(binary.indices.len(), data.indices.len());
+        for (a, b) in corners(&binary).iter().zip(corners(&data)) {
+            assert!((*a - b).length() < 1e-6);
+        }
+
+        // facet normals point out of the sphere and are shared by the three corners
+        for t in ascii.indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| ascii.vertices[t[i] as usize]);
+            let n = ascii.normals[t[0] as usize];
+            assert!((n.length() - 1.0).abs() < 1e-9);
+            assert!(t.iter().all(|i| ascii.normals[*i as usize] == n));
+            // triangles at poles have no area
+            if (b - a).cross(&(c - a)).length() > 1e-9 {
+                assert!(n.dot(&((a + b + c) / 3.0 - sphere.center)) > 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn zero_normal() {
+        let src = "solid test\nfacet normal 0 0 0\n outer loop\n  vertex 0 0 0\n  vertex 1 0 0\n  vertex 0 1 0\n endloop\nendfacet\nendsolid test\n";
+        let data = parse_stl(src.as_bytes()).unwrap();
+        assert_eq!(data.vertices.len(), 3);
+        assert!(data.normals.iter().all(|n| *n == Vec3::z_axis()));
+    }
+
+    #[test]
+    fn malformed() {
+        let facet = "solid\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nvertex 1 0 0\nvertex 0 1 0\nendloop\nendfacet\n";
+        assert!(matches!(parse_stl(facet.as_bytes()), Err(StlError::UnexpectedEof)));
+        let src = facet.replace("vertex 1 0 0", "vertex 1 x 0");
+        assert!(matches!(parse_stl(src.as_bytes()), Err(StlError::InvalidNumber { line: 5 })));
+        let src = facet.replace("outer loop", "inner loop");
+        assert!(matches!(parse_stl(src.as_bytes()), Err(StlError::UnexpectedToken { line: 3 })));
+
+        let mut bytes = vec![0u8; 80];
+        bytes.extend(2u32.to_le_bytes());
+        bytes.extend([0u8; 50]);
+        assert!(matches!(parse_stl(&bytes), Err(StlError::UnexpectedEof)));
+        assert!(matches!(read_stl("/nonexistent/file.stl"), Err(StlError::Io(_))));
+    }
+}
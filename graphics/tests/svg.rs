@@ -0,0 +1,164 @@
+#[cfg(test)]
+mod test {
+    use geometric::geom2d::*;
+    use graphics::svg::*;
+    use math::matrix::*;
+    use math::precision::Real;
+    use std::f64::consts::PI;
+
+    fn document(y_up: bool) -> SvgDocument {
+        SvgDocument::new(AABB::from_min_max(Vec2::zeros(), Vec2::from_xy(10.0, 5.0)), 200, y_up).unwrap()
+    }
+
+    /// points of every polyline
+    fn polylines(svg: &str) -> Vec<Vec<Vec2>> {
+        svg.lines()
+            .filter_map(|l| l.strip_prefix("<polyline points=\""))
+            .map(|l| {
+                l[..l.find('"').unwrap()]
+                    .split(' ')
+                    .map(|p| {
+                        let (x, y) = p.split_once(',').unwrap();
+                        Vec2::from_xy(x.parse().unwrap(), y.parse().unwrap())
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lines_are_clipped() {
+        let mut doc = document(true);
+        assert_eq!(doc.height, 100);
+        let style = SvgStyle::default();
+        let layer = doc.layer("lines");
+        layer.line(&Line2D::new(Vec2::from_xy(-100.0, 1.0), Vec2::x_axis()), &style);
+        layer.line(&Line2D::new(Vec2::from_xy(3.0, 0.0), Vec2::from_xy(1.0, 1.0)), &style);
+        layer.ray(&Ray2D::new(Vec2::from_xy(5.0, 2.5), Vec2::y_axis()), &style);
+        layer.segment(&Segment2D::new(Vec2::from_xy(8.0, 1.0), Vec2::from_xy(12.0, 1.0)), &style);
+        // entirely outside
+        layer.ray(&Ray2D::new(Vec2::from_xy(5.0, 2.5), Vec2::from_xy(5.0, 2.5) - Vec2::from_xy(5.0, 10.0)), &style);
+        layer.ray(&Ray2D::new(Vec2::from_xy(5.0, 10.0), Vec2::y_axis()), &style);
+        layer.line(&Line2D::new(Vec2::from_xy(-1.0, 0.0), Vec2::y_axis()), &style);
+
+        let svg = doc.to_svg();
+        let lines: Vec<&str> = svg.lines().filter(|l| l.starts_with("<line")).collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].starts_with(r#"<line x1="0" y1="80" x2="200" y2="80" "#));
+        assert!(lines[1].starts_with(r#"<line x1="60" y1="100" x2="160" y2="0" "#));
+        assert!(lines[2].starts_with(r#"<line x1="100" y1="50" x2="100" y2="0" "#));
+        assert!(lines[3].starts_with(r#"<line x1="160" y1="80" x2="200" y2="80" "#));
+        assert!(lines[4].starts_with(r#"<line x1="100" y1="50" x2="100" y2="100" "#));
+    }
+
+    #[test]
+    fn shapes() {
+        for y_up in [true, false] {
+            let mut doc = document(y_up);
+            let style = SvgStyle::stroke(Vec4::from_xyzw(1.0, 0.0, 0.0, 0.5), 2.0)
+                .with_fill(Vec4::from_xyzw(0.0, 0.0, 1.0, 1.0))
+                .with_dash(4.0, 2.0);
+            let layer = doc.layer("shapes");
+            layer.circle(&Circle::new(Vec2::from_xy(1.0, 1.0), 0.5), &style);
+            layer.aabb(&AABB::from_min_max(Vec2::from_xy(1.0, 1.0), Vec2::from_xy(2.0, 3.0)), &style);
+            let mut obb = OBB::new(Vec2::from_xy(5.0, 2.5), Vec2::from_xy(1.0, 0.5));
+            obb.set_rotation(PI / 2.0);
+            layer.obb(&obb, &style);
+            layer.triangle(&Triangle2D::new([Vec2::zeros(), Vec2::x_axis(), Vec2::y_axis()]), &style);
+            layer.arc(&CircleArc::new(Vec2::from_xy(5.0, 2.5), 1.0, (0.0, PI / 2.0)), &style);
+            layer.arc(&CircleArc::new(Vec2::from_xy(5.0, 2.5), 1.0, (0.0, 2.0 * PI)), &style);
+            let mut ellipse = Ellipse::new(Vec2::from_xy(5.0, 2.5), 2.0, 1.0);
+            ellipse.rotation = PI / 6.0;
+            layer.conic(&Conic::Ellipse(ellipse), &style);
+            layer.point(&Vec2::from_xy(1.0, 1.0), 3.0, Vec4::ones());
+
+            let svg = doc.to_svg();
+            let y = |v: Real| if y_up { 100.0 - v } else { v };
+            assert!(svg.contains(&format!(
+                r##"<circle cx="20" cy="{}" r="10" fill="#0000ff" stroke="#ff0000" stroke-width="2" stroke-opacity="0.5" stroke-dasharray="4 2"/>"##,
+                y(20.0)
+            )));
+            assert!(svg.contains(&format!(r#"<polygon points="20,{} 40,{} 40,{} 20,{}""#, y(20.0), y(20.0), y(60.0), y(60.0))));
+            assert!(svg.contains(&format!(r#"<polygon points="110,{} 110,{} 90,{} 90,{}""#, y(30.0), y(70.0), y(70.0), y(30.0))));
+            assert!(svg.contains(&format!(r#"<polygon points="0,{} 20,{} 0,{}""#, y(0.0), y(0.0), y(20.0))));
+            // quarter arc ends at the top in world
+            assert!(svg.contains(&format!(r#"<path d="M 120 50 A 20 20 0 0 {} 100 {}" fill="none""#, !y_up as u8, y(70.0))));
+            assert!(svg.contains(r#"<circle cx="100" cy="50" r="20" "#));
+            let angle = if y_up { "-30" } else { "30" };
+            assert!(svg.contains(&format!(r#"<ellipse cx="100" cy="50" rx="40" ry="20" transform="rotate({} 100 50)" "#, angle)));
+            assert!(svg.contains(&format!(r##"<circle cx="20" cy="{}" r="3" fill="#ffffff"/>"##, y(20.0))));
+        }
+    }
+
+    #[test]
+    fn open_conics_leave_viewport() {
+        let mut doc = document(true);
+        let mut hyperbola = Hyperbola::new(Vec2::from_xy(5.0, 2.5), 1.0, 0.5);
+        hyperbola.rotation = 0.3;
+        let mut parabola = Parabola::new(Vec2::from_xy(2.0, 1.0), 0.25);
+        parabola.rotation = -1.0;
+        let layer = doc.layer("conics");
+        layer.conic(&Conic::Hyperbola(hyperbola), &SvgStyle::default());
+        layer.conic(&Conic::Parabola(parabola), &SvgStyle::default());
+
+        let curves = polylines(&doc.to_svg());
+        assert_eq!(curves.len(), 3);
+        let outside = |p: &Vec2| p.x() < 0.0 || p.x() > 200.0 || p.y() < 0.0 || p.y() > 100.0;
+        for curve in &curves {
+            assert!(outside(&curve[0]) && outside(curve.last().unwrap()));
+        }
+        // world points back from image coordinate lie on the conics
+        let world = |p: &Vec2| Vec2::from_xy(p.x() / 20.0, 5.0 - p.y() / 20.0);
+        for (conic, curves) in [(Conic::Hyperbola(hyperbola), &curves[..2]), (Conic::Parabola(parabola), &curves[2..])] {
+            for p in curves.iter().flatten().map(world) {
+                let local = conic.to_local(&p);
+                let residual = match conic {
+                    Conic::Hyperbola(h) => local.x().powi(2) / (h.a * h.a) - local.y().powi(2) / (h.b * h.b) - 1.0,
+                    Conic::Parabola(q) => local.y().powi(2) - 2.0 * q.p * local.x(),
+                    Conic::Ellipse(_) => unreachable!(),
+                };
+                // image coordinates are rounded to 1e-3 pixels
+                assert!(residual.abs() < 1e-2 * (1.0 + local.length_sqrd()));
+            }
+        }
+    }
+
+    #[test]
+    fn layers_and_labels() {
+        let mut doc = document(false);
+        doc.background = Some(Vec4::ones());
+        doc.layer("a").label(&Vec2::from_xy(1.0, 1.0), "p < q & \"r\"", 12.0, Vec4::from_xyzw(0.0, 0.0, 0.0, 1.0));
+        doc.layer("b").visible = false;
+        doc.layer("a").point(&Vec2::zeros(), 2.0, Vec4::ones());
+        doc.layer("2 \"x\"");
+        assert_eq!(doc.layers().len(), 3);
+        assert_eq!(doc.layers()[0].name, "a");
+
+        let svg = doc.to_svg();
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg""#));
+        assert!(svg.contains(r#"width="200" height="100" viewBox="0 0 200 100""#));
+        assert!(svg.contains(r##"<rect width="100%" height="100%" fill="#ffffff"/>"##));
+        assert!(svg.contains(r#"inkscape:label="a" id="layer1">"#));
+        assert!(svg.contains(r#"inkscape:label="b" id="layer2" style="display:none">"#));
+        assert!(svg.contains(r#"inkscape:label="2 &quot;x&quot;" id="layer3">"#));
+        assert!(svg.contains(r##"<text x="20" y="20" font-family="sans-serif" font-size="12" fill="#000000">p &lt; q &amp; &quot;r&quot;</text>"##));
+        assert!(svg.find("<text").unwrap() < svg.find(r#"r="2""#).unwrap());
+        assert_eq!(svg.matches("<g ").count(), svg.matches("</g>").count());
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn degenerate_viewport() {
+        let flat = AABB::from_min_max(Vec2::zeros(), Vec2::from_xy(0.0, 5.0));
+        assert!(SvgDocument::new(flat, 200, true).is_err());
+        let flat = AABB::from_min_max(Vec2::zeros(), Vec2::from_xy(10.0, 0.0));
+        assert!(SvgDocument::new(flat, 200, true).is_err());
+        let nan = AABB::from_min_max(Vec2::zeros(), Vec2::from_xy(Real::NAN, 5.0));
+        assert!(SvgDocument::new(nan, 200, true).is_err());
+        let infinite = AABB::from_min_max(Vec2::zeros(), Vec2::from_xy(Real::INFINITY, 5.0));
+        assert!(SvgDocument::new(infinite, 200, true).is_err());
+        let valid = AABB::from_min_max(Vec2::zeros(), Vec2::from_xy(10.0, 5.0));
+        assert!(SvgDocument::new(valid, 0, true).is_err());
+        assert_eq!(SvgDocument::new(valid, 1, true).unwrap().height, 1);
+    }
+}
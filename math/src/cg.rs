@@ -307,10 +307,11 @@ impl Transformation3D for EularRotationXYZ {
     }
 }
 
+/// perspective projection looking at `-z`, depth maps to `[-1, 1]`. `half_fovy` is the vertical half angle and `aspect` is width / height
 #[rustfmt::skip]
 pub fn create_persp_project(near: Real, far: Real, half_fovy: Real, aspect: Real) -> Mat44 {
-    let inv_half_w = 1.0 / (half_fovy.tan() * near);
-    let inv_half_h = aspect * inv_half_w;
+    let inv_half_h = 1.0 / (half_fovy.tan() * near);
+    let inv_half_w = inv_half_h / aspect;
 
     Mat44::from_row(&[
         near * inv_half_w,               0.0,                         0.0,                             0.0,
@@ -320,6 +321,7 @@ pub fn create_persp_project(near: Real, far: Real, half_fovy: Real, aspect: Real
     ])
 }
 
+/// orthographic projection looking at `-z`, `near` and `far` are distances in front of eye, depth maps to `[-1, 1]`
 #[rustfmt::skip]
 pub fn create_ortho_project(left: Real, right: Real, bottom: Real, top: Real, far: Real, near: Real) -> Mat44 {
     let inv_rl = 1.0 / (right - left);
@@ -329,11 +331,32 @@ pub fn create_ortho_project(left: Real, right: Real, bottom: Real, top: Real, fa
     Mat44::from_row(&[
         2.0 * inv_rl,          0.0,          0.0, - (left + right) * inv_rl,
                  0.0, 2.0 * inv_tb,          0.0, - (top + bottom) * inv_tb,
-                 0.0,          0.0, 2.0 * inv_nf,   - (near + far) * inv_nf,
+                 0.0,          0.0, 2.0 * inv_nf,     (near + far) * inv_nf,
                  0.0,          0.0,          0.0,                       1.0,
     ])
 }
 
+/// view matrix of eye at `eye` looking at `target`, camera looks at `-z` with `y` up in view space
+#[rustfmt::skip]
+pub fn create_look_at(eye: Vec3, target: Vec3, up: Vec3) -> Mat44 {
+    let z = (eye - target).normalize();
+    let x = up.cross(&z).normalize();
+    let y = z.cross(&x);
+
+    Mat44::from_row(&[
+        x.x(), x.y(), x.z(), -x.dot(&eye),
+        y.x(), y.y(), y.z(), -y.dot(&eye),
+        z.x(), z.y(), z.z(), -z.dot(&eye),
+          0.0,   0.0,   0.0,          1.0,
+    ])
+}
+
+/// rotate `v` around normalized `axis` by `radians` with [Rodrigues' formula](https://en.wikipedia.org/wiki/Rodrigues%27_rotation_formula)
+pub fn rotate_around_axis(v: &Vec3, axis: &Vec3, radians: Real) -> Vec3 {
+    let (sin, cos) = radians.sin_cos();
+    *v * cos + axis.cross(v) * sin + *axis * (axis.dot(v) * (1.0 - cos))
+}
+
 pub struct Berycentric {
     alpha: Real,
     beta: Real,
@@ -369,7 +392,6 @@ impl Berycentric {
     }
 }
 
-// TODO: implement Rodriguez's formula
 // TODO: implement Mirror transform
 // TODO: implement Quaternion
 // TODO: implement Schmit Orthograph
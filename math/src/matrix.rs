@@ -115,6 +115,38 @@ impl<T: ArithmeticGroup<T> + PartialEq> Matrix<T, 2, 2> {
     }
 }
 
+impl Matrix<Real, 4, 4> {
+    /// inverse by gauss-jordan elimination with partial pivoting, `None` if singular
+    pub fn inv(&self) -> Option<Self> {
+        let mut m = *self;
+        let mut result = Self::identity();
+        for col in 0..4 {
+            let pivot = (col..4).max_by(|a, b| m.get(col, *a).abs().total_cmp(&m.get(col, *b).abs()))?;
+            if m.get(col, pivot) == 0.0 {
+                return None;
+            }
+            for x in 0..4 {
+                m.data[x].swap(col, pivot);
+                result.data[x].swap(col, pivot);
+            }
+
+            let inv_pivot = 1.0 / m.get(col, col);
+            for x in 0..4 {
+                m.data[x][col] *= inv_pivot;
+                result.data[x][col] *= inv_pivot;
+            }
+            for row in (0..4).filter(|row| *row != col) {
+                let factor = m.get(col, row);
+                for x in 0..4 {
+                    m.data[x][row] -= factor * m.data[x][col];
+                    result.data[x][row] -= factor * result.data[x][col];
+                }
+            }
+        }
+        Some(result)
+    }
+}
+
 impl<T: ArithmeticGroup<T>, const LEN: usize> Matrix<T, LEN, LEN> {
     pub fn identity() -> Self {
         let mut result = Self::zeros();
@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod test {
+    use math::cg::*;
+    use math::matrix::*;
+    use math::precision::Real;
+    use std::f64::consts::PI;
+
+    /// normalized device coordinate of view space point `p`
+    fn project(m: &Mat44, p: Vec3) -> Vec3 {
+        let clip = *m * Vec4::from(p);
+        clip.xyz() * (1.0 / clip.w())
+    }
+
+    fn assert_near(a: Real, b: Real) {
+        assert!((a - b).abs() < 1e-9, "{} {}", a, b);
+    }
+
+    #[test]
+    fn persp_project() {
+        let (near, far, half_fovy, aspect) = (1.0, 10.0, PI / 6.0, 2.0);
+        let m = create_persp_project(near, far, half_fovy, aspect);
+        // `half_fovy` spans the height, the width is `aspect` times of it
+        let half_height = half_fovy.tan() * 5.0;
+        assert_near(project(&m, Vec3::from_xyz(0.0, half_height, -5.0)).y(), 1.0);
+        assert_near(project(&m, Vec3::from_xyz(-half_height * aspect, 0.0, -5.0)).x(), -1.0);
+        assert_near(project(&m, Vec3::from_xyz(0.0, 0.0, -near)).z(), -1.0);
+        assert_near(project(&m, Vec3::from_xyz(0.0, 0.0, -far)).z(), 1.0);
+    }
+
+    #[test]
+    fn ortho_project() {
+        // `far` comes before `near`, both are distances in front of the eye
+        let m = create_ortho_project(-2.0, 4.0, -1.0, 3.0, 10.0, 1.0);
+        let min = project(&m, Vec3::from_xyz(-2.0, -1.0, -1.0));
+        let max = project(&m, Vec3::from_xyz(4.0, 3.0, -10.0));
+        for i in 0..3 {
+            assert_near(min[i], -1.0);
+            assert_near(max[i], 1.0);
+        }
+        assert_near(project(&m, Vec3::from_xyz(1.0, 1.0, -5.5)).z(), 0.0);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn inverse() {
+        let m = Mat44::from_row(&[
+            2.0, 0.0, 1.0,  3.0,
+            0.0, 1.0, 0.0, -1.0,
+            1.0, 0.0, 3.0,  0.0,
+            0.0, 4.0, 0.0,  1.0,
+        ]);
+        let inv = m.inv().unwrap();
+        for product in [m * inv, inv * m] {
+            for x in 0..4 {
+                for y in 0..4 {
+                    assert_near(product.get(x, y), if x == y { 1.0 } else { 0.0 });
+                }
+            }
+        }
+        assert_eq!(Mat44::identity().inv().unwrap(), Mat44::identity());
+
+        // third row is the sum of the first two
+        let singular = Mat44::from_row(&[
+            1.0, 2.0, 3.0, 4.0,
+            0.0, 1.0, 0.0, 2.0,
+            1.0, 3.0, 3.0, 6.0,
+            0.0, 0.0, 0.0, 1.0,
+        ]);
+        assert!(singular.inv().is_none());
+        assert!(Mat44::zeros().inv().is_none());
+    }
+
+    #[test]
+    fn look_at() {
+        let (eye, target) = (Vec3::from_xyz(3.0, 4.0, 5.0), Vec3::from_xyz(1.0, 1.0, 1.0));
+        let view = create_look_at(eye, target, Vec3::y_axis());
+        let at = |p: Vec3| (view * Vec4::from(p)).xyz();
+        assert!(at(eye).length() < 1e-9);
+        let t = at(target);
+        assert_near(t.x(), 0.0);
+        assert_near(t.y(), 0.0);
+        assert_near(t.z(), -(eye - target).length());
+        // up stays up, and the view is a rigid transform
+        assert!(at(eye + Vec3::y_axis()).y() > 0.0);
+        assert_near((at(Vec3::zeros()) - at(Vec3::x_axis())).length(), 1.0);
+    }
+
+    #[test]
+    fn rotation_around_axis() {
+        let y = rotate_around_axis(&Vec3::x_axis(), &Vec3::z_axis(), PI / 2.0);
+        assert!((y - Vec3::y_axis()).length() < 1e-9);
+
+        let axis = Vec3::from_xyz(1.0, 1.0, 1.0).normalize();
+        let v = Vec3::from_xyz(1.0, -2.0, 0.5);
+        assert!((rotate_around_axis(&axis, &axis, 1.0) - axis).length() < 1e-9);
+        let rotated = rotate_around_axis(&v, &axis, 0.7);
+        assert_near(rotated.length(), v.length());
+        assert_near(rotated.dot(&axis), v.dot(&axis));
+        // a third of a turn around the diagonal cycles the axes
+        let cycled = rotate_around_axis(&Vec3::x_axis(), &axis, 2.0 * PI / 3.0);
+        assert!((cycled - Vec3::y_axis()).length() < 1e-9);
+        assert!((rotate_around_axis(&rotated, &axis, -0.7) - v).length() < 1e-9);
+    }
+}